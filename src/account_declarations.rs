@@ -0,0 +1,114 @@
+//! Accounts declared with `account` directives.
+//!
+//! A journal that declares its accounts can be checked for typos: a posting to an
+//! account nobody declared is usually a misspelled one. Declarations also carry
+//! `note` and `assert` subdirectives worth showing next to the account.
+
+use crate::accounts::Account;
+
+/// An `account` directive with the subdirectives indented under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDeclaration {
+    pub account: Account,
+    /// Text of `note` subdirectives.
+    pub notes: Vec<String>,
+    /// Value expressions of `assert` subdirectives, as written.
+    pub asserts: Vec<String>,
+}
+
+/// Reads the `account` directives of a journal.
+pub fn parse_declarations(text: &str) -> Vec<AccountDeclaration> {
+    let mut declarations = Vec::<AccountDeclaration>::new();
+    let mut in_block = false;
+    for line in text.lines() {
+        let indented = line.starts_with([' ', '\t']);
+        let line = line.trim();
+        if !indented {
+            in_block = false;
+            if let Some(rest) = line.strip_prefix("account ") {
+                let name = rest.split_once(';').map_or(rest, |(name, _)| name).trim();
+                if !name.is_empty() {
+                    declarations.push(AccountDeclaration {
+                        account: Account::parse(name),
+                        notes: Vec::new(),
+                        asserts: Vec::new(),
+                    });
+                    in_block = true;
+                }
+            }
+            continue;
+        }
+
+        let Some(declaration) = declarations.last_mut().filter(|_| in_block) else {
+            continue;
+        };
+        if let Some(note) = line.strip_prefix("note ") {
+            declaration.notes.push(note.trim().to_string());
+        } else if let Some(assert) = line.strip_prefix("assert ") {
+            declaration.asserts.push(assert.trim().to_string());
+        }
+    }
+    declarations
+}
+
+/// Whether the account, or one of its subaccounts, is declared.
+///
+/// Journals without any declarations don't use them, so every account counts as
+/// declared there.
+pub fn is_declared(declarations: &[AccountDeclaration], account: &Account) -> bool {
+    declarations.is_empty()
+        || declarations.iter().any(|declaration| {
+            declaration.account == *account || account.is_parent_of(&declaration.account)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_declarations() {
+        let text = "\
+account assets:bank ; main account
+    note Joint account with Alex
+    alias bank
+    assert amount >= 0
+account expenses:food
+
+2025-01-01 Shop
+    note this is a posting, not a declaration
+    expenses:food  10 USD
+    assets:bank
+";
+        assert_eq!(
+            parse_declarations(text),
+            [
+                AccountDeclaration {
+                    account: Account::parse("assets:bank"),
+                    notes: vec!["Joint account with Alex".to_string()],
+                    asserts: vec!["amount >= 0".to_string()],
+                },
+                AccountDeclaration {
+                    account: Account::parse("expenses:food"),
+                    notes: Vec::new(),
+                    asserts: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_declared() {
+        let declarations = parse_declarations("account assets:bank:checking\n");
+        assert!(is_declared(
+            &declarations,
+            &Account::parse("assets:bank:checking")
+        ));
+        assert!(is_declared(&declarations, &Account::parse("assets")));
+        assert!(!is_declared(
+            &declarations,
+            &Account::parse("assets:bank:savings")
+        ));
+        assert!(is_declared(&[], &Account::parse("assets:cash")));
+    }
+}
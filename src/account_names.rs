@@ -0,0 +1,152 @@
+//! Display names for accounts.
+//!
+//! Names come from the journal's `alias` directives and from user-defined names in
+//! settings. They only change what is shown; filters and limits keep using the
+//! canonical account path.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::accounts::Account;
+
+/// Reads `(alias, account)` pairs from a journal.
+///
+/// Both the top-level `alias NAME=ACCOUNT` directive and `alias NAME` inside an
+/// `account ACCOUNT` block are recognized.
+pub fn parse_aliases(text: &str) -> Vec<(String, Account)> {
+    let mut aliases = Vec::new();
+    let mut current_account = None;
+    for line in text.lines() {
+        let indented = line.starts_with([' ', '\t']);
+        let line = line.trim();
+        if !indented {
+            current_account = None;
+        }
+
+        if let Some(rest) = line.strip_prefix("account ") {
+            if !indented {
+                current_account = Some(Account::parse(strip_comment(rest)));
+            }
+        } else if let Some(rest) = line.strip_prefix("alias ") {
+            let rest = strip_comment(rest);
+            match (indented, &current_account, rest.split_once('=')) {
+                (false, _, Some((alias, account))) => {
+                    aliases.push((alias.trim().to_string(), Account::parse(account.trim())));
+                }
+                (true, Some(account), _) if !rest.is_empty() => {
+                    aliases.push((rest.to_string(), account.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+    aliases
+}
+
+/// Drops a trailing `;` comment and surrounding whitespace.
+fn strip_comment(text: &str) -> &str {
+    text.split_once(';').map_or(text, |(value, _)| value).trim()
+}
+
+/// Maps canonical accounts to the names shown in the UI.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountNames {
+    names: HashMap<Account, String>,
+}
+
+impl AccountNames {
+    /// Creates names from journal aliases, overridden by user display names keyed by
+    /// the full account path.
+    ///
+    /// When an account has several aliases the first one is used.
+    pub fn new(aliases: &[(String, Account)], display_names: &BTreeMap<String, String>) -> Self {
+        let mut names = HashMap::new();
+        for (alias, account) in aliases {
+            names
+                .entry(account.clone())
+                .or_insert_with(|| alias.clone());
+        }
+        for (account, name) in display_names {
+            let name = name.trim();
+            if !name.is_empty() {
+                names.insert(Account::parse(account), name.to_string());
+            }
+        }
+        Self { names }
+    }
+
+    pub fn get(&self, account: &Account) -> Option<&str> {
+        self.names.get(account).map(String::as_str)
+    }
+
+    /// Name to show for the account in lists of full paths, like the register.
+    pub fn full_name(&self, account: &Account) -> String {
+        self.get(account)
+            .map_or_else(|| account.to_string(), str::to_string)
+    }
+
+    /// Name to show for the account under its parent, like in the accounts tree.
+    pub fn short_name<'a>(&'a self, account: &'a Account) -> &'a str {
+        self.get(account).unwrap_or_else(|| account.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aliases() {
+        let journal = "\
+alias checking = assets:bank:se:nordea:1234
+alias cc=liabilities:amex ; personal card
+
+account expenses:food:groceries
+    alias food
+    note weekly shopping
+account assets:cash
+alias broken
+
+2025-01-01 Shop
+    food  10 USD
+    checking
+";
+        let aliases = parse_aliases(journal);
+        assert_eq!(
+            aliases,
+            vec![
+                (
+                    "checking".to_string(),
+                    Account::parse("assets:bank:se:nordea:1234")
+                ),
+                ("cc".to_string(), Account::parse("liabilities:amex")),
+                (
+                    "food".to_string(),
+                    Account::parse("expenses:food:groceries")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display_names_override_aliases() {
+        let aliases = vec![
+            ("checking".to_string(), Account::parse("assets:bank:nordea")),
+            ("main".to_string(), Account::parse("assets:bank:nordea")),
+            ("cc".to_string(), Account::parse("liabilities:amex")),
+        ];
+        let display_names =
+            BTreeMap::from([("liabilities:amex".to_string(), "Amex Gold".to_string())]);
+        let names = AccountNames::new(&aliases, &display_names);
+
+        let nordea = Account::parse("assets:bank:nordea");
+        assert_eq!(names.get(&nordea), Some("checking"));
+        assert_eq!(
+            names.full_name(&Account::parse("liabilities:amex")),
+            "Amex Gold"
+        );
+
+        let cash = Account::parse("assets:cash");
+        assert_eq!(names.full_name(&cash), "assets:cash");
+        assert_eq!(names.short_name(&cash), "cash");
+    }
+}
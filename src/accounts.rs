@@ -1,9 +1,11 @@
 use core::fmt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
+use chrono::NaiveDate;
 use fastnum::D128;
 
-use crate::transactions::CurrencyAmount;
+use crate::transactions::{CurrencyAmount, Posting, PostingStatus, Transaction};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Account {
@@ -16,6 +18,12 @@ impl fmt::Display for Account {
     }
 }
 
+impl serde::Serialize for Account {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl Account {
     pub fn is_parent_of(&self, other: &Account) -> bool {
         if self.segments.len() >= other.segments.len() {
@@ -53,7 +61,6 @@ impl Account {
         self.segments.last().unwrap()
     }
 
-    #[cfg(test)]
     pub fn parent(&self) -> Option<Account> {
         if self.segments.len() > 1 {
             Some(Account {
@@ -78,13 +85,20 @@ pub struct Balance {
 impl fmt::Display for Balance {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut parts = Vec::new();
-        for amount in self.by_commodity.values() {
+        for amount in self.sorted_amounts() {
             parts.push(format!("{}", amount));
         }
         write!(f, "{}", parts.join(", "))
     }
 }
 
+/// Amounts sorted by commodity.
+impl serde::Serialize for Balance {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.sorted_amounts())
+    }
+}
+
 impl Balance {
     pub fn new() -> Self {
         Self {
@@ -102,12 +116,256 @@ impl Balance {
             });
         entry.value += amount.value;
     }
+
+    pub fn get(&self, commodity: &str) -> Option<&CurrencyAmount> {
+        self.by_commodity.get(commodity)
+    }
+
+    pub fn amounts(&self) -> impl Iterator<Item = &CurrencyAmount> {
+        self.by_commodity.values()
+    }
+
+    /// Amounts ordered by commodity, for output that does not change between runs.
+    pub fn sorted_amounts(&self) -> Vec<&CurrencyAmount> {
+        let mut amounts = self.by_commodity.values().collect::<Vec<_>>();
+        amounts.sort_by(|a, b| a.commodity.cmp(&b.commodity));
+        amounts
+    }
+
+    /// True when nothing has been added to the balance.
+    pub fn is_empty(&self) -> bool {
+        self.by_commodity.is_empty()
+    }
+
+    /// Largest absolute amount in any commodity, which balances are compared by when
+    /// sorting accounts.
+    pub fn magnitude(&self) -> D128 {
+        self.amounts()
+            .map(|amount| amount.value.abs())
+            .max()
+            .unwrap_or(D128::ZERO)
+    }
+
+    /// True when every commodity sums to zero, which an empty balance does too.
+    pub fn is_zero(&self) -> bool {
+        self.by_commodity
+            .values()
+            .all(|amount| amount.value.is_zero())
+    }
+
+    /// The balance with commodities that have a rate converted to `target` and summed,
+    /// and the rest kept as they are.
+    pub fn convert(&self, target: &str, rate: impl Fn(&str) -> Option<D128>) -> Balance {
+        let mut converted = Balance::new();
+        for amount in self.by_commodity.values() {
+            match rate(&amount.commodity) {
+                Some(rate) if amount.commodity != target => converted.add_amount(CurrencyAmount {
+                    value: amount.value * rate,
+                    commodity: target.to_string(),
+                }),
+                _ => converted.add_amount(amount.clone()),
+            }
+        }
+        converted
+    }
+}
+
+impl AddAssign<&Balance> for Balance {
+    fn add_assign(&mut self, other: &Balance) {
+        for amount in other.by_commodity.values() {
+            self.add_amount(amount.clone());
+        }
+    }
+}
+
+impl SubAssign<&Balance> for Balance {
+    fn sub_assign(&mut self, other: &Balance) {
+        *self += &-other;
+    }
+}
+
+impl Add<&Balance> for Balance {
+    type Output = Balance;
+
+    fn add(mut self, other: &Balance) -> Balance {
+        self += other;
+        self
+    }
+}
+
+impl Sub<&Balance> for Balance {
+    type Output = Balance;
+
+    fn sub(mut self, other: &Balance) -> Balance {
+        self -= other;
+        self
+    }
+}
+
+impl Neg for &Balance {
+    type Output = Balance;
+
+    fn neg(self) -> Balance {
+        Balance {
+            by_commodity: self
+                .by_commodity
+                .iter()
+                .map(|(commodity, amount)| {
+                    let negated = CurrencyAmount {
+                        value: -amount.value,
+                        commodity: amount.commodity.clone(),
+                    };
+                    (commodity.clone(), negated)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Neg for Balance {
+    type Output = Balance;
+
+    fn neg(self) -> Balance {
+        -&self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    /// Balance must stay at or above the amount.
+    MinimumBalance,
+    /// Balance may go as far below zero as the amount.
+    CreditLimit,
+}
+
+/// A bound an account balance is expected to stay within.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountLimit {
+    pub kind: LimitKind,
+    /// Limit amount; an empty commodity applies it to every commodity.
+    pub amount: CurrencyAmount,
+}
+
+impl AccountLimit {
+    /// Lowest balance that does not violate the limit.
+    pub fn floor(&self) -> D128 {
+        match self.kind {
+            LimitKind::MinimumBalance => self.amount.value,
+            LimitKind::CreditLimit => -self.amount.value.abs(),
+        }
+    }
+
+    pub fn is_violated_by(&self, balance: &Balance) -> bool {
+        let floor = self.floor();
+        if self.amount.commodity.is_empty() {
+            balance.amounts().any(|amount| amount.value < floor)
+        } else {
+            balance
+                .get(&self.amount.commodity)
+                .is_some_and(|amount| amount.value < floor)
+        }
+    }
+}
+
+/// Accounts a register is narrowed to, each with its subaccounts: postings to the
+/// included accounts, or to any when none are, except postings to excluded accounts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountFilter {
+    pub include: HashSet<Account>,
+    pub exclude: HashSet<Account>,
+}
+
+impl AccountFilter {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, account: &Account) -> bool {
+        let covers = |filter: &Account| filter == account || filter.is_parent_of(account);
+        (self.include.is_empty() || self.include.iter().any(covers))
+            && !self.exclude.iter().any(covers)
+    }
+}
+
+/// Order accounts are listed in under their parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountSort {
+    /// As they first appear in the journal.
+    #[default]
+    Journal,
+    Name,
+    /// Largest balance first.
+    Balance,
+    /// Most recently posted to first.
+    Activity,
+    /// Most transactions first.
+    Transactions,
+}
+
+impl AccountSort {
+    /// Next order when cycling through them.
+    pub fn next(self) -> Self {
+        match self {
+            AccountSort::Journal => AccountSort::Name,
+            AccountSort::Name => AccountSort::Balance,
+            AccountSort::Balance => AccountSort::Activity,
+            AccountSort::Activity => AccountSort::Transactions,
+            AccountSort::Transactions => AccountSort::Journal,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AccountSort::Journal => "Journal",
+            AccountSort::Name => "Name",
+            AccountSort::Balance => "Balance",
+            AccountSort::Activity => "Activity",
+            AccountSort::Transactions => "Transactions",
+        }
+    }
+}
+
+/// How an account, with its subaccounts, is used in the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AccountActivity {
+    /// Date of the latest transaction posting to it.
+    pub last: Option<NaiveDate>,
+    /// Transactions posting to it.
+    pub transactions: usize,
+}
+
+/// Activity of every account posted to and of its ancestors.
+pub fn activity<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> HashMap<Account, AccountActivity> {
+    let mut activity = HashMap::<Account, AccountActivity>::new();
+    for transaction in transactions {
+        let mut accounts = HashSet::new();
+        for posting in &transaction.postings {
+            let mut account = Some(posting.account.clone());
+            while let Some(current) = account {
+                account = current.parent();
+                if !accounts.insert(current) {
+                    break;
+                }
+            }
+        }
+        for account in accounts {
+            let entry = activity.entry(account).or_default();
+            entry.last = entry.last.max(Some(transaction.time));
+            entry.transactions += 1;
+        }
+    }
+    activity
 }
 
 #[derive(Clone)]
 pub struct TreeNode {
     pub account: Account,
     pub balance: Balance,
+    /// Balance of cleared postings only, what a bank statement should show.
+    pub cleared_balance: Balance,
     pub children: Vec<TreeNode>,
 }
 
@@ -116,6 +374,7 @@ impl TreeNode {
         Self {
             account: Account::empty(),
             balance: Balance::new(),
+            cleared_balance: Balance::new(),
             children: Vec::new(),
         }
     }
@@ -124,6 +383,7 @@ impl TreeNode {
         self.children = Vec::new();
         self.account = Account::empty();
         self.balance = Balance::new();
+        self.cleared_balance = Balance::new();
     }
 
     pub fn add_account(&mut self, account: &Account) {
@@ -149,6 +409,7 @@ impl TreeNode {
                 self.children.push(TreeNode {
                     account: current,
                     balance: Balance::new(),
+                    cleared_balance: Balance::new(),
                     children: Vec::new(),
                 });
                 self.children.len() - 1
@@ -160,14 +421,95 @@ impl TreeNode {
         child.add_account_recursive(account, depth + 1)
     }
 
+    /// All accounts below this node, parents before their children.
+    pub fn accounts(&self) -> Vec<Account> {
+        let mut accounts = Vec::new();
+        for child in &self.children {
+            accounts.push(child.account.clone());
+            accounts.extend(child.accounts());
+        }
+        accounts
+    }
+
+    /// Copy of the tree with accounts deeper than `depth` folded into their ancestor,
+    /// like `ledger bal --depth N`, and without zero balances when `hide_zero` is set.
+    ///
+    /// Balances already include subaccounts, so folding only drops children. A zero
+    /// balance is kept while it has non-zero subaccounts to show.
+    pub fn pruned(&self, depth: Option<usize>, hide_zero: bool) -> TreeNode {
+        let children = if depth.is_some_and(|depth| self.account.segments.len() >= depth) {
+            Vec::new()
+        } else {
+            self.children
+                .iter()
+                .map(|child| child.pruned(depth, hide_zero))
+                .filter(|child| {
+                    !(hide_zero && child.balance.is_zero() && child.children.is_empty())
+                })
+                .collect()
+        };
+        TreeNode {
+            account: self.account.clone(),
+            balance: self.balance.clone(),
+            cleared_balance: self.cleared_balance.clone(),
+            children,
+        }
+    }
+
+    /// Orders the children at every level, by name when they tie. `transactions` are
+    /// only looked at to sort by activity or transaction count.
+    pub fn sort<'a>(
+        &mut self,
+        sort: AccountSort,
+        transactions: impl IntoIterator<Item = &'a Transaction>,
+    ) {
+        let activity = match sort {
+            AccountSort::Activity | AccountSort::Transactions => activity(transactions),
+            _ => HashMap::new(),
+        };
+        self.sort_by(sort, &activity);
+    }
+
+    fn sort_by(&mut self, sort: AccountSort, activity: &HashMap<Account, AccountActivity>) {
+        if sort == AccountSort::Journal {
+            return;
+        }
+        let of = |node: &TreeNode| activity.get(&node.account).copied().unwrap_or_default();
+        self.children.sort_by(|a, b| {
+            let order = match sort {
+                AccountSort::Journal | AccountSort::Name => std::cmp::Ordering::Equal,
+                AccountSort::Balance => b.balance.magnitude().cmp(&a.balance.magnitude()),
+                AccountSort::Activity => of(b).last.cmp(&of(a).last),
+                AccountSort::Transactions => of(b).transactions.cmp(&of(a).transactions),
+            };
+            order.then_with(|| {
+                a.account
+                    .name()
+                    .to_lowercase()
+                    .cmp(&b.account.name().to_lowercase())
+            })
+        });
+        for child in &mut self.children {
+            child.sort_by(sort, activity);
+        }
+    }
+
+    #[cfg(test)]
     pub fn add_amount_to_account(&mut self, account: &Account, amount: &CurrencyAmount) {
-        self.add_amount_recursive(account, amount, 0);
+        self.add_amount_recursive(account, amount, false, 0);
+    }
+
+    /// Adds a posting's amount, counting it towards the cleared balance when cleared.
+    pub fn add_posting(&mut self, posting: &Posting) {
+        let cleared = posting.status == PostingStatus::Cleared;
+        self.add_amount_recursive(&posting.account, &posting.amount.value, cleared, 0);
     }
 
     fn add_amount_recursive(
         &mut self,
         account: &Account,
         amount: &CurrencyAmount,
+        cleared: bool,
         depth: usize,
     ) -> bool {
         if depth >= account.segments.len() {
@@ -182,15 +524,15 @@ impl TreeNode {
             .iter_mut()
             .find(|child| child.account.eq(&current))
         {
-            // If this is the target account, add the amount
-            if child.account.eq(account) {
-                child.balance.add_amount(amount.clone());
-                return true;
-            }
-
-            // Otherwise, recurse to children and if found, add to this node's balance too
-            if child.add_amount_recursive(account, amount, depth + 1) {
+            // If this is the target account, or it is found among the children, add the
+            // amount to this node's balance
+            if child.account.eq(account)
+                || child.add_amount_recursive(account, amount, cleared, depth + 1)
+            {
                 child.balance.add_amount(amount.clone());
+                if cleared {
+                    child.cleared_balance.add_amount(amount.clone());
+                }
                 return true;
             }
         }
@@ -231,6 +573,209 @@ mod tests {
         assert_eq!(account.depth(), 1);
     }
 
+    fn balance(amounts: &[&str]) -> Balance {
+        let mut balance = Balance::new();
+        for amount in amounts {
+            balance.add_amount(CurrencyAmount::parse(amount).unwrap());
+        }
+        balance
+    }
+
+    #[test]
+    fn test_balance_arithmetic() {
+        let a = balance(&["10 USD", "2 EUR"]);
+        let b = balance(&["4 USD", "1 BTC"]);
+
+        assert_eq!(
+            (a.clone() + &b).to_string(),
+            balance(&["1 BTC", "2 EUR", "14 USD"]).to_string()
+        );
+        let difference = a.clone() - &b;
+        assert_eq!(difference.get("USD").unwrap().value, D128::from(6));
+        assert_eq!(difference.get("BTC").unwrap().value, D128::from(-1));
+        assert_eq!((-&a).get("EUR").unwrap().value, D128::from(-2));
+
+        let zero = a.clone() - &a;
+        assert!(!zero.is_empty());
+        assert!(zero.is_zero());
+        assert!(Balance::new().is_empty());
+        assert!(!a.is_zero());
+
+        let commodities = (a + &b)
+            .sorted_amounts()
+            .into_iter()
+            .map(|amount| amount.commodity.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(commodities, ["BTC", "EUR", "USD"]);
+    }
+
+    #[test]
+    fn test_balance_convert() {
+        let balance = balance(&["10 USD", "2 EUR", "1 XAU"]);
+        let converted = balance.convert("USD", |commodity| match commodity {
+            "EUR" => Some(D128::from(2)),
+            "USD" => Some(D128::from(1)),
+            _ => None,
+        });
+        assert_eq!(converted.get("USD").unwrap().value, D128::from(14));
+        assert_eq!(converted.get("XAU").unwrap().value, D128::from(1));
+        assert!(converted.get("EUR").is_none());
+    }
+
+    #[test]
+    fn test_pruned() {
+        let mut tree = TreeNode::new();
+        for (account, amount) in [
+            ("assets:bank:checking", "100 USD"),
+            ("assets:bank:savings", "-100 USD"),
+            ("assets:cash", "0 USD"),
+            ("expenses:food", "20 USD"),
+        ] {
+            let account = Account::parse(account);
+            tree.add_account(&account);
+            tree.add_amount_to_account(&account, &CurrencyAmount::parse(amount).unwrap());
+        }
+
+        let shallow = tree.pruned(Some(1), false);
+        assert_eq!(
+            shallow.accounts(),
+            [Account::parse("assets"), Account::parse("expenses")]
+        );
+        assert_eq!(
+            shallow.children[1].balance.get("USD").unwrap().value,
+            D128::from(20)
+        );
+
+        // assets and assets:bank sum to zero but still have accounts to show
+        let non_zero = tree.pruned(None, true);
+        assert_eq!(
+            non_zero.accounts(),
+            [
+                Account::parse("assets"),
+                Account::parse("assets:bank"),
+                Account::parse("assets:bank:checking"),
+                Account::parse("assets:bank:savings"),
+                Account::parse("expenses"),
+                Account::parse("expenses:food"),
+            ]
+        );
+        assert_eq!(
+            tree.pruned(Some(2), true).accounts(),
+            [Account::parse("expenses"), Account::parse("expenses:food")]
+        );
+    }
+
+    #[test]
+    fn test_account_filter() {
+        let accounts = |names: &[&str]| names.iter().map(|name| Account::parse(name)).collect();
+        let filter = AccountFilter {
+            include: accounts(&["expenses"]),
+            exclude: accounts(&["expenses:pending", "assets:transfers"]),
+        };
+        assert!(filter.matches(&Account::parse("expenses:food")));
+        assert!(!filter.matches(&Account::parse("expenses:pending")));
+        assert!(!filter.matches(&Account::parse("expenses:pending:refund")));
+        assert!(!filter.matches(&Account::parse("assets:checking")));
+
+        let exclude_only = AccountFilter {
+            exclude: filter.exclude,
+            ..AccountFilter::default()
+        };
+        assert!(exclude_only.matches(&Account::parse("assets:checking")));
+        assert!(!exclude_only.matches(&Account::parse("assets:transfers")));
+        assert!(AccountFilter::default().is_empty());
+        assert!(!exclude_only.is_empty());
+    }
+
+    #[test]
+    fn test_sort() {
+        let transaction = |time: &str, accounts: &[&str]| {
+            let postings = accounts
+                .iter()
+                .map(|account| (*account, "1 USD"))
+                .collect::<Vec<_>>();
+            let time = NaiveDate::parse_from_str(time, "%Y-%m-%d").unwrap();
+            Transaction::fixture(time, "Test", &postings)
+        };
+        let transactions = [
+            transaction("2025-01-01", &["expenses:rent", "assets:bank"]),
+            transaction("2025-01-05", &["expenses:food", "assets:cash"]),
+            transaction("2025-01-03", &["expenses:food", "assets:cash"]),
+            transaction("2025-01-04", &["income:salary", "assets:bank"]),
+        ];
+
+        let mut tree = TreeNode::new();
+        for (account, amount) in [
+            ("expenses:rent", "900 USD"),
+            ("expenses:food", "-50 EUR"),
+            ("income:salary", "20 USD"),
+        ] {
+            let account = Account::parse(account);
+            tree.add_account(&account);
+            tree.add_amount_to_account(&account, &CurrencyAmount::parse(amount).unwrap());
+        }
+        let sorted = |sort| {
+            let mut tree = tree.pruned(None, false);
+            tree.sort(sort, &transactions);
+            tree.children[0]
+                .children
+                .iter()
+                .map(|child| child.account.name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(AccountSort::Journal), ["rent", "food"]);
+        assert_eq!(sorted(AccountSort::Name), ["food", "rent"]);
+        assert_eq!(sorted(AccountSort::Balance), ["rent", "food"]);
+        assert_eq!(sorted(AccountSort::Activity), ["food", "rent"]);
+        assert_eq!(sorted(AccountSort::Transactions), ["food", "rent"]);
+
+        let activity = activity(&transactions);
+        assert_eq!(
+            activity[&Account::parse("assets")],
+            AccountActivity {
+                last: NaiveDate::from_ymd_opt(2025, 1, 5),
+                transactions: 4,
+            }
+        );
+        assert_eq!(activity[&Account::parse("expenses:food")].transactions, 2);
+    }
+
+    #[test]
+    fn test_account_limit() {
+        use fastnum::D128;
+
+        let mut balance = Balance::new();
+        balance.add_amount(CurrencyAmount {
+            value: "-600.00".parse::<D128>().unwrap(),
+            commodity: "USD".to_string(),
+        });
+
+        let credit = AccountLimit {
+            kind: LimitKind::CreditLimit,
+            amount: CurrencyAmount::parse("500 USD").unwrap(),
+        };
+        assert!(credit.is_violated_by(&balance));
+
+        let credit = AccountLimit {
+            kind: LimitKind::CreditLimit,
+            amount: CurrencyAmount::parse("1000").unwrap(),
+        };
+        assert!(!credit.is_violated_by(&balance));
+
+        let minimum = AccountLimit {
+            kind: LimitKind::MinimumBalance,
+            amount: CurrencyAmount::parse("-700 USD").unwrap(),
+        };
+        assert!(!minimum.is_violated_by(&balance));
+
+        let minimum = AccountLimit {
+            kind: LimitKind::MinimumBalance,
+            amount: CurrencyAmount::parse("0 EUR").unwrap(),
+        };
+        assert!(!minimum.is_violated_by(&balance));
+    }
+
     #[test]
     fn test_tree_single_account() {
         let mut tree = TreeNode::new();
@@ -268,6 +813,24 @@ mod tests {
         let bank = &assets.children[0];
         assert_eq!(bank.account, Account::parse("assets:bank"));
         assert_eq!(bank.children.len(), 2); // checking and savings
+
+        let accounts = tree
+            .accounts()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            accounts,
+            vec![
+                "assets",
+                "assets:bank",
+                "assets:bank:checking",
+                "assets:bank:savings",
+                "assets:cash",
+                "expenses",
+                "expenses:groceries",
+            ]
+        );
     }
 
     #[test]
@@ -387,4 +950,28 @@ mod tests {
         let assets_balance = assets.balance.to_string();
         assert!(assets_balance.contains("100.00 USD") && assets_balance.contains("50.00 EUR"));
     }
+
+    #[test]
+    fn test_cleared_balance() {
+        use crate::transactions::Amount;
+
+        let mut tree = TreeNode::new();
+        let checking = Account::parse("assets:bank:checking");
+        tree.add_account(&checking);
+
+        let posting = |amount: &str, status: PostingStatus| Posting {
+            account: checking.clone(),
+            amount: Amount::parse(amount).expect("should parse amount"),
+            status,
+            note: None,
+        };
+        tree.add_posting(&posting("100.00 USD", PostingStatus::Cleared));
+        tree.add_posting(&posting("-30.00 USD", PostingStatus::Pending));
+        tree.add_posting(&posting("-20.00 USD", PostingStatus::Uncleared));
+
+        let bank = &tree.children[0].children[0];
+        assert_eq!(bank.balance.to_string(), "50.00 USD");
+        assert_eq!(bank.cleared_balance.to_string(), "100.00 USD");
+        assert_eq!(bank.children[0].cleared_balance.to_string(), "100.00 USD");
+    }
 }
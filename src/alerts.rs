@@ -0,0 +1,264 @@
+//! Alert rules checked against the journal after each reload.
+//!
+//! A rule compares an account with its budget or with an amount:
+//!
+//! - `expenses:dining > budget` – this month's spending, including subaccounts, is over
+//!   what the account's envelope has, its budget plus what rolled over
+//! - `assets:checking < 500 SEK` – the balance in SEK, including subaccounts, is under
+//!   500 SEK
+//!
+//! Any of the register's comparisons can be used: `<`, `<=`, `=`, `!=`, `>=` and `>`.
+
+use chrono::NaiveDate;
+use fastnum::D128;
+
+use crate::accounts::Account;
+use crate::amount_format::AmountFormatter;
+use crate::budget::{self, BudgetLine};
+use crate::query::Comparison;
+use crate::transactions::{CurrencyAmount, Transaction};
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum AlertRuleError {
+    #[error("`{0}` has no comparison, expected e.g. `assets:checking < 500 SEK`")]
+    MissingComparison(String),
+    #[error("`{0}` has no account")]
+    MissingAccount(String),
+    #[error("invalid amount `{0}`, expected `budget` or e.g. `500 SEK`")]
+    InvalidAmount(String),
+}
+
+/// What an account is compared with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Threshold {
+    /// Money in the account's envelopes this month.
+    Budget,
+    /// Balance in the amount's commodity.
+    Amount(CurrencyAmount),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertRule {
+    /// The rule as written, which alerts are snoozed by.
+    pub text: String,
+    pub account: Account,
+    pub comparison: Comparison,
+    pub threshold: Threshold,
+}
+
+impl AlertRule {
+    pub fn parse(text: &str) -> Result<Self, AlertRuleError> {
+        let text = text.trim();
+        let (account, (comparison, threshold)) = text
+            .find(['<', '>', '=', '!'])
+            .and_then(|ix| Some((&text[..ix], Comparison::split(&text[ix..])?)))
+            .ok_or_else(|| AlertRuleError::MissingComparison(text.to_string()))?;
+        let account = account.trim();
+        if account.is_empty() {
+            return Err(AlertRuleError::MissingAccount(text.to_string()));
+        }
+        let threshold = threshold.trim();
+        let threshold = if threshold.eq_ignore_ascii_case("budget") {
+            Threshold::Budget
+        } else {
+            CurrencyAmount::parse(threshold)
+                .map(Threshold::Amount)
+                .map_err(|_| AlertRuleError::InvalidAmount(threshold.to_string()))?
+        };
+        Ok(Self {
+            text: text.to_string(),
+            account: Account::parse(account),
+            comparison,
+            threshold,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertKind {
+    /// Spent this month out of what the envelope had.
+    Budget {
+        spent: CurrencyAmount,
+        available: CurrencyAmount,
+    },
+    Balance(CurrencyAmount),
+}
+
+/// A rule that holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    pub rule: String,
+    pub kind: AlertKind,
+}
+
+impl Alert {
+    pub fn message(&self, formatter: &AmountFormatter) -> String {
+        match &self.kind {
+            AlertKind::Budget { spent, available } => format!(
+                "Spent {} of {} this month",
+                formatter.format(spent),
+                formatter.format(available)
+            ),
+            AlertKind::Balance(balance) => format!("Balance is {}", formatter.format(balance)),
+        }
+    }
+}
+
+/// Alerts of the rules that hold on `today`, in the order of the rules. A budget rule
+/// alerts for each commodity the account is budgeted in.
+pub fn check(
+    rules: &[AlertRule],
+    budget: &[BudgetLine],
+    transactions: &[&Transaction],
+    today: NaiveDate,
+) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    for rule in rules {
+        match &rule.threshold {
+            Threshold::Budget => {
+                let lines = budget
+                    .iter()
+                    .filter(|line| line.account == rule.account)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for envelope in budget::envelopes(&lines, transactions, today) {
+                    let Some(month) = envelope.current() else {
+                        continue;
+                    };
+                    let available = month.budgeted + month.carried;
+                    if rule.comparison.holds(&month.spent, &available) {
+                        let amount = |value| CurrencyAmount {
+                            value,
+                            commodity: envelope.commodity.clone(),
+                        };
+                        alerts.push(Alert {
+                            rule: rule.text.clone(),
+                            kind: AlertKind::Budget {
+                                spent: amount(month.spent),
+                                available: amount(available),
+                            },
+                        });
+                    }
+                }
+            }
+            Threshold::Amount(threshold) => {
+                let value = transactions
+                    .iter()
+                    .filter(|transaction| transaction.time <= today)
+                    .flat_map(|transaction| &transaction.postings)
+                    .filter(|posting| {
+                        (posting.account == rule.account
+                            || rule.account.is_parent_of(&posting.account))
+                            && posting.amount.value.commodity == threshold.commodity
+                    })
+                    .fold(D128::ZERO, |total, posting| {
+                        total + posting.amount.value.value
+                    });
+                if rule.comparison.holds(&value, &threshold.value) {
+                    alerts.push(Alert {
+                        rule: rule.text.clone(),
+                        kind: AlertKind::Balance(CurrencyAmount {
+                            value,
+                            commodity: threshold.commodity.clone(),
+                        }),
+                    });
+                }
+            }
+        }
+    }
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn transaction(time: &str, to: &str, from: &str, amount: &str) -> Transaction {
+        Transaction::fixture(
+            date(time),
+            "Test",
+            &[(to, amount), (from, &format!("-{amount}"))],
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            AlertRule::parse(" expenses:dining > budget "),
+            Ok(AlertRule {
+                text: "expenses:dining > budget".into(),
+                account: Account::parse("expenses:dining"),
+                comparison: Comparison::Greater,
+                threshold: Threshold::Budget,
+            })
+        );
+        assert_eq!(
+            AlertRule::parse("assets:checking<=500 SEK").map(|rule| rule.threshold),
+            Ok(Threshold::Amount(CurrencyAmount::parse("500 SEK").unwrap()))
+        );
+        assert_eq!(
+            AlertRule::parse("assets:checking"),
+            Err(AlertRuleError::MissingComparison("assets:checking".into()))
+        );
+        assert_eq!(
+            AlertRule::parse("< 500 SEK"),
+            Err(AlertRuleError::MissingAccount("< 500 SEK".into()))
+        );
+        assert_eq!(
+            AlertRule::parse("assets:checking < lots"),
+            Err(AlertRuleError::InvalidAmount("lots".into()))
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let transactions = [
+            transaction("2025-03-01", "assets:checking", "income:salary", "1000 SEK"),
+            transaction(
+                "2025-03-02",
+                "expenses:dining:cafe",
+                "assets:checking",
+                "300 SEK",
+            ),
+            transaction(
+                "2025-03-05",
+                "expenses:dining",
+                "assets:checking",
+                "250 SEK",
+            ),
+            // Not yet on the day alerts are checked
+            transaction("2025-03-20", "expenses:rent", "assets:checking", "400 SEK"),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let budget = budget::parse_budget("~ Monthly\n    expenses:dining  500 SEK\n");
+        let rules = [
+            "expenses:dining > budget",
+            "expenses:rent > budget",
+            "assets:checking < 500 SEK",
+            "assets:checking < 100 SEK",
+        ]
+        .map(|rule| AlertRule::parse(rule).unwrap());
+        let sek = |amount: &str| CurrencyAmount::parse(amount).unwrap();
+
+        assert_eq!(
+            check(&rules, &budget, &transactions, date("2025-03-10")),
+            [
+                Alert {
+                    rule: "expenses:dining > budget".into(),
+                    kind: AlertKind::Budget {
+                        spent: sek("550 SEK"),
+                        available: sek("500 SEK"),
+                    },
+                },
+                Alert {
+                    rule: "assets:checking < 500 SEK".into(),
+                    kind: AlertKind::Balance(sek("450 SEK")),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,422 @@
+//! Display formatting for amounts.
+//!
+//! Amounts are rounded to the commodity's precision, grouped with a thousands
+//! separator and shown with the commodity before or after the number.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use fastnum::D128;
+
+use crate::accounts::Balance;
+use crate::commodities::CommodityRegistry;
+use crate::transactions::{Amount, Cost, CurrencyAmount};
+
+/// Where the commodity goes relative to the number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolPosition {
+    /// `$1,000.00`
+    Before,
+    /// `1,000.00 USD`
+    #[default]
+    After,
+}
+
+impl SymbolPosition {
+    pub fn next(self) -> Self {
+        match self {
+            SymbolPosition::Before => SymbolPosition::After,
+            SymbolPosition::After => SymbolPosition::Before,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SymbolPosition::Before => "Before",
+            SymbolPosition::After => "After",
+        }
+    }
+}
+
+/// How negative amounts are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NegativeStyle {
+    /// `-1,000.00 USD`
+    #[default]
+    Minus,
+    /// `(1,000.00 USD)`
+    Parentheses,
+}
+
+impl NegativeStyle {
+    pub fn next(self) -> Self {
+        match self {
+            NegativeStyle::Minus => NegativeStyle::Parentheses,
+            NegativeStyle::Parentheses => NegativeStyle::Minus,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NegativeStyle::Minus => "-1.00",
+            NegativeStyle::Parentheses => "(1.00)",
+        }
+    }
+}
+
+/// User preferences for writing amounts.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct AmountFormat {
+    pub thousands_separator: String,
+    pub decimal_separator: String,
+    pub symbol_position: SymbolPosition,
+    pub negative_style: NegativeStyle,
+    /// Whether views draw negative amounts in the theme's danger color.
+    pub red_negatives: bool,
+    /// Decimal places by commodity, overriding the journal's precision.
+    pub decimal_places: BTreeMap<String, usize>,
+}
+
+impl Default for AmountFormat {
+    fn default() -> Self {
+        Self {
+            thousands_separator: ",".to_string(),
+            decimal_separator: ".".to_string(),
+            symbol_position: SymbolPosition::default(),
+            negative_style: NegativeStyle::default(),
+            red_negatives: false,
+            decimal_places: BTreeMap::new(),
+        }
+    }
+}
+
+/// Decimal places each commodity is written with in the journal.
+///
+/// Like ledger, the precision of a commodity is the largest one seen in its amounts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommodityPrecision(HashMap<String, usize>);
+
+impl CommodityPrecision {
+    pub fn record(&mut self, amount: &CurrencyAmount) {
+        let places = usize::try_from(amount.value.fractional_digits_count()).unwrap_or(0);
+        let precision = self.0.entry(amount.commodity.clone()).or_insert(0);
+        *precision = (*precision).max(places);
+    }
+
+    pub fn get(&self, commodity: &str) -> Option<usize> {
+        self.0.get(commodity).copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Characters amounts are padded to after their integer part, so amounts right-aligned
+/// in a monospace font line up on the decimal separator and their commodities line up
+/// in a column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Alignment {
+    /// Decimal separator and fraction digits.
+    fraction: usize,
+    /// Commodity and closing parenthesis after the number.
+    suffix: usize,
+}
+
+/// An amount as written, split where a column of amounts is aligned.
+struct Parts {
+    /// Sign, commodity written before the number and integer digits.
+    whole: String,
+    fraction: String,
+    suffix: String,
+}
+
+/// Writes amounts according to an [`AmountFormat`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmountFormatter {
+    format: AmountFormat,
+    /// Decimal places for commodities without a configured or journal precision
+    default_places: usize,
+    precision: CommodityPrecision,
+    commodities: CommodityRegistry,
+}
+
+impl AmountFormatter {
+    pub fn new(
+        format: AmountFormat,
+        default_places: usize,
+        precision: CommodityPrecision,
+        commodities: CommodityRegistry,
+    ) -> Self {
+        Self {
+            format,
+            default_places,
+            precision,
+            commodities,
+        }
+    }
+
+    /// Name the commodity is shown as.
+    pub fn commodity_name<'a>(&'a self, commodity: &'a str) -> &'a str {
+        self.commodities.display(commodity)
+    }
+
+    /// Decimal places used for the commodity.
+    pub fn places(&self, commodity: &str) -> usize {
+        self.format
+            .decimal_places
+            .get(commodity)
+            .copied()
+            .or_else(|| self.precision.get(commodity))
+            .unwrap_or(self.default_places)
+    }
+
+    /// Whether the amount should be drawn in the danger color.
+    pub fn is_highlighted(&self, value: D128) -> bool {
+        self.format.red_negatives && value.is_negative()
+    }
+
+    pub fn format(&self, amount: &CurrencyAmount) -> String {
+        let parts = self.parts(amount);
+        format!("{}{}{}", parts.whole, parts.fraction, parts.suffix)
+    }
+
+    /// Alignment fitting every one of the amounts.
+    pub fn alignment<'a>(
+        &self,
+        amounts: impl IntoIterator<Item = &'a CurrencyAmount>,
+    ) -> Alignment {
+        amounts.into_iter().map(|amount| self.parts(amount)).fold(
+            Alignment::default(),
+            |alignment, parts| Alignment {
+                fraction: alignment.fraction.max(parts.fraction.chars().count()),
+                suffix: alignment.suffix.max(parts.suffix.chars().count()),
+            },
+        )
+    }
+
+    /// Formats the amount padded with spaces to the alignment, for showing it
+    /// right-aligned in a monospace font.
+    pub fn format_aligned(&self, amount: &CurrencyAmount, alignment: Alignment) -> String {
+        let parts = self.parts(amount);
+        format!(
+            "{}{:<fraction$}{:<suffix$}",
+            parts.whole,
+            parts.fraction,
+            parts.suffix,
+            fraction = alignment.fraction,
+            suffix = alignment.suffix,
+        )
+    }
+
+    fn parts(&self, amount: &CurrencyAmount) -> Parts {
+        let places = self.places(&amount.commodity);
+        let scale = i16::try_from(places).unwrap_or(i16::MAX);
+        let digits = amount.value.abs().rescale(scale).to_string();
+        self.layout_parts(amount.value.is_negative(), &digits, &amount.commodity)
+    }
+
+    /// Formats each commodity of a balance, sorted by commodity.
+    pub fn format_balance(&self, balance: &Balance) -> String {
+        balance
+            .sorted_amounts()
+            .into_iter()
+            .map(|amount| self.format(amount))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Formats a posting amount along with its lot price, date and cost.
+    pub fn format_amount(&self, amount: &Amount) -> String {
+        let mut text = self.format(&amount.value);
+        if let Some(price) = &amount.price {
+            let _ = write!(text, " {{{}}}", self.format(price));
+        }
+        if let Some(date) = &amount.date {
+            let _ = write!(text, " [{}]", date.format("%Y/%m/%d"));
+        }
+        match &amount.cost {
+            Some(Cost::PerUnit(cost)) => {
+                let _ = write!(text, " @ {}", self.format(cost));
+            }
+            Some(Cost::Total(cost)) => {
+                let _ = write!(text, " @@ {}", self.format(cost));
+            }
+            None => {}
+        }
+        text
+    }
+
+    /// Formats a chart value, which has already lost decimal precision.
+    pub fn format_f64(&self, value: f64, commodity: &str) -> String {
+        let places = self.places(commodity);
+        let digits = format!("{:.*}", places, value.abs());
+        self.layout(value < 0.0, &digits, commodity)
+    }
+
+    /// Assembles separators, commodity and sign around plain `1234.50` digits.
+    fn layout(&self, negative: bool, digits: &str, commodity: &str) -> String {
+        let parts = self.layout_parts(negative, digits, commodity);
+        format!("{}{}{}", parts.whole, parts.fraction, parts.suffix)
+    }
+
+    fn layout_parts(&self, negative: bool, digits: &str, commodity: &str) -> Parts {
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let integer = group_thousands(integer, &self.format.thousands_separator);
+        let fraction = if fraction.is_empty() {
+            String::new()
+        } else {
+            format!("{}{fraction}", self.format.decimal_separator)
+        };
+
+        let commodity = self.commodity_name(commodity);
+        let (prefix, suffix) = match (commodity.is_empty(), self.format.symbol_position) {
+            (true, _) => (String::new(), String::new()),
+            (false, SymbolPosition::Before) if is_symbol(commodity) => {
+                (commodity.to_string(), String::new())
+            }
+            (false, SymbolPosition::Before) => (format!("{commodity} "), String::new()),
+            (false, SymbolPosition::After) => (String::new(), format!(" {commodity}")),
+        };
+
+        // Values that round to zero are not shown as negative
+        let negative = negative && digits.bytes().any(|b| b.is_ascii_digit() && b != b'0');
+        let (open, close) = match (negative, self.format.negative_style) {
+            (false, _) => ("", ""),
+            (true, NegativeStyle::Minus) => ("-", ""),
+            (true, NegativeStyle::Parentheses) => ("(", ")"),
+        };
+        Parts {
+            whole: format!("{open}{prefix}{integer}"),
+            fraction,
+            suffix: format!("{suffix}{close}"),
+        }
+    }
+}
+
+/// Whether the commodity is a currency sign like `$` rather than a code like `USD`.
+fn is_symbol(commodity: &str) -> bool {
+    !commodity.chars().any(char::is_alphanumeric)
+}
+
+fn group_thousands(integer: &str, separator: &str) -> String {
+    let mut grouped = String::with_capacity(integer.len() + integer.len() / 3);
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i).is_multiple_of(3) {
+            grouped.push_str(separator);
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(s: &str) -> CurrencyAmount {
+        CurrencyAmount::parse(s).expect("should parse")
+    }
+
+    #[test]
+    fn test_group_thousands() {
+        assert_eq!(group_thousands("0", ","), "0");
+        assert_eq!(group_thousands("123", ","), "123");
+        assert_eq!(group_thousands("1234", ","), "1,234");
+        assert_eq!(group_thousands("1234567", " "), "1 234 567");
+    }
+
+    #[test]
+    fn test_format_defaults() {
+        let formatter = AmountFormatter::new(
+            AmountFormat::default(),
+            2,
+            CommodityPrecision::default(),
+            CommodityRegistry::default(),
+        );
+        assert_eq!(formatter.format(&amount("1234.5 CAD")), "1,234.50 CAD");
+        assert_eq!(
+            formatter.format(&amount("-1234567 SEK")),
+            "-1,234,567.00 kr"
+        );
+        assert_eq!(formatter.format(&amount("-0.001 CAD")), "0.00 CAD");
+        assert_eq!(formatter.format_f64(-12.345, "EUR"), "-12.35 €");
+        assert_eq!(
+            formatter.format_amount(&Amount::parse("10 AAPL {1500 CAD} [2025/01/02]").unwrap()),
+            "10.00 AAPL {1,500.00 CAD} [2025/01/02]"
+        );
+        assert_eq!(
+            formatter.format_amount(&Amount::parse("-20 AAPL @ 1500 CAD").unwrap()),
+            "-20.00 AAPL @ 1,500.00 CAD"
+        );
+
+        let mut balance = Balance::new();
+        balance.add_amount(amount("3 USD"));
+        balance.add_amount(amount("1234.5 CAD"));
+        balance.add_amount(amount("2 USD"));
+        assert_eq!(formatter.format_balance(&balance), "1,234.50 CAD, 5.00 $");
+    }
+
+    #[test]
+    fn test_format_custom() {
+        let format = AmountFormat {
+            thousands_separator: ".".to_string(),
+            decimal_separator: ",".to_string(),
+            symbol_position: SymbolPosition::Before,
+            negative_style: NegativeStyle::Parentheses,
+            decimal_places: BTreeMap::from([("BTC".to_string(), 8)]),
+            ..AmountFormat::default()
+        };
+        let commodities =
+            CommodityRegistry::new(&BTreeMap::from([("BTC".to_string(), "BTC".to_string())]));
+        let formatter = AmountFormatter::new(format, 2, CommodityPrecision::default(), commodities);
+        assert_eq!(formatter.format(&amount("-1234.5 USD")), "($1.234,50)");
+        assert_eq!(formatter.format(&amount("1234.5 CAD")), "CAD 1.234,50");
+        assert_eq!(formatter.format(&amount("0.5 BTC")), "BTC 0,50000000");
+    }
+
+    #[test]
+    fn test_format_aligned() {
+        let mut precision = CommodityPrecision::default();
+        precision.record(&amount("10 JPY"));
+        let formatter = AmountFormatter::new(
+            AmountFormat {
+                negative_style: NegativeStyle::Parentheses,
+                ..AmountFormat::default()
+            },
+            2,
+            precision,
+            CommodityRegistry::default(),
+        );
+        let amounts = [amount("1234.5 CAD"), amount("1500 JPY"), amount("-3 EUR")];
+        let alignment = formatter.alignment(&amounts);
+        let aligned = amounts
+            .iter()
+            .map(|amount| formatter.format_aligned(amount, alignment))
+            .collect::<Vec<_>>();
+        assert_eq!(aligned, ["1,234.50 CAD", "1,500    ¥  ", "(3.00 €) "]);
+        assert_eq!(formatter.format(&amounts[2]), "(3.00 €)");
+    }
+
+    #[test]
+    fn test_journal_precision() {
+        let mut precision = CommodityPrecision::default();
+        precision.record(&amount("10 JPY"));
+        precision.record(&amount("1.5 EUR"));
+        precision.record(&amount("1.125 EUR"));
+
+        let formatter = AmountFormatter::new(
+            AmountFormat::default(),
+            2,
+            precision,
+            CommodityRegistry::default(),
+        );
+        assert_eq!(formatter.places("JPY"), 0);
+        assert_eq!(formatter.places("EUR"), 3);
+        assert_eq!(formatter.places("USD"), 2);
+        assert_eq!(formatter.format(&amount("1500.4 JPY")), "1,500 ¥");
+    }
+}
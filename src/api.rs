@@ -0,0 +1,380 @@
+//! Automation API: scripts drive a running app over a Unix socket, set in
+//! `api_socket`, with JSON-RPC 2.0 requests written one per line.
+//!
+//! - `reload` reloads the journal.
+//! - `add_transaction`, with `description`, `postings` of `account` and `amount`, and
+//!   `date`, appends a transaction dated today when `date` is left out. One posting may
+//!   leave out its amount to balance the rest.
+//! - `balances`, with `account`, lists balances by account, of the account and its
+//!   subaccounts when one is given.
+//! - `set_filter`, with `accounts`, `payee`, `query`, `from` and `to`, filters the
+//!   register like a `ledger-desktop://register` link.
+//!
+//! `{"jsonrpc": "2.0", "id": 1, "method": "balances", "params": {"account": "assets"}}`
+
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{
+    accounts::{Account, TreeNode},
+    balancing,
+    deep_link::RegisterFilters,
+    settings::SavedView,
+    transactions::{Amount, Posting, PostingStatus, Transaction},
+};
+
+const VERSION: &str = "2.0";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApiError {
+    #[error("invalid JSON: {0}")]
+    Parse(String),
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("unknown method `{0}`")]
+    UnknownMethod(String),
+    #[error("invalid params: {0}")]
+    InvalidParams(String),
+    /// The request was valid, but the app couldn't carry it out.
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl ApiError {
+    /// JSON-RPC error code.
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::Parse(_) => -32700,
+            Self::InvalidRequest(_) => -32600,
+            Self::UnknownMethod(_) => -32601,
+            Self::InvalidParams(_) => -32602,
+            Self::Failed(_) => -32000,
+        }
+    }
+}
+
+/// What a request asks the app to do.
+#[derive(Debug, Clone)]
+pub enum Method {
+    Reload,
+    AddTransaction(Transaction),
+    Balances { account: Option<Account> },
+    SetFilter(SavedView),
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// `None` for a notification, which gets no response.
+    pub id: Option<Value>,
+    pub method: Method,
+}
+
+#[derive(serde::Deserialize)]
+struct Envelope {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransactionParams {
+    #[serde(default)]
+    date: Option<NaiveDate>,
+    description: String,
+    postings: Vec<PostingParams>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PostingParams {
+    account: String,
+    #[serde(default)]
+    amount: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BalancesParams {
+    #[serde(default)]
+    account: Option<String>,
+}
+
+/// Reads a request line. A transaction without a date is dated `today`, and so is the
+/// period of a filter without dates.
+///
+/// Errors come with the id of the request, `null` when it has none.
+pub fn parse_request(line: &str, today: NaiveDate) -> Result<Request, (Value, ApiError)> {
+    let request = serde_json::from_str::<Value>(line)
+        .map_err(|e| (Value::Null, ApiError::Parse(e.to_string())))?;
+    let id = request.get("id").cloned();
+    let fail = |error| (id.clone().unwrap_or_default(), error);
+
+    let envelope = serde_json::from_value::<Envelope>(request)
+        .map_err(|e| fail(ApiError::InvalidRequest(e.to_string())))?;
+    if envelope.jsonrpc != VERSION {
+        return Err(fail(ApiError::InvalidRequest(format!(
+            "unsupported version `{}`, expected {VERSION}",
+            envelope.jsonrpc
+        ))));
+    }
+    let method = match envelope.method.as_str() {
+        "reload" => Ok(Method::Reload),
+        "add_transaction" => params(envelope.params)
+            .and_then(|params| transaction(&params, today))
+            .map(Method::AddTransaction),
+        "balances" => params(envelope.params).map(|params: BalancesParams| Method::Balances {
+            account: params.account.as_deref().map(Account::parse),
+        }),
+        "set_filter" => params(envelope.params)
+            .map(|filters: RegisterFilters| Method::SetFilter(filters.view(today))),
+        method => Err(ApiError::UnknownMethod(method.to_string())),
+    }
+    .map_err(fail)?;
+    Ok(Request { id, method })
+}
+
+/// Params of a method, missing ones being the same as empty ones.
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, ApiError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| ApiError::InvalidParams(e.to_string()))
+}
+
+fn transaction(params: &TransactionParams, today: NaiveDate) -> Result<Transaction, ApiError> {
+    let invalid = |message: String| ApiError::InvalidParams(message);
+    let description = params.description.trim();
+    if description.is_empty() {
+        return Err(invalid("a description is required".into()));
+    }
+    // Each line of an entry is a posting, so names can't span lines
+    let single_line = |text: &str| !text.contains(['\n', '\r']);
+    if !single_line(description) {
+        return Err(invalid("the description must be a single line".into()));
+    }
+    if params.postings.len() < 2 {
+        return Err(invalid("a transaction needs at least two postings".into()));
+    }
+
+    let mut postings = Vec::with_capacity(params.postings.len());
+    for posting in &params.postings {
+        let account = Account::parse(posting.account.trim());
+        if account.segments.is_empty() || !single_line(&posting.account) {
+            return Err(invalid(format!("invalid account `{}`", posting.account)));
+        }
+        let amount = posting
+            .amount
+            .as_deref()
+            .map(Amount::parse)
+            .transpose()
+            .map_err(|e| invalid(format!("invalid amount for {account}: {e}")))?;
+        postings.push((account, amount));
+    }
+    let amounts = postings
+        .iter()
+        .map(|(_, amount)| amount.as_ref())
+        .collect::<Vec<_>>();
+    let elided = balancing::balance(&amounts)
+        .map_err(|e| invalid(e.to_string()))?
+        .map(|value| Amount {
+            value,
+            price: None,
+            date: None,
+            cost: None,
+        });
+
+    Ok(Transaction {
+        file: std::path::PathBuf::new(),
+        line: 0,
+        time: params.date.unwrap_or(today),
+        description: description.to_string(),
+        postings: postings
+            .into_iter()
+            .filter_map(|(account, amount)| {
+                Some(Posting {
+                    account,
+                    amount: amount.or_else(|| elided.clone())?,
+                    status: PostingStatus::Uncleared,
+                    note: None,
+                })
+            })
+            .collect(),
+    })
+}
+
+/// Balances of the accounts in the tree, parents before their children, starting with
+/// `account` itself when given.
+pub fn balances(tree: &TreeNode, account: Option<&Account>) -> Result<Value, ApiError> {
+    fn find<'a>(node: &'a TreeNode, account: &Account) -> Option<&'a TreeNode> {
+        node.children.iter().find_map(|child| {
+            if child.account == *account {
+                Some(child)
+            } else if child.account.is_parent_of(account) {
+                find(child, account)
+            } else {
+                None
+            }
+        })
+    }
+    fn collect(node: &TreeNode, rows: &mut Vec<Value>) {
+        rows.push(json!({ "account": node.account, "balance": node.balance }));
+        for child in &node.children {
+            collect(child, rows);
+        }
+    }
+
+    let mut rows = Vec::new();
+    match account {
+        Some(account) => {
+            let node = find(tree, account)
+                .ok_or_else(|| ApiError::Failed(format!("no account {account}")))?;
+            collect(node, &mut rows);
+        }
+        None => tree
+            .children
+            .iter()
+            .for_each(|child| collect(child, &mut rows)),
+    }
+    Ok(Value::Array(rows))
+}
+
+/// Response line to a request, without the newline.
+pub fn response(id: &Value, result: Result<Value, ApiError>) -> String {
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": VERSION, "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": VERSION,
+            "id": id,
+            "error": { "code": error.code(), "message": error.to_string() },
+        }),
+    };
+    response.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()
+    }
+
+    fn parse(line: &str) -> Result<Method, ApiError> {
+        parse_request(line, today())
+            .map(|request| request.method)
+            .map_err(|(_, error)| error)
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let request = parse_request(r#"{"jsonrpc":"2.0","id":7,"method":"reload"}"#, today())
+            .expect("should parse");
+        assert_eq!(request.id, Some(json!(7)));
+        assert!(matches!(request.method, Method::Reload));
+
+        let Ok(Method::AddTransaction(transaction)) = parse(
+            r#"{"jsonrpc":"2.0","id":1,"method":"add_transaction","params":{
+                "description":"Groceries",
+                "postings":[{"account":"expenses:food","amount":"12.50 USD"},{"account":"assets:cash"}]
+            }}"#,
+        ) else {
+            panic!("should parse a transaction");
+        };
+        assert_eq!(
+            transaction.to_string(),
+            "2025-03-14 Groceries\n    expenses:food  12.50 USD\n    assets:cash  -12.50 USD\n"
+        );
+
+        let Ok(Method::Balances { account }) =
+            parse(r#"{"jsonrpc":"2.0","id":2,"method":"balances","params":{"account":"assets"}}"#)
+        else {
+            panic!("should parse balances");
+        };
+        assert_eq!(account, Some(Account::parse("assets")));
+
+        let Ok(Method::SetFilter(view)) = parse(
+            r#"{"jsonrpc":"2.0","id":3,"method":"set_filter","params":{"accounts":["expenses:food"],"from":"2025-01-01"}}"#,
+        ) else {
+            panic!("should parse a filter");
+        };
+        assert_eq!(view.accounts, vec!["expenses:food".to_string()]);
+        assert_eq!(view.query.as_deref(), Some("date>=2025-01-01"));
+
+        let notification =
+            parse_request(r#"{"jsonrpc":"2.0","method":"reload"}"#, today()).unwrap();
+        assert_eq!(notification.id, None);
+    }
+
+    #[test]
+    fn test_parse_request_errors() {
+        assert_eq!(
+            parse_request("{", today()).map(|_| ()),
+            Err((
+                Value::Null,
+                ApiError::Parse("EOF while parsing an object at line 1 column 1".into())
+            ))
+        );
+        let Err((id, error)) =
+            parse_request(r#"{"jsonrpc":"1.0","id":"a","method":"reload"}"#, today())
+        else {
+            panic!("should fail");
+        };
+        assert_eq!(id, json!("a"));
+        assert_eq!(error.code(), -32600);
+        assert_eq!(
+            parse(r#"{"jsonrpc":"2.0","id":1,"method":"quit"}"#).map(|_| ()),
+            Err(ApiError::UnknownMethod("quit".into()))
+        );
+        assert_eq!(
+            parse(
+                r#"{"jsonrpc":"2.0","id":1,"method":"add_transaction","params":{
+                    "description":"Groceries",
+                    "postings":[{"account":"expenses:food","amount":"12.50 USD"},{"account":"assets:cash","amount":"-10 USD"}]
+                }}"#
+            )
+            .map(|_| ()),
+            Err(ApiError::InvalidParams(
+                "transaction is off by 2.50 USD".into()
+            ))
+        );
+        assert!(matches!(
+            parse(r#"{"jsonrpc":"2.0","id":1,"method":"set_filter","params":{"acount":"x"}}"#),
+            Err(ApiError::InvalidParams(_))
+        ));
+    }
+
+    #[test]
+    fn test_balances() {
+        let mut tree = TreeNode::new();
+        for (account, amount) in [("assets:cash", "10 USD"), ("assets:bank", "20 USD")] {
+            let account = Account::parse(account);
+            tree.add_account(&account);
+            tree.add_amount_to_account(&account, &Amount::parse(amount).unwrap().value);
+        }
+
+        let balances = balances(&tree, Some(&Account::parse("assets"))).unwrap();
+        assert_eq!(balances[0]["account"], "assets");
+        assert_eq!(
+            balances[0]["balance"],
+            json!([{ "value": "30", "commodity": "USD" }])
+        );
+        assert_eq!(balances.as_array().map(Vec::len), Some(3));
+        assert_eq!(
+            super::balances(&tree, Some(&Account::parse("income"))),
+            Err(ApiError::Failed("no account income".into()))
+        );
+    }
+
+    #[test]
+    fn test_response() {
+        assert_eq!(
+            response(&json!(1), Ok(Value::Null)),
+            r#"{"id":1,"jsonrpc":"2.0","result":null}"#
+        );
+        assert_eq!(
+            response(&Value::Null, Err(ApiError::UnknownMethod("quit".into()))),
+            r#"{"error":{"code":-32601,"message":"unknown method `quit`"},"id":null,"jsonrpc":"2.0"}"#
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Balance assertions and the checker that compares them with computed balances.
+//!
+//! Assertions come from `= AMOUNT` on journal postings or are entered by the user as
+//! an expected balance on a date. A failed assertion points at the transaction where
+//! the balance most likely started to diverge, as a place to start reconciling.
+
+use std::borrow::Borrow;
+use std::path::{Path, PathBuf};
+
+use fastnum::D128;
+
+use crate::accounts::Account;
+use crate::transactions::{parse_header, CurrencyAmount, Transaction};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssertionSource {
+    /// Posting in the journal, identified by the transaction it belongs to.
+    Journal { file: PathBuf, line: i64 },
+    /// Entered in the app; holds at the end of the day.
+    User,
+}
+
+/// Expected balance of an account in one commodity, excluding subaccounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceAssertion {
+    pub account: Account,
+    pub date: chrono::NaiveDate,
+    pub expected: CurrencyAmount,
+    pub source: AssertionSource,
+}
+
+/// A failed assertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub assertion: BalanceAssertion,
+    pub actual: D128,
+    /// Index of the transaction the divergence most likely starts at.
+    pub diverging: Option<usize>,
+}
+
+impl Discrepancy {
+    /// Expected minus actual balance.
+    pub fn difference(&self) -> D128 {
+        self.assertion.expected.value - self.actual
+    }
+}
+
+/// Reads balance assertions from a journal file's text.
+///
+/// Balance assignments (`= AMOUNT` without a posting amount) are skipped, since ledger
+/// derives the posting amount from them and they always hold.
+pub fn parse_assertions(text: &str, file: &Path) -> Vec<BalanceAssertion> {
+    let mut assertions = Vec::new();
+    let mut transaction = None;
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i64::try_from(i + 1).unwrap_or(i64::MAX);
+        let content = line.split_once(';').map_or(line, |(content, _)| content);
+        if !line.starts_with([' ', '\t']) {
+            transaction = parse_header(content).map(|(date, _)| (date, line_number));
+            continue;
+        }
+        let Some((date, transaction_line)) = transaction else {
+            continue;
+        };
+        let Some((posting, expected)) = content.split_once('=') else {
+            continue;
+        };
+        // Account and amount are separated by two spaces or a tab
+        let posting = posting.trim().trim_start_matches(['*', '!']).trim_start();
+        let Some((account, amount)) = posting.split_once("  ").or(posting.split_once('\t')) else {
+            continue;
+        };
+        if amount.trim().is_empty() {
+            continue;
+        }
+        let Ok(expected) = CurrencyAmount::parse(expected) else {
+            continue;
+        };
+        assertions.push(BalanceAssertion {
+            account: Account::parse(account.trim()),
+            date,
+            expected,
+            source: AssertionSource::Journal {
+                file: file.to_path_buf(),
+                line: transaction_line,
+            },
+        });
+    }
+    assertions
+}
+
+/// Checks assertions against transactions in journal order.
+///
+/// Journal assertions include every transaction up to and including their own, user
+/// assertions every transaction dated on or before theirs.
+pub fn check(
+    assertions: &[BalanceAssertion],
+    transactions: &[impl Borrow<Transaction>],
+) -> Vec<Discrepancy> {
+    // Assertions that held, by account and commodity, as the last known good position
+    let mut checked = assertions
+        .iter()
+        .filter_map(|assertion| Some((assertion, end_index(assertion, transactions)?)))
+        .collect::<Vec<_>>();
+    checked.sort_by_key(|(_, end)| *end);
+
+    let mut discrepancies = Vec::new();
+    let mut good = Vec::<(&Account, &str, usize)>::new();
+    for (assertion, end) in checked {
+        let commodity = assertion.expected.commodity.as_str();
+        let actual = postings(transactions, 0..end, &assertion.account, commodity)
+            .fold(D128::ZERO, |sum, (_, value)| sum + value);
+        if actual == assertion.expected.value {
+            good.push((&assertion.account, commodity, end));
+            continue;
+        }
+
+        let start = good
+            .iter()
+            .rev()
+            .find(|(account, good_commodity, _)| {
+                *account == &assertion.account && *good_commodity == commodity
+            })
+            .map_or(0, |(_, _, end)| *end);
+        let difference = assertion.expected.value - actual;
+        // A posting that is exactly off is likely missing, duplicated or has the wrong
+        // sign; otherwise the divergence starts right after the last good assertion
+        let mut candidates = postings(transactions, start..end, &assertion.account, commodity);
+        let first = candidates.clone().next();
+        let diverging = candidates
+            .find(|(_, value)| value.abs() == difference.abs())
+            .or(first)
+            .map(|(ix, _)| ix);
+
+        discrepancies.push(Discrepancy {
+            assertion: assertion.clone(),
+            actual,
+            diverging,
+        });
+    }
+    discrepancies
+}
+
+/// Amounts of the account's postings in the commodity, with their transaction index.
+fn postings<'a>(
+    transactions: &'a [impl Borrow<Transaction>],
+    range: std::ops::Range<usize>,
+    account: &'a Account,
+    commodity: &'a str,
+) -> impl Iterator<Item = (usize, D128)> + Clone + 'a {
+    range.flat_map(move |ix| {
+        transactions[ix]
+            .borrow()
+            .postings
+            .iter()
+            .filter(move |posting| {
+                &posting.account == account && posting.amount.value.commodity == commodity
+            })
+            .map(move |posting| (ix, posting.amount.value.value))
+    })
+}
+
+/// Number of leading transactions the assertion covers.
+fn end_index(
+    assertion: &BalanceAssertion,
+    transactions: &[impl Borrow<Transaction>],
+) -> Option<usize> {
+    let mut transactions = transactions.iter().map(Borrow::borrow);
+    match &assertion.source {
+        AssertionSource::Journal { file, line } => transactions
+            .position(|transaction: &Transaction| {
+                &transaction.file == file && transaction.line == *line
+            })
+            .map(|ix| ix + 1),
+        AssertionSource::User => Some(
+            transactions
+                .rposition(|transaction: &Transaction| transaction.time <= assertion.date)
+                .map_or(0, |ix| ix + 1),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(line: i64, date: &str, postings: &[(&str, &str)]) -> Transaction {
+        let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        Transaction {
+            line,
+            ..Transaction::fixture(date, &format!("line {line}"), postings)
+        }
+    }
+
+    fn user_assertion(account: &str, date: &str, expected: &str) -> BalanceAssertion {
+        BalanceAssertion {
+            account: Account::parse(account),
+            date: chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            expected: CurrencyAmount::parse(expected).unwrap(),
+            source: AssertionSource::User,
+        }
+    }
+
+    #[test]
+    fn test_parse_assertions() {
+        let journal = "\
+2025/01/01 Opening
+    assets:checking  1000 USD = 1000 USD
+    equity:opening
+
+2025-01-05 Shop ; groceries
+    * expenses:food  50 USD
+    assets:checking\t-50 USD = 950 USD ; after shopping
+    assets:savings  = 200 USD
+";
+        let assertions = parse_assertions(journal, Path::new("main.ledger"));
+        assert_eq!(assertions.len(), 2);
+        assert_eq!(assertions[0].account, Account::parse("assets:checking"));
+        assert_eq!(
+            assertions[0].expected,
+            CurrencyAmount::parse("1000 USD").unwrap()
+        );
+        assert_eq!(
+            assertions[1].source,
+            AssertionSource::Journal {
+                file: PathBuf::from("main.ledger"),
+                line: 5
+            }
+        );
+        assert_eq!(
+            assertions[1].date,
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 5).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_check_finds_diverging_transaction() {
+        let transactions = vec![
+            transaction(1, "2025-01-01", &[("assets:checking", "1000 USD")]),
+            transaction(4, "2025-01-05", &[("assets:checking", "-50 USD")]),
+            transaction(7, "2025-01-06", &[("assets:checking", "-20 USD")]),
+            transaction(10, "2025-01-07", &[("assets:checking", "-30 USD")]),
+        ];
+        let assertions = vec![
+            user_assertion("assets:checking", "2025-01-01", "1000 USD"),
+            user_assertion("assets:checking", "2025-01-31", "920 USD"),
+            user_assertion("assets:checking", "2025-01-31", "900 USD"),
+        ];
+
+        let discrepancies = check(&assertions, &transactions);
+        assert_eq!(discrepancies.len(), 1);
+        let discrepancy = &discrepancies[0];
+        assert_eq!(discrepancy.actual, "900".parse::<D128>().unwrap());
+        assert_eq!(discrepancy.difference(), "20".parse::<D128>().unwrap());
+        // The -20 USD posting exactly explains the difference
+        assert_eq!(discrepancy.diverging, Some(2));
+    }
+
+    #[test]
+    fn test_check_journal_assertion_position() {
+        let transactions = vec![
+            transaction(1, "2025-01-01", &[("assets:checking", "100 USD")]),
+            transaction(4, "2025-01-01", &[("assets:checking", "50 USD")]),
+        ];
+        let assertion = |line, expected| BalanceAssertion {
+            source: AssertionSource::Journal {
+                file: PathBuf::from("main.ledger"),
+                line,
+            },
+            ..user_assertion("assets:checking", "2025-01-01", expected)
+        };
+
+        assert!(check(&[assertion(1, "100 USD")], &transactions).is_empty());
+        let discrepancies = check(&[assertion(1, "120 USD")], &transactions);
+        assert_eq!(discrepancies[0].diverging, Some(0));
+    }
+}
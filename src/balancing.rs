@@ -0,0 +1,131 @@
+//! Checking that a transaction's postings balance.
+//!
+//! Follows ledger's rules: amounts add up to zero in every commodity, lot prices count
+//! in the price's commodity, and one posting may leave its amount out to take whatever
+//! balances the rest. Two commodities that don't balance on their own are taken as an
+//! exchange between them, like ledger does.
+
+use fastnum::D128;
+
+use crate::transactions::{Amount, CurrencyAmount};
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum BalanceError {
+    #[error("only one posting can have no amount")]
+    SeveralElided,
+    #[error("amounts in several commodities need to be written out")]
+    ElidedCommodities,
+    #[error("transaction is off by {0}")]
+    Unbalanced(CurrencyAmount),
+}
+
+/// Checks the postings' amounts, `None` for a posting without one.
+///
+/// Returns the amount the posting without one gets, if there is such a posting.
+pub fn balance(amounts: &[Option<&Amount>]) -> Result<Option<CurrencyAmount>, BalanceError> {
+    let elided = amounts.iter().filter(|amount| amount.is_none()).count();
+    if elided > 1 {
+        return Err(BalanceError::SeveralElided);
+    }
+
+    let mut sums = Vec::<CurrencyAmount>::new();
+    for amount in amounts.iter().flatten() {
+        let cost = cost(amount);
+        match sums.iter_mut().find(|sum| sum.commodity == cost.commodity) {
+            Some(sum) => sum.value += cost.value,
+            None => sums.push(cost),
+        }
+    }
+    sums.retain(|sum| !sum.value.is_zero());
+
+    if elided == 1 {
+        return match sums.as_slice() {
+            [] => Ok(Some(CurrencyAmount {
+                value: D128::ZERO,
+                commodity: amounts
+                    .iter()
+                    .flatten()
+                    .next()
+                    .map(|amount| amount.value.commodity.clone())
+                    .unwrap_or_default(),
+            })),
+            [sum] => Ok(Some(CurrencyAmount {
+                value: -sum.value,
+                commodity: sum.commodity.clone(),
+            })),
+            _ => Err(BalanceError::ElidedCommodities),
+        };
+    }
+
+    match sums.as_slice() {
+        [] => Ok(None),
+        [a, b] if a.value.is_sign_negative() != b.value.is_sign_negative() => Ok(None),
+        [sum, ..] => Err(BalanceError::Unbalanced(sum.clone())),
+    }
+}
+
+/// What the amount counts as when balancing: its lot cost when it has one.
+fn cost(amount: &Amount) -> CurrencyAmount {
+    match &amount.price {
+        Some(price) => CurrencyAmount {
+            value: amount.value.value * price.value,
+            commodity: price.commodity.clone(),
+        },
+        None => amount.value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amounts(amounts: &[Option<&str>]) -> Vec<Option<Amount>> {
+        amounts
+            .iter()
+            .map(|amount| amount.map(|amount| Amount::parse(amount).unwrap()))
+            .collect()
+    }
+
+    fn check(values: &[Option<&str>]) -> Result<Option<CurrencyAmount>, BalanceError> {
+        let amounts = amounts(values);
+        balance(&amounts.iter().map(Option::as_ref).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_balanced() {
+        assert_eq!(check(&[Some("10 USD"), Some("-10 USD")]), Ok(None));
+        assert_eq!(
+            check(&[Some("10 AAPL {150 USD}"), Some("-1500 USD")]),
+            Ok(None)
+        );
+        // Exchange between two commodities
+        assert_eq!(check(&[Some("10 EUR"), Some("-11 USD")]), Ok(None));
+    }
+
+    #[test]
+    fn test_elided() {
+        assert_eq!(
+            check(&[Some("10 USD"), Some("5 USD"), None]),
+            Ok(Some(CurrencyAmount::parse("-15 USD").unwrap()))
+        );
+        assert_eq!(
+            check(&[None, None, Some("10 USD")]),
+            Err(BalanceError::SeveralElided)
+        );
+        assert_eq!(
+            check(&[Some("10 USD"), Some("10 EUR"), None]),
+            Err(BalanceError::ElidedCommodities)
+        );
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        assert_eq!(
+            check(&[Some("10 USD"), Some("-9 USD")]),
+            Err(BalanceError::Unbalanced(
+                CurrencyAmount::parse("1 USD").unwrap()
+            ))
+        );
+        assert!(check(&[Some("10 EUR"), Some("11 USD")]).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Export of transactions as a beancount journal.
+//!
+//! Beancount is stricter than ledger about names: accounts start with one of five
+//! capitalized types and commodities are upper case codes. Names are rewritten to fit,
+//! so the output loads in beancount without edits.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::accounts::Account;
+use crate::transactions::{Amount, Cost, CurrencyAmount, Posting, PostingStatus, Transaction};
+
+/// Account types beancount accepts, by the ledger names that commonly map to them.
+const ACCOUNT_TYPES: &[(&str, &str)] = &[
+    ("assets", "Assets"),
+    ("asset", "Assets"),
+    ("liabilities", "Liabilities"),
+    ("liability", "Liabilities"),
+    ("equity", "Equity"),
+    ("income", "Income"),
+    ("revenue", "Income"),
+    ("revenues", "Income"),
+    ("expenses", "Expenses"),
+    ("expense", "Expenses"),
+];
+
+/// Codes for the currency symbols journals use in place of a code.
+const SYMBOL_CODES: &[(&str, &str)] = &[("$", "USD"), ("€", "EUR"), ("£", "GBP")];
+
+/// Writes the transactions as a beancount journal.
+///
+/// Every account is opened on the date of its first transaction. A transaction is
+/// flagged `*` when all its postings are cleared and `!` when all are pending,
+/// otherwise postings keep their own flags.
+pub fn export(transactions: &[Transaction]) -> String {
+    let mut opened = BTreeMap::new();
+    for transaction in transactions {
+        for posting in &transaction.postings {
+            opened
+                .entry(account_name(&posting.account))
+                .and_modify(|date| *date = transaction.time.min(*date))
+                .or_insert(transaction.time);
+        }
+    }
+    let mut opens = opened.into_iter().collect::<Vec<_>>();
+    opens.sort_by_key(|(_, date)| *date);
+
+    let mut journal = String::new();
+    for (account, date) in &opens {
+        writeln!(journal, "{} open {account}", date.format("%Y-%m-%d")).expect("infallible");
+    }
+    for transaction in transactions {
+        journal.push('\n');
+        write_transaction(&mut journal, transaction);
+    }
+    journal
+}
+
+fn write_transaction(journal: &mut String, transaction: &Transaction) {
+    let all = |status| {
+        transaction
+            .postings
+            .iter()
+            .all(|posting| posting.status == status)
+    };
+    let flag = if all(PostingStatus::Cleared) {
+        Some('*')
+    } else if all(PostingStatus::Pending) {
+        Some('!')
+    } else {
+        None
+    };
+    writeln!(
+        journal,
+        "{} {} \"{}\"",
+        transaction.time.format("%Y-%m-%d"),
+        flag.map_or_else(|| "txn".to_string(), String::from),
+        escape(&transaction.description)
+    )
+    .expect("infallible");
+    for posting in &transaction.postings {
+        write_posting(journal, posting, flag.is_none());
+    }
+}
+
+fn write_posting(journal: &mut String, posting: &Posting, flagged: bool) {
+    journal.push_str("  ");
+    if flagged {
+        match posting.status {
+            PostingStatus::Uncleared => {}
+            PostingStatus::Pending => journal.push_str("! "),
+            PostingStatus::Cleared => journal.push_str("* "),
+        }
+    }
+    write!(
+        journal,
+        "{}  {}",
+        account_name(&posting.account),
+        amount(&posting.amount)
+    )
+    .expect("infallible");
+    if let Some(note) = &posting.note {
+        write!(journal, " ; {}", note.trim()).expect("infallible");
+    }
+    journal.push('\n');
+}
+
+/// The amount with its lot as a beancount cost and its conversion as a price.
+fn amount(amount: &Amount) -> String {
+    let mut text = currency_amount(&amount.value);
+    match (&amount.price, amount.date) {
+        (Some(price), Some(date)) => {
+            write!(
+                text,
+                " {{{}, {}}}",
+                currency_amount(price),
+                date.format("%Y-%m-%d")
+            )
+        }
+        (Some(price), None) => write!(text, " {{{}}}", currency_amount(price)),
+        (None, _) => Ok(()),
+    }
+    .expect("infallible");
+    match &amount.cost {
+        Some(Cost::PerUnit(cost)) => write!(text, " @ {}", currency_amount(cost)),
+        Some(Cost::Total(cost)) => write!(text, " @@ {}", currency_amount(cost)),
+        None => Ok(()),
+    }
+    .expect("infallible");
+    text
+}
+
+fn currency_amount(amount: &CurrencyAmount) -> String {
+    format!("{} {}", amount.value, commodity(&amount.commodity))
+}
+
+/// The account under one of beancount's account types, with each segment
+/// capitalized.
+///
+/// Accounts outside the five types are kept under `Equity`.
+pub fn account_name(account: &Account) -> String {
+    let mut segments = account
+        .segments
+        .iter()
+        .map(|segment| account_segment(segment));
+    let root = account.segments.first().map(|root| root.to_lowercase());
+    let account_type = ACCOUNT_TYPES
+        .iter()
+        .find(|(name, _)| root.as_deref() == Some(*name))
+        .map(|(_, account_type)| *account_type);
+    let mut name = match account_type {
+        Some(account_type) => {
+            segments.next();
+            account_type.to_string()
+        }
+        None => "Equity".to_string(),
+    };
+    for segment in segments {
+        name.push(':');
+        name.push_str(&segment);
+    }
+    name
+}
+
+/// Starts with an upper case letter or digit, followed by letters, digits and dashes.
+fn account_segment(segment: &str) -> String {
+    let segment = segment
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+    let mut chars = segment.chars();
+    match chars.next() {
+        Some(first) if first.is_alphanumeric() => first.to_uppercase().chain(chars).collect(),
+        _ => format!("X{segment}"),
+    }
+}
+
+/// Upper case letters, digits and `'._-`, starting with a letter and ending with a
+/// letter or digit.
+pub fn commodity(commodity: &str) -> String {
+    if let Some((_, code)) = SYMBOL_CODES.iter().find(|(symbol, _)| *symbol == commodity) {
+        return (*code).to_string();
+    }
+    let code = commodity
+        .to_uppercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || "'._-".contains(*c))
+        .collect::<String>();
+    let code = code.trim_end_matches(|c: char| !c.is_ascii_alphanumeric());
+    match code.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => code.to_string(),
+        _ => format!("C{code}"),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let mut broker = Transaction::fixture(
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
+            "Broker \"AB\"",
+            &[
+                ("assets:broker", "10 AAPL {100 SEK} [2025/01/01]"),
+                ("assets:bank", "-1000 SEK"),
+            ],
+        );
+        for posting in &mut broker.postings {
+            posting.status = PostingStatus::Cleared;
+        }
+        let mut cafe = Transaction::fixture(
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 3).unwrap(),
+            "Cafe",
+            &[
+                ("expenses:eating out", "5 $"),
+                ("assets:bank", "-50 SEK @@ 5 $"),
+            ],
+        );
+        cafe.line = 5;
+        cafe.postings[0].status = PostingStatus::Pending;
+        let transactions = [broker, cafe];
+        assert_eq!(
+            export(&transactions),
+            "2025-01-02 open Assets:Bank\n\
+             2025-01-02 open Assets:Broker\n\
+             2025-01-03 open Expenses:Eating-out\n\
+             \n\
+             2025-01-02 * \"Broker \\\"AB\\\"\"\n\
+             \x20 Assets:Broker  10 AAPL {100 SEK, 2025-01-01}\n\
+             \x20 Assets:Bank  -1000 SEK\n\
+             \n\
+             2025-01-03 txn \"Cafe\"\n\
+             \x20 ! Expenses:Eating-out  5 USD\n\
+             \x20 Assets:Bank  -50 SEK @@ 5 USD\n"
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(account_name(&Account::parse("Revenue:job")), "Income:Job");
+        assert_eq!(
+            account_name(&Account::parse("budget:food")),
+            "Equity:Budget:Food"
+        );
+        assert_eq!(
+            account_name(&Account::parse("assets:_cash")),
+            "Assets:X-cash"
+        );
+        assert_eq!(commodity("VANG 500"), "VANG500");
+        assert_eq!(commodity("€"), "EUR");
+        assert_eq!(commodity("1INCH"), "C1INCH");
+    }
+}
@@ -0,0 +1,272 @@
+//! Monthly budgets kept as envelopes.
+//!
+//! A budget is written in the journal as a `~ Monthly` periodic transaction, the way
+//! ledger's own `--budget` reads it. Every month each account's envelope gets its
+//! amount, spending takes from it, and whatever is left over, or overspent, rolls over
+//! into the next month.
+
+use chrono::NaiveDate;
+use fastnum::D128;
+
+use crate::{
+    accounts::Account,
+    period::{Period, PeriodKind},
+    transactions::{CurrencyAmount, Transaction},
+};
+
+/// An amount put into an account's envelope every month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetLine {
+    pub account: Account,
+    pub amount: CurrencyAmount,
+    /// Date the budget starts, from `~ Monthly from 2025-01-01`.
+    pub from: Option<NaiveDate>,
+}
+
+/// Reads the postings of `~ Monthly` entries. Postings without an amount, like the
+/// account the budget is paid from, have no envelope.
+pub fn parse_budget(text: &str) -> Vec<BudgetLine> {
+    let mut lines = Vec::new();
+    let mut in_monthly = false;
+    // Start date of the monthly entry the lines are indented under
+    let mut from = None;
+    for line in text.lines() {
+        let line = line.split(';').next().unwrap_or_default();
+        if !line.starts_with([' ', '\t']) {
+            let period = line.strip_prefix('~').map(str::trim).unwrap_or_default();
+            in_monthly = is_monthly(period);
+            from = period_start(period);
+            continue;
+        }
+        if !in_monthly {
+            continue;
+        }
+        let line = line.trim();
+        let Some((account, amount)) = line.split_once("  ").or_else(|| line.split_once('\t'))
+        else {
+            continue;
+        };
+        let Ok(amount) = CurrencyAmount::parse(amount) else {
+            continue;
+        };
+        lines.push(BudgetLine {
+            account: Account::parse(account.trim()),
+            amount,
+            from,
+        });
+    }
+    lines
+}
+
+fn is_monthly(period: &str) -> bool {
+    let lower = period.to_lowercase();
+    lower.starts_with("monthly") || lower.starts_with("every month")
+}
+
+/// Date after `from` in a period expression.
+fn period_start(period: &str) -> Option<NaiveDate> {
+    let (_, date) = period.split_once(" from ")?;
+    let date = date.trim();
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y/%m/%d"))
+        .ok()
+}
+
+/// One month of an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvelopeMonth {
+    pub month: Period,
+    pub budgeted: D128,
+    /// Left over from the month before, negative when it was overspent.
+    pub carried: D128,
+    pub spent: D128,
+}
+
+impl EnvelopeMonth {
+    pub fn available(&self) -> D128 {
+        self.budgeted + self.carried - self.spent
+    }
+}
+
+/// An account's budget in one commodity, month by month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub account: Account,
+    pub commodity: String,
+    /// From the month the budget starts, oldest first.
+    pub months: Vec<EnvelopeMonth>,
+}
+
+impl Envelope {
+    /// The last month, the one the envelope stands at.
+    pub fn current(&self) -> Option<&EnvelopeMonth> {
+        self.months.last()
+    }
+}
+
+/// Envelopes of the budget up to the month containing `until`. Spending is what was
+/// posted to the account and its subaccounts; a budget without a start date starts with
+/// the first transaction.
+pub fn envelopes(
+    budget: &[BudgetLine],
+    transactions: &[&Transaction],
+    until: NaiveDate,
+) -> Vec<Envelope> {
+    let first_transaction = transactions
+        .iter()
+        .map(|transaction| transaction.time)
+        .min();
+    let last = Period::containing(until, PeriodKind::Month);
+
+    let mut keys = Vec::<(&Account, &str)>::new();
+    for line in budget {
+        let key = (&line.account, line.amount.commodity.as_str());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    keys.into_iter()
+        .map(|(account, commodity)| {
+            let lines = budget
+                .iter()
+                .filter(|line| &line.account == account && line.amount.commodity == commodity)
+                .collect::<Vec<_>>();
+            let start = lines
+                .iter()
+                .map(|line| line.from.or(first_transaction).unwrap_or(until))
+                .min()
+                .unwrap_or(until);
+
+            let mut months = Vec::new();
+            let mut carried = D128::ZERO;
+            let mut month = Period::containing(start, PeriodKind::Month);
+            while month.start <= last.start {
+                let budgeted = lines
+                    .iter()
+                    .filter(|line| line.from.is_none_or(|from| from < month.end()))
+                    .map(|line| line.amount.value)
+                    .fold(D128::ZERO, |total, value| total + value);
+                let spent = transactions
+                    .iter()
+                    .filter(|transaction| month.contains(transaction.time))
+                    .flat_map(|transaction| &transaction.postings)
+                    .filter(|posting| {
+                        (&posting.account == account || account.is_parent_of(&posting.account))
+                            && posting.amount.value.commodity == commodity
+                    })
+                    .map(|posting| posting.amount.value.value)
+                    .fold(D128::ZERO, |total, value| total + value);
+                let entry = EnvelopeMonth {
+                    month,
+                    budgeted,
+                    carried,
+                    spent,
+                };
+                carried = entry.available();
+                months.push(entry);
+                month = month.next();
+            }
+            Envelope {
+                account: account.clone(),
+                commodity: commodity.to_string(),
+                months,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn spend(time: &str, account: &str, value: i64) -> Transaction {
+        Transaction::fixture(
+            date(time),
+            "Shop",
+            &[
+                (account, &format!("{value} USD")),
+                ("assets:checking", &format!("{} USD", -value)),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        let text = "\
+~ Monthly
+    expenses:food  400 USD
+    expenses:rent  1,000 USD ; fixed
+    assets:checking
+
+~ Monthly from 2025/03/01
+    expenses:fun\t50 USD
+
+~ Yearly
+    expenses:gifts  300 USD
+
+2025-01-01 Shop
+    expenses:food  10 USD
+    assets:checking
+";
+        let budget = parse_budget(text);
+        assert_eq!(
+            budget
+                .iter()
+                .map(|line| (line.account.to_string(), line.amount.to_string(), line.from))
+                .collect::<Vec<_>>(),
+            vec![
+                ("expenses:food".into(), "400 USD".into(), None),
+                ("expenses:rent".into(), "1000 USD".into(), None),
+                (
+                    "expenses:fun".into(),
+                    "50 USD".into(),
+                    Some(date("2025-03-01"))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_envelopes() {
+        let budget = parse_budget(
+            "~ Monthly\n    expenses:food  100 USD\n\n~ Monthly from 2025-02-01\n    expenses:fun  20 USD\n",
+        );
+        let transactions = [
+            spend("2025-01-05", "expenses:food:groceries", 60),
+            spend("2025-02-10", "expenses:food", 150),
+            spend("2025-02-11", "expenses:fun", 5),
+            spend("2025-03-01", "expenses:food", 20),
+            spend("2025-03-02", "expenses:travel", 500),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let envelopes = envelopes(&budget, &transactions, date("2025-03-15"));
+        assert_eq!(envelopes.len(), 2);
+
+        let food = &envelopes[0];
+        assert_eq!(food.account, Account::parse("expenses:food"));
+        assert_eq!(
+            food.months
+                .iter()
+                .map(|month| (month.carried, month.spent, month.available()))
+                .collect::<Vec<_>>(),
+            vec![
+                (D128::ZERO, D128::from(60), D128::from(40)),
+                (D128::from(40), D128::from(150), D128::from(-10)),
+                (D128::from(-10), D128::from(20), D128::from(70)),
+            ]
+        );
+
+        let fun = &envelopes[1];
+        assert_eq!(fun.months.len(), 2);
+        assert_eq!(fun.months[0].month.start, date("2025-02-01"));
+        assert_eq!(
+            fun.current().map(EnvelopeMonth::available),
+            Some(D128::from(35))
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Changes applied to the text of several journal entries at once.
+//!
+//! Each function takes an entry as written in the journal, its header line and the
+//! indented lines after it, and returns the changed entry.
+
+use std::path::Path;
+
+use crate::accounts::Account;
+use crate::rename::rename_edits;
+
+/// Marks the entry cleared with `*` after its date, replacing a pending `!` mark.
+pub fn mark_cleared(entry: &str) -> String {
+    let (header, body) = entry.split_at(entry.find('\n').unwrap_or(entry.len()));
+    let (date, rest) = header.split_at(header.find(char::is_whitespace).unwrap_or(header.len()));
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix(['*', '!']).map_or(rest, str::trim_start);
+    format!("{}{body}", format!("{date} * {rest}").trim_end())
+}
+
+/// Adds a tag comment right after the header: `; :tag:`, or `; key: value` for
+/// metadata.
+pub fn add_tag(entry: &str, tag: &str) -> String {
+    let tag = tag.trim();
+    let comment = if tag.contains(':') {
+        format!("    ; {tag}\n")
+    } else {
+        format!("    ; :{tag}:\n")
+    };
+    match entry.find('\n') {
+        Some(end) => format!("{}{comment}{}", &entry[..=end], &entry[end + 1..]),
+        None => format!("{entry}\n{}", comment.trim_end()),
+    }
+}
+
+/// Moves the entry's postings to `from` and its subaccounts over to `to`.
+pub fn change_account(entry: &str, from: &Account, to: &Account) -> String {
+    rename_edits(Path::new(""), entry, from, to)
+        .iter()
+        .try_fold(entry.to_string(), |text, edit| edit.apply(&text))
+        .unwrap_or_else(|_| entry.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_cleared() {
+        let entry = "2025-01-05 Shop\n    expenses:food  50 USD\n    assets:cash\n";
+        assert_eq!(
+            mark_cleared(entry),
+            "2025-01-05 * Shop\n    expenses:food  50 USD\n    assets:cash\n"
+        );
+        assert_eq!(
+            mark_cleared("2025-01-05 ! (12) Shop\n"),
+            "2025-01-05 * (12) Shop\n"
+        );
+        assert_eq!(mark_cleared("2025-01-05 * Shop"), "2025-01-05 * Shop");
+        assert_eq!(mark_cleared("2025-01-05\n"), "2025-01-05 *\n");
+    }
+
+    #[test]
+    fn test_add_tag() {
+        let entry = "2025-01-05 Shop\n    assets:cash\n";
+        assert_eq!(
+            add_tag(entry, "shared"),
+            "2025-01-05 Shop\n    ; :shared:\n    assets:cash\n"
+        );
+        assert_eq!(
+            add_tag(entry, "payee: Bob"),
+            "2025-01-05 Shop\n    ; payee: Bob\n    assets:cash\n"
+        );
+    }
+
+    #[test]
+    fn test_change_account() {
+        let entry = "2025-01-05 Shop\n    expenses:misc  50 USD\n    assets:cash\n";
+        assert_eq!(
+            change_account(
+                entry,
+                &Account::parse("expenses:misc"),
+                &Account::parse("expenses:food")
+            ),
+            "2025-01-05 Shop\n    expenses:food  50 USD\n    assets:cash\n"
+        );
+    }
+}
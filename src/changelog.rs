@@ -0,0 +1,160 @@
+//! Log of every change the app wrote to journal files, kept apart from undo history
+//! and from any version control the journal is in.
+//!
+//! Changes are appended as JSON lines to a file in the user's data directory and are
+//! never rewritten, so the log is an audit trail of edits made through the app across
+//! sessions.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+
+use crate::history::{self, DiffLine, FileEdit, Operation};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChangelogError {
+    #[error("failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("failed to write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error("invalid change on line {0}: {1}")]
+    Parse(usize, serde_json::Error),
+}
+
+/// An operation as it was written to the journal files.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Change {
+    pub time: DateTime<Local>,
+    pub label: String,
+    pub edits: Vec<FileEdit>,
+}
+
+impl Change {
+    pub fn new(operation: &Operation, time: DateTime<Local>) -> Self {
+        Self {
+            time,
+            label: operation.label.clone(),
+            edits: operation.edits.clone(),
+        }
+    }
+}
+
+/// Where the log is kept.
+pub fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("ledger-desktop").join("changelog.jsonl"))
+}
+
+/// Adds a change to the end of the log, creating it if needed.
+pub fn append(path: &Path, change: &Change) -> Result<(), ChangelogError> {
+    let write_error = |e| ChangelogError::Write(path.to_path_buf(), e);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(write_error)?;
+    }
+    let mut line =
+        serde_json::to_string(change).map_err(|e| write_error(std::io::Error::other(e)))?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(write_error)
+}
+
+/// Changes in the log, oldest first. There are none before the first is appended.
+pub fn read(path: &Path) -> Result<Vec<Change>, ChangelogError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(ChangelogError::Read(path.to_path_buf(), e)),
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(ix, line)| serde_json::from_str(line).map_err(|e| ChangelogError::Parse(ix + 1, e)))
+        .collect()
+}
+
+/// The changes as text for people: each one's time and label, then the lines it
+/// removed and added in each file.
+pub fn to_text(changes: &[Change]) -> String {
+    let mut text = String::new();
+    for change in changes {
+        writeln!(
+            text,
+            "{}  {}",
+            change.time.format("%Y-%m-%d %H:%M:%S %:z"),
+            change.label
+        )
+        .expect("infallible");
+        for edit in &change.edits {
+            writeln!(text, "  {}", edit.file.display()).expect("infallible");
+            for line in history::diff_lines(&edit.removed, &edit.inserted) {
+                match line {
+                    DiffLine::Removed(line) => writeln!(text, "  - {line}"),
+                    DiffLine::Added(line) => writeln!(text, "  + {line}"),
+                    DiffLine::Same(_) => Ok(()),
+                }
+                .expect("infallible");
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(label: &str, removed: &str, inserted: &str) -> Change {
+        Change {
+            time: DateTime::parse_from_rfc3339("2025-03-01T12:30:00+01:00")
+                .unwrap()
+                .with_timezone(&Local),
+            label: label.into(),
+            edits: vec![FileEdit {
+                file: PathBuf::from("main.ledger"),
+                offset: 0,
+                removed: removed.into(),
+                inserted: inserted.into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let path = std::env::temp_dir()
+            .join(format!("ledger-desktop-changelog-{}", std::process::id()))
+            .join("changelog.jsonl");
+        assert!(read(&path).unwrap().is_empty());
+
+        let changes = [
+            change("Edit Shop", "2025-01-01 Shop\n", "2025-01-01 Market\n"),
+            change("Delete Rent", "2025-01-02 Rent\n", "; 2025-01-02 Rent\n"),
+        ];
+        for change in &changes {
+            append(&path, change).unwrap();
+        }
+        assert_eq!(read(&path).unwrap(), changes);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_to_text() {
+        let change = change(
+            "Edit Shop",
+            "2025-01-01 Shop\n    expenses:food  10 USD\n",
+            "2025-01-01 Market\n    expenses:food  10 USD\n",
+        );
+        let time = change.time.format("%Y-%m-%d %H:%M:%S %:z");
+        assert_eq!(
+            to_text(&[change]),
+            format!(
+                "{time}  Edit Shop\n  main.ledger\n  - 2025-01-01 Shop\n  + 2025-01-01 Market\n\n"
+            )
+        );
+    }
+}
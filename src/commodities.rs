@@ -0,0 +1,91 @@
+//! Display names for commodities.
+//!
+//! Journals usually name currencies by ISO code. The registry maps those codes to the
+//! symbols people read them as, and lets the user override or add names in settings.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Symbols for common currency codes.
+const BUILTIN_SYMBOLS: &[(&str, &str)] = &[
+    ("USD", "$"),
+    ("EUR", "€"),
+    ("GBP", "£"),
+    ("JPY", "¥"),
+    ("CNY", "¥"),
+    ("INR", "₹"),
+    ("KRW", "₩"),
+    ("RUB", "₽"),
+    ("UAH", "₴"),
+    ("GEL", "₾"),
+    ("TRY", "₺"),
+    ("ILS", "₪"),
+    ("PLN", "zł"),
+    ("CHF", "Fr"),
+    ("SEK", "kr"),
+    ("NOK", "kr"),
+    ("DKK", "kr"),
+    ("ISK", "kr"),
+    ("BTC", "₿"),
+];
+
+/// Maps journal commodity names to the names shown in the UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommodityRegistry {
+    names: HashMap<String, String>,
+}
+
+impl Default for CommodityRegistry {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+impl CommodityRegistry {
+    /// Creates a registry of the built-in symbols with user aliases on top.
+    ///
+    /// An alias equal to the commodity itself turns its built-in symbol off.
+    pub fn new(aliases: &BTreeMap<String, String>) -> Self {
+        let mut names = BUILTIN_SYMBOLS
+            .iter()
+            .map(|(code, symbol)| ((*code).to_string(), (*symbol).to_string()))
+            .collect::<HashMap<_, _>>();
+        names.extend(
+            aliases
+                .iter()
+                .filter(|(_, alias)| !alias.trim().is_empty())
+                .map(|(commodity, alias)| (commodity.clone(), alias.trim().to_string())),
+        );
+        Self { names }
+    }
+
+    /// Name to show for the commodity, the commodity itself when it has none.
+    pub fn display<'a>(&'a self, commodity: &'a str) -> &'a str {
+        self.names.get(commodity).map_or(commodity, String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_symbols() {
+        let registry = CommodityRegistry::default();
+        assert_eq!(registry.display("SEK"), "kr");
+        assert_eq!(registry.display("USD"), "$");
+        assert_eq!(registry.display("AAPL"), "AAPL");
+        assert_eq!(registry.display(""), "");
+    }
+
+    #[test]
+    fn test_aliases() {
+        let registry = CommodityRegistry::new(&BTreeMap::from([
+            ("USD".to_string(), "USD".to_string()),
+            ("VWCE".to_string(), "Vanguard All-World".to_string()),
+            ("SEK".to_string(), "  ".to_string()),
+        ]));
+        assert_eq!(registry.display("USD"), "USD");
+        assert_eq!(registry.display("VWCE"), "Vanguard All-World");
+        assert_eq!(registry.display("SEK"), "kr");
+    }
+}
@@ -0,0 +1,153 @@
+//! Reports added as code. A [`ReportProvider`] turns the transactions of the selected
+//! period into a table or a chart, and every provider in the [`ReportRegistry`] gets a
+//! section in the sidebar.
+//!
+//! A new report is a module here implementing the trait, registered in
+//! [`ReportRegistry::builtin`].
+
+use crate::period::Period;
+use crate::transactions::{CurrencyAmount, Transaction};
+
+mod monthly_expenses;
+mod top_payees;
+
+/// What reports are made from.
+pub struct ReportInput<'a> {
+    /// Transactions income and expense reports count, ordered by date, including the
+    /// ones outside of `period`.
+    pub transactions: &'a [&'a Transaction],
+    /// Period picked in the app.
+    pub period: Period,
+    /// Commodity to total amounts in: the base currency, or else the most used one.
+    pub commodity: &'a str,
+}
+
+/// Value of a table cell. Amounts are formatted by the app, like the rest of its amounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Text(String),
+    Amount(CurrencyAmount),
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<CurrencyAmount> for Cell {
+    fn from(amount: CurrencyAmount) -> Self {
+        Self::Amount(amount)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+/// Labelled amounts drawn as bars, in order, scaled to the largest of them.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Chart {
+    pub bars: Vec<(String, CurrencyAmount)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportOutput {
+    Table(Table),
+    Chart(Chart),
+}
+
+pub trait ReportProvider {
+    /// Label of the report's section in the sidebar, unique among the reports.
+    fn name(&self) -> &'static str;
+
+    fn report(&self, input: &ReportInput) -> ReportOutput;
+}
+
+/// Reports listed in the sidebar, in the order they were registered.
+#[derive(Default)]
+pub struct ReportRegistry {
+    providers: Vec<Box<dyn ReportProvider>>,
+}
+
+impl ReportRegistry {
+    /// The reports that come with the app.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register(top_payees::TopPayees);
+        registry.register(monthly_expenses::MonthlyExpenses);
+        registry
+    }
+
+    /// Adds a report, in place of a registered one with the same name.
+    pub fn register(&mut self, provider: impl ReportProvider + 'static) {
+        let provider = Box::new(provider);
+        match self
+            .providers
+            .iter_mut()
+            .find(|registered| registered.name() == provider.name())
+        {
+            Some(registered) => *registered = provider,
+            None => self.providers.push(provider),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.providers.iter().map(|provider| provider.name())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ReportProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name() == name)
+            .map(AsRef::as_ref)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, usize);
+
+    impl ReportProvider for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn report(&self, _input: &ReportInput) -> ReportOutput {
+            ReportOutput::Table(Table {
+                columns: vec!["Rows".into()],
+                rows: vec![vec![Cell::Text(self.1.to_string())]],
+            })
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = ReportRegistry::builtin();
+        let builtin = registry.names().count();
+        registry.register(Fixed("Custom", 1));
+        registry.register(Fixed("Custom", 2));
+        assert_eq!(registry.names().count(), builtin + 1);
+        assert_eq!(registry.names().last(), Some("Custom"));
+
+        let input = ReportInput {
+            transactions: &[],
+            period: Period::containing(
+                chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+                crate::period::PeriodKind::Month,
+            ),
+            commodity: "USD",
+        };
+        let Some(ReportOutput::Table(table)) =
+            registry.get("Custom").map(|report| report.report(&input))
+        else {
+            panic!("should be a table");
+        };
+        assert_eq!(table.rows, [[Cell::Text("2".into())]]);
+        assert!(registry.get("Missing").is_none());
+    }
+}
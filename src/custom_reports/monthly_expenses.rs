@@ -0,0 +1,33 @@
+use crate::reports::monthly_flows;
+use crate::transactions::CurrencyAmount;
+
+use super::{Chart, ReportInput, ReportOutput, ReportProvider};
+
+/// Months charted, up to the end of the period.
+const MONTHS: usize = 12;
+
+/// Expenses of each of the last twelve months of the period, in the main commodity.
+pub struct MonthlyExpenses;
+
+impl ReportProvider for MonthlyExpenses {
+    fn name(&self) -> &'static str {
+        "Monthly expenses"
+    }
+
+    fn report(&self, input: &ReportInput) -> ReportOutput {
+        let last_day = input.period.end().pred_opt().unwrap_or(input.period.start);
+        let flows = monthly_flows(input.transactions, last_day, MONTHS, input.commodity);
+        ReportOutput::Chart(Chart {
+            bars: flows
+                .into_iter()
+                .map(|flow| {
+                    let amount = CurrencyAmount {
+                        value: flow.expenses,
+                        commodity: input.commodity.to_string(),
+                    };
+                    (flow.month.start.format("%b %Y").to_string(), amount)
+                })
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use fastnum::D128;
+
+use crate::transactions::CurrencyAmount;
+
+use super::{Cell, ReportInput, ReportOutput, ReportProvider, Table};
+
+/// Payees listed, the ones spent the most on first.
+const TOP: usize = 20;
+
+/// Payees spent the most on in the period, by their expenses in the main commodity.
+pub struct TopPayees;
+
+impl ReportProvider for TopPayees {
+    fn name(&self) -> &'static str {
+        "Top payees"
+    }
+
+    fn report(&self, input: &ReportInput) -> ReportOutput {
+        let mut payees = Vec::<(&str, usize, D128)>::new();
+        for transaction in input.transactions {
+            if !input.period.contains(transaction.time) {
+                continue;
+            }
+            let spent = transaction
+                .postings
+                .iter()
+                .filter(|posting| {
+                    posting
+                        .account
+                        .segments
+                        .first()
+                        .is_some_and(|root| root.eq_ignore_ascii_case("expenses"))
+                        && posting.amount.value.commodity == input.commodity
+                })
+                .fold(D128::ZERO, |spent, posting| {
+                    spent + posting.amount.value.value
+                });
+            if spent <= D128::ZERO {
+                continue;
+            }
+            let payee = transaction.description.as_str();
+            match payees.iter_mut().find(|(name, _, _)| *name == payee) {
+                Some((_, count, total)) => {
+                    *count += 1;
+                    *total += spent;
+                }
+                None => payees.push((payee, 1, spent)),
+            }
+        }
+        payees.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+        payees.truncate(TOP);
+
+        ReportOutput::Table(Table {
+            columns: vec!["Payee".into(), "Transactions".into(), "Spent".into()],
+            rows: payees
+                .into_iter()
+                .map(|(payee, count, spent)| {
+                    vec![
+                        Cell::Text(payee.to_string()),
+                        Cell::Text(count.to_string()),
+                        Cell::Amount(CurrencyAmount {
+                            value: spent,
+                            commodity: input.commodity.to_string(),
+                        }),
+                    ]
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::period::{Period, PeriodKind};
+    use crate::transactions::Transaction;
+
+    fn transaction(day: u32, payee: &str, amount: &str) -> Transaction {
+        Transaction::fixture(
+            chrono::NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            payee,
+            &[
+                ("expenses:food", amount),
+                ("assets:cash", &format!("-{amount}")),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_report() {
+        let transactions = [
+            transaction(1, "Bakery", "5 USD"),
+            transaction(2, "Grocer", "40 USD"),
+            transaction(9, "Bakery", "6 USD"),
+            transaction(10, "Cafe", "3 EUR"),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let input = ReportInput {
+            transactions: &transactions,
+            period: Period::containing(transactions[0].time, PeriodKind::Month),
+            commodity: "USD",
+        };
+        let ReportOutput::Table(table) = TopPayees.report(&input) else {
+            panic!("should be a table");
+        };
+        let usd = |value: i32| {
+            Cell::Amount(CurrencyAmount {
+                value: D128::from(value),
+                commodity: "USD".into(),
+            })
+        };
+        assert_eq!(
+            table.rows,
+            [
+                vec![Cell::Text("Grocer".into()), Cell::Text("1".into()), usd(40)],
+                vec![Cell::Text("Bakery".into()), Cell::Text("2".into()), usd(11)],
+            ]
+        );
+    }
+}
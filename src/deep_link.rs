@@ -0,0 +1,239 @@
+//! `ledger-desktop://` links opening the app on a view, from notes and other tools.
+//!
+//! `ledger-desktop://register?account=expenses:food&from=2025-01-01` opens a register
+//! tab filtered to the account from that day on. `account` can be repeated, and
+//! `to`, `payee` and `query` narrow it further.
+
+use chrono::NaiveDate;
+
+use crate::{
+    period::{Period, PeriodKind},
+    settings::SavedView,
+};
+
+/// Scheme the app registers itself for. macOS only hands the app links of a scheme its
+/// bundle declares, from `Cargo.toml`'s bundle metadata, and Linux desktops find the
+/// handler in `resources/ledger-desktop.desktop`.
+pub const SCHEME: &str = "ledger-desktop";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DeepLinkError {
+    #[error("not a {SCHEME}:// link")]
+    Scheme,
+    #[error("unknown view `{0}`, expected register")]
+    UnknownView(String),
+    #[error("unknown parameter `{0}`")]
+    UnknownParameter(String),
+    #[error("invalid date `{0}`, expected e.g. 2025-01-31")]
+    InvalidDate(String),
+}
+
+/// What a link opens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLink {
+    /// A register tab with the filters of the view.
+    Register(SavedView),
+}
+
+/// Decodes `%XX` escapes and `+` for spaces. Invalid escapes are kept as they are.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        match bytes[ix] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let byte = text
+                    .get(ix + 1..ix + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = byte {
+                    decoded.push(byte);
+                    ix += 3;
+                    continue;
+                }
+                decoded.push(b'%');
+            }
+            byte => decoded.push(byte),
+        }
+        ix += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, DeepLinkError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| DeepLinkError::InvalidDate(value.to_string()))
+}
+
+/// Shortest calendar period holding both days, the year of `from` when none does.
+fn covering_period(from: NaiveDate, to: NaiveDate) -> Period {
+    [PeriodKind::Month, PeriodKind::Quarter, PeriodKind::Year]
+        .into_iter()
+        .map(|kind| Period::containing(from, kind))
+        .find(|period| period.contains(to))
+        .unwrap_or_else(|| Period::containing(from, PeriodKind::Year))
+}
+
+/// Filters of a register opened from outside the app, by a link or the automation API.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegisterFilters {
+    pub accounts: Vec<String>,
+    pub payee: Option<String>,
+    /// Query narrowing the register, on top of the other filters.
+    pub query: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl RegisterFilters {
+    /// The register as a view. Without dates it shows the year containing `today`.
+    pub fn view(self, today: NaiveDate) -> SavedView {
+        let Self {
+            accounts,
+            payee,
+            query,
+            from,
+            to,
+        } = self;
+        let mut terms = query
+            .map(|query| format!("({query})"))
+            .into_iter()
+            .collect::<Vec<_>>();
+        if let Some(from) = from {
+            terms.push(format!("date>={}", from.format("%Y-%m-%d")));
+        }
+        if let Some(to) = to {
+            terms.push(format!("date<={}", to.format("%Y-%m-%d")));
+        }
+        let period = match (from, to) {
+            (Some(from), Some(to)) => covering_period(from, to),
+            (Some(from), None) => covering_period(from, today.max(from)),
+            (None, Some(to)) => Period::containing(to, PeriodKind::Year),
+            (None, None) => Period::containing(today, PeriodKind::Year),
+        };
+
+        let name = match accounts.as_slice() {
+            [] => payee.clone().unwrap_or_else(|| "Register".to_string()),
+            accounts => accounts.join(", "),
+        };
+        SavedView {
+            name,
+            accounts,
+            excluded_accounts: Vec::new(),
+            period,
+            payee,
+            query: (!terms.is_empty()).then(|| terms.join(" and ")),
+        }
+    }
+}
+
+/// Reads a link. A register without dates shows the year containing `today`.
+pub fn parse(url: &str, today: NaiveDate) -> Result<DeepLink, DeepLinkError> {
+    let rest = url
+        .strip_prefix(SCHEME)
+        .and_then(|rest| rest.strip_prefix("://"))
+        .ok_or(DeepLinkError::Scheme)?;
+    let (view, parameters) = rest.split_once('?').unwrap_or((rest, ""));
+    let view = view.trim_end_matches('/');
+    if view != "register" {
+        return Err(DeepLinkError::UnknownView(decode(view)));
+    }
+
+    let mut filters = RegisterFilters::default();
+    for parameter in parameters.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+        let value = decode(value);
+        match key {
+            "account" => filters.accounts.push(value),
+            "payee" => filters.payee = Some(value),
+            "query" => filters.query = Some(value),
+            "from" => filters.from = Some(parse_date(&value)?),
+            "to" => filters.to = Some(parse_date(&value)?),
+            _ => return Err(DeepLinkError::UnknownParameter(decode(key))),
+        }
+    }
+    Ok(DeepLink::Register(filters.view(today)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let today = date(2025, 3, 14);
+        assert_eq!(
+            parse(
+                "ledger-desktop://register?account=expenses:food&from=2025-01-01",
+                today
+            ),
+            Ok(DeepLink::Register(SavedView {
+                name: "expenses:food".into(),
+                accounts: vec!["expenses:food".into()],
+                excluded_accounts: Vec::new(),
+                period: Period::containing(today, PeriodKind::Quarter),
+                payee: None,
+                query: Some("date>=2025-01-01".into()),
+            }))
+        );
+
+        let Ok(DeepLink::Register(view)) = parse(
+            "ledger-desktop://register/?payee=Whole+Foods&query=amount%3E100&from=2024-11-01&to=2025-02-28",
+            today,
+        ) else {
+            panic!("should parse");
+        };
+        assert_eq!(view.name, "Whole Foods");
+        assert_eq!(view.payee.as_deref(), Some("Whole Foods"));
+        assert_eq!(
+            view.query.as_deref(),
+            Some("(amount>100) and date>=2024-11-01 and date<=2025-02-28")
+        );
+        assert_eq!(
+            view.period,
+            Period::containing(date(2024, 1, 1), PeriodKind::Year)
+        );
+
+        assert_eq!(
+            parse("ledger-desktop://register", today),
+            Ok(DeepLink::Register(SavedView {
+                name: "Register".into(),
+                accounts: Vec::new(),
+                excluded_accounts: Vec::new(),
+                period: Period::containing(today, PeriodKind::Year),
+                payee: None,
+                query: None,
+            }))
+        );
+        assert_eq!(parse("https://register", today), Err(DeepLinkError::Scheme));
+        assert_eq!(
+            parse("ledger-desktop://budget", today),
+            Err(DeepLinkError::UnknownView("budget".into()))
+        );
+        assert_eq!(
+            parse("ledger-desktop://register?from=yesterday", today),
+            Err(DeepLinkError::InvalidDate("yesterday".into()))
+        );
+    }
+
+    #[test]
+    fn test_scheme_is_declared() {
+        let bundle = include_str!("../Cargo.toml");
+        assert!(bundle.contains(&format!("osx_url_schemes = [\"{SCHEME}\"]")));
+        let desktop = include_str!("../resources/ledger-desktop.desktop");
+        assert!(desktop.contains(&format!("MimeType=x-scheme-handler/{SCHEME};")));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("a%20b+c"), "a b c");
+        assert_eq!(decode("caf%C3%A9"), "café");
+        assert_eq!(decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,130 @@
+//! Numbers for reporting performance problems with large journals: how much memory the
+//! transactions take and how much the ledger process uses.
+//!
+//! Sizes of transactions are estimates: the structs themselves plus the heap buffers of
+//! their strings and vectors, without the allocator's overhead.
+
+use std::borrow::Borrow;
+use std::mem::size_of;
+
+use crate::transactions::{Amount, Cost, CurrencyAmount, Posting, Transaction};
+
+fn amount_size(amount: &CurrencyAmount) -> usize {
+    amount.commodity.capacity()
+}
+
+fn posting_size(posting: &Posting) -> usize {
+    let Amount {
+        value, price, cost, ..
+    } = &posting.amount;
+    let cost = match cost {
+        Some(Cost::PerUnit(cost) | Cost::Total(cost)) => amount_size(cost),
+        None => 0,
+    };
+    size_of::<Posting>()
+        + posting.account.segments.capacity() * size_of::<String>()
+        + posting
+            .account
+            .segments
+            .iter()
+            .map(String::capacity)
+            .sum::<usize>()
+        + amount_size(value)
+        + price.as_ref().map_or(0, amount_size)
+        + cost
+        + posting.note.as_ref().map_or(0, String::capacity)
+}
+
+/// Estimated bytes the transaction takes, postings included.
+pub fn transaction_size(transaction: &Transaction) -> usize {
+    size_of::<Transaction>()
+        + transaction.file.capacity()
+        + transaction.description.capacity()
+        + (transaction.postings.capacity() - transaction.postings.len()) * size_of::<Posting>()
+        + transaction.postings.iter().map(posting_size).sum::<usize>()
+}
+
+/// Estimated bytes the transactions take.
+pub fn model_size(transactions: &[impl Borrow<Transaction>]) -> usize {
+    transactions
+        .iter()
+        .map(|transaction| transaction_size(transaction.borrow()))
+        .sum()
+}
+
+/// Resident memory of a process in bytes, from `ps -o rss=` printing it in kilobytes.
+fn parse_rss(output: &str) -> Option<u64> {
+    output
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|kilobytes| kilobytes * 1024)
+}
+
+/// Resident memory of the process in bytes. Runs `ps`, so it blocks.
+pub fn process_rss(pid: u32) -> Option<u64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_rss(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Bytes in the largest unit that keeps them at least one, e.g. `1.5 MB`.
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while value >= 1024. && unit + 1 < UNITS.len() {
+        value /= 1024.;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_size() {
+        let transaction = Transaction::fixture(
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            "Grocer",
+            &[("Expenses:Food", "10 USD"), ("Assets:Cash", "-10 USD")],
+        );
+        let size = transaction_size(&transaction);
+        assert!(size >= size_of::<Transaction>() + 2 * size_of::<Posting>() + "Grocer".len());
+
+        let mut noted = transaction.clone();
+        let unnoted = transaction_size(&noted);
+        noted.postings[0].note = Some(" :transfer:".into());
+        assert_eq!(transaction_size(&noted), unnoted + " :transfer:".len());
+
+        assert_eq!(
+            model_size(&[&transaction, &noted]),
+            size + transaction_size(&noted)
+        );
+        assert_eq!(model_size(&[] as &[Transaction]), 0);
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        assert_eq!(parse_rss("  10240\n"), Some(10240 * 1024));
+        assert_eq!(parse_rss(""), None);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+}
@@ -0,0 +1,710 @@
+//! Small expression language for register columns and filters computed from each
+//! posting, so they can be added without recompiling.
+//!
+//! - Fields: `date`, `payee`, `account`, `amount` (the number), `commodity`, `note`,
+//!   `weekday` (`Mon` to `Sun`), `year`, `month` and `day`
+//! - Literals: numbers, `"text"`, `true` and `false`
+//! - Operators: `+ - * /`, `+` also joining text, comparisons `= != < <= > >=`, and
+//!   `and`, `or` and `not`. Dates compare to text like `"2025-03-01"`.
+//! - Functions: `value("EUR")` is the amount valued in another commodity on the
+//!   transaction's date, `abs(x)`, `round(x, places)`, `lower(text)`, `upper(text)`,
+//!   `contains(text, part)` ignoring case, and `if(condition, then, else)`.
+//!
+//! `value` is empty without a price to value the amount at, and so is anything made
+//! from an empty value or divided by zero.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{Datelike as _, NaiveDate};
+use fastnum::D128;
+
+use crate::prices::PriceHistory;
+use crate::query::Comparison;
+use crate::transactions::{Posting, Transaction};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExprError {
+    #[error("unexpected `{0}`")]
+    Unexpected(String),
+    #[error("expected an expression after `{0}`")]
+    MissingOperand(String),
+    #[error("missing `)`")]
+    UnclosedParen,
+    #[error("missing closing quote")]
+    UnclosedQuote,
+    #[error("unknown field `{0}`")]
+    UnknownField(String),
+    #[error("unknown function `{0}`")]
+    UnknownFunction(String),
+    #[error("`{0}` takes {1} arguments")]
+    Arguments(&'static str, &'static str),
+    #[error("can't {0} {1} and {2}")]
+    Types(&'static str, &'static str, &'static str),
+    #[error("can't negate {0}")]
+    Negate(&'static str),
+    #[error("expected a condition, got {0}")]
+    NotCondition(&'static str),
+    #[error("`{0}` expects {1}, got {2}")]
+    Argument(&'static str, &'static str, &'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// Missing, like the value of an amount without a price.
+    Empty,
+    Bool(bool),
+    Number(D128),
+    Text(String),
+    Date(NaiveDate),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Empty => "nothing",
+            Self::Bool(_) => "a condition",
+            Self::Number(_) => "a number",
+            Self::Text(_) => "text",
+            Self::Date(_) => "a date",
+        }
+    }
+
+    /// Whether a filter keeps the posting.
+    pub fn is_true(&self) -> bool {
+        matches!(self, Self::Bool(true))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => Ok(()),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Text(value) => write!(f, "{value}"),
+            Self::Date(value) => write!(f, "{}", value.format("%Y-%m-%d")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Date,
+    Payee,
+    Account,
+    Amount,
+    Commodity,
+    Note,
+    Weekday,
+    Year,
+    Month,
+    Day,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "date" => Self::Date,
+            "payee" => Self::Payee,
+            "account" => Self::Account,
+            "amount" => Self::Amount,
+            "commodity" => Self::Commodity,
+            "note" => Self::Note,
+            "weekday" => Self::Weekday,
+            "year" => Self::Year,
+            "month" => Self::Month,
+            "day" => Self::Day,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Value,
+    Abs,
+    Round,
+    Lower,
+    Upper,
+    Contains,
+    If,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "value" => Self::Value,
+            "abs" => Self::Abs,
+            "round" => Self::Round,
+            "lower" => Self::Lower,
+            "upper" => Self::Upper,
+            "contains" => Self::Contains,
+            "if" => Self::If,
+            _ => return None,
+        })
+    }
+
+    /// Name and the number of arguments, as written in errors.
+    fn signature(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Value => ("value", "1"),
+            Self::Abs => ("abs", "1"),
+            Self::Round => ("round", "2"),
+            Self::Lower => ("lower", "1"),
+            Self::Upper => ("upper", "1"),
+            Self::Contains => ("contains", "2"),
+            Self::If => ("if", "3"),
+        }
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Self::Value | Self::Abs | Self::Lower | Self::Upper => 1,
+            Self::Round | Self::Contains => 2,
+            Self::If => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Compare(Comparison),
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Literal(Value),
+    Field(Field),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+/// What an expression is evaluated against.
+pub struct Env<'a> {
+    pub transaction: &'a Transaction,
+    pub posting: &'a Posting,
+    /// Prices by the commodity they are in, for `value`.
+    pub prices: &'a HashMap<String, PriceHistory>,
+}
+
+impl Expr {
+    pub fn parse(text: &str) -> Result<Self, ExprError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some(token) => Err(ExprError::Unexpected(token.text())),
+        }
+    }
+
+    /// Commodities `value` is asked for, which prices are needed in.
+    pub fn value_commodities(&self) -> Vec<String> {
+        let mut commodities = Vec::new();
+        self.visit(&mut |expr| {
+            if let Self::Call(Function::Value, args) = expr {
+                if let [Self::Literal(Value::Text(commodity))] = args.as_slice() {
+                    commodities.push(commodity.clone());
+                }
+            }
+        });
+        commodities
+    }
+
+    fn visit(&self, f: &mut impl FnMut(&Self)) {
+        f(self);
+        match self {
+            Self::Literal(_) | Self::Field(_) => {}
+            Self::Negate(expr) | Self::Not(expr) => expr.visit(f),
+            Self::Binary(_, left, right) => {
+                left.visit(f);
+                right.visit(f);
+            }
+            Self::Call(_, args) => args.iter().for_each(|arg| arg.visit(f)),
+        }
+    }
+
+    pub fn eval(&self, env: &Env) -> Result<Value, ExprError> {
+        match self {
+            Self::Literal(value) => Ok(value.clone()),
+            Self::Field(field) => Ok(field_value(*field, env)),
+            Self::Negate(expr) => match expr.eval(env)? {
+                Value::Number(value) => Ok(Value::Number(-value)),
+                Value::Empty => Ok(Value::Empty),
+                value => Err(ExprError::Negate(value.type_name())),
+            },
+            Self::Not(expr) => Ok(Value::Bool(!condition(&expr.eval(env)?)?)),
+            Self::Binary(Operator::And, left, right) => Ok(Value::Bool(
+                condition(&left.eval(env)?)? && condition(&right.eval(env)?)?,
+            )),
+            Self::Binary(Operator::Or, left, right) => Ok(Value::Bool(
+                condition(&left.eval(env)?)? || condition(&right.eval(env)?)?,
+            )),
+            Self::Binary(operator, left, right) => {
+                binary(*operator, &left.eval(env)?, &right.eval(env)?)
+            }
+            Self::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(env))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(*function, &args, env)
+            }
+        }
+    }
+}
+
+fn field_value(field: Field, env: &Env) -> Value {
+    let date = env.transaction.time;
+    let number = |value: u32| Value::Number(D128::from(value));
+    match field {
+        Field::Date => Value::Date(date),
+        Field::Payee => Value::Text(env.transaction.description.clone()),
+        Field::Account => Value::Text(env.posting.account.to_string()),
+        Field::Amount => Value::Number(env.posting.amount.value.value),
+        Field::Commodity => Value::Text(env.posting.amount.value.commodity.clone()),
+        Field::Note => Value::Text(env.posting.note.clone().unwrap_or_default()),
+        Field::Weekday => Value::Text(date.format("%a").to_string()),
+        Field::Year => Value::Number(D128::from(date.year())),
+        Field::Month => number(date.month()),
+        Field::Day => number(date.day()),
+    }
+}
+
+/// A condition's truth; nothing counts as false.
+fn condition(value: &Value) -> Result<bool, ExprError> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        Value::Empty => Ok(false),
+        value => Err(ExprError::NotCondition(value.type_name())),
+    }
+}
+
+fn binary(operator: Operator, left: &Value, right: &Value) -> Result<Value, ExprError> {
+    let types = |doing| ExprError::Types(doing, left.type_name(), right.type_name());
+    match (operator, left, right) {
+        (Operator::Compare(comparison), left, right) => compare(comparison, left, right)
+            .map(Value::Bool)
+            .ok_or_else(|| types("compare")),
+        (_, Value::Empty, _) | (_, _, Value::Empty) => Ok(Value::Empty),
+        (Operator::Add, Value::Text(left), right) => Ok(Value::Text(format!("{left}{right}"))),
+        (Operator::Add, left, Value::Text(right)) => Ok(Value::Text(format!("{left}{right}"))),
+        (_, Value::Number(left), Value::Number(right)) => Ok(match operator {
+            Operator::Add => Value::Number(*left + *right),
+            Operator::Subtract => Value::Number(*left - *right),
+            Operator::Multiply => Value::Number(*left * *right),
+            Operator::Divide if right.is_zero() => Value::Empty,
+            Operator::Divide => Value::Number(*left / *right),
+            Operator::Compare(_) | Operator::And | Operator::Or => {
+                unreachable!("handled above")
+            }
+        }),
+        _ => Err(types("calculate with")),
+    }
+}
+
+/// Comparison of two values of the same type, or of a date and text naming a day.
+/// Comparisons with nothing don't hold; `None` for types that can't be compared.
+fn compare(comparison: Comparison, left: &Value, right: &Value) -> Option<bool> {
+    let day = |text: &str| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok();
+    Some(match (left, right) {
+        (Value::Empty, _) | (_, Value::Empty) => false,
+        (Value::Bool(left), Value::Bool(right)) => comparison.holds(left, right),
+        (Value::Number(left), Value::Number(right)) => comparison.holds(left, right),
+        (Value::Text(left), Value::Text(right)) => comparison.holds(left, right),
+        (Value::Date(left), Value::Date(right)) => comparison.holds(left, right),
+        (Value::Date(left), Value::Text(right)) => comparison.holds(left, &day(right)?),
+        (Value::Text(left), Value::Date(right)) => comparison.holds(&day(left)?, right),
+        _ => return None,
+    })
+}
+
+fn call(function: Function, args: &[Value], env: &Env) -> Result<Value, ExprError> {
+    let (name, _) = function.signature();
+    let types = |value: &Value, expected| ExprError::Argument(name, expected, value.type_name());
+    let text = |value: &Value| match value {
+        Value::Text(text) => Ok(text.clone()),
+        value => Err(types(value, "text")),
+    };
+    let [first, rest @ ..] = args else {
+        unreachable!("functions take arguments");
+    };
+    if matches!(first, Value::Empty) && function != Function::If {
+        return Ok(Value::Empty);
+    }
+    Ok(match (function, first, rest) {
+        (Function::Value, commodity, []) => {
+            let commodity = text(commodity)?;
+            let amount = &env.posting.amount.value;
+            if amount.commodity == commodity {
+                Value::Number(amount.value)
+            } else {
+                env.prices
+                    .get(&commodity)
+                    .and_then(|prices| prices.on(&amount.commodity, env.transaction.time))
+                    .map_or(Value::Empty, |price| Value::Number(amount.value * price))
+            }
+        }
+        (Function::Abs, Value::Number(value), []) => Value::Number(value.abs()),
+        (Function::Round, Value::Number(value), [Value::Number(places)]) => {
+            let places = places.to_string().parse::<i16>().unwrap_or(0);
+            Value::Number(value.rescale(places))
+        }
+        (Function::Lower, value, []) => Value::Text(text(value)?.to_lowercase()),
+        (Function::Upper, value, []) => Value::Text(text(value)?.to_uppercase()),
+        (Function::Contains, value, [part]) => Value::Bool(
+            text(value)?
+                .to_lowercase()
+                .contains(&text(part)?.to_lowercase()),
+        ),
+        (Function::If, condition_value, [then, otherwise]) => {
+            if condition(condition_value)? {
+                then.clone()
+            } else {
+                otherwise.clone()
+            }
+        }
+        (_, value, _) => return Err(types(value, "a number")),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(D128),
+    Text(String),
+    Name(String),
+    Symbol(&'static str),
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Self::Number(value) => value.to_string(),
+            Self::Text(text) => format!("\"{text}\""),
+            Self::Name(name) => name.clone(),
+            Self::Symbol(symbol) => (*symbol).to_string(),
+        }
+    }
+}
+
+/// Symbols, longest first so `<=` isn't read as `<`.
+const SYMBOLS: [&str; 14] = [
+    "!=", "<=", ">=", "(", ")", ",", "+", "-", "*", "/", "=", "<", ">", "!",
+];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let (text, after) = rest[1..].split_once('"').ok_or(ExprError::UnclosedQuote)?;
+            tokens.push(Token::Text(text.to_string()));
+            rest = after;
+        } else if c.is_ascii_digit() || c == '.' {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| ExprError::Unexpected(rest[..end].to_string()))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(rest[..end].to_lowercase()));
+            rest = &rest[end..];
+        } else {
+            let symbol = SYMBOLS
+                .into_iter()
+                .find(|symbol| rest.starts_with(symbol))
+                .ok_or_else(|| ExprError::Unexpected(c.to_string()))?;
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Takes the next token if it is the symbol or keyword.
+    fn eat(&mut self, word: &str) -> bool {
+        let found = match self.peek() {
+            Some(Token::Symbol(symbol)) => *symbol == word,
+            Some(Token::Name(name)) => name == word,
+            _ => false,
+        };
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn previous_text(&self) -> String {
+        self.pos
+            .checked_sub(1)
+            .and_then(|ix| self.tokens.get(ix))
+            .map(Token::text)
+            .unwrap_or_default()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.parse_and()?;
+        while self.eat("or") {
+            let right = self.parse_and()?;
+            expr = Expr::Binary(Operator::Or, Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.parse_not()?;
+        while self.eat("and") {
+            let right = self.parse_not()?;
+            expr = Expr::Binary(Operator::And, Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, ExprError> {
+        if self.eat("not") || self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, ExprError> {
+        let left = self.parse_sum()?;
+        let comparison = [
+            ("=", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ]
+        .into_iter()
+        .find_map(|(symbol, comparison)| self.eat(symbol).then_some(comparison));
+        let Some(comparison) = comparison else {
+            return Ok(left);
+        };
+        let right = self.parse_sum()?;
+        Ok(Expr::Binary(
+            Operator::Compare(comparison),
+            Box::new(left),
+            Box::new(right),
+        ))
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.parse_product()?;
+        loop {
+            let operator = if self.eat("+") {
+                Operator::Add
+            } else if self.eat("-") {
+                Operator::Subtract
+            } else {
+                return Ok(expr);
+            };
+            let right = self.parse_product()?;
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn parse_product(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            let operator = if self.eat("*") {
+                Operator::Multiply
+            } else if self.eat("/") {
+                Operator::Divide
+            } else {
+                return Ok(expr);
+            };
+            let right = self.parse_unary()?;
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ExprError> {
+        if self.eat("-") {
+            return Ok(Expr::Negate(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ExprError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(ExprError::MissingOperand(self.previous_text()));
+        };
+        self.pos += 1;
+        match token {
+            Token::Number(value) => Ok(Expr::Literal(Value::Number(value))),
+            Token::Text(text) => Ok(Expr::Literal(Value::Text(text))),
+            Token::Symbol("(") => {
+                let expr = self.parse_or()?;
+                if self.eat(")") {
+                    Ok(expr)
+                } else {
+                    Err(ExprError::UnclosedParen)
+                }
+            }
+            Token::Symbol(symbol) => Err(ExprError::Unexpected(symbol.to_string())),
+            Token::Name(name) if name == "true" || name == "false" => {
+                Ok(Expr::Literal(Value::Bool(name == "true")))
+            }
+            Token::Name(name) if self.eat("(") => {
+                let function = Function::parse(&name).ok_or(ExprError::UnknownFunction(name))?;
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.parse_or()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        if !self.eat(",") {
+                            return Err(ExprError::UnclosedParen);
+                        }
+                    }
+                }
+                if args.len() != function.arity() {
+                    let (name, arity) = function.signature();
+                    return Err(ExprError::Arguments(name, arity));
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Token::Name(name) => Field::parse(&name)
+                .map(Expr::Field)
+                .ok_or(ExprError::UnknownField(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::prices::PriceDirective;
+    use crate::transactions::CurrencyAmount;
+
+    fn transaction() -> Transaction {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        let mut transaction =
+            Transaction::fixture(date, "Whole Foods", &[("expenses:food", "120 USD")]);
+        transaction.postings[0].note = Some(" :shared:".into());
+        transaction
+    }
+
+    fn eval(text: &str) -> Result<Value, ExprError> {
+        let transaction = transaction();
+        let directive = PriceDirective {
+            date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            commodity: "USD".into(),
+            price: CurrencyAmount {
+                value: "0.9".parse().unwrap(),
+                commodity: "EUR".into(),
+            },
+        };
+        let prices = HashMap::from([(
+            "EUR".to_string(),
+            PriceHistory::new(&[], &[directive], "EUR"),
+        )]);
+        let env = Env {
+            transaction: &transaction,
+            posting: &transaction.postings[0],
+            prices: &prices,
+        };
+        Expr::parse(text)?.eval(&env)
+    }
+
+    fn number(text: &str) -> Value {
+        Value::Number(text.parse().unwrap())
+    }
+
+    #[test]
+    fn test_eval() {
+        assert_eq!(eval("value(\"EUR\")"), Ok(number("108.0")));
+        assert_eq!(eval("value(\"SEK\")"), Ok(Value::Empty));
+        assert_eq!(eval("weekday"), Ok(Value::Text("Sat".into())));
+        assert_eq!(eval("amount / 4 - 10 * 2"), Ok(number("10")));
+        assert_eq!(eval("-(amount + 1)"), Ok(number("-121")));
+        assert_eq!(eval("round(amount / 7, 2)"), Ok(number("17.14")));
+        assert_eq!(eval("amount / 0"), Ok(Value::Empty));
+        assert_eq!(
+            eval("payee + \" (\" + month + \")\""),
+            Ok(Value::Text("Whole Foods (3)".into()))
+        );
+        assert_eq!(
+            eval("if(amount > 100, \"big\", \"small\")"),
+            Ok(Value::Text("big".into()))
+        );
+        assert_eq!(
+            eval(
+                "contains(account, \"FOOD\") and date >= \"2025-03-01\" and not weekday = \"Sun\""
+            ),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(eval("value(\"SEK\") > 0"), Ok(Value::Bool(false)));
+        assert_eq!(
+            eval("payee * 2"),
+            Err(ExprError::Types("calculate with", "text", "a number"))
+        );
+        assert_eq!(
+            eval("abs(payee)"),
+            Err(ExprError::Argument("abs", "a number", "text"))
+        );
+        assert_eq!(eval("not amount"), Err(ExprError::NotCondition("a number")));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Expr::parse("amount >"),
+            Err(ExprError::MissingOperand(">".into()))
+        );
+        assert_eq!(Expr::parse("(amount"), Err(ExprError::UnclosedParen));
+        assert_eq!(Expr::parse("\"text"), Err(ExprError::UnclosedQuote));
+        assert_eq!(
+            Expr::parse("amount amount"),
+            Err(ExprError::Unexpected("amount".into()))
+        );
+        assert_eq!(
+            Expr::parse("price"),
+            Err(ExprError::UnknownField("price".into()))
+        );
+        assert_eq!(
+            Expr::parse("sum(amount)"),
+            Err(ExprError::UnknownFunction("sum".into()))
+        );
+        assert_eq!(
+            Expr::parse("round(amount)"),
+            Err(ExprError::Arguments("round", "2"))
+        );
+        assert_eq!(
+            Expr::parse("value(\"EUR\") + value(\"SEK\")").map(|expr| expr.value_commodities()),
+            Ok(vec!["EUR".to_string(), "SEK".to_string()])
+        );
+    }
+}
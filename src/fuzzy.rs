@@ -0,0 +1,107 @@
+//! Fuzzy matching for pickers.
+//!
+//! A query matches a candidate when its characters appear in the candidate in order,
+//! ignoring case. Matches at the start of words and runs of consecutive characters
+//! score higher, so `ab` ranks `assets:bank` above `cabinet`.
+
+/// Points for every matched character.
+const MATCH_SCORE: i64 = 16;
+/// Extra points for a match at the start of the candidate or right after a separator.
+const WORD_START_BONUS: i64 = 24;
+/// Extra points for a match right after the previous one.
+const CONSECUTIVE_BONUS: i64 = 12;
+/// Points lost for every skipped character between two matches.
+const GAP_PENALTY: i64 = 1;
+
+/// Scores the candidate against the query, `None` when it does not match.
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut query_chars = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut total = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut previous_char = None;
+    for (i, c) in candidate.chars().enumerate() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            total += MATCH_SCORE;
+            if previous_char.is_none_or(is_separator) {
+                total += WORD_START_BONUS;
+            }
+            match previous_match {
+                Some(previous) if previous + 1 == i => total += CONSECUTIVE_BONUS,
+                Some(previous) => {
+                    total -= GAP_PENALTY * i64::try_from(i - previous - 1).unwrap_or(i64::MAX);
+                }
+                None => {}
+            }
+            previous_match = Some(i);
+            query_chars.next();
+        }
+        previous_char = Some(c);
+    }
+
+    query_chars.peek().is_none().then_some(total)
+}
+
+/// Keeps the candidates that match the query, best first.
+///
+/// Ties go to the shorter candidate, then to the original order.
+pub fn rank<T>(query: &str, candidates: Vec<T>, key: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut scored = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let score = score(query, key(&candidate))?;
+            let len = key(&candidate).chars().count();
+            Some((score, len, candidate))
+        })
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored
+        .into_iter()
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, ':' | ' ' | '-' | '_' | '/' | '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_matches_subsequence() {
+        assert!(score("abc", "a:b:c").is_some());
+        assert!(score("ABC", "a_b_c").is_some());
+        assert!(score("acb", "a:b:c").is_none());
+        assert!(score("abcd", "abc").is_none());
+        assert_eq!(score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_rank_prefers_word_starts() {
+        let accounts = vec![
+            "expenses:cabinet",
+            "assets:bank:checking",
+            "assets:bank",
+            "liabilities:amex",
+        ];
+        assert_eq!(
+            rank("ab", accounts.clone(), |a| a),
+            vec![
+                "assets:bank",
+                "assets:bank:checking",
+                "expenses:cabinet",
+                "liabilities:amex"
+            ]
+        );
+        assert_eq!(rank("chk", accounts, |a| a), vec!["assets:bank:checking"]);
+    }
+}
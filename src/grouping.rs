@@ -0,0 +1,179 @@
+//! Grouping of register postings, with a subtotal for each group.
+
+use crate::accounts::{Account, Balance};
+use crate::transactions::Transaction;
+
+/// What register postings are grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Grouping {
+    #[default]
+    None,
+    Month,
+    Payee,
+    Account,
+}
+
+impl Grouping {
+    pub const ALL: [Grouping; 4] = [
+        Grouping::None,
+        Grouping::Month,
+        Grouping::Payee,
+        Grouping::Account,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Grouping::None => "None",
+            Grouping::Month => "Month",
+            Grouping::Payee => "Payee",
+            Grouping::Account => "Account",
+        }
+    }
+}
+
+/// Postings sharing a month, payee or account.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub label: String,
+    /// Transaction and posting indices, in the order of the transactions.
+    pub postings: Vec<(usize, usize)>,
+    /// Sum of the group's postings by commodity.
+    pub subtotal: Balance,
+}
+
+/// Groups `postings`, given as transaction and posting indices with the transaction
+/// they are of, in date order.
+///
+/// Months are in date order, payees and accounts by label. Accounts are labelled with
+/// `account_label`, so groups can follow the names accounts are shown as. With
+/// [`Grouping::None`] there are no groups.
+pub fn group_postings<'a>(
+    postings: impl IntoIterator<Item = (usize, usize, &'a Transaction)>,
+    grouping: Grouping,
+    account_label: impl Fn(&Account) -> String,
+) -> Vec<Group> {
+    if grouping == Grouping::None {
+        return Vec::new();
+    }
+
+    let mut groups = Vec::<Group>::new();
+    for (tx_ix, posting_ix, transaction) in postings {
+        let posting = &transaction.postings[posting_ix];
+        let label = match grouping {
+            Grouping::None => unreachable!("handled above"),
+            Grouping::Month => transaction.time.format("%B %Y").to_string(),
+            Grouping::Payee => transaction.description.clone(),
+            Grouping::Account => account_label(&posting.account),
+        };
+        let group = if let Some(ix) = groups.iter().position(|group| group.label == label) {
+            &mut groups[ix]
+        } else {
+            groups.push(Group {
+                label,
+                postings: Vec::new(),
+                subtotal: Balance::new(),
+            });
+            groups.last_mut().expect("group was just added")
+        };
+        group.postings.push((tx_ix, posting_ix));
+        group.subtotal.add_amount(posting.amount.value.clone());
+    }
+    if grouping != Grouping::Month {
+        groups.sort_by(|a, b| a.label.cmp(&b.label));
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(date: (i32, u32, u32), payee: &str, postings: &[(&str, &str)]) -> Transaction {
+        let date = chrono::NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+        Transaction::fixture(date, payee, postings)
+    }
+
+    /// All postings of `transactions`, as the register passes them.
+    fn all_postings(
+        transactions: &[Transaction],
+    ) -> impl Iterator<Item = (usize, usize, &Transaction)> {
+        transactions
+            .iter()
+            .enumerate()
+            .flat_map(|(tx_ix, t)| (0..t.postings.len()).map(move |ix| (tx_ix, ix, t)))
+    }
+
+    #[test]
+    fn test_group_postings() {
+        let transactions = [
+            transaction(
+                (2025, 2, 3),
+                "Shop",
+                &[("expenses:food", "10 USD"), ("assets:bank", "-10 USD")],
+            ),
+            transaction(
+                (2025, 2, 20),
+                "Cafe",
+                &[("expenses:food", "5 USD"), ("assets:cash", "-5 USD")],
+            ),
+            transaction(
+                (2025, 3, 1),
+                "Shop",
+                &[("expenses:food", "7 USD"), ("assets:bank", "-7 USD")],
+            ),
+        ];
+
+        let by_month = group_postings(
+            all_postings(&transactions),
+            Grouping::Month,
+            Account::to_string,
+        );
+        assert_eq!(
+            by_month
+                .iter()
+                .map(|group| (group.label.as_str(), group.postings.len()))
+                .collect::<Vec<_>>(),
+            [("February 2025", 4), ("March 2025", 2)]
+        );
+        assert!(by_month[0].subtotal.is_zero());
+
+        let by_payee = group_postings(
+            all_postings(&transactions),
+            Grouping::Payee,
+            Account::to_string,
+        );
+        assert_eq!(by_payee[0].label, "Cafe");
+        assert_eq!(by_payee[1].postings, [(0, 0), (0, 1), (2, 0), (2, 1)]);
+
+        let by_account = group_postings(
+            all_postings(&transactions),
+            Grouping::Account,
+            Account::to_string,
+        );
+        assert_eq!(
+            by_account
+                .iter()
+                .map(|group| (group.label.as_str(), group.subtotal.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                ("assets:bank", "-17 USD".to_string()),
+                ("assets:cash", "-5 USD".to_string()),
+                ("expenses:food", "22 USD".to_string()),
+            ]
+        );
+
+        assert!(group_postings(
+            all_postings(&transactions),
+            Grouping::None,
+            Account::to_string
+        )
+        .is_empty());
+
+        // Postings the register does not show are left out
+        let shown = [(0, 0, &transactions[0]), (2, 1, &transactions[2])];
+        let by_payee = group_postings(shown, Grouping::Payee, Account::to_string);
+        assert_eq!(by_payee.len(), 1);
+        assert_eq!(by_payee[0].postings, [(0, 0), (2, 1)]);
+        assert_eq!(by_payee[0].subtotal.to_string(), "3 USD");
+    }
+}
@@ -0,0 +1,470 @@
+//! Reports printed to stdout without opening a window, for scripts:
+//! `ledger-desktop --headless report balances --format json`.
+//!
+//! Transactions are read with the same ledger command and added up by the same code as
+//! in the app, so the numbers are the ones its windows show.
+
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+use crate::{
+    accounts::{Balance, TreeNode},
+    budget::{self, BudgetLine},
+    ledger,
+    prices::{self, PriceDirective, PriceHistory, Valuation},
+    settings::{Settings, SettingsError},
+    sexpr,
+    transactions::Transaction,
+};
+
+const USAGE: &str = "usage: ledger-desktop --headless report <balances|budget|net-worth> \
+                     [--format csv|json] [--file <journal>]";
+
+/// Top-level accounts that add up to net worth, as on the dashboard.
+const NET_WORTH: [&str; 2] = ["assets", "liabilities"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum HeadlessError {
+    #[error("{USAGE}")]
+    Usage,
+    #[error("unknown report {0:?}, expected balances, budget or net-worth")]
+    UnknownReport(String),
+    #[error("unknown format {0:?}, expected csv or json")]
+    UnknownFormat(String),
+    #[error(transparent)]
+    Settings(#[from] SettingsError),
+    #[error("failed to run {0}: {1}")]
+    Run(PathBuf, std::io::Error),
+    #[error("ledger failed: {0}")]
+    Ledger(String),
+    #[error("failed to read ledger output: {0}")]
+    Output(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Report {
+    /// Balance of every account, subaccounts included, as in the balances tree.
+    Balances,
+    /// This month of each budget envelope.
+    Budget,
+    /// Assets and liabilities summed by commodity.
+    NetWorth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+/// What `--headless` was asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub report: Report,
+    pub format: Format,
+    /// Journal to read instead of the one in settings.
+    pub journal: Option<PathBuf>,
+}
+
+/// Reads `--headless report <name>` and its options from the arguments after the
+/// binary's name. Without `--headless` the app opens its window, so there is nothing.
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, HeadlessError> {
+    let Some(rest) = args.strip_prefix(&["--headless".to_string()]) else {
+        return Ok(None);
+    };
+    let [subcommand, name, options @ ..] = rest else {
+        return Err(HeadlessError::Usage);
+    };
+    if subcommand != "report" {
+        return Err(HeadlessError::Usage);
+    }
+    let report = match name.as_str() {
+        "balances" => Report::Balances,
+        "budget" => Report::Budget,
+        "net-worth" => Report::NetWorth,
+        _ => return Err(HeadlessError::UnknownReport(name.clone())),
+    };
+    let mut command = Command {
+        report,
+        format: Format::Csv,
+        journal: None,
+    };
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().ok_or(HeadlessError::Usage)?;
+        match option.as_str() {
+            "--format" => {
+                command.format = match value.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => return Err(HeadlessError::UnknownFormat(value.clone())),
+                }
+            }
+            "--file" => command.journal = Some(PathBuf::from(value)),
+            _ => return Err(HeadlessError::Usage),
+        }
+    }
+    Ok(Some(command))
+}
+
+/// Rows of a report, each with a value for every column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub columns: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn to_csv(&self) -> String {
+        let line = |values: &mut dyn Iterator<Item = &str>| {
+            let mut line = values.map(csv_field).collect::<Vec<_>>().join(",");
+            line.push('\n');
+            line
+        };
+        let mut csv = line(&mut self.columns.iter().copied());
+        for row in &self.rows {
+            csv.push_str(&line(&mut row.iter().map(String::as_str)));
+        }
+        csv
+    }
+
+    /// An array with an object for each row. Amounts stay strings so no digits are lost.
+    pub fn to_json(&self) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, value)| ((*column).to_string(), value.clone().into()))
+                    .collect::<serde_json::Map<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        let mut json = serde_json::to_string_pretty(&rows).expect("rows serialize to JSON");
+        json.push('\n');
+        json
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Transactions read from ledger the way the app does, with the query it streams.
+fn read_transactions(
+    settings: &Settings,
+    journal: Option<&PathBuf>,
+) -> Result<Vec<Transaction>, HeadlessError> {
+    let mut command = std::process::Command::new(&settings.ledger_path);
+    if let Some(journal) = journal {
+        command.arg("--file").arg(journal);
+    }
+    let output = command
+        .args(ledger::TRANSACTIONS_QUERY.split_whitespace())
+        .output()
+        .map_err(|e| HeadlessError::Run(settings.ledger_path.clone(), e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(HeadlessError::Ledger(stderr.trim().to_string()));
+    }
+    let mut parser = sexpr::Parser::new();
+    parser
+        .take(&String::from_utf8_lossy(&output.stdout))
+        .map_err(|e| HeadlessError::Output(e.to_string()))?;
+    // Ledger prints the transactions wrapped in a list
+    let values = parser
+        .finish()
+        .map_err(|e| HeadlessError::Output(e.to_string()))?
+        .into_iter()
+        .flat_map(|value| match value {
+            sexpr::Value::List(children) => children,
+            other => vec![other],
+        })
+        .collect::<Vec<_>>();
+
+    let mut transactions = Vec::with_capacity(values.len());
+    for value in values {
+        let parsed = match &value {
+            sexpr::Value::List(list) => {
+                Transaction::from_sexpr(list).map_err(|e| HeadlessError::Output(e.to_string()))
+            }
+            other => Err(HeadlessError::Output(format!(
+                "expected a list, got {other}"
+            ))),
+        };
+        match parsed {
+            Ok(transaction) => transactions.push(transaction),
+            // As when loading in the app, lenient parsing skips what it cannot read
+            Err(e) if settings.lenient_parsing => eprintln!("Skipping transaction: {e}"),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(transactions)
+}
+
+/// Price directives and budget lines of the journal files transactions came from, and
+/// of the price database.
+fn read_directives(
+    transactions: &[Transaction],
+    journal: Option<&PathBuf>,
+    settings: &Settings,
+) -> (Vec<PriceDirective>, Vec<BudgetLine>) {
+    let mut files = transactions
+        .iter()
+        .map(|transaction| &transaction.file)
+        .chain(journal)
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+
+    let mut directives = Vec::new();
+    let mut budget = Vec::new();
+    for file in files {
+        let Ok(text) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        directives.extend(prices::parse_price_directives(&text));
+        budget.extend(budget::parse_budget(&text));
+    }
+    if let Some(text) = settings
+        .price_db
+        .as_ref()
+        .and_then(|db| std::fs::read_to_string(db).ok())
+    {
+        directives.extend(prices::parse_price_directives(&text));
+    }
+    (directives, budget)
+}
+
+/// Accounts with their balances, valued as settings say, like the balances tree.
+fn accounts(
+    transactions: &[&Transaction],
+    directives: &[PriceDirective],
+    settings: &Settings,
+) -> TreeNode {
+    let valuation = settings.valuation;
+    let prices = (valuation != Valuation::Native).then(|| {
+        let unit = settings
+            .base_currency
+            .clone()
+            .or_else(|| prices::most_used_commodity(transactions))
+            .unwrap_or_default();
+        PriceHistory::new(transactions, directives, &unit)
+    });
+    let mut accounts = TreeNode::new();
+    for transaction in transactions {
+        let valued = prices
+            .as_ref()
+            .map(|prices| prices.value_transaction(transaction, valuation));
+        for posting in &valued.as_ref().unwrap_or(transaction).postings {
+            accounts.add_account(&posting.account);
+            accounts.add_posting(posting);
+        }
+    }
+    accounts
+}
+
+fn balance_rows(node: &TreeNode, rows: &mut Vec<Vec<String>>) {
+    for child in &node.children {
+        for amount in child.balance.sorted_amounts() {
+            rows.push(vec![
+                child.account.to_string(),
+                amount.commodity.clone(),
+                amount.value.to_string(),
+            ]);
+        }
+        balance_rows(child, rows);
+    }
+}
+
+/// The report over the transactions, with budgets as of the month containing `today`.
+pub fn report(
+    report: Report,
+    transactions: &[&Transaction],
+    directives: &[PriceDirective],
+    budget: &[BudgetLine],
+    settings: &Settings,
+    today: NaiveDate,
+) -> Table {
+    match report {
+        Report::Balances => {
+            let mut rows = Vec::new();
+            balance_rows(&accounts(transactions, directives, settings), &mut rows);
+            Table {
+                columns: &["account", "commodity", "amount"],
+                rows,
+            }
+        }
+        Report::NetWorth => {
+            let accounts = accounts(transactions, directives, settings);
+            let mut net_worth = Balance::new();
+            accounts
+                .children
+                .iter()
+                .filter(|node| {
+                    node.account
+                        .segments
+                        .first()
+                        .is_some_and(|root| NET_WORTH.iter().any(|r| root.eq_ignore_ascii_case(r)))
+                })
+                .for_each(|node| net_worth += &node.balance);
+            Table {
+                columns: &["commodity", "amount"],
+                rows: net_worth
+                    .sorted_amounts()
+                    .into_iter()
+                    .map(|amount| vec![amount.commodity.clone(), amount.value.to_string()])
+                    .collect(),
+            }
+        }
+        Report::Budget => Table {
+            columns: &[
+                "account",
+                "commodity",
+                "month",
+                "budgeted",
+                "carried",
+                "spent",
+                "available",
+            ],
+            rows: budget::envelopes(budget, transactions, today)
+                .iter()
+                .filter_map(|envelope| {
+                    let month = envelope.current()?;
+                    Some(vec![
+                        envelope.account.to_string(),
+                        envelope.commodity.clone(),
+                        month.month.start.format("%Y-%m").to_string(),
+                        month.budgeted.to_string(),
+                        month.carried.to_string(),
+                        month.spent.to_string(),
+                        month.available().to_string(),
+                    ])
+                })
+                .collect(),
+        },
+    }
+}
+
+/// Runs the command, returning what to print.
+pub fn run(command: &Command) -> Result<String, HeadlessError> {
+    let settings = Settings::load()?;
+    let journal = command.journal.as_ref().or(settings.journal.as_ref());
+    let transactions = read_transactions(&settings, journal)?;
+    let (directives, budget) = read_directives(&transactions, journal, &settings);
+    let transactions = transactions.iter().collect::<Vec<_>>();
+    let today = chrono::Local::now().date_naive();
+    let table = report(
+        command.report,
+        &transactions,
+        &directives,
+        &budget,
+        &settings,
+        today,
+    );
+    Ok(match command.format {
+        Format::Csv => table.to_csv(),
+        Format::Json => table.to_json(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&args("")).unwrap(), None);
+        assert_eq!(
+            parse_args(&args(
+                "--headless report net-worth --format json --file a.ledger"
+            ))
+            .unwrap(),
+            Some(Command {
+                report: Report::NetWorth,
+                format: Format::Json,
+                journal: Some(PathBuf::from("a.ledger")),
+            })
+        );
+        assert_eq!(
+            parse_args(&args("--headless report balances"))
+                .unwrap()
+                .map(|command| command.format),
+            Some(Format::Csv)
+        );
+        assert!(matches!(
+            parse_args(&args("--headless report income")),
+            Err(HeadlessError::UnknownReport(_))
+        ));
+        assert!(matches!(
+            parse_args(&args("--headless report budget --format")),
+            Err(HeadlessError::Usage)
+        ));
+    }
+
+    fn transaction(date: (i32, u32, u32), postings: &[(&str, &str)]) -> Transaction {
+        let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+        Transaction::fixture(date, "Payee", postings)
+    }
+
+    #[test]
+    fn test_report() {
+        let transactions = [
+            transaction(
+                (2025, 1, 1),
+                &[
+                    ("Assets:Checking", "1000 USD"),
+                    ("Equity:Opening", "-1000 USD"),
+                ],
+            ),
+            transaction(
+                (2025, 3, 5),
+                &[("Expenses:Food", "60 USD"), ("Liabilities:Card", "-60 USD")],
+            ),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let budget = budget::parse_budget(
+            "~ Monthly from 2025-03-01\n    Expenses:Food  100 USD\n    Assets:Checking\n",
+        );
+        let settings = Settings::default();
+        let today = NaiveDate::from_ymd_opt(2025, 3, 20).unwrap();
+        let table = |kind| report(kind, &transactions, &[], &budget, &settings, today);
+
+        assert_eq!(
+            table(Report::Balances).to_csv(),
+            "account,commodity,amount\n\
+             Assets,USD,1000\n\
+             Assets:Checking,USD,1000\n\
+             Equity,USD,-1000\n\
+             Equity:Opening,USD,-1000\n\
+             Expenses,USD,60\n\
+             Expenses:Food,USD,60\n\
+             Liabilities,USD,-60\n\
+             Liabilities:Card,USD,-60\n"
+        );
+        assert_eq!(
+            table(Report::NetWorth).to_json(),
+            "[\n  {\n    \"amount\": \"940\",\n    \"commodity\": \"USD\"\n  }\n]\n"
+        );
+        assert_eq!(
+            table(Report::Budget).rows,
+            [["Expenses:Food", "USD", "2025-03", "100", "0", "60", "40"]]
+        );
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("Assets"), "Assets");
+        assert_eq!(csv_field("Shop, \"Main\""), "\"Shop, \"\"Main\"\"\"");
+    }
+}
@@ -0,0 +1,513 @@
+//! Undo and redo for changes the app makes to journal files.
+//!
+//! Every change is recorded as text replacements that know what they removed, so
+//! undoing one is applying its inverse. A replacement only applies when the file still
+//! has the expected text at its offset; anything else means the file was changed
+//! outside the app, and the step is refused instead of corrupting the journal.
+//!
+//! Entries are edited the same way: an edit is only made when the entry is still the
+//! text it was when the journal was loaded. Otherwise the conflict is handed back with
+//! both versions, for the user to pick one.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::transactions::parse_header;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error("failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("failed to write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error("{0} was changed outside the app")]
+    Conflict(PathBuf),
+    #[error("the entry on line {} of {} was changed outside the app", .0.line, .0.file.display())]
+    EntryConflict(Box<EntryConflict>),
+}
+
+/// An entry edited in the app that was also changed in its file since it was loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryConflict {
+    pub file: PathBuf,
+    /// Line of the entry's header when it was loaded.
+    pub line: i64,
+    /// The entry as it was loaded.
+    pub loaded: String,
+    /// The entry on that line of the file now, if there is one.
+    pub current: Option<String>,
+    /// What the app was going to write in place of the entry.
+    pub mine: String,
+}
+
+impl EntryConflict {
+    /// Edit writing the app's version over the file's, given the file's text now. With
+    /// no entry left on the line, the app's version is added to the end of the file.
+    pub fn keep_mine(&self, text: &str) -> Result<FileEdit, HistoryError> {
+        let Some(current) = &self.current else {
+            let separator = if text.is_empty() || text.ends_with("\n\n") {
+                ""
+            } else if text.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            return Ok(FileEdit {
+                file: self.file.clone(),
+                offset: text.len(),
+                removed: String::new(),
+                inserted: format!("{separator}{}", self.mine),
+            });
+        };
+        match entry_range(text, self.line) {
+            Some(range) if text[range.clone()] == *current => Ok(FileEdit {
+                file: self.file.clone(),
+                offset: range.start,
+                removed: current.clone(),
+                inserted: self.mine.clone(),
+            }),
+            _ => Err(HistoryError::Conflict(self.file.clone())),
+        }
+    }
+}
+
+/// Replacement of `removed` at byte `offset` of a file with `inserted`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileEdit {
+    pub file: PathBuf,
+    pub offset: usize,
+    pub removed: String,
+    pub inserted: String,
+}
+
+impl FileEdit {
+    /// Edit appending text to the end of the file.
+    pub fn append(file: &Path, inserted: String) -> Result<Self, HistoryError> {
+        let len = std::fs::metadata(file)
+            .map_err(|e| HistoryError::Read(file.to_path_buf(), e))?
+            .len();
+        Ok(Self {
+            file: file.to_path_buf(),
+            offset: usize::try_from(len).unwrap_or(usize::MAX),
+            removed: String::new(),
+            inserted,
+        })
+    }
+
+    /// Edit that undoes this one.
+    pub fn inverse(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            offset: self.offset,
+            removed: self.inserted.clone(),
+            inserted: self.removed.clone(),
+        }
+    }
+
+    /// Applies the edit to the file's text.
+    pub fn apply(&self, text: &str) -> Result<String, HistoryError> {
+        let end = self.offset + self.removed.len();
+        if text.get(self.offset..end) != Some(self.removed.as_str()) {
+            return Err(HistoryError::Conflict(self.file.clone()));
+        }
+        let mut result =
+            String::with_capacity(text.len() - self.removed.len() + self.inserted.len());
+        result.push_str(&text[..self.offset]);
+        result.push_str(&self.inserted);
+        result.push_str(&text[end..]);
+        Ok(result)
+    }
+}
+
+/// Edit replacing the entry whose header is on the 1-based `line` with what `replace`
+/// makes of its text.
+pub fn entry_edit(
+    file: &Path,
+    text: &str,
+    line: i64,
+    replace: impl FnOnce(&str) -> String,
+) -> Option<FileEdit> {
+    let range = entry_range(text, line)?;
+    let removed = &text[range.clone()];
+    Some(FileEdit {
+        file: file.to_path_buf(),
+        offset: range.start,
+        removed: removed.to_string(),
+        inserted: replace(removed),
+    })
+}
+
+/// Like [`entry_edit`], but only when the entry is still as it was in `loaded`, the
+/// file's text when the journal was loaded. An unchanged entry that lines above it
+/// moved is edited where it is now.
+pub fn checked_entry_edit(
+    file: &Path,
+    loaded: &str,
+    text: &str,
+    line: i64,
+    replace: impl FnOnce(&str) -> String,
+) -> Result<FileEdit, HistoryError> {
+    let loaded_entry = entry_range(loaded, line)
+        .map(|range| &loaded[range])
+        .ok_or_else(|| HistoryError::Conflict(file.to_path_buf()))?;
+    let on_line = entry_range(text, line);
+    let offset = match &on_line {
+        Some(range) if text[range.clone()] == *loaded_entry => Some(range.start),
+        _ => {
+            // Whole entries only: starting a line, and not followed by more postings
+            let mut found = text
+                .match_indices(loaded_entry)
+                .map(|(offset, _)| offset)
+                .filter(|offset| {
+                    (*offset == 0 || text[..*offset].ends_with('\n'))
+                        && !text[offset + loaded_entry.len()..].starts_with([' ', '\t'])
+                });
+            match (found.next(), found.next()) {
+                (Some(offset), None) => Some(offset),
+                _ => None,
+            }
+        }
+    };
+    match offset {
+        Some(offset) => Ok(FileEdit {
+            file: file.to_path_buf(),
+            offset,
+            removed: loaded_entry.to_string(),
+            inserted: replace(loaded_entry),
+        }),
+        None => Err(HistoryError::EntryConflict(Box::new(EntryConflict {
+            file: file.to_path_buf(),
+            line,
+            loaded: loaded_entry.to_string(),
+            current: on_line.map(|range| text[range].to_string()),
+            mine: replace(loaded_entry),
+        }))),
+    }
+}
+
+/// Bytes of the entry whose header is on the 1-based `line`: the header and the
+/// indented lines right after it.
+fn entry_range(text: &str, line: i64) -> Option<Range<usize>> {
+    let lines = lines_with_offsets(text).collect::<Vec<_>>();
+    let header = usize::try_from(line).ok()?.checked_sub(1)?;
+    let (start, header_line) = *lines.get(header)?;
+    parse_header(header_line)?;
+
+    let body = lines[header + 1..]
+        .iter()
+        .take_while(|(_, line)| line.starts_with([' ', '\t']) && !line.trim().is_empty())
+        .count();
+    let end = lines[header..=header + body]
+        .last()
+        .map(|(offset, line)| offset + line.len())?;
+    Some(start..end)
+}
+
+/// A line of a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Lines of `old` and `new` in order, with the ones only in one of them marked, keeping
+/// as many lines in common as there can be.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    // common[i][j] is how many lines old[i..] and new[j..] have in common
+    let mut common = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// Lines including their newline, with the byte offset each starts at.
+pub fn lines_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    })
+}
+
+/// A user-visible change, like committing transactions or renaming an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    pub label: String,
+    /// Edits in the order they are applied, each against the result of the previous.
+    pub edits: Vec<FileEdit>,
+}
+
+impl Operation {
+    pub fn new(label: impl Into<String>, edits: Vec<FileEdit>) -> Self {
+        Self {
+            label: label.into(),
+            edits,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            label: self.label.clone(),
+            edits: self.edits.iter().rev().map(FileEdit::inverse).collect(),
+        }
+    }
+
+    /// Applies the edits to disk, stopping at the first that fails.
+    ///
+    /// Consecutive edits of the same file are applied together, with a single read
+    /// and write.
+    pub fn apply(&self) -> Result<(), HistoryError> {
+        for edits in self.edits.chunk_by(|a, b| a.file == b.file) {
+            let file = &edits[0].file;
+            let text =
+                std::fs::read_to_string(file).map_err(|e| HistoryError::Read(file.clone(), e))?;
+            let text = edits
+                .iter()
+                .try_fold(text, |text, edit| edit.apply(&text))?;
+            std::fs::write(file, text).map_err(|e| HistoryError::Write(file.clone(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Undo and redo stacks of operations.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+impl History {
+    /// Records an operation that was just applied, dropping everything that could be
+    /// redone.
+    pub fn record(&mut self, operation: Operation) {
+        self.undo.push(operation);
+        self.redo.clear();
+    }
+
+    /// Operation undo reverts. It stays on the undo stack until [`History::undone`],
+    /// so a failed write leaves the history as it was.
+    pub fn next_undo(&self) -> Option<&Operation> {
+        self.undo.last()
+    }
+
+    /// Moves `operation` to the redo stack once its inverse is written. Nothing moves
+    /// when another operation was recorded or undone in the meantime.
+    pub fn undone(&mut self, operation: &Operation) {
+        if self.undo.last() == Some(operation) {
+            self.redo.extend(self.undo.pop());
+        }
+    }
+
+    /// Operation redo applies again, left on the redo stack until [`History::redone`].
+    pub fn next_redo(&self) -> Option<&Operation> {
+        self.redo.last()
+    }
+
+    /// Moves `operation` back to the undo stack once it is written again.
+    pub fn redone(&mut self, operation: &Operation) {
+        if self.redo.last() == Some(operation) {
+            self.undo.extend(self.redo.pop());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Operations that can be undone, oldest first.
+    pub fn undo_stack(&self) -> &[Operation] {
+        &self.undo
+    }
+
+    /// Operations that can be redone, the next one last.
+    pub fn redo_stack(&self) -> &[Operation] {
+        &self.redo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(offset: usize, removed: &str, inserted: &str) -> FileEdit {
+        FileEdit {
+            file: PathBuf::from("main.ledger"),
+            offset,
+            removed: removed.to_string(),
+            inserted: inserted.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_edit_inverse() {
+        let text = "2025-01-01 Shop\n    expenses:food  10 USD\n";
+        let edit = edit(35, "10", "12");
+        let edited = edit.apply(text).unwrap();
+        assert_eq!(edited, "2025-01-01 Shop\n    expenses:food  12 USD\n");
+        assert_eq!(edit.inverse().apply(&edited).unwrap(), text);
+    }
+
+    #[test]
+    fn test_edit_conflict() {
+        let text = "2025-01-01 Shop\n";
+        assert!(matches!(
+            edit(0, "2024", "2025").apply(text),
+            Err(HistoryError::Conflict(_))
+        ));
+        assert!(matches!(
+            edit(100, "", "x").apply(text),
+            Err(HistoryError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_entry_edit() {
+        let text =
+            "2025-01-01 Shop\n    expenses:food  10 USD\n    assets:cash\n\n2025-01-02 Rent\n";
+        let edit = entry_edit(Path::new("main.ledger"), text, 1, |entry| {
+            entry.replace("Shop", "Market")
+        })
+        .unwrap();
+        assert_eq!(edit.offset, 0);
+        assert_eq!(
+            edit.apply(text).unwrap(),
+            "2025-01-01 Market\n    expenses:food  10 USD\n    assets:cash\n\n2025-01-02 Rent\n"
+        );
+        assert!(entry_edit(Path::new("main.ledger"), text, 2, str::to_string).is_none());
+    }
+
+    #[test]
+    fn test_checked_entry_edit() {
+        let file = Path::new("main.ledger");
+        let loaded = "2025-01-01 Shop\n    expenses:food  10 USD\n    assets:cash\n";
+        let market = |entry: &str| entry.replace("Shop", "Market");
+
+        let edit = checked_entry_edit(file, loaded, loaded, 1, market).unwrap();
+        assert_eq!(edit.offset, 0);
+
+        // Moved down by an entry added above it
+        let moved =
+            format!("2024-12-31 Cafe\n    expenses:food  3 USD\n    assets:cash\n\n{loaded}");
+        let edit = checked_entry_edit(file, loaded, &moved, 1, market).unwrap();
+        assert_eq!(edit.apply(&moved).unwrap(), moved.replace("Shop", "Market"));
+
+        let changed = "2025-01-01 Shop\n    expenses:food  12 USD\n    assets:cash\n";
+        let Err(HistoryError::EntryConflict(conflict)) =
+            checked_entry_edit(file, loaded, changed, 1, market)
+        else {
+            panic!("should conflict");
+        };
+        assert_eq!(conflict.loaded, loaded);
+        assert_eq!(conflict.current.as_deref(), Some(changed));
+        assert_eq!(conflict.mine, loaded.replace("Shop", "Market"));
+        assert_eq!(
+            conflict.keep_mine(changed).unwrap().apply(changed).unwrap(),
+            conflict.mine
+        );
+
+        // A posting added to the entry is a change too
+        let extended = format!("{loaded}    assets:bank\n");
+        assert!(matches!(
+            checked_entry_edit(file, loaded, &extended, 1, market),
+            Err(HistoryError::EntryConflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(
+            diff_lines("a\nb\nc", "a\nx\nc\nd"),
+            [
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Added("x"),
+                DiffLine::Same("c"),
+                DiffLine::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operation_inverse_reverses_edits() {
+        let text = "abc";
+        let operation = Operation::new("edit", vec![edit(0, "a", "xy"), edit(2, "b", "")]);
+        let edited = operation
+            .edits
+            .iter()
+            .try_fold(text.to_string(), |text, edit| edit.apply(&text))
+            .unwrap();
+        assert_eq!(edited, "xyc");
+        let restored = operation
+            .inverse()
+            .edits
+            .iter()
+            .try_fold(edited, |text, edit| edit.apply(&text))
+            .unwrap();
+        assert_eq!(restored, text);
+    }
+
+    #[test]
+    fn test_history_stacks() {
+        let mut history = History::default();
+        history.record(Operation::new("first", vec![edit(0, "", "a")]));
+        history.record(Operation::new("second", vec![edit(1, "", "b")]));
+
+        // Nothing moves until the undo is written
+        let second = history.next_undo().unwrap().clone();
+        assert_eq!(second.label, "second");
+        assert_eq!(second.inverse().edits, vec![edit(1, "b", "")]);
+        assert_eq!(history.undo_stack().len(), 2);
+        assert!(history.redo_stack().is_empty());
+
+        history.undone(&second);
+        assert_eq!(history.undo_stack().len(), 1);
+        assert_eq!(history.redo_stack(), [second.clone()]);
+        // Undoing it again once the write finished is not applied twice
+        history.undone(&second);
+        assert_eq!(history.undo_stack().len(), 1);
+
+        let redo = history.next_redo().unwrap().clone();
+        assert_eq!(redo.edits, vec![edit(1, "", "b")]);
+        history.redone(&redo);
+        assert_eq!(history.undo_stack().len(), 2);
+        assert!(history.redo_stack().is_empty());
+
+        let second = history.next_undo().unwrap().clone();
+        history.undone(&second);
+        history.record(Operation::new("third", vec![edit(1, "", "c")]));
+        assert!(history.next_redo().is_none());
+        assert_eq!(history.undo_stack().len(), 2);
+        // An undo written after another change was recorded moves nothing
+        history.undone(&second);
+        assert_eq!(history.undo_stack().len(), 2);
+        assert!(history.redo_stack().is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+//! Text of the app in the user's language, and the date and amount formats usual
+//! for it.
+//!
+//! Messages are an enum, so a language missing a translation does not compile. Text
+//! that has no message yet is shown in English.
+//!
+//! Note: only the menus, the title bar and the sidebar have messages so far. The views
+//! (welcome screen, register, balances, reports, budget, loans, receivables, imports,
+//! settings and the rest), their dialogs, toasts and error text are still written in
+//! English in place. So are the labels of settings options such as groupings and
+//! valuations. Each view is moved over by adding its strings to [`Message`].
+
+use crate::amount_format::AmountFormat;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub fn next(self) -> Self {
+        match self {
+            Language::English => Language::German,
+            Language::German => Language::English,
+        }
+    }
+
+    /// Name of the language in itself.
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// How dates are usually written in the language, as a `chrono` format.
+    pub fn date_format(self) -> &'static str {
+        match self {
+            Language::English => "%Y-%m-%d",
+            Language::German => "%d.%m.%Y",
+        }
+    }
+
+    /// The amount format with the language's thousands and decimal separators.
+    pub fn amount_format(self, format: &AmountFormat) -> AmountFormat {
+        let (thousands, decimal) = match self {
+            Language::English => (",", "."),
+            Language::German => (".", ","),
+        };
+        AmountFormat {
+            thousands_separator: thousands.to_string(),
+            decimal_separator: decimal.to_string(),
+            ..format.clone()
+        }
+    }
+
+    pub fn text(self, message: Message) -> &'static str {
+        match self {
+            Language::English => english(message),
+            Language::German => german(message),
+        }
+    }
+}
+
+/// Text shown in the menus, the title bar and the sidebar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    Quit,
+    File,
+    Welcome,
+    QuickAdd,
+    OpenRecent,
+    View,
+    ZoomIn,
+    ZoomOut,
+    ActualSize,
+    Debug,
+    Diagnostics,
+    Settings,
+    Dashboard,
+    Register,
+    Balances,
+    Budget,
+    Loans,
+    Receivables,
+    Reports,
+    Imports,
+}
+
+fn english(message: Message) -> &'static str {
+    match message {
+        Message::Quit => "Quit",
+        Message::File => "File",
+        Message::Welcome => "Welcome",
+        Message::QuickAdd => "Quick Add…",
+        Message::OpenRecent => "Open Recent",
+        Message::View => "View",
+        Message::ZoomIn => "Zoom In",
+        Message::ZoomOut => "Zoom Out",
+        Message::ActualSize => "Actual Size",
+        Message::Debug => "Debug",
+        Message::Diagnostics => "Diagnostics",
+        Message::Settings => "Settings",
+        Message::Dashboard => "Dashboard",
+        Message::Register => "Register",
+        Message::Balances => "Balances",
+        Message::Budget => "Budget",
+        Message::Loans => "Loans",
+        Message::Receivables => "Receivables",
+        Message::Reports => "Reports",
+        Message::Imports => "Imports",
+    }
+}
+
+fn german(message: Message) -> &'static str {
+    match message {
+        Message::Quit => "Beenden",
+        Message::File => "Ablage",
+        Message::Welcome => "Willkommen",
+        Message::QuickAdd => "Schnell erfassen…",
+        Message::OpenRecent => "Zuletzt geöffnet",
+        Message::View => "Darstellung",
+        Message::ZoomIn => "Vergrößern",
+        Message::ZoomOut => "Verkleinern",
+        Message::ActualSize => "Originalgröße",
+        Message::Debug => "Debug",
+        Message::Diagnostics => "Diagnose",
+        Message::Settings => "Einstellungen",
+        Message::Dashboard => "Übersicht",
+        Message::Register => "Buchungen",
+        Message::Balances => "Salden",
+        Message::Budget => "Budget",
+        Message::Loans => "Kredite",
+        Message::Receivables => "Forderungen",
+        Message::Reports => "Berichte",
+        Message::Imports => "Importe",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount_format::{AmountFormatter, CommodityPrecision};
+    use crate::commodities::CommodityRegistry;
+    use crate::transactions::CurrencyAmount;
+
+    #[test]
+    fn test_formats() {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        assert_eq!(
+            date.format(Language::German.date_format()).to_string(),
+            "01.03.2025"
+        );
+
+        let format = |language: Language| {
+            let formatter = AmountFormatter::new(
+                language.amount_format(&AmountFormat::default()),
+                2,
+                CommodityPrecision::default(),
+                CommodityRegistry::default(),
+            );
+            formatter.format(&CurrencyAmount::parse("1234.5 CAD").unwrap())
+        };
+        assert_eq!(format(Language::English), "1,234.50 CAD");
+        assert_eq!(format(Language::German), "1.234,50 CAD");
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(Language::English.text(Message::Settings), "Settings");
+        assert_eq!(Language::German.text(Message::Settings), "Einstellungen");
+    }
+}
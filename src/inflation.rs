@@ -0,0 +1,175 @@
+use chrono::NaiveDate;
+
+/// US CPI-U annual averages, used when no custom table is provided.
+const BUILTIN_CPI: &[(i32, f64)] = &[
+    (2000, 172.2),
+    (2001, 177.1),
+    (2002, 179.9),
+    (2003, 184.0),
+    (2004, 188.9),
+    (2005, 195.3),
+    (2006, 201.6),
+    (2007, 207.342),
+    (2008, 215.303),
+    (2009, 214.537),
+    (2010, 218.056),
+    (2011, 224.939),
+    (2012, 229.594),
+    (2013, 232.957),
+    (2014, 236.736),
+    (2015, 237.017),
+    (2016, 240.007),
+    (2017, 245.120),
+    (2018, 251.107),
+    (2019, 255.657),
+    (2020, 258.811),
+    (2021, 270.970),
+    (2022, 292.655),
+    (2023, 304.702),
+    (2024, 313.689),
+];
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum ParseCpiError {
+    #[error("line {0}: expected `date,index`")]
+    InvalidFormat(usize),
+    #[error("line {0}: invalid date {1:?}")]
+    InvalidDate(usize, String),
+    #[error("line {0}: invalid index {1:?}")]
+    InvalidIndex(usize, String),
+    #[error("table is empty")]
+    Empty,
+}
+
+/// Consumer price index series used to express amounts in today's money.
+#[derive(Debug, Clone)]
+pub struct CpiTable {
+    /// Index values sorted by the date they take effect
+    points: Vec<(NaiveDate, f64)>,
+}
+
+impl CpiTable {
+    pub fn builtin() -> Self {
+        Self {
+            points: BUILTIN_CPI
+                .iter()
+                .filter_map(|(year, index)| {
+                    NaiveDate::from_ymd_opt(*year, 1, 1).map(|date| (date, *index))
+                })
+                .collect(),
+        }
+    }
+
+    /// Parses `date,index` lines, where date is `YYYY-MM-DD`, `YYYY-MM` or `YYYY`.
+    ///
+    /// Blank lines, `#` comments and a non-numeric header row are skipped.
+    pub fn parse_csv(text: &str) -> Result<Self, ParseCpiError> {
+        let mut points = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (date, index) = line
+                .split_once(',')
+                .ok_or(ParseCpiError::InvalidFormat(line_no))?;
+            let (date, index) = (date.trim(), index.trim());
+            if points.is_empty() && !date.starts_with(|c: char| c.is_ascii_digit()) {
+                // Header row
+                continue;
+            }
+            let date = parse_date(date)
+                .ok_or_else(|| ParseCpiError::InvalidDate(line_no, date.to_string()))?;
+            let index = index
+                .parse::<f64>()
+                .ok()
+                .filter(|index| *index > 0.0)
+                .ok_or_else(|| ParseCpiError::InvalidIndex(line_no, index.to_string()))?;
+            points.push((date, index));
+        }
+        if points.is_empty() {
+            return Err(ParseCpiError::Empty);
+        }
+        points.sort_by_key(|(date, _)| *date);
+        Ok(Self { points })
+    }
+
+    /// Index in effect on the given date, clamped to the table's range.
+    pub fn index_at(&self, date: NaiveDate) -> f64 {
+        let idx = self.points.partition_point(|(d, _)| *d <= date);
+        self.points[idx.saturating_sub(1)].1
+    }
+
+    /// Converts an amount from the given date into the money of the latest table entry.
+    pub fn to_today(&self, value: f64, date: NaiveDate) -> f64 {
+        let latest = self.points.last().map_or(1.0, |(_, index)| *index);
+        value * latest / self.index_at(date)
+    }
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d").ok())
+        .or_else(|| NaiveDate::parse_from_str(&format!("{s}-01-01"), "%Y-%m-%d").ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let table = CpiTable::parse_csv("date,cpi\n2020,100\n# comment\n\n2021-06,110.5\n")
+            .expect("should parse");
+        assert_eq!(
+            table.points,
+            vec![
+                (NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(), 100.0),
+                (NaiveDate::from_ymd_opt(2021, 6, 1).unwrap(), 110.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_errors() {
+        assert_eq!(
+            CpiTable::parse_csv("").map(|t| t.points),
+            Err(ParseCpiError::Empty)
+        );
+        assert!(matches!(
+            CpiTable::parse_csv("2020,abc"),
+            Err(ParseCpiError::InvalidIndex(1, _))
+        ));
+        assert!(matches!(
+            CpiTable::parse_csv("2020,100\nnope,1"),
+            Err(ParseCpiError::InvalidDate(2, _))
+        ));
+        assert_eq!(
+            CpiTable::parse_csv("2020").map(|t| t.points),
+            Err(ParseCpiError::InvalidFormat(1))
+        );
+    }
+
+    #[test]
+    fn test_to_today() {
+        let table = CpiTable::parse_csv("2020,100\n2022,200").expect("should parse");
+        let date = NaiveDate::from_ymd_opt(2020, 7, 1).unwrap();
+        assert!((table.to_today(50.0, date) - 100.0).abs() < f64::EPSILON);
+
+        // Dates before the table use the first entry
+        let date = NaiveDate::from_ymd_opt(1990, 1, 1).unwrap();
+        assert!((table.to_today(50.0, date) - 100.0).abs() < f64::EPSILON);
+
+        let date = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        assert!((table.to_today(50.0, date) - 50.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_builtin() {
+        let table = CpiTable::builtin();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert!((table.to_today(10.0, date) - 10.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,269 @@
+//! Spending that stands out from its own history.
+//!
+//! A month's spending in an expense category, or at a payee, is a spike when it is well
+//! above the average of the months before and further from it than those months
+//! usually are. Each spike keeps the transactions that drove it.
+
+use chrono::NaiveDate;
+use fastnum::D128;
+
+use crate::{
+    accounts::Account,
+    period::{Period, PeriodKind},
+    transactions::Transaction,
+};
+
+/// Months of history a month is compared to.
+const HISTORY_MONTHS: usize = 12;
+/// Months with spending needed before a spike can be told from noise.
+const MIN_HISTORY: usize = 3;
+/// How many times the average spending has to be.
+const MIN_RATIO: f64 = 1.5;
+/// How many standard deviations above the average spending has to be.
+const MIN_DEVIATIONS: f64 = 2.0;
+/// Transactions kept to explain a spike.
+const DRIVERS: usize = 3;
+
+/// What spent more than usual.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpikeSubject {
+    /// An expense category, the first two segments of the account.
+    Account(Account),
+    Payee(String),
+}
+
+/// A transaction that contributed to a spike.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Driver {
+    pub date: NaiveDate,
+    pub description: String,
+    pub amount: D128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spike {
+    pub subject: SpikeSubject,
+    pub month: Period,
+    pub spent: D128,
+    /// Monthly average of the months before.
+    pub average: D128,
+    /// Largest contributions first.
+    pub drivers: Vec<Driver>,
+}
+
+impl Spike {
+    /// How many times the average was spent.
+    pub fn ratio(&self) -> f64 {
+        to_f64(self.spent) / to_f64(self.average)
+    }
+}
+
+/// Category of an expense posting, `None` for other postings.
+fn category(account: &Account) -> Option<Account> {
+    let root = account.segments.first()?;
+    if !root.eq_ignore_ascii_case("expenses") {
+        return None;
+    }
+    let depth = account.segments.len().min(2);
+    Some(Account::from_segments(account.segments[..depth].to_vec()))
+}
+
+/// Expense postings of the transaction in the commodity that count towards the subject.
+fn spending(transaction: &Transaction, subject: &SpikeSubject, commodity: &str) -> D128 {
+    if let SpikeSubject::Payee(payee) = subject {
+        if &transaction.description != payee {
+            return D128::ZERO;
+        }
+    }
+    transaction
+        .postings
+        .iter()
+        .filter(|posting| posting.amount.value.commodity == commodity)
+        .filter(|posting| match (subject, category(&posting.account)) {
+            (SpikeSubject::Account(account), Some(category)) => &category == account,
+            (SpikeSubject::Payee(_), Some(_)) => true,
+            (_, None) => false,
+        })
+        .fold(D128::ZERO, |total, posting| {
+            total + posting.amount.value.value
+        })
+}
+
+/// Spikes in the month containing `until`, in one commodity, largest excess first.
+pub fn find_spikes(transactions: &[&Transaction], until: NaiveDate, commodity: &str) -> Vec<Spike> {
+    let month = Period::containing(until, PeriodKind::Month);
+    let Some(first) = transactions
+        .iter()
+        .map(|transaction| transaction.time)
+        .min()
+    else {
+        return Vec::new();
+    };
+    // Months before the current one that the journal covers, oldest first
+    let mut history = Vec::new();
+    let mut previous = month.prev();
+    while history.len() < HISTORY_MONTHS && previous.end() > first {
+        history.push(previous);
+        previous = previous.prev();
+    }
+    history.reverse();
+    if history.len() < MIN_HISTORY {
+        return Vec::new();
+    }
+
+    let current = transactions
+        .iter()
+        .filter(|transaction| month.contains(transaction.time))
+        .copied()
+        .collect::<Vec<_>>();
+    let mut subjects = Vec::new();
+    for transaction in &current {
+        for posting in &transaction.postings {
+            let Some(category) = category(&posting.account) else {
+                continue;
+            };
+            for subject in [
+                SpikeSubject::Account(category),
+                SpikeSubject::Payee(transaction.description.clone()),
+            ] {
+                if !subjects.contains(&subject) {
+                    subjects.push(subject);
+                }
+            }
+        }
+    }
+
+    let mut spikes = subjects
+        .into_iter()
+        .filter_map(|subject| {
+            let spent_in = |period: Period| {
+                transactions
+                    .iter()
+                    .filter(|transaction| period.contains(transaction.time))
+                    .fold(D128::ZERO, |total, transaction| {
+                        total + spending(transaction, &subject, commodity)
+                    })
+            };
+            let spent = spent_in(month);
+            let past = history
+                .iter()
+                .map(|period| spent_in(*period))
+                .collect::<Vec<_>>();
+            // A payee new this month has no history to be above
+            if past.iter().filter(|spent| **spent > D128::ZERO).count() < MIN_HISTORY {
+                return None;
+            }
+            let average = past.iter().fold(D128::ZERO, |total, spent| total + *spent)
+                / D128::from(past.len());
+            let mean = to_f64(average);
+            #[allow(clippy::cast_precision_loss)]
+            let variance = past
+                .iter()
+                .map(|spent| (to_f64(*spent) - mean).powi(2))
+                .sum::<f64>()
+                / past.len() as f64;
+            let excess = to_f64(spent) - mean;
+            if to_f64(spent) < mean * MIN_RATIO || excess <= variance.sqrt() * MIN_DEVIATIONS {
+                return None;
+            }
+            Some(Spike {
+                drivers: drivers(&current, &subject, commodity),
+                subject,
+                month,
+                spent,
+                average,
+            })
+        })
+        .collect::<Vec<_>>();
+    spikes.sort_by(|a, b| (b.spent - b.average).cmp(&(a.spent - a.average)));
+    spikes
+}
+
+/// Transactions spending on the subject, largest first.
+fn drivers(transactions: &[&Transaction], subject: &SpikeSubject, commodity: &str) -> Vec<Driver> {
+    let mut drivers = transactions
+        .iter()
+        .filter_map(|transaction| {
+            let amount = spending(transaction, subject, commodity);
+            (amount > D128::ZERO).then(|| Driver {
+                date: transaction.time,
+                description: transaction.description.clone(),
+                amount,
+            })
+        })
+        .collect::<Vec<_>>();
+    drivers.sort_by(|a, b| b.amount.cmp(&a.amount));
+    drivers.truncate(DRIVERS);
+    drivers
+}
+
+fn to_f64(value: D128) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(date: (i32, u32, u32), payee: &str, account: &str, amount: &str) -> Transaction {
+        Transaction::fixture(
+            NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+            payee,
+            &[(account, amount), ("Assets:Bank", &format!("-{amount}"))],
+        )
+    }
+
+    #[test]
+    fn test_find_spikes() {
+        let mut transactions = Vec::new();
+        for month in 1..=6 {
+            transactions.push(transaction(
+                (2025, month, 3),
+                "Grocer",
+                "Expenses:Food:Groceries",
+                "100 USD",
+            ));
+            transactions.push(transaction(
+                (2025, month, 5),
+                "Landlord",
+                "Expenses:Rent",
+                "500 USD",
+            ));
+        }
+        transactions.extend([
+            transaction((2025, 7, 3), "Grocer", "Expenses:Food:Groceries", "110 USD"),
+            transaction((2025, 7, 5), "Landlord", "Expenses:Rent", "500 USD"),
+            transaction((2025, 7, 9), "Bistro", "Expenses:Food:Dining", "150 USD"),
+            transaction((2025, 7, 12), "Bistro", "Expenses:Food:Dining", "60 USD"),
+            transaction((2025, 7, 20), "Bistro", "Expenses:Food:Dining", "30 EUR"),
+        ]);
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let until = NaiveDate::from_ymd_opt(2025, 7, 31).unwrap();
+
+        let spikes = find_spikes(&transactions, until, "USD");
+        assert_eq!(spikes.len(), 1);
+        let spike = &spikes[0];
+        assert_eq!(
+            spike.subject,
+            SpikeSubject::Account(Account::parse("Expenses:Food"))
+        );
+        assert_eq!(spike.spent, D128::from(320));
+        assert_eq!(spike.average, D128::from(100));
+        assert_eq!(
+            spike
+                .drivers
+                .iter()
+                .map(|driver| (driver.description.as_str(), driver.amount))
+                .collect::<Vec<_>>(),
+            [
+                ("Bistro", D128::from(150)),
+                ("Grocer", D128::from(110)),
+                ("Bistro", D128::from(60)),
+            ]
+        );
+
+        // Too little history to compare to
+        let until = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        assert!(find_spikes(&transactions, until, "USD").is_empty());
+    }
+}
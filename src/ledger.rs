@@ -1,4 +1,5 @@
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
@@ -10,14 +11,23 @@ use futures_lite::{Future, Stream};
 use crate::sexpr;
 use crate::transactions;
 
+#[cfg(test)]
+mod fake;
+
 const MARKER: &[u8] = b"__END_OF_RESPONSE__";
 
+/// Command used to stream all transactions.
+pub const TRANSACTIONS_QUERY: &str = "lisp --lisp-date-format %Y-%m-%d";
+
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum LedgerError {
     #[error(transparent)]
     Io(#[from] Arc<std::io::Error>),
     #[error("{0}")]
     Stderr(String),
+    /// A transaction ledger printed could not be read. Later ones still can be.
+    #[error("Failed to parse transaction: {0}")]
+    Parse(String),
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -38,19 +48,36 @@ struct LedgerCommand {
 #[derive(Clone)]
 pub struct LedgerHandle {
     cmd_tx: Sender<LedgerCommand>,
+    /// Id of the ledger process, zero until it has started.
+    pid: Arc<AtomicU32>,
 }
 
 impl LedgerHandle {
-    pub fn spawn(cx: &mut gpui::App, file: Option<std::path::PathBuf>) -> Self {
+    pub fn spawn(
+        cx: &mut gpui::App,
+        binary: std::path::PathBuf,
+        file: Option<std::path::PathBuf>,
+    ) -> Self {
         let (cmd_tx, cmd_rx) = bounded::<LedgerCommand>(16);
+        let pid = Arc::new(AtomicU32::new(0));
 
         cx.background_executor()
-            .spawn(async move {
-                run_actor(file, cmd_rx).await.expect("Ledger actor failed");
+            .spawn({
+                let pid = pid.clone();
+                async move {
+                    if let Err(e) = run_actor(binary, file, cmd_rx, pid).await {
+                        eprintln!("Ledger actor failed: {}", e);
+                    }
+                }
             })
             .detach();
 
-        Self { cmd_tx }
+        Self { cmd_tx, pid }
+    }
+
+    /// Id of the ledger process, once it has started.
+    pub fn pid(&self) -> Option<u32> {
+        Some(self.pid.load(Ordering::Relaxed)).filter(|pid| *pid != 0)
     }
 
     async fn send(&self, cmd: &str) -> Result<Receiver<LedgerEvent>, ChannelClosed> {
@@ -73,7 +100,7 @@ impl LedgerHandle {
     }
 
     pub async fn transactions(&self) -> Result<TransactionStream<LineStream>, ChannelClosed> {
-        let event_rx = self.send("lisp --lisp-date-format %Y-%m-%d").await?;
+        let event_rx = self.send(TRANSACTIONS_QUERY).await?;
         let line_stream = LineStream::from_events(event_rx);
         Ok(line_stream.sexpr().transactions())
     }
@@ -194,8 +221,9 @@ where
                         )))));
                     }
 
-                    // Check if any complete s-expressions are ready
-                    let mut completed = this.parser.drain_output();
+                    // Lists closed earlier come before the children of the open one
+                    let mut completed = unwrap_lists(this.parser.drain_output());
+                    completed.extend(this.parser.drain_open_list());
                     if !completed.is_empty() {
                         // Reverse so we can pop from the end
                         completed.reverse();
@@ -212,7 +240,7 @@ where
                     // Stream ended - finish parsing
                     *this.finished = true;
                     let parser = std::mem::replace(this.parser, sexpr::Parser::new());
-                    match parser.finish() {
+                    match parser.finish().map(unwrap_lists) {
                         Ok(mut values) => {
                             if values.is_empty() {
                                 return Poll::Ready(None);
@@ -234,6 +262,19 @@ where
     }
 }
 
+/// Ledger prints its results wrapped in a list, so the children of top-level lists
+/// are what the stream yields. Reading them from the open list lets transactions
+/// arrive while ledger is still printing.
+fn unwrap_lists(values: Vec<sexpr::Value>) -> Vec<sexpr::Value> {
+    values
+        .into_iter()
+        .flat_map(|value| match value {
+            sexpr::Value::List(children) => children,
+            other => vec![other],
+        })
+        .collect()
+}
+
 pin_project_lite::pin_project! {
     pub struct TransactionStream<S> {
         #[pin]
@@ -263,18 +304,14 @@ where
             Poll::Ready(Some(Ok(sexpr_value))) => {
                 // Parse the sexpr value as a transaction
                 let sexpr::Value::List(ref list) = sexpr_value else {
-                    return Poll::Ready(Some(Err(LedgerError::Stderr(format!(
-                        "Expected list for transaction, got: {:?}",
-                        sexpr_value
+                    return Poll::Ready(Some(Err(LedgerError::Parse(format!(
+                        "expected a list, got {sexpr_value}"
                     )))));
                 };
 
                 match transactions::Transaction::from_sexpr(list) {
                     Ok(transaction) => Poll::Ready(Some(Ok(transaction))),
-                    Err(e) => Poll::Ready(Some(Err(LedgerError::Stderr(format!(
-                        "Failed to parse transaction: {}",
-                        e
-                    ))))),
+                    Err(e) => Poll::Ready(Some(Err(LedgerError::Parse(e.to_string())))),
                 }
             }
             Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
@@ -286,17 +323,34 @@ where
 
 #[derive(Debug, thiserror::Error)]
 pub enum ActorError {
-    #[error(transparent)]
-    Io(#[from] std::io::Error),
     #[error(transparent)]
     Send(#[from] async_channel::SendError<LedgerEvent>),
 }
 
 async fn run_actor(
+    binary: std::path::PathBuf,
     file: Option<std::path::PathBuf>,
     cmd_rx: Receiver<LedgerCommand>,
+    pid: Arc<AtomicU32>,
 ) -> Result<(), ActorError> {
-    let mut ledger = Ledger::spawn(file).await.map_err(ActorError::Io)?;
+    let mut ledger = match Ledger::spawn(binary.clone(), file).await {
+        Ok(ledger) => ledger,
+        // The binary is set in settings and may be wrong, commands fail with why
+        Err(e) => {
+            let error = LedgerError::Io(Arc::new(std::io::Error::new(
+                e.kind(),
+                format!("Failed to start {}: {e}", binary.display()),
+            )));
+            while let Ok(LedgerCommand { response_tx, .. }) = cmd_rx.recv().await {
+                response_tx
+                    .send(LedgerEvent::Done(Err(error.clone())))
+                    .await
+                    .ok();
+            }
+            return Ok(());
+        }
+    };
+    pid.store(ledger.child.id(), Ordering::Relaxed);
 
     while let Ok(command) = cmd_rx.recv().await {
         let LedgerCommand { cmd, response_tx } = command;
@@ -383,7 +437,7 @@ struct Ledger {
     stdin: async_process::ChildStdin,
     stdout_reader: BufReader<async_process::ChildStdout>,
     stderr_reader: BufReader<async_process::ChildStderr>,
-    _child: async_process::Child,
+    child: async_process::Child,
 }
 
 enum ReadResult {
@@ -392,14 +446,19 @@ enum ReadResult {
 }
 
 impl Ledger {
-    async fn spawn(file: Option<std::path::PathBuf>) -> std::io::Result<Self> {
-        let mut cmd = Command::new("ledger");
+    async fn spawn(
+        binary: std::path::PathBuf,
+        file: Option<std::path::PathBuf>,
+    ) -> std::io::Result<Self> {
+        let mut cmd = Command::new(binary);
 
         if let Some(file_path) = file {
             cmd.arg("--file").arg(file_path);
         }
 
+        // Respawned on every reload, the old process goes with its handle
         let mut child = cmd
+            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -422,7 +481,7 @@ impl Ledger {
             stdin,
             stdout_reader,
             stderr_reader,
-            _child: child,
+            child,
         };
         repl.drain().await?;
 
@@ -500,9 +559,164 @@ impl Ledger {
 
 #[cfg(test)]
 mod tests {
+    use super::fake::{self, FakeLedger};
     use super::*;
     use futures_lite::StreamExt;
 
+    /// Handle on the fake ledger, with the actor running on a thread of its own.
+    fn spawn_fake(fake: &FakeLedger) -> LedgerHandle {
+        spawn_actor(FakeLedger::binary(), fake.journal())
+    }
+
+    fn spawn_actor(binary: std::path::PathBuf, journal: std::path::PathBuf) -> LedgerHandle {
+        let (cmd_tx, cmd_rx) = bounded::<LedgerCommand>(16);
+        let pid = Arc::new(AtomicU32::new(0));
+        std::thread::spawn({
+            let pid = pid.clone();
+            move || futures_lite::future::block_on(run_actor(binary, Some(journal), cmd_rx, pid))
+        });
+        LedgerHandle { cmd_tx, pid }
+    }
+
+    #[test]
+    fn test_fake_streams_transactions() {
+        let fake = FakeLedger::new();
+        fake.respond(TRANSACTIONS_QUERY, &fake::lisp_transactions(1000));
+        let handle = spawn_fake(&fake);
+        futures_lite::future::block_on(async {
+            let transactions = handle
+                .transactions()
+                .await
+                .expect("Failed to send command")
+                .map(|transaction| transaction.expect("Should parse transaction"))
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(transactions.len(), 1000);
+            assert_eq!(transactions[0].description, "Payee 0");
+            assert_eq!(transactions[999].description, "Payee 999");
+            assert_eq!(
+                transactions[999].postings[0].amount.value.to_string(),
+                "1000 USD"
+            );
+            assert!(handle.pid().is_some());
+        });
+    }
+
+    #[test]
+    fn test_fake_skips_unparsable_transaction() {
+        let fake = FakeLedger::new();
+        let output = fake::lisp_transactions(3).replacen("\"Payee 1\"", "42", 1);
+        fake.respond(TRANSACTIONS_QUERY, &output);
+        let handle = spawn_fake(&fake);
+        futures_lite::future::block_on(async {
+            let results = handle
+                .transactions()
+                .await
+                .expect("Failed to send command")
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(results.len(), 3);
+            assert!(matches!(results[1], Err(LedgerError::Parse(_))));
+            assert_eq!(results[2].as_ref().unwrap().description, "Payee 2");
+        });
+    }
+
+    #[test]
+    fn test_missing_binary_fails_commands() {
+        let handle = spawn_actor("/nonexistent/ledger".into(), "main.ledger".into());
+        futures_lite::future::block_on(async {
+            for _ in 0..2 {
+                let mut stream = handle
+                    .stream("balance")
+                    .await
+                    .expect("Failed to send command");
+                let Some(Err(LedgerError::Io(e))) = stream.next().await else {
+                    panic!("Expected an io error");
+                };
+                assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+                assert!(e
+                    .to_string()
+                    .starts_with("Failed to start /nonexistent/ledger"));
+            }
+            assert!(handle.pid().is_none());
+        });
+    }
+
+    #[test]
+    fn test_fake_reports_stderr() {
+        let fake = FakeLedger::new();
+        fake.fail("balance", "Error: No journal file was specified\n");
+        let handle = spawn_fake(&fake);
+        futures_lite::future::block_on(async {
+            let mut stream = handle
+                .stream("balance")
+                .await
+                .expect("Failed to send command");
+            let Some(Err(LedgerError::Stderr(message))) = stream.next().await else {
+                panic!("Expected a stderr error");
+            };
+            assert_eq!(message, "Error: No journal file was specified");
+        });
+    }
+
+    #[test]
+    fn test_fake_dropped_stream_is_drained() {
+        let fake = FakeLedger::new();
+        fake.respond(TRANSACTIONS_QUERY, &fake::lisp_transactions(1000))
+            .respond("balance", "10 USD  assets:cash\n");
+        let handle = spawn_fake(&fake);
+        futures_lite::future::block_on(async {
+            let mut transactions = handle.transactions().await.expect("Failed to send command");
+            let first = transactions.next().await;
+            assert_eq!(first.unwrap().unwrap().description, "Payee 0");
+            drop(transactions);
+
+            // What was left of the cancelled command is not read as the next answer
+            let lines = handle
+                .stream("balance")
+                .await
+                .expect("Failed to send command")
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(lines.len(), 1);
+            assert_eq!(lines[0].as_ref().unwrap(), "10 USD  assets:cash\n");
+        });
+    }
+
+    #[test]
+    fn test_fake_killed_when_dropped() {
+        let fake = FakeLedger::new();
+        fake.respond("balance", "10 USD  assets:cash\n");
+        let handle = spawn_fake(&fake);
+        let lines = futures_lite::future::block_on(async {
+            handle
+                .stream("balance")
+                .await
+                .expect("Failed to send command")
+                .collect::<Vec<_>>()
+                .await
+        });
+        assert_eq!(lines.len(), 1);
+        let pid = handle.pid().expect("ledger should have started");
+        drop(handle);
+
+        // Gone, or killed and waiting to be reaped
+        let running = || {
+            std::process::Command::new("ps")
+                .args(["-o", "stat=", "-p", &pid.to_string()])
+                .output()
+                .is_ok_and(|output| {
+                    let stat = String::from_utf8_lossy(&output.stdout);
+                    output.status.success() && !stat.trim().starts_with('Z')
+                })
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while running() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(!running(), "ledger process {pid} outlived its handle");
+    }
+
     #[test]
     fn test_valid_command_no_stderr() {
         futures_lite::future::block_on(async {
@@ -510,9 +724,19 @@ mod tests {
             let (cmd_tx, cmd_rx) = bounded::<LedgerCommand>(16);
 
             // Spawn actor in background
-            std::thread::spawn(move || futures_lite::future::block_on(run_actor(None, cmd_rx)));
+            std::thread::spawn(move || {
+                futures_lite::future::block_on(run_actor(
+                    "ledger".into(),
+                    None,
+                    cmd_rx,
+                    Arc::default(),
+                ))
+            });
 
-            let handle = LedgerHandle { cmd_tx };
+            let handle = LedgerHandle {
+                cmd_tx,
+                pid: Arc::default(),
+            };
 
             // Send valid command
             let mut stream = handle
@@ -544,9 +768,19 @@ mod tests {
             // Set up actor manually
             let (cmd_tx, cmd_rx) = bounded::<LedgerCommand>(16);
 
-            std::thread::spawn(move || futures_lite::future::block_on(run_actor(None, cmd_rx)));
+            std::thread::spawn(move || {
+                futures_lite::future::block_on(run_actor(
+                    "ledger".into(),
+                    None,
+                    cmd_rx,
+                    Arc::default(),
+                ))
+            });
 
-            let handle = LedgerHandle { cmd_tx };
+            let handle = LedgerHandle {
+                cmd_tx,
+                pid: Arc::default(),
+            };
 
             // Send invalid command
             let mut stream = handle
@@ -582,10 +816,18 @@ mod tests {
             let (cmd_tx, cmd_rx) = bounded::<LedgerCommand>(16);
 
             std::thread::spawn(move || {
-                futures_lite::future::block_on(run_actor(Some(test_file), cmd_rx))
+                futures_lite::future::block_on(run_actor(
+                    "ledger".into(),
+                    Some(test_file),
+                    cmd_rx,
+                    Arc::default(),
+                ))
             });
 
-            let handle = LedgerHandle { cmd_tx };
+            let handle = LedgerHandle {
+                cmd_tx,
+                pid: Arc::default(),
+            };
 
             let stream = handle.stream("lisp").await.expect("Failed to send command");
             let mut sexp_stream = stream.sexpr();
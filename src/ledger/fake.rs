@@ -0,0 +1,86 @@
+//! Fake ledger for tests, so streaming and failing commands can be tested without
+//! ledger installed.
+//!
+//! `src/fixtures/fake-ledger` answers each command from files a [`FakeLedger`] writes
+//! into a directory of its own, passed to it as the journal.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Answers scripted for the fake ledger. The directory is removed on drop.
+pub struct FakeLedger {
+    dir: PathBuf,
+}
+
+impl FakeLedger {
+    pub fn new() -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "fake-ledger-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).expect("create fake ledger directory");
+        Self { dir }
+    }
+
+    /// The script to run instead of ledger.
+    pub fn binary() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/fixtures/fake-ledger")
+    }
+
+    /// Passed to the script as the journal.
+    pub fn journal(&self) -> PathBuf {
+        self.dir.clone()
+    }
+
+    /// File the script reads the answer to `cmd` from, see the script.
+    fn answer(&self, cmd: &str, extension: &str) -> PathBuf {
+        let key = cmd
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        self.dir.join(format!("{key}.{extension}"))
+    }
+
+    /// Answers `cmd` by printing `stdout`.
+    pub fn respond(&self, cmd: &str, stdout: &str) -> &Self {
+        std::fs::write(self.answer(cmd, "out"), stdout).expect("write fake ledger answer");
+        self
+    }
+
+    /// Answers `cmd` with `stderr`, the way ledger reports errors.
+    pub fn fail(&self, cmd: &str, stderr: &str) -> &Self {
+        std::fs::write(self.answer(cmd, "err"), stderr).expect("write fake ledger answer");
+        self
+    }
+}
+
+impl Drop for FakeLedger {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+/// `ledger lisp` output of `count` generated transactions, one a day from 2000-01-01,
+/// each moving `n + 1` USD from `assets:cash` to `expenses:food` for the n-th of them.
+pub fn lisp_transactions(count: usize) -> String {
+    let start = chrono::NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date");
+    let mut output = String::from("(");
+    for (n, date) in start.iter_days().take(count).enumerate() {
+        let line = n * 3 + 1;
+        let amount = n + 1;
+        let date = date.format("%Y-%m-%d");
+        let _ = write!(
+            output,
+            "(\"main.ledger\" {line} \"{date}\" nil \"Payee {n}\"\n \
+             ({} \"expenses:food\" \"{amount} USD\" t)\n \
+             ({} \"assets:cash\" \"-{amount} USD\" nil))\n",
+            line + 1,
+            line + 2,
+        );
+    }
+    output.push_str(")\n");
+    output
+}
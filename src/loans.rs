@@ -0,0 +1,237 @@
+//! Loans kept as liability accounts.
+//!
+//! Interest is booked to an expense account, so it is linked to its loan with a
+//! `loan: liabilities:mortgage` tag on the interest posting. The accounts named by those
+//! tags are the loans. Payments reduce the principal, and recent ones project when the
+//! loan is paid off.
+
+use chrono::NaiveDate;
+use fastnum::D128;
+
+use crate::{
+    accounts::Account,
+    period::{Period, PeriodKind},
+    transactions::{Posting, Transaction},
+};
+
+/// Months of payments the payoff date is projected from.
+pub const RECENT_MONTHS: usize = 6;
+
+/// Loan a posting's `loan:` tag links it to.
+fn linked_loan(posting: &Posting) -> Option<Account> {
+    let note = posting.note.as_deref()?;
+    let (_, value) = note.split_once("loan:")?;
+    let value = value.trim_start_matches(':');
+    let value = value.split([';', '\n']).next().unwrap_or_default().trim();
+    (!value.is_empty()).then(|| Account::parse(value))
+}
+
+/// Accounts named by `loan:` tags, sorted by name.
+pub fn loan_accounts(transactions: &[&Transaction]) -> Vec<Account> {
+    let mut accounts = Vec::new();
+    for posting in transactions
+        .iter()
+        .flat_map(|transaction| &transaction.postings)
+    {
+        if let Some(account) = linked_loan(posting) {
+            if !accounts.contains(&account) {
+                accounts.push(account);
+            }
+        }
+    }
+    accounts.sort_by_key(ToString::to_string);
+    accounts
+}
+
+/// One month of a loan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoanMonth {
+    pub month: Period,
+    /// Principal left at the end of the month.
+    pub principal: D128,
+    /// Principal paid back during the month.
+    pub repaid: D128,
+    /// Interest linked to the loan during the month.
+    pub interest: D128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loan {
+    pub account: Account,
+    pub commodity: String,
+    /// From the month the loan was taken, oldest first.
+    pub months: Vec<LoanMonth>,
+}
+
+impl Loan {
+    /// Principal left after the last month.
+    pub fn principal(&self) -> D128 {
+        self.months
+            .last()
+            .map_or(D128::ZERO, |month| month.principal)
+    }
+
+    pub fn interest_paid(&self) -> D128 {
+        self.months
+            .iter()
+            .fold(D128::ZERO, |total, month| total + month.interest)
+    }
+
+    /// Month the principal runs out if it keeps being repaid at the average of the
+    /// recent months, `None` without recent repayments. A paid off loan has no payoff
+    /// ahead of it either.
+    pub fn projected_payoff(&self) -> Option<Period> {
+        let last = self.months.last()?;
+        if self.principal() <= D128::ZERO {
+            return None;
+        }
+        let recent = &self.months[self.months.len().saturating_sub(RECENT_MONTHS)..];
+        let repaid = recent
+            .iter()
+            .fold(D128::ZERO, |total, month| total + month.repaid)
+            / D128::from(recent.len());
+        if repaid <= D128::ZERO {
+            return None;
+        }
+        let months = (self.principal() / repaid).ceil();
+        let months = months.to_string().parse::<usize>().ok()?;
+        let mut payoff = last.month;
+        for _ in 0..months {
+            payoff = payoff.next();
+        }
+        Some(payoff)
+    }
+}
+
+/// History of a loan up to the month containing `until`, in the commodity it was first
+/// posted in. `None` when nothing was posted to the account.
+pub fn track(transactions: &[&Transaction], account: &Account, until: NaiveDate) -> Option<Loan> {
+    let in_loan =
+        |posting: &Posting| &posting.account == account || account.is_parent_of(&posting.account);
+    let (start, commodity) = transactions
+        .iter()
+        .filter_map(|transaction| {
+            let posting = transaction
+                .postings
+                .iter()
+                .find(|posting| in_loan(posting))?;
+            Some((transaction.time, posting.amount.value.commodity.clone()))
+        })
+        .min_by_key(|(date, _)| *date)?;
+
+    let last = Period::containing(until, PeriodKind::Month);
+    let mut months = Vec::new();
+    let mut principal = D128::ZERO;
+    let mut month = Period::containing(start, PeriodKind::Month);
+    while month.start <= last.start {
+        let mut repaid = D128::ZERO;
+        let mut interest = D128::ZERO;
+        let postings = transactions
+            .iter()
+            .filter(|transaction| month.contains(transaction.time))
+            .flat_map(|transaction| &transaction.postings)
+            .filter(|posting| posting.amount.value.commodity == commodity);
+        for posting in postings {
+            let value = posting.amount.value.value;
+            if in_loan(posting) {
+                // Liabilities are negative, so borrowing more is a negative posting
+                principal -= value;
+                if value > D128::ZERO {
+                    repaid += value;
+                }
+            } else if linked_loan(posting).as_ref() == Some(account) {
+                interest += value;
+            }
+        }
+        months.push(LoanMonth {
+            month,
+            principal,
+            repaid,
+            interest,
+        });
+        month = month.next();
+    }
+    Some(Loan {
+        account: account.clone(),
+        commodity,
+        months,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(date: (i32, u32, u32), postings: &[(&str, &str, Option<&str>)]) -> Transaction {
+        let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+        let amounts = postings
+            .iter()
+            .map(|(account, amount, _)| (*account, *amount))
+            .collect::<Vec<_>>();
+        let mut transaction = Transaction::fixture(date, "Bank", &amounts);
+        for (posting, (_, _, note)) in transaction.postings.iter_mut().zip(postings) {
+            posting.note = note.map(ToString::to_string);
+        }
+        transaction
+    }
+
+    fn payment(month: u32) -> Transaction {
+        transaction(
+            (2025, month, 15),
+            &[
+                ("Liabilities:Car", "100 USD", None),
+                (
+                    "Expenses:Interest",
+                    "10 USD",
+                    Some(" loan: Liabilities:Car"),
+                ),
+                ("Assets:Bank", "-110 USD", None),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_track() {
+        let mut transactions = vec![transaction(
+            (2025, 1, 2),
+            &[
+                ("Assets:Bank", "1000 USD", None),
+                ("Liabilities:Car", "-1000 USD", None),
+            ],
+        )];
+        transactions.extend((2..=4).map(payment));
+        let transactions = transactions.iter().collect::<Vec<_>>();
+
+        let accounts = loan_accounts(&transactions);
+        assert_eq!(accounts, [Account::parse("Liabilities:Car")]);
+
+        let until = NaiveDate::from_ymd_opt(2025, 5, 10).unwrap();
+        let loan = track(&transactions, &accounts[0], until).unwrap();
+        assert_eq!(loan.commodity, "USD");
+        assert_eq!(
+            loan.months
+                .iter()
+                .map(|month| (month.principal, month.repaid, month.interest))
+                .collect::<Vec<_>>(),
+            [
+                (D128::from(1000), D128::ZERO, D128::ZERO),
+                (D128::from(900), D128::from(100), D128::from(10)),
+                (D128::from(800), D128::from(100), D128::from(10)),
+                (D128::from(700), D128::from(100), D128::from(10)),
+                (D128::from(700), D128::ZERO, D128::ZERO),
+            ]
+        );
+        assert_eq!(loan.principal(), D128::from(700));
+        assert_eq!(loan.interest_paid(), D128::from(30));
+        // 300 repaid over the last 5 months is 60 a month, 12 more months from May
+        assert_eq!(
+            loan.projected_payoff().map(|month| month.start),
+            NaiveDate::from_ymd_opt(2026, 5, 1)
+        );
+
+        assert_eq!(
+            track(&transactions, &Account::parse("Liabilities:Card"), until),
+            None
+        );
+    }
+}
@@ -1,33 +1,88 @@
+mod account_declarations;
+mod account_names;
 mod accounts;
+mod alerts;
+mod amount_format;
+mod api;
+mod assertions;
+mod balancing;
+mod beancount;
+mod budget;
+mod bulk;
+mod changelog;
+mod commodities;
+mod custom_reports;
+mod deep_link;
+mod diagnostics;
+mod expr;
+mod fuzzy;
+mod grouping;
+mod headless;
+mod history;
+mod i18n;
+mod inflation;
+mod insights;
 mod ledger;
+mod loans;
+mod new_journal;
+mod period;
+mod print;
+mod prices;
+mod query;
+mod receivables;
+mod rename;
+mod reports;
+mod settings;
 mod sexpr;
+mod snippet;
+mod split;
 mod transactions;
+mod transfers;
+mod trash;
 mod ui;
+mod xact;
 
-#[allow(clippy::wildcard_imports)]
-use gpui::*;
-use gpui_component::Root;
+use gpui::Application;
 use gpui_component_assets::Assets;
 
 fn main() {
-    Application::new().with_assets(Assets).run(move |cx| {
-        gpui_component::init(cx);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match headless::parse_args(&args) {
+        Ok(Some(command)) => {
+            match headless::run(&command) {
+                Ok(output) => print!("{output}"),
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    }
 
-        cx.open_window(
-            WindowOptions {
-                titlebar: Some(TitlebarOptions {
-                    title: Some("ledger-desktop".into()),
-                    appears_transparent: true,
-                    ..TitlebarOptions::default()
-                }),
-                ..WindowOptions::default()
-            },
-            |window, cx| {
-                let view = cx.new(|cx| ui::Window::new(window, cx));
-                cx.new(|cx| Root::new(view, window, cx))
-            },
-        )
-        .ok();
+    let app = Application::new().with_assets(Assets);
+    // Links can arrive before the app has finished launching, when they launch it
+    let (links_tx, links_rx) = async_channel::unbounded();
+    let links = args
+        .into_iter()
+        .filter(|arg| arg.starts_with(&format!("{}://", deep_link::SCHEME)))
+        .collect::<Vec<_>>();
+    if !links.is_empty() {
+        links_tx.try_send(links).ok();
+    }
+    app.on_open_urls(move |urls| {
+        links_tx.try_send(urls).ok();
+    });
+    app.run(move |cx| {
+        gpui_component::init(cx);
+        ui::init(cx);
+        ui::open_window(None, cx);
+        ui::open_deep_links(links_rx, cx);
 
         cx.activate(true);
     });
@@ -0,0 +1,419 @@
+//! Starter text for journals created from the app, and for files continuing a journal
+//! from a date, like one per year.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use fastnum::D128;
+
+use crate::accounts::{Account, Balance};
+use crate::transactions::{CurrencyAmount, Transaction};
+
+/// Account balancing the opening balances.
+const OPENING_BALANCES: &str = "equity:opening balances";
+
+/// Chart of accounts a new journal starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Template {
+    #[default]
+    Personal,
+    Freelancer,
+    Family,
+}
+
+impl Template {
+    pub const ALL: [Template; 3] = [Template::Personal, Template::Freelancer, Template::Family];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Template::Personal => "Personal",
+            Template::Freelancer => "Freelancer",
+            Template::Family => "Family",
+        }
+    }
+
+    /// Accounts the journal declares, so postings to them are not taken for typos.
+    pub fn accounts(self) -> &'static [&'static str] {
+        match self {
+            Template::Personal => &[
+                "assets:checking",
+                "assets:savings",
+                "liabilities:credit card",
+                "income:salary",
+                "expenses:food",
+                "expenses:housing",
+                "expenses:transport",
+                "expenses:fun",
+            ],
+            Template::Freelancer => &[
+                "assets:business checking",
+                "assets:personal checking",
+                "assets:receivables",
+                "liabilities:credit card",
+                "liabilities:taxes",
+                "income:clients",
+                "expenses:business:software",
+                "expenses:business:equipment",
+                "expenses:business:travel",
+                "expenses:taxes",
+                "expenses:living",
+            ],
+            Template::Family => &[
+                "assets:joint checking",
+                "assets:savings",
+                "assets:children savings",
+                "liabilities:mortgage",
+                "liabilities:credit card",
+                "income:salary",
+                "income:child benefit",
+                "expenses:groceries",
+                "expenses:housing",
+                "expenses:childcare",
+                "expenses:health",
+                "expenses:transport",
+            ],
+        }
+    }
+
+    /// Accounts that can have a balance when the journal starts.
+    pub fn balance_accounts(self) -> impl Iterator<Item = &'static str> {
+        self.accounts()
+            .iter()
+            .copied()
+            .filter(|account| account.starts_with("assets:") || account.starts_with("liabilities:"))
+    }
+}
+
+/// What the new journal starts with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewJournal {
+    pub template: Template,
+    /// Commodity amounts are in, declared with a display format.
+    pub commodity: String,
+    /// Date of the opening balances.
+    pub date: chrono::NaiveDate,
+    /// Balances by account when the journal starts; zero ones are left out.
+    pub opening_balances: Vec<(String, D128)>,
+}
+
+impl NewJournal {
+    /// Journal text with a commodity declaration, the template's accounts and the
+    /// opening balances.
+    pub fn scaffold(&self) -> String {
+        let date = self.date.format("%Y-%m-%d");
+        let commodity = &self.commodity;
+        let mut text = format!(
+            "; Journal created by ledger-desktop on {date}.\n\
+             ; Transactions are a date and description, followed by indented postings:\n\
+             ;\n\
+             ; {date} Coffee shop\n\
+             ;     expenses:food  4.50 {commodity}\n\
+             ;     assets:checking\n\n\
+             commodity {commodity}\n    format 1,000.00 {commodity}\n\n"
+        );
+        for account in self.template.accounts().iter().chain([&OPENING_BALANCES]) {
+            writeln!(text, "account {account}").expect("infallible");
+        }
+
+        let balances = self
+            .opening_balances
+            .iter()
+            .filter(|(_, balance)| !balance.is_zero())
+            .collect::<Vec<_>>();
+        if !balances.is_empty() {
+            writeln!(text, "\n{date} * Opening balances").expect("infallible");
+            for (account, balance) in balances {
+                let amount = CurrencyAmount {
+                    value: *balance,
+                    commodity: commodity.clone(),
+                };
+                writeln!(text, "    {account}  {amount}").expect("infallible");
+            }
+            writeln!(text, "    {OPENING_BALANCES}").expect("infallible");
+        }
+        text
+    }
+}
+
+/// Top-level accounts whose balances a new file opens with.
+const BALANCE_SHEET: [&str; 2] = ["assets", "liabilities"];
+
+/// Top-level accounts closed into [`RETAINED_EARNINGS`] at the end of a year.
+const INCOME_STATEMENT: [&str; 2] = ["income", "expenses"];
+
+/// Account the year's income and expenses are closed into.
+const RETAINED_EARNINGS: &str = "equity:retained earnings";
+
+/// Balances of the accounts under one of `roots` from the transactions before `date`.
+/// Zero balances are left out and accounts are sorted by name.
+pub fn balances_before(
+    transactions: &[&Transaction],
+    roots: &[&str],
+    date: chrono::NaiveDate,
+) -> Vec<(Account, Balance)> {
+    let mut balances = Vec::<(Account, Balance)>::new();
+    let postings = transactions
+        .iter()
+        .filter(|transaction| transaction.time < date)
+        .flat_map(|transaction| &transaction.postings)
+        .filter(|posting| {
+            posting
+                .account
+                .segments
+                .first()
+                .is_some_and(|root| roots.iter().any(|r| root.eq_ignore_ascii_case(r)))
+        });
+    for posting in postings {
+        let amount = posting.amount.value.clone();
+        if let Some((_, balance)) = balances.iter_mut().find(|(a, _)| *a == posting.account) {
+            balance.add_amount(amount);
+        } else {
+            let mut balance = Balance::new();
+            balance.add_amount(amount);
+            balances.push((posting.account.clone(), balance));
+        }
+    }
+    balances.retain(|(_, balance)| !balance.is_zero());
+    balances.sort_by_key(|(account, _)| account.to_string());
+    balances
+}
+
+/// Text that starts a new file on a date, like one per year, and what the journal gets
+/// appended: the balances closed the day before and an `include` of the new file.
+///
+/// The new file opens the asset and liability balances against
+/// `equity:opening balances`, and the journal closes them against the same account, so
+/// the two together count them once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStart {
+    pub journal: PathBuf,
+    pub file: PathBuf,
+    /// Appended to the journal.
+    pub closing: String,
+    /// Text of the new file.
+    pub opening: String,
+}
+
+impl FileStart {
+    /// With `close_income`, the income and expense balances are also closed into
+    /// `equity:retained earnings`, as at the end of a year.
+    pub fn new(
+        transactions: &[&Transaction],
+        journal: &Path,
+        file: &Path,
+        date: chrono::NaiveDate,
+        close_income: bool,
+    ) -> Self {
+        let end = date.pred_opt().unwrap_or(date);
+        let mut entries = Vec::new();
+        if close_income {
+            let income = balances_before(transactions, &INCOME_STATEMENT, date);
+            entries.push(balances_entry(
+                &income,
+                end,
+                "Closing income and expenses",
+                true,
+                RETAINED_EARNINGS,
+            ));
+        }
+        let balances = balances_before(transactions, &BALANCE_SHEET, date);
+        entries.push(balances_entry(
+            &balances,
+            end,
+            "Closing balances",
+            true,
+            OPENING_BALANCES,
+        ));
+        entries.push(include_directive(journal, file));
+
+        let mut closing = String::new();
+        for entry in entries.iter().filter(|entry| !entry.is_empty()) {
+            writeln!(closing, "\n{}", entry.trim_end()).expect("infallible");
+        }
+        let opening = balances_entry(&balances, date, "Opening balances", false, OPENING_BALANCES);
+        Self {
+            journal: journal.to_path_buf(),
+            file: file.to_path_buf(),
+            closing,
+            opening: format!("; Continues {}.\n\n{opening}", journal.display()),
+        }
+    }
+
+    /// The changes as a diff, for reviewing them before they are written.
+    pub fn diff(&self) -> String {
+        let journal = self.journal.display();
+        let file = self.file.display();
+        let mut diff = format!("--- {journal}\n+++ {journal}\n");
+        for line in self.closing.lines() {
+            writeln!(diff, "+{line}").expect("infallible");
+        }
+        writeln!(diff, "--- /dev/null\n+++ {file}").expect("infallible");
+        for line in self.opening.lines() {
+            writeln!(diff, "+{line}").expect("infallible");
+        }
+        diff
+    }
+}
+
+/// Transaction moving the balances against `equity`, or nothing when there are none.
+fn balances_entry(
+    balances: &[(Account, Balance)],
+    date: chrono::NaiveDate,
+    description: &str,
+    negate: bool,
+    equity: &str,
+) -> String {
+    if balances.is_empty() {
+        return String::new();
+    }
+    let mut text = format!("{} * {description}\n", date.format("%Y-%m-%d"));
+    let mut total = Balance::new();
+    for (account, balance) in balances {
+        let balance = if negate { -balance } else { balance.clone() };
+        for amount in balance.sorted_amounts() {
+            if amount.value.is_zero() {
+                continue;
+            }
+            writeln!(text, "    {account}  {amount}").expect("infallible");
+        }
+        total += &balance;
+    }
+    for amount in (-total).sorted_amounts() {
+        if amount.value.is_zero() {
+            continue;
+        }
+        writeln!(text, "    {equity}  {amount}").expect("infallible");
+    }
+    text
+}
+
+/// `include` directive for `file` in `journal`, relative to the journal's directory
+/// when the file is under it.
+fn include_directive(journal: &Path, file: &Path) -> String {
+    let relative = journal
+        .parent()
+        .and_then(|dir| file.strip_prefix(dir).ok())
+        .unwrap_or(file);
+    format!("include {}", relative.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::account_declarations::parse_declarations;
+    use crate::snippet;
+
+    #[test]
+    fn test_scaffold() {
+        let journal = NewJournal {
+            template: Template::Freelancer,
+            commodity: "EUR".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+            opening_balances: vec![
+                ("assets:business checking".to_string(), D128::from(1200)),
+                ("assets:receivables".to_string(), D128::ZERO),
+                ("liabilities:credit card".to_string(), D128::from(-300)),
+            ],
+        };
+        let text = journal.scaffold();
+        assert!(text.starts_with("; Journal created by ledger-desktop on 2025-01-31.\n"));
+        assert!(text.contains("commodity EUR\n    format 1,000.00 EUR\n"));
+        assert_eq!(
+            parse_declarations(&text).len(),
+            Template::Freelancer.accounts().len() + 1
+        );
+
+        let opening = text.find("2025-01-31 * Opening balances").unwrap();
+        let transactions = snippet::parse(&text[opening..]).unwrap();
+        assert_eq!(
+            transactions[0]
+                .postings
+                .iter()
+                .map(|posting| format!("{} {}", posting.account, posting.amount.value))
+                .collect::<Vec<_>>(),
+            [
+                "assets:business checking 1200 EUR",
+                "liabilities:credit card -300 EUR",
+                "equity:opening balances -900 EUR",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_start() {
+        let transactions = snippet::parse(
+            "2024-03-01 Salary\n    assets:checking  1000 EUR\n    income:salary\n\n\
+             2024-06-01 Trip\n    expenses:travel  200 USD\n    liabilities:card  -200 USD\n\n\
+             2024-12-01 Transfer\n    assets:checking  -1000 EUR\n    assets:savings  1000 EUR\n\n\
+             2025-01-02 Groceries\n    expenses:food  40 EUR\n    assets:savings\n",
+        )
+        .unwrap();
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        assert_eq!(
+            balances_before(&transactions, &BALANCE_SHEET, date)
+                .iter()
+                .map(|(account, _)| account.to_string())
+                .collect::<Vec<_>>(),
+            ["assets:savings", "liabilities:card"]
+        );
+
+        let start = FileStart::new(
+            &transactions,
+            Path::new("/books/main.ledger"),
+            Path::new("/books/2025.ledger"),
+            date,
+            true,
+        );
+        assert_eq!(
+            start.opening,
+            "; Continues /books/main.ledger.\n\n\
+             2025-01-01 * Opening balances\n\
+             \x20   assets:savings  1000 EUR\n\
+             \x20   liabilities:card  -200 USD\n\
+             \x20   equity:opening balances  -1000 EUR\n\
+             \x20   equity:opening balances  200 USD\n"
+        );
+        assert_eq!(
+            start.closing,
+            "\n2024-12-31 * Closing income and expenses\n\
+             \x20   expenses:travel  -200 USD\n\
+             \x20   income:salary  1000 EUR\n\
+             \x20   equity:retained earnings  -1000 EUR\n\
+             \x20   equity:retained earnings  200 USD\n\
+             \n2024-12-31 * Closing balances\n\
+             \x20   assets:savings  -1000 EUR\n\
+             \x20   liabilities:card  200 USD\n\
+             \x20   equity:opening balances  1000 EUR\n\
+             \x20   equity:opening balances  -200 USD\n\
+             \ninclude 2025.ledger\n"
+        );
+        assert!(start
+            .diff()
+            .contains("+include 2025.ledger\n--- /dev/null\n+++ /books/2025.ledger\n"));
+
+        let start = FileStart::new(
+            &transactions,
+            Path::new("/books/main.ledger"),
+            Path::new("/tmp/2025.ledger"),
+            date,
+            false,
+        );
+        assert!(start
+            .closing
+            .starts_with("\n2024-12-31 * Closing balances\n"));
+        assert!(start.closing.ends_with("\ninclude /tmp/2025.ledger\n"));
+    }
+
+    #[test]
+    fn test_scaffold_without_balances() {
+        let journal = NewJournal {
+            template: Template::Personal,
+            commodity: "USD".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+            opening_balances: Vec::new(),
+        };
+        assert!(!journal.scaffold().contains("Opening balances"));
+    }
+}
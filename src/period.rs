@@ -0,0 +1,132 @@
+use core::fmt;
+
+use chrono::{Datelike, NaiveDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodKind {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl PeriodKind {
+    fn months(self) -> i32 {
+        match self {
+            PeriodKind::Month => 1,
+            PeriodKind::Quarter => 3,
+            PeriodKind::Year => 12,
+        }
+    }
+}
+
+/// A calendar-aligned date range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Period {
+    pub kind: PeriodKind,
+    /// First day of the period
+    pub start: NaiveDate,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            PeriodKind::Month => write!(f, "{}", self.start.format("%B %Y")),
+            PeriodKind::Quarter => {
+                write!(f, "Q{} {}", self.start.month0() / 3 + 1, self.start.year())
+            }
+            PeriodKind::Year => write!(f, "{}", self.start.year()),
+        }
+    }
+}
+
+impl Period {
+    /// The period of the given kind that contains the date.
+    pub fn containing(date: NaiveDate, kind: PeriodKind) -> Self {
+        let months = kind.months().unsigned_abs();
+        let first_month = date.month0() / months * months + 1;
+        Self {
+            kind,
+            start: NaiveDate::from_ymd_opt(date.year(), first_month, 1).unwrap_or(date),
+        }
+    }
+
+    /// The period of another kind that contains this period's start.
+    pub fn with_kind(self, kind: PeriodKind) -> Self {
+        Self::containing(self.start, kind)
+    }
+
+    /// First day after the period.
+    pub fn end(self) -> NaiveDate {
+        self.shifted(1).start
+    }
+
+    pub fn contains(self, date: NaiveDate) -> bool {
+        self.start <= date && date < self.end()
+    }
+
+    pub fn next(self) -> Self {
+        self.shifted(1)
+    }
+
+    pub fn prev(self) -> Self {
+        self.shifted(-1)
+    }
+
+    /// Moves the period by `n` of its own lengths.
+    fn shifted(self, n: i32) -> Self {
+        let months =
+            self.start.year() * 12 + self.start.month0().cast_signed() + n * self.kind.months();
+        let year = months.div_euclid(12);
+        let month = months.rem_euclid(12).unsigned_abs() + 1;
+        Self {
+            kind: self.kind,
+            start: NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(self.start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_containing() {
+        let period = Period::containing(date(2025, 8, 17), PeriodKind::Quarter);
+        assert_eq!(period.start, date(2025, 7, 1));
+        assert_eq!(period.end(), date(2025, 10, 1));
+        assert_eq!(period.to_string(), "Q3 2025");
+
+        let period = Period::containing(date(2025, 8, 17), PeriodKind::Month);
+        assert_eq!(period.start, date(2025, 8, 1));
+        assert_eq!(period.to_string(), "August 2025");
+
+        let period = Period::containing(date(2025, 8, 17), PeriodKind::Year);
+        assert_eq!(period.start, date(2025, 1, 1));
+        assert_eq!(period.end(), date(2026, 1, 1));
+        assert_eq!(period.to_string(), "2025");
+    }
+
+    #[test]
+    fn test_navigation() {
+        let period = Period::containing(date(2025, 12, 5), PeriodKind::Month);
+        assert_eq!(period.next().start, date(2026, 1, 1));
+        assert_eq!(period.next().prev(), period);
+
+        let period = Period::containing(date(2025, 2, 5), PeriodKind::Quarter);
+        assert_eq!(period.prev().start, date(2024, 10, 1));
+    }
+
+    #[test]
+    fn test_contains() {
+        let period = Period::containing(date(2025, 2, 5), PeriodKind::Month);
+        assert!(period.contains(date(2025, 2, 1)));
+        assert!(period.contains(date(2025, 2, 28)));
+        assert!(!period.contains(date(2025, 3, 1)));
+        assert!(!period.contains(date(2025, 1, 31)));
+    }
+}
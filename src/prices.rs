@@ -0,0 +1,429 @@
+//! Exchange rates implied by the journal's lot prices and costs, and `P` price
+//! directives, including ones fetched from online sources.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+
+use fastnum::D128;
+
+use crate::transactions::{Amount, Cost, CurrencyAmount, Posting, Transaction};
+
+/// Value of one unit of each commodity in `target`, from the prices postings were
+/// bought or converted at.
+///
+/// Transactions are sorted by date, so the latest price of a commodity wins. The
+/// target itself is not in the map.
+pub fn implied_rates(
+    transactions: &[impl Borrow<Transaction>],
+    target: &str,
+) -> HashMap<String, D128> {
+    let mut rates = HashMap::new();
+    let postings = transactions
+        .iter()
+        .flat_map(|transaction| &transaction.borrow().postings);
+    for posting in postings {
+        if let Some(rate) = implied_rate(&posting.amount, target) {
+            rates.insert(posting.amount.value.commodity.clone(), rate);
+        }
+    }
+    rates
+}
+
+/// Price in `target` of one unit of the amount's commodity, from its cost or lot price.
+fn implied_rate(amount: &Amount, target: &str) -> Option<D128> {
+    if amount.value.commodity == target {
+        return None;
+    }
+    match &amount.cost {
+        Some(Cost::PerUnit(cost)) if cost.commodity == target => Some(cost.value),
+        Some(Cost::Total(cost)) if cost.commodity == target && !amount.value.value.is_zero() => {
+            Some(cost.value / amount.value.value.abs())
+        }
+        _ => amount
+            .price
+            .as_ref()
+            .filter(|price| price.commodity == target)
+            .map(|price| price.value),
+    }
+}
+
+/// How amounts are valued in balances, net worth and charts, after ledger's `-B`, `-V`
+/// and `-X` with `-H`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Valuation {
+    /// In their own commodities.
+    #[default]
+    Native,
+    /// At what was paid for them, from costs and lot prices.
+    Cost,
+    /// At the latest price.
+    Market,
+    /// At the price on the date of each posting.
+    Historical,
+}
+
+impl Valuation {
+    /// Next mode when cycling through them.
+    pub fn next(self) -> Self {
+        match self {
+            Valuation::Native => Valuation::Cost,
+            Valuation::Cost => Valuation::Market,
+            Valuation::Market => Valuation::Historical,
+            Valuation::Historical => Valuation::Native,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Valuation::Native => "Native",
+            Valuation::Cost => "Cost",
+            Valuation::Market => "Market",
+            Valuation::Historical => "Historical",
+        }
+    }
+}
+
+/// Dated prices of commodities in one target commodity, from the journal's costs and
+/// lot prices and from `P` directives.
+#[derive(Debug, Clone, Default)]
+pub struct PriceHistory {
+    target: String,
+    /// Prices by commodity, sorted by date.
+    prices: HashMap<String, Vec<(chrono::NaiveDate, D128)>>,
+}
+
+impl PriceHistory {
+    pub fn new(transactions: &[&Transaction], directives: &[PriceDirective], target: &str) -> Self {
+        let mut prices = HashMap::<String, Vec<(chrono::NaiveDate, D128)>>::new();
+        for transaction in transactions {
+            for posting in &transaction.postings {
+                if let Some(rate) = implied_rate(&posting.amount, target) {
+                    prices
+                        .entry(posting.amount.value.commodity.clone())
+                        .or_default()
+                        .push((transaction.time, rate));
+                }
+            }
+        }
+        let directives = directives.iter().filter(|directive| {
+            directive.price.commodity == target && directive.commodity != target
+        });
+        for directive in directives {
+            prices
+                .entry(directive.commodity.clone())
+                .or_default()
+                .push((directive.date, directive.price.value));
+        }
+        // Stable, so a directive wins over a posting of the same day
+        for history in prices.values_mut() {
+            history.sort_by_key(|(date, _)| *date);
+        }
+        Self {
+            target: target.to_string(),
+            prices,
+        }
+    }
+
+    /// Latest known price of the commodity.
+    pub fn latest(&self, commodity: &str) -> Option<D128> {
+        self.prices.get(commodity)?.last().map(|(_, price)| *price)
+    }
+
+    /// Price of the commodity on the date: the latest one known by then.
+    pub fn on(&self, commodity: &str, date: chrono::NaiveDate) -> Option<D128> {
+        let history = self.prices.get(commodity)?;
+        let known = history.partition_point(|(day, _)| *day <= date);
+        known.checked_sub(1).map(|ix| history[ix].1)
+    }
+
+    /// The amount of a posting on `date` valued as `valuation` says. Amounts without a
+    /// price to value them at are kept as they are.
+    pub fn value(
+        &self,
+        amount: &Amount,
+        date: chrono::NaiveDate,
+        valuation: Valuation,
+    ) -> CurrencyAmount {
+        let value = &amount.value;
+        let rate = match valuation {
+            Valuation::Native => None,
+            Valuation::Cost => {
+                return match (&amount.cost, &amount.price) {
+                    (Some(Cost::PerUnit(cost)), _) | (None, Some(cost)) => CurrencyAmount {
+                        value: value.value * cost.value,
+                        commodity: cost.commodity.clone(),
+                    },
+                    (Some(Cost::Total(cost)), _) => CurrencyAmount {
+                        value: if value.value.is_sign_negative() {
+                            -cost.value
+                        } else {
+                            cost.value
+                        },
+                        commodity: cost.commodity.clone(),
+                    },
+                    (None, None) => value.clone(),
+                };
+            }
+            Valuation::Market => self.latest(&value.commodity),
+            Valuation::Historical => self.on(&value.commodity, date),
+        };
+        match rate {
+            Some(rate) if value.commodity != self.target => CurrencyAmount {
+                value: value.value * rate,
+                commodity: self.target.clone(),
+            },
+            _ => value.clone(),
+        }
+    }
+
+    /// The transaction with the amounts of its postings valued, without the prices and
+    /// costs that went into them.
+    pub fn value_transaction(
+        &self,
+        transaction: &Transaction,
+        valuation: Valuation,
+    ) -> Transaction {
+        let postings = transaction
+            .postings
+            .iter()
+            .map(|posting| Posting {
+                amount: Amount {
+                    value: self.value(&posting.amount, transaction.time, valuation),
+                    price: None,
+                    date: None,
+                    cost: None,
+                },
+                ..posting.clone()
+            })
+            .collect();
+        Transaction {
+            postings,
+            ..transaction.clone()
+        }
+    }
+}
+
+/// `P` directive: the price of one unit of a commodity on a date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceDirective {
+    pub date: chrono::NaiveDate,
+    pub commodity: String,
+    pub price: CurrencyAmount,
+}
+
+impl fmt::Display for PriceDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "P {} {} {}",
+            self.date.format("%Y-%m-%d"),
+            self.commodity,
+            self.price
+        )
+    }
+}
+
+/// Reads the `P` directives of a journal or price database, like
+/// `P 2025-01-31 AAPL 236.00 USD`. A time after the date is skipped.
+pub fn parse_price_directives(text: &str) -> Vec<PriceDirective> {
+    text.lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("P ")?.trim_start();
+            let (date, rest) = rest.split_once(char::is_whitespace)?;
+            let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .or_else(|_| chrono::NaiveDate::parse_from_str(date, "%Y/%m/%d"))
+                .ok()?;
+            let mut rest = rest.trim_start();
+            if let Some((time, after)) = rest.split_once(char::is_whitespace) {
+                if chrono::NaiveTime::parse_from_str(time, "%H:%M:%S").is_ok()
+                    || chrono::NaiveTime::parse_from_str(time, "%H:%M").is_ok()
+                {
+                    rest = after.trim_start();
+                }
+            }
+            let (commodity, price) = rest.split_once(char::is_whitespace)?;
+            let price = CurrencyAmount::parse(price.split(';').next()?).ok()?;
+            Some(PriceDirective {
+                date,
+                commodity: commodity.trim_matches('"').to_string(),
+                price,
+            })
+        })
+        .collect()
+}
+
+/// Commodity most postings are in, what amounts are valued in without a base currency.
+/// Ties go to the commodity first by name.
+pub fn most_used_commodity(transactions: &[impl Borrow<Transaction>]) -> Option<String> {
+    let mut counts = HashMap::<&str, usize>::new();
+    for posting in transactions
+        .iter()
+        .flat_map(|transaction| &transaction.borrow().postings)
+    {
+        *counts.entry(&posting.amount.value.commodity).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(commodity, count)| (*count, std::cmp::Reverse(*commodity)))
+        .map(|(commodity, _)| commodity.to_string())
+}
+
+/// Latest price in `target` of each commodity with a directive for it.
+pub fn latest_rates(directives: &[PriceDirective], target: &str) -> HashMap<String, D128> {
+    let mut directives = directives
+        .iter()
+        .filter(|directive| directive.price.commodity == target && directive.commodity != target)
+        .collect::<Vec<_>>();
+    directives.sort_by_key(|directive| directive.date);
+    directives
+        .into_iter()
+        .map(|directive| (directive.commodity.clone(), directive.price.value))
+        .collect()
+}
+
+/// Shell running a price command. The commodity and base currency are the script's
+/// `$1` and `$2`, which `{commodity}` and `{base}` stand for, so names from the
+/// journal are never run as part of the script.
+pub fn quote_command(template: &str, commodity: &str, base: &str) -> std::process::Command {
+    let script = template
+        .replace("{commodity}", "\"$1\"")
+        .replace("{base}", "\"$2\"");
+    let mut command = std::process::Command::new("sh");
+    command
+        .arg("-c")
+        .arg(script)
+        .arg("sh")
+        .arg(commodity)
+        .arg(base);
+    command
+}
+
+/// Price a command printed: the last line, as a number or an amount. A bare number
+/// is in `base`.
+pub fn parse_quote(output: &str, base: &str) -> Option<CurrencyAmount> {
+    let line = output.lines().rev().find(|line| !line.trim().is_empty())?;
+    let mut price = CurrencyAmount::parse(line).ok()?;
+    if price.commodity.is_empty() {
+        price.commodity = base.to_string();
+    }
+    Some(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(amounts: &[&str]) -> Transaction {
+        let postings = amounts
+            .iter()
+            .map(|amount| ("assets:broker", *amount))
+            .collect::<Vec<_>>();
+        Transaction::fixture(
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            "",
+            &postings,
+        )
+    }
+
+    #[test]
+    fn test_implied_rates() {
+        let transactions = [
+            transaction(&["10 AAPL {100 SEK}", "5 EUR @ 11 SEK"]),
+            transaction(&["-4 USD @@ 40 SEK", "1 BTC {20000 USD}", "-1000 SEK"]),
+            transaction(&["2 AAPL {120 SEK} [2025/01/01]"]),
+        ];
+        let rates = implied_rates(&transactions, "SEK");
+        assert_eq!(
+            rates,
+            HashMap::from([
+                ("AAPL".to_string(), D128::from(120)),
+                ("EUR".to_string(), D128::from(11)),
+                ("USD".to_string(), D128::from(10)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_valuation() {
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+        let mut bought = transaction(&["10 AAPL {100 SEK}", "-1000 SEK"]);
+        bought.time = date(5);
+        let mut sold = transaction(&["-4 AAPL @ 130 SEK", "520 SEK"]);
+        sold.time = date(20);
+        let directives = parse_price_directives("P 2025-01-10 AAPL 110 SEK\n");
+        let prices = PriceHistory::new(&[&bought, &sold], &directives, "SEK");
+
+        assert_eq!(prices.on("AAPL", date(4)), None);
+        assert_eq!(prices.on("AAPL", date(12)), Some(D128::from(110)));
+        assert_eq!(prices.latest("AAPL"), Some(D128::from(130)));
+
+        let amount = Amount::parse("10 AAPL {100 SEK}").unwrap();
+        let value = |valuation| prices.value(&amount, date(12), valuation).to_string();
+        assert_eq!(value(Valuation::Native), "10 AAPL");
+        assert_eq!(value(Valuation::Cost), "1000 SEK");
+        assert_eq!(value(Valuation::Market), "1300 SEK");
+        assert_eq!(value(Valuation::Historical), "1100 SEK");
+
+        let valued = prices.value_transaction(&bought, Valuation::Cost);
+        assert_eq!(valued.postings[0].amount.to_string(), "1000 SEK");
+
+        let converted = Amount::parse("-4 USD @@ 40 SEK").unwrap();
+        assert_eq!(
+            prices
+                .value(&converted, date(12), Valuation::Cost)
+                .to_string(),
+            "-40 SEK"
+        );
+        let unpriced = Amount::parse("1 BTC").unwrap();
+        assert_eq!(
+            prices
+                .value(&unpriced, date(12), Valuation::Market)
+                .to_string(),
+            "1 BTC"
+        );
+    }
+
+    #[test]
+    fn test_price_directives() {
+        let text = "; prices\n\
+                    P 2025-01-31 AAPL 236.00 USD\n\
+                    P 2025/01/02 12:00:00 \"VWCE\" 120 EUR ; fetched\n\
+                    P 2025-01-01 AAPL 230 USD\n\
+                    P 2025-01-01 EUR 1.04 USD\n\
+                    2025-01-01 Not a price\n";
+        let directives = parse_price_directives(text);
+        assert_eq!(directives.len(), 4);
+        assert_eq!(directives[1].commodity, "VWCE");
+        assert_eq!(directives[1].to_string(), "P 2025-01-02 VWCE 120 EUR");
+
+        assert_eq!(
+            latest_rates(&directives, "USD"),
+            HashMap::from([
+                ("AAPL".to_string(), "236.00".parse::<D128>().unwrap()),
+                ("EUR".to_string(), "1.04".parse::<D128>().unwrap()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_quotes() {
+        let output = quote_command("printf '%s|%s' {commodity} \"$2\"", "A&B $(x);", "USD")
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "A&B $(x);|USD");
+        assert_eq!(
+            parse_quote("fetching…\n236.5\n\n", "USD")
+                .unwrap()
+                .to_string(),
+            "236.5 USD"
+        );
+        assert_eq!(
+            parse_quote("1,234.5 SEK", "USD").unwrap().to_string(),
+            "1234.5 SEK"
+        );
+        assert!(parse_quote("error: unknown symbol", "USD").is_none());
+        assert!(parse_quote("", "USD").is_none());
+    }
+}
@@ -0,0 +1,216 @@
+//! Print layout of the register: an HTML document with a page per sheet of paper,
+//! which opens the system print dialog of the browser it is loaded in.
+//!
+//! Each page repeats the title and column headers and is numbered. The total of the
+//! pages before it is carried forward to the top of each page, and the bottom of the
+//! last one has the total of everything printed.
+
+use std::fmt::Write as _;
+
+use crate::accounts::Balance;
+use crate::amount_format::AmountFormatter;
+use crate::transactions::Transaction;
+
+/// Postings on a page, leaving room for the headers and totals on A4 and Letter.
+pub const ROWS_PER_PAGE: usize = 40;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; font-size: 10pt; margin: 0; }
+.page { break-after: page; }
+.page:last-child { break-after: auto; }
+header { display: flex; justify-content: space-between; margin-bottom: 0.5em; }
+.filters { color: #666; font-size: 8pt; }
+table { width: 100%; border-collapse: collapse; }
+th, td { padding: 2pt 4pt; text-align: left; vertical-align: top; }
+th { border-bottom: 1pt solid #000; }
+.amount { text-align: right; white-space: nowrap; font-family: monospace; }
+.total td { border-top: 1pt solid #000; font-weight: bold; }
+.carried td { color: #666; font-style: italic; }
+";
+
+/// The register's postings as a paginated HTML document.
+pub fn register_html(
+    title: &str,
+    filters: &[String],
+    transactions: &[&Transaction],
+    formatter: &AmountFormatter,
+    date_format: &str,
+    rows_per_page: usize,
+) -> String {
+    let rows = transactions
+        .iter()
+        .flat_map(|transaction| {
+            transaction
+                .postings
+                .iter()
+                .enumerate()
+                .map(move |(ix, posting)| (*transaction, ix, posting))
+        })
+        .collect::<Vec<_>>();
+    let mut pages = rows.chunks(rows_per_page.max(1)).collect::<Vec<_>>();
+    if pages.is_empty() {
+        pages.push(&[]);
+    }
+
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>\n{STYLE}</style>\n\
+         <script>window.addEventListener(\"load\", () => window.print());</script>\n\
+         </head>\n<body>",
+        escape(title)
+    )
+    .expect("infallible");
+
+    let mut total = Balance::new();
+    for (page_ix, page) in pages.iter().enumerate() {
+        html.push_str("<section class=\"page\">\n<header>\n");
+        writeln!(html, "<strong>{}</strong>", escape(title)).expect("infallible");
+        writeln!(html, "<span>Page {} of {}</span>", page_ix + 1, pages.len()).expect("infallible");
+        html.push_str("</header>\n");
+        if !filters.is_empty() {
+            writeln!(
+                html,
+                "<div class=\"filters\">{}</div>",
+                escape(&filters.join(" · "))
+            )
+            .expect("infallible");
+        }
+        html.push_str(
+            "<table>\n<thead><tr><th>Date</th><th>Description</th><th>Account</th>\
+             <th class=\"amount\">Amount</th></tr></thead>\n<tbody>\n",
+        );
+        if page_ix > 0 {
+            total_row(&mut html, "carried", "Carried forward", &total, formatter);
+        }
+        for (row_ix, (transaction, posting_ix, posting)) in page.iter().enumerate() {
+            // Transactions split across pages repeat their header on the next one
+            let (date, description) = if *posting_ix == 0 || row_ix == 0 {
+                (
+                    transaction.time.format(date_format).to_string(),
+                    escape(&transaction.description),
+                )
+            } else {
+                (String::new(), String::new())
+            };
+            writeln!(
+                html,
+                "<tr><td>{date}</td><td>{description}</td><td>{}</td>\
+                 <td class=\"amount\">{}</td></tr>",
+                escape(&posting.account.to_string()),
+                escape(&formatter.format_amount(&posting.amount))
+            )
+            .expect("infallible");
+            total.add_amount(posting.amount.value.clone());
+        }
+        let last = page_ix + 1 == pages.len();
+        let (class, label) = if last {
+            ("total", "Total")
+        } else {
+            ("carried", "Carried forward")
+        };
+        total_row(&mut html, class, label, &total, formatter);
+        html.push_str("</tbody>\n</table>\n</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn total_row(
+    html: &mut String,
+    class: &str,
+    label: &str,
+    total: &Balance,
+    formatter: &AmountFormatter,
+) {
+    writeln!(
+        html,
+        "<tr class=\"{class}\"><td colspan=\"3\">{label}</td><td class=\"amount\">{}</td></tr>",
+        escape(&formatter.format_balance(total))
+    )
+    .expect("infallible");
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount_format::{AmountFormat, CommodityPrecision};
+    use crate::commodities::CommodityRegistry;
+
+    fn transaction(time: &str, description: &str, amount: &str) -> Transaction {
+        Transaction::fixture(
+            chrono::NaiveDate::parse_from_str(time, "%Y-%m-%d").unwrap(),
+            description,
+            &[
+                ("expenses:food", amount),
+                ("assets:checking", &format!("-{amount}")),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_register_html() {
+        let transactions = [
+            transaction("2025-03-01", "Shop", "10 CAD"),
+            transaction("2025-03-02", "Fish & <Chips>", "5 CAD"),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let formatter = AmountFormatter::new(
+            AmountFormat::default(),
+            2,
+            CommodityPrecision::default(),
+            CommodityRegistry::default(),
+        );
+        let html = register_html(
+            "Register",
+            &["account: expenses".to_string()],
+            &transactions,
+            &formatter,
+            "%Y-%m-%d",
+            3,
+        );
+
+        assert!(html.contains("window.print()"));
+        assert!(html.contains("Page 1 of 2"));
+        assert!(html.contains("Page 2 of 2"));
+        assert!(html.contains("account: expenses"));
+        assert!(html.contains("Fish &amp; &lt;Chips&gt;"));
+        // The second page starts with the second transaction's second posting and
+        // repeats its header
+        assert_eq!(
+            html.matches("<td>2025-03-02</td><td>Fish &amp; &lt;Chips&gt;</td>")
+                .count(),
+            2
+        );
+        // 10 CAD went in and out on the first page, then 5 CAD in
+        assert!(html.contains(
+            "<tr class=\"carried\"><td colspan=\"3\">Carried forward</td>\
+             <td class=\"amount\">5.00 CAD</td></tr>"
+        ));
+        assert!(html.contains(
+            "<tr class=\"total\"><td colspan=\"3\">Total</td>\
+             <td class=\"amount\">0.00 CAD</td></tr>"
+        ));
+    }
+
+    #[test]
+    fn test_register_html_empty() {
+        let html = register_html(
+            "Register",
+            &[],
+            &[],
+            &AmountFormatter::default(),
+            "%Y-%m-%d",
+            ROWS_PER_PAGE,
+        );
+        assert!(html.contains("Page 1 of 1"));
+    }
+}
@@ -0,0 +1,574 @@
+//! Ledger-style query expressions for filtering transactions.
+//!
+//! A query is made of terms joined with `and`, `or` and `not`, grouped with
+//! parentheses. Terms next to each other are joined with `or`, like ledger does.
+//!
+//! - `expenses`, `account:expenses` – account name contains the text
+//! - `@amazon`, `payee:amazon` – description contains the text
+//! - `%shared`, `tag:shared` – posting note has the tag or metadata key
+//! - `=gift`, `note:gift` – posting note contains the text
+//! - `amount>100` – posting amount compares to the number, with `<`, `<=`, `=`, `!=`,
+//!   `>=` or `>`
+//! - `date>=2025-01` – transaction date compares to a day, month or year. A month or
+//!   year is the whole period: `date=2025-01` is any day of January, `date>2025` is
+//!   after the end of 2025 and `date<=2025-03` up to the end of March
+//! - `{weekday = "Sat" and amount > 50}` – [expression](crate::expr) that holds for
+//!   the posting. Filters have no prices, so `value` is only known for amounts already
+//!   in the commodity asked for.
+//!
+//! Text is matched ignoring case, and can be quoted to include spaces:
+//! `@"whole foods"`. Terms are evaluated against each posting, so
+//! `expenses and amount>100` is an expense posting over 100.
+
+use std::collections::HashMap;
+
+use fastnum::D128;
+
+use crate::expr::{Env, Expr, ExprError};
+use crate::transactions::{Posting, Transaction};
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum QueryError {
+    #[error("unexpected `{0}`")]
+    Unexpected(String),
+    #[error("missing `)`")]
+    UnclosedParen,
+    #[error("missing closing quote")]
+    UnclosedQuote,
+    #[error("expected a term after `{0}`")]
+    MissingTerm(String),
+    #[error("invalid comparison `{0}`, expected e.g. `amount>100`")]
+    InvalidComparison(String),
+    #[error("invalid amount `{0}`")]
+    InvalidAmount(String),
+    #[error("invalid date `{0}`, expected e.g. `2025-01-31`, `2025-01` or `2025`")]
+    InvalidDate(String),
+    #[error("missing `}}`")]
+    UnclosedBrace,
+    #[error("in `{{{0}}}`: {1}")]
+    Expr(String, ExprError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// Splits the operator off the front of `text`.
+    pub fn split(text: &str) -> Option<(Self, &str)> {
+        [
+            (">=", Self::GreaterOrEqual),
+            ("<=", Self::LessOrEqual),
+            ("!=", Self::NotEqual),
+            (">", Self::Greater),
+            ("<", Self::Less),
+            ("=", Self::Equal),
+        ]
+        .into_iter()
+        .find_map(|(operator, comparison)| {
+            text.strip_prefix(operator).map(|rest| (comparison, rest))
+        })
+    }
+
+    pub fn holds<T: PartialOrd>(self, left: &T, right: &T) -> bool {
+        match self {
+            Self::Less => left < right,
+            Self::LessOrEqual => left <= right,
+            Self::Equal => left == right,
+            Self::NotEqual => left != right,
+            Self::GreaterOrEqual => left >= right,
+            Self::Greater => left > right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// Account name contains the lowercase text.
+    Account(String),
+    Payee(String),
+    Tag(String),
+    Note(String),
+    Amount(Comparison, D128),
+    /// Transaction date compares to the period from the first to the last day, both
+    /// included.
+    Date(Comparison, chrono::NaiveDate, chrono::NaiveDate),
+    Expr(Expr),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+impl Query {
+    /// Parses a query, `None` when the text is blank.
+    pub fn parse(text: &str) -> Result<Option<Self>, QueryError> {
+        let tokens = tokenize(text)?;
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let query = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Some(query)),
+            Some(token) => Err(QueryError::Unexpected(token.text().to_string())),
+        }
+    }
+
+    /// Whether any posting of the transaction matches.
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        transaction
+            .postings
+            .iter()
+            .any(|posting| self.matches_posting(transaction, posting))
+    }
+
+    pub fn matches_posting(&self, transaction: &Transaction, posting: &Posting) -> bool {
+        let contains = |text: &str, pattern: &str| text.to_lowercase().contains(pattern);
+        let note = posting.note.as_deref().unwrap_or_default();
+        match self {
+            Self::Account(pattern) => contains(&posting.account.to_string(), pattern),
+            Self::Payee(pattern) => contains(&transaction.description, pattern),
+            Self::Tag(tag) => contains(note, &format!("{tag}:")),
+            Self::Note(pattern) => contains(note, pattern),
+            Self::Amount(comparison, value) => comparison.holds(&posting.amount.value.value, value),
+            Self::Date(comparison, first, last) => {
+                let date = &transaction.time;
+                match comparison {
+                    Comparison::Less | Comparison::GreaterOrEqual => comparison.holds(date, first),
+                    Comparison::LessOrEqual | Comparison::Greater => comparison.holds(date, last),
+                    Comparison::Equal => (first..=last).contains(&date),
+                    Comparison::NotEqual => !(first..=last).contains(&date),
+                }
+            }
+            Self::Expr(expr) => {
+                let env = Env {
+                    transaction,
+                    posting,
+                    prices: &HashMap::new(),
+                };
+                expr.eval(&env).is_ok_and(|value| value.is_true())
+            }
+            Self::Not(query) => !query.matches_posting(transaction, posting),
+            Self::And(left, right) => {
+                left.matches_posting(transaction, posting)
+                    && right.matches_posting(transaction, posting)
+            }
+            Self::Or(left, right) => {
+                left.matches_posting(transaction, posting)
+                    || right.matches_posting(transaction, posting)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    /// Text of a term or keyword, and whether any of it was quoted.
+    Word(String, bool),
+    /// Text of an expression between braces.
+    Expr(String),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Self::Open => "(",
+            Self::Close => ")",
+            Self::Word(text, _) | Self::Expr(text) => text,
+        }
+    }
+
+    /// Unquoted word that is one of the keywords, ignoring case.
+    fn is_keyword(&self, keywords: &[&str]) -> bool {
+        let Self::Word(text, false) = self else {
+            return false;
+        };
+        keywords
+            .iter()
+            .any(|keyword| text.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '{' => {
+                chars.next();
+                let mut expr = String::new();
+                let mut quoted = false;
+                loop {
+                    match chars.next() {
+                        Some('}') if !quoted => break,
+                        Some(c) => {
+                            quoted ^= c == '"';
+                            expr.push(c);
+                        }
+                        None => return Err(QueryError::UnclosedBrace),
+                    }
+                }
+                tokens.push(Token::Expr(expr));
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        quoted = true;
+                        loop {
+                            match chars.next() {
+                                Some('"') => break,
+                                Some(c) => word.push(c),
+                                None => return Err(QueryError::UnclosedQuote),
+                            }
+                        }
+                    } else {
+                        word.push(c);
+                    }
+                }
+                tokens.push(Token::Word(word, quoted));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn previous_text(&self) -> String {
+        self.pos
+            .checked_sub(1)
+            .and_then(|ix| self.tokens.get(ix))
+            .map(|token| token.text().to_string())
+            .unwrap_or_default()
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.parse_and()?;
+        loop {
+            match self.peek() {
+                Some(token) if token.is_keyword(&["or", "|"]) => self.pos += 1,
+                // Terms next to each other are alternatives
+                Some(Token::Word(..) | Token::Expr(_) | Token::Open) => {}
+                _ => return Ok(query),
+            }
+            let right = self.parse_and()?;
+            query = Query::Or(Box::new(query), Box::new(right));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.parse_not()?;
+        while self
+            .peek()
+            .is_some_and(|token| token.is_keyword(&["and", "&"]))
+        {
+            self.pos += 1;
+            let right = self.parse_not()?;
+            query = Query::And(Box::new(query), Box::new(right));
+        }
+        Ok(query)
+    }
+
+    fn parse_not(&mut self) -> Result<Query, QueryError> {
+        if self.peek().is_some_and(|token| token.is_keyword(&["not"])) {
+            self.pos += 1;
+            return Ok(Query::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, QueryError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(QueryError::MissingTerm(self.previous_text()));
+        };
+        self.pos += 1;
+        match token {
+            Token::Open => {
+                let query = self.parse_or()?;
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(query)
+                    }
+                    _ => Err(QueryError::UnclosedParen),
+                }
+            }
+            Token::Close => Err(QueryError::Unexpected(")".to_string())),
+            token if token.is_keyword(&["and", "&", "or", "|"]) => {
+                Err(QueryError::Unexpected(token.text().to_string()))
+            }
+            Token::Word(word, quoted) => self.parse_term(word, quoted),
+            Token::Expr(text) => Expr::parse(&text)
+                .map(Query::Expr)
+                .map_err(|e| QueryError::Expr(text, e)),
+        }
+    }
+
+    fn parse_term(&mut self, word: String, quoted: bool) -> Result<Query, QueryError> {
+        let lower = word.to_lowercase();
+        // Quoted text is never a comparison
+        let fields = if quoted {
+            [].as_slice()
+        } else {
+            &["amount", "date"]
+        };
+        for field in fields {
+            let Some(rest) = lower.strip_prefix(field) else {
+                continue;
+            };
+            if !rest.is_empty() && Comparison::split(rest).is_none() {
+                continue;
+            }
+            // The operator and value may be separate words, `amount > 100`
+            let mut comparison = rest.to_string();
+            while !has_value(&comparison) {
+                match self.peek() {
+                    Some(Token::Word(next, _)) => {
+                        comparison.push_str(next);
+                        self.pos += 1;
+                    }
+                    _ => return Err(QueryError::InvalidComparison(word)),
+                }
+            }
+            return parse_comparison(field, &comparison);
+        }
+
+        let prefixed = [
+            ("@", Query::Payee as fn(String) -> Query),
+            ("payee:", Query::Payee),
+            ("desc:", Query::Payee),
+            ("%", Query::Tag),
+            ("tag:", Query::Tag),
+            ("=", Query::Note),
+            ("note:", Query::Note),
+            ("account:", Query::Account),
+        ];
+        Ok(prefixed
+            .into_iter()
+            .find_map(|(prefix, query)| lower.strip_prefix(prefix).map(|rest| query(rest.into())))
+            .unwrap_or(Query::Account(lower)))
+    }
+}
+
+/// Whether the text has an operator followed by something to compare with.
+fn has_value(comparison: &str) -> bool {
+    Comparison::split(comparison).is_some_and(|(_, value)| !value.is_empty())
+}
+
+fn parse_comparison(field: &str, text: &str) -> Result<Query, QueryError> {
+    let (comparison, value) = Comparison::split(text)
+        .ok_or_else(|| QueryError::InvalidComparison(format!("{field}{text}")))?;
+    if field == "amount" {
+        let amount = value
+            .replace(',', "")
+            .parse()
+            .map_err(|_| QueryError::InvalidAmount(value.to_string()))?;
+        return Ok(Query::Amount(comparison, amount));
+    }
+
+    let parse = |text: &str| chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").ok();
+    // Last day of the months starting on `first`
+    let end = |first: chrono::NaiveDate, months| {
+        first
+            .checked_add_months(chrono::Months::new(months))
+            .and_then(|next| next.pred_opt())
+            .unwrap_or(chrono::NaiveDate::MAX)
+    };
+    let (first, last) = if let Some(day) =
+        parse(value).or_else(|| chrono::NaiveDate::parse_from_str(value, "%Y/%m/%d").ok())
+    {
+        (day, day)
+    } else if let Some(month) = parse(&format!("{value}-01")) {
+        (month, end(month, 1))
+    } else if let Some(year) = parse(&format!("{value}-01-01")) {
+        (year, end(year, 12))
+    } else {
+        return Err(QueryError::InvalidDate(value.to_string()));
+    };
+    Ok(Query::Date(comparison, first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(description: &str, postings: &[(&str, &str, Option<&str>)]) -> Transaction {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let amounts = postings
+            .iter()
+            .map(|(account, amount, _)| (*account, *amount))
+            .collect::<Vec<_>>();
+        let mut transaction = Transaction::fixture(date, description, &amounts);
+        for (posting, (_, _, note)) in transaction.postings.iter_mut().zip(postings) {
+            posting.note = note.map(str::to_string);
+        }
+        transaction
+    }
+
+    fn matches(query: &str, transaction: &Transaction) -> bool {
+        Query::parse(query).unwrap().unwrap().matches(transaction)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Query::parse("  "), Ok(None));
+        assert_eq!(
+            Query::parse("expenses and @Amazon and amount > 100"),
+            Ok(Some(Query::And(
+                Box::new(Query::And(
+                    Box::new(Query::Account("expenses".into())),
+                    Box::new(Query::Payee("amazon".into())),
+                )),
+                Box::new(Query::Amount(Comparison::Greater, D128::from(100))),
+            )))
+        );
+        assert_eq!(
+            Query::parse("food groceries"),
+            Ok(Some(Query::Or(
+                Box::new(Query::Account("food".into())),
+                Box::new(Query::Account("groceries".into())),
+            )))
+        );
+        assert_eq!(
+            Query::parse("date>=2025"),
+            Ok(Some(Query::Date(
+                Comparison::GreaterOrEqual,
+                chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                chrono::NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            )))
+        );
+        assert_eq!(
+            Query::parse("date<=2024-02"),
+            Ok(Some(Query::Date(
+                Comparison::LessOrEqual,
+                chrono::NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+                chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+            )))
+        );
+        let day = chrono::NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        assert_eq!(
+            Query::parse("date=2025/03/14"),
+            Ok(Some(Query::Date(Comparison::Equal, day, day)))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Query::parse("expenses and"),
+            Err(QueryError::MissingTerm("and".into()))
+        );
+        assert_eq!(
+            Query::parse("(expenses or income"),
+            Err(QueryError::UnclosedParen)
+        );
+        assert_eq!(
+            Query::parse("expenses)"),
+            Err(QueryError::Unexpected(")".into()))
+        );
+        assert_eq!(Query::parse("@\"whole"), Err(QueryError::UnclosedQuote));
+        assert_eq!(
+            Query::parse("amount>lots"),
+            Err(QueryError::InvalidAmount("lots".into()))
+        );
+        assert_eq!(
+            Query::parse("amount>"),
+            Err(QueryError::InvalidComparison("amount>".into()))
+        );
+        assert_eq!(Query::parse("{amount > 1"), Err(QueryError::UnclosedBrace));
+        assert_eq!(
+            Query::parse("{amount >}"),
+            Err(QueryError::Expr(
+                "amount >".into(),
+                ExprError::MissingOperand(">".into())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let shop = transaction(
+            "Whole Foods",
+            &[
+                ("expenses:food", "120 USD", Some(" :shared:")),
+                ("assets:checking", "-120 USD", None),
+            ],
+        );
+        assert!(matches(
+            "expenses and @\"whole foods\" and amount>100",
+            &shop
+        ));
+        assert!(!matches("expenses and amount<0", &shop));
+        // Both terms have to hold for the same posting
+        assert!(matches("assets and amount<0", &shop));
+        assert!(!matches("assets and amount>0", &shop));
+        assert!(matches("%shared", &shop));
+        assert!(matches("not income", &shop));
+        assert!(!matches(
+            "(income or liabilities) and date>=2025-01-01",
+            &shop
+        ));
+        assert!(matches("date<2025-04", &shop));
+        assert!(matches("{weekday = \"Fri\" and amount > 100}", &shop));
+        assert!(!matches("expenses and {contains(note, \"}\")}", &shop));
+    }
+
+    #[test]
+    fn test_matches_date_periods() {
+        // Dated 2025-03-14
+        let shop = transaction("Shop", &[("expenses:food", "10 USD", None)]);
+        for (query, expected) in [
+            ("date=2025-03", true),
+            ("date=2025", true),
+            ("date=2025-03-14", true),
+            ("date=2025-03-15", false),
+            ("date!=2025-03", false),
+            ("date!=2025-02", true),
+            ("date<=2025-03", true),
+            ("date<=2025-02", false),
+            ("date>2025-03", false),
+            ("date>2025-02", true),
+            ("date>=2025-03", true),
+            ("date>=2025-04", false),
+            ("date<2025-03", false),
+            ("date<2026", true),
+            ("date>2024", true),
+            ("date>2025", false),
+        ] {
+            assert_eq!(matches(query, &shop), expected, "{query}");
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Money owed to the journal's owner, kept under `assets:receivable`.
+//!
+//! Each account under it is a customer or an invoice. Amounts posted to it are owed
+//! until payments, posted the other way, settle them, oldest first.
+
+use chrono::NaiveDate;
+use fastnum::D128;
+
+use crate::{
+    accounts::Account,
+    transactions::{Amount, CurrencyAmount, Posting, PostingStatus, Transaction},
+};
+
+/// How long a receivable has been open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AgeBucket {
+    Current,
+    Over30,
+    Over60,
+    Over90,
+}
+
+impl AgeBucket {
+    pub const ALL: [AgeBucket; 4] = [
+        AgeBucket::Current,
+        AgeBucket::Over30,
+        AgeBucket::Over60,
+        AgeBucket::Over90,
+    ];
+
+    pub fn for_days(days: i64) -> Self {
+        match days {
+            ..=30 => AgeBucket::Current,
+            31..=60 => AgeBucket::Over30,
+            61..=90 => AgeBucket::Over60,
+            _ => AgeBucket::Over90,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AgeBucket::Current => "0–30 days",
+            AgeBucket::Over30 => "31–60 days",
+            AgeBucket::Over60 => "61–90 days",
+            AgeBucket::Over90 => "Over 90 days",
+        }
+    }
+}
+
+/// What is left to be paid of a posting to a receivable account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenReceivable {
+    pub account: Account,
+    pub date: NaiveDate,
+    pub description: String,
+    pub amount: CurrencyAmount,
+}
+
+impl OpenReceivable {
+    pub fn age(&self, today: NaiveDate) -> i64 {
+        (today - self.date).num_days()
+    }
+
+    pub fn bucket(&self, today: NaiveDate) -> AgeBucket {
+        AgeBucket::for_days(self.age(today))
+    }
+
+    /// Transaction moving what is left from the receivable into `deposit`.
+    pub fn settlement(&self, date: NaiveDate, deposit: &Account) -> Transaction {
+        let posting = |account: &Account, value: D128| Posting {
+            account: account.clone(),
+            amount: Amount {
+                value: CurrencyAmount {
+                    value,
+                    commodity: self.amount.commodity.clone(),
+                },
+                price: None,
+                date: None,
+                cost: None,
+            },
+            status: PostingStatus::Uncleared,
+            note: None,
+        };
+        Transaction {
+            file: std::path::PathBuf::new(),
+            line: 0,
+            time: date,
+            description: format!("Payment: {}", self.description),
+            postings: vec![
+                posting(deposit, self.amount.value),
+                posting(&self.account, -self.amount.value),
+            ],
+        }
+    }
+}
+
+fn is_receivable(account: &Account) -> bool {
+    matches!(
+        account.segments.as_slice(),
+        [assets, receivable, ..]
+            if assets.eq_ignore_ascii_case("assets")
+                && receivable.eq_ignore_ascii_case("receivable")
+    )
+}
+
+/// Postings to receivable accounts not settled yet, oldest first.
+pub fn open_receivables(transactions: &[&Transaction]) -> Vec<OpenReceivable> {
+    let mut transactions = transactions.to_vec();
+    transactions.sort_by_key(|transaction| transaction.time);
+
+    let mut open = Vec::<OpenReceivable>::new();
+    for transaction in transactions {
+        for posting in &transaction.postings {
+            if !is_receivable(&posting.account) {
+                continue;
+            }
+            let amount = &posting.amount.value;
+            if amount.value > D128::ZERO {
+                open.push(OpenReceivable {
+                    account: posting.account.clone(),
+                    date: transaction.time,
+                    description: transaction.description.clone(),
+                    amount: amount.clone(),
+                });
+                continue;
+            }
+            // A payment settles the oldest amounts owed first
+            let mut payment = -amount.value;
+            for receivable in open.iter_mut().filter(|receivable| {
+                receivable.account == posting.account
+                    && receivable.amount.commodity == amount.commodity
+            }) {
+                if payment <= D128::ZERO {
+                    break;
+                }
+                let settled = payment.min(receivable.amount.value);
+                receivable.amount.value -= settled;
+                payment -= settled;
+            }
+        }
+        open.retain(|receivable| receivable.amount.value > D128::ZERO);
+    }
+    open
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(
+        date: (i32, u32, u32),
+        description: &str,
+        postings: &[(&str, &str)],
+    ) -> Transaction {
+        let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+        Transaction::fixture(date, description, postings)
+    }
+
+    #[test]
+    fn test_open_receivables() {
+        let transactions = [
+            transaction(
+                (2025, 1, 10),
+                "Invoice 1",
+                &[
+                    ("Assets:Receivable:Acme", "500 USD"),
+                    ("Income:Consulting", "-500 USD"),
+                ],
+            ),
+            transaction(
+                (2025, 2, 10),
+                "Invoice 2",
+                &[
+                    ("Assets:Receivable:Acme", "300 USD"),
+                    ("Income:Consulting", "-300 USD"),
+                ],
+            ),
+            transaction(
+                (2025, 3, 1),
+                "Invoice 3",
+                &[
+                    ("Assets:Receivable:Globex", "200 USD"),
+                    ("Income:Consulting", "-200 USD"),
+                ],
+            ),
+            transaction(
+                (2025, 3, 5),
+                "Acme pays",
+                &[
+                    ("Assets:Checking", "600 USD"),
+                    ("Assets:Receivable:Acme", "-600 USD"),
+                ],
+            ),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let open = open_receivables(&transactions);
+        assert_eq!(
+            open.iter()
+                .map(|receivable| format!("{}: {}", receivable.description, receivable.amount))
+                .collect::<Vec<_>>(),
+            ["Invoice 2: 200 USD", "Invoice 3: 200 USD"]
+        );
+
+        let today = NaiveDate::from_ymd_opt(2025, 4, 15).unwrap();
+        assert_eq!(open[0].age(today), 64);
+        assert_eq!(open[0].bucket(today), AgeBucket::Over60);
+        assert_eq!(open[1].bucket(today), AgeBucket::Over30);
+
+        let settlement = open[0].settlement(today, &Account::parse("Assets:Checking"));
+        assert_eq!(settlement.description, "Payment: Invoice 2");
+        assert_eq!(
+            settlement
+                .postings
+                .iter()
+                .map(|posting| (
+                    posting.account.to_string(),
+                    posting.amount.value.to_string()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("Assets:Checking".to_string(), "200 USD".to_string()),
+                ("Assets:Receivable:Acme".to_string(), "-200 USD".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Renaming accounts in journal files.
+//!
+//! Postings and `account` directives of the account and its subaccounts get the new
+//! name, everything else in the file stays as it was.
+
+use std::path::Path;
+
+use crate::accounts::Account;
+use crate::history::{lines_with_offsets, FileEdit};
+
+/// Edits renaming `from` to `to` in a journal file's text, in the order they apply.
+pub fn rename_edits(file: &Path, text: &str, from: &Account, to: &Account) -> Vec<FileEdit> {
+    let from = from.to_string();
+    let to = to.to_string();
+    let mut edits = Vec::new();
+    // Edits apply one after another, so later offsets move by what earlier ones changed
+    let (mut removed_len, mut inserted_len) = (0, 0);
+    for (start, line) in lines_with_offsets(text) {
+        let Some((name_start, name)) = account_in_line(line) else {
+            continue;
+        };
+        let renamed = if name == from {
+            to.clone()
+        } else {
+            match name
+                .strip_prefix(&from)
+                .filter(|rest| rest.starts_with(':'))
+            {
+                Some(rest) => format!("{to}{rest}"),
+                None => continue,
+            }
+        };
+        edits.push(FileEdit {
+            file: file.to_path_buf(),
+            offset: start + name_start + inserted_len - removed_len,
+            removed: name.to_string(),
+            inserted: renamed.clone(),
+        });
+        removed_len += name.len();
+        inserted_len += renamed.len();
+    }
+    edits
+}
+
+/// Account named on a posting line or in an `account` directive, with its byte offset
+/// in the line.
+fn account_in_line(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let body = if let Some(directive) = line.strip_prefix("account ") {
+        directive.trim_start()
+    } else if line.starts_with([' ', '\t']) {
+        let posting = line.trim_start();
+        let posting = posting
+            .strip_prefix(['*', '!'])
+            .map_or(posting, str::trim_start);
+        // Virtual postings wrap the account in parentheses or brackets
+        posting.strip_prefix(['(', '[']).unwrap_or(posting)
+    } else {
+        return None;
+    };
+    if body.is_empty() || body.starts_with([';', '#']) {
+        return None;
+    }
+
+    let end = [body.find("  "), body.find(['\t', ')', ']', ';'])]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(body.len());
+    let name = body[..end].trim_end();
+    (!name.is_empty()).then(|| (line.len() - body.len(), name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(text: &str, from: &str, to: &str) -> String {
+        rename_edits(
+            Path::new("main.ledger"),
+            text,
+            &Account::parse(from),
+            &Account::parse(to),
+        )
+        .iter()
+        .try_fold(text.to_string(), |text, edit| edit.apply(&text))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rename_postings_and_subaccounts() {
+        let text = "\
+account expenses:food
+2025-01-01 Shop ; expenses:food in a comment
+    expenses:food  10 USD
+    * expenses:food:snacks  2 USD ; expenses:food
+    (expenses:foodstuff)  1 USD
+    ; expenses:food
+    assets:cash
+";
+        assert_eq!(
+            rename(text, "expenses:food", "expenses:groceries"),
+            "\
+account expenses:groceries
+2025-01-01 Shop ; expenses:food in a comment
+    expenses:groceries  10 USD
+    * expenses:groceries:snacks  2 USD ; expenses:food
+    (expenses:foodstuff)  1 USD
+    ; expenses:food
+    assets:cash
+"
+        );
+    }
+
+    #[test]
+    fn test_rename_virtual_and_elided() {
+        let text = "2025-01-01 Shop\n    [assets:cash]  -5 USD\n\tassets:cash\n";
+        assert_eq!(
+            rename(text, "assets:cash", "assets:wallet"),
+            "2025-01-01 Shop\n    [assets:wallet]  -5 USD\n\tassets:wallet\n"
+        );
+    }
+}
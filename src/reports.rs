@@ -0,0 +1,218 @@
+//! Totals of postings over a period, for reports and the dashboard.
+
+use fastnum::D128;
+
+use crate::accounts::{Account, Balance};
+use crate::period::{Period, PeriodKind};
+use crate::transactions::Transaction;
+
+/// Totals of the period's postings by account, rolled up to the first `depth` segments.
+///
+/// Only accounts under one of the top-level `roots` count, compared ignoring case, so
+/// `&["income", "expenses"]` gives an income statement. Accounts are sorted by name.
+pub fn period_totals(
+    transactions: &[&Transaction],
+    period: Period,
+    roots: &[&str],
+    depth: usize,
+) -> Vec<(Account, Balance)> {
+    let mut totals = Vec::<(Account, Balance)>::new();
+    let postings = transactions
+        .iter()
+        .filter(|transaction| period.contains(transaction.time))
+        .flat_map(|transaction| &transaction.postings);
+    for posting in postings {
+        let in_roots = posting
+            .account
+            .segments
+            .first()
+            .is_some_and(|root| roots.iter().any(|r| root.eq_ignore_ascii_case(r)));
+        if !in_roots {
+            continue;
+        }
+        let segments = posting.account.segments.len().min(depth.max(1));
+        let account = Account::from_segments(posting.account.segments[..segments].to_vec());
+        if let Some((_, balance)) = totals.iter_mut().find(|(total, _)| *total == account) {
+            balance.add_amount(posting.amount.value.clone());
+        } else {
+            let mut balance = Balance::new();
+            balance.add_amount(posting.amount.value.clone());
+            totals.push((account, balance));
+        }
+    }
+    totals.sort_by_key(|(account, _)| account.to_string());
+    totals
+}
+
+/// Income and expenses of a month in one commodity, income counted as positive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonthlyFlow {
+    pub month: Period,
+    pub income: D128,
+    pub expenses: D128,
+}
+
+impl MonthlyFlow {
+    /// Share of the income left after expenses, `None` without income.
+    pub fn savings_rate(&self) -> Option<f64> {
+        if self.income <= D128::ZERO {
+            return None;
+        }
+        Some(to_f64((self.income - self.expenses) / self.income))
+    }
+}
+
+/// Income and expenses of the `months` months up to the one containing `until`, oldest
+/// first. Amounts in other commodities are left out.
+pub fn monthly_flows(
+    transactions: &[&Transaction],
+    until: chrono::NaiveDate,
+    months: usize,
+    commodity: &str,
+) -> Vec<MonthlyFlow> {
+    let mut flows = Vec::with_capacity(months);
+    let mut month = Period::containing(until, PeriodKind::Month);
+    for _ in 0..months {
+        let totals = period_totals(transactions, month, &["income", "expenses"], 1);
+        let total = |root: &str| {
+            totals
+                .iter()
+                .find(|(account, _)| {
+                    account
+                        .segments
+                        .first()
+                        .is_some_and(|first| first.eq_ignore_ascii_case(root))
+                })
+                .and_then(|(_, balance)| balance.get(commodity))
+                .map_or(D128::ZERO, |amount| amount.value)
+        };
+        flows.push(MonthlyFlow {
+            month,
+            income: -total("income"),
+            expenses: total("expenses"),
+        });
+        month = month.prev();
+    }
+    flows.reverse();
+    flows
+}
+
+/// Average of the last `months` values, `None` when there are fewer.
+pub fn trailing_average(values: &[D128], months: usize) -> Option<D128> {
+    if months == 0 || values.len() < months {
+        return None;
+    }
+    let total = values[values.len() - months..]
+        .iter()
+        .fold(D128::ZERO, |total, value| total + *value);
+    Some(total / D128::from(months))
+}
+
+/// Change from `previous` to `current` as a fraction of `previous`.
+pub fn growth(current: D128, previous: D128) -> Option<f64> {
+    if previous == D128::ZERO {
+        return None;
+    }
+    Some(to_f64((current - previous) / previous.abs()))
+}
+
+fn to_f64(value: D128) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(date: (i32, u32, u32), postings: &[(&str, &str)]) -> Transaction {
+        let date = chrono::NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+        Transaction::fixture(date, "", postings)
+    }
+
+    #[test]
+    fn test_period_totals() {
+        let transactions = [
+            transaction(
+                (2025, 3, 1),
+                &[("Income:Salary", "-1000 USD"), ("Assets:Bank", "1000 USD")],
+            ),
+            transaction(
+                (2025, 3, 5),
+                &[
+                    ("Expenses:Food:Groceries", "40 USD"),
+                    ("Expenses:Food:Dining", "25 USD"),
+                    ("Assets:Bank", "-65 USD"),
+                ],
+            ),
+            transaction(
+                (2025, 4, 1),
+                &[("Expenses:Rent", "500 USD"), ("Assets:Bank", "-500 USD")],
+            ),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let period = Period::containing(
+            chrono::NaiveDate::from_ymd_opt(2025, 3, 10).unwrap(),
+            PeriodKind::Month,
+        );
+
+        let totals = period_totals(&transactions, period, &["income", "expenses"], 2);
+        let names = totals
+            .iter()
+            .map(|(account, _)| account.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Expenses:Food", "Income:Salary"]);
+        assert_eq!(
+            totals[0].1.get("USD").unwrap().value,
+            fastnum::D128::from(65)
+        );
+
+        let totals = period_totals(&transactions, period, &["expenses"], 1);
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].0, Account::parse("Expenses"));
+    }
+
+    #[test]
+    fn test_monthly_flows() {
+        let transactions = [
+            transaction(
+                (2025, 2, 1),
+                &[("Income:Salary", "-1000 USD"), ("Assets:Bank", "1000 USD")],
+            ),
+            transaction(
+                (2025, 2, 3),
+                &[("Expenses:Rent", "750 USD"), ("Assets:Bank", "-750 USD")],
+            ),
+            transaction(
+                (2025, 3, 5),
+                &[("Expenses:Food", "100 EUR"), ("Assets:Bank", "-100 EUR")],
+            ),
+            transaction(
+                (2025, 4, 1),
+                &[("Income:Salary", "-1200 USD"), ("Assets:Bank", "1200 USD")],
+            ),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let until = chrono::NaiveDate::from_ymd_opt(2025, 4, 20).unwrap();
+
+        let flows = monthly_flows(&transactions, until, 3, "USD");
+        assert_eq!(
+            flows
+                .iter()
+                .map(|flow| (flow.month.start.to_string(), flow.income, flow.expenses))
+                .collect::<Vec<_>>(),
+            [
+                ("2025-02-01".to_string(), D128::from(1000), D128::from(750)),
+                ("2025-03-01".to_string(), D128::ZERO, D128::ZERO),
+                ("2025-04-01".to_string(), D128::from(1200), D128::ZERO),
+            ]
+        );
+        assert_eq!(flows[0].savings_rate(), Some(0.25));
+        assert_eq!(flows[1].savings_rate(), None);
+
+        let expenses = flows.iter().map(|flow| flow.expenses).collect::<Vec<_>>();
+        assert_eq!(trailing_average(&expenses, 3), Some(D128::from(250)));
+        assert_eq!(trailing_average(&expenses, 4), None);
+        assert_eq!(growth(D128::from(1200), D128::from(1000)), Some(0.2));
+        assert_eq!(growth(D128::from(1200), D128::ZERO), None);
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::accounts::{Account, AccountFilter, AccountSort};
+use crate::amount_format::AmountFormat;
+use crate::i18n::Language;
+use crate::period::Period;
+use crate::prices::Valuation;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("failed to write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error("invalid settings file: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("failed to serialize settings: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("no config directory on this platform")]
+    NoConfigDir,
+}
+
+/// Which appearance the app should use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    /// Follow the operating system's light/dark appearance.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemePreference {
+    /// Next preference when cycling through them from the title bar.
+    pub fn next(self) -> Self {
+        match self {
+            ThemePreference::System => ThemePreference::Light,
+            ThemePreference::Light => ThemePreference::Dark,
+            ThemePreference::Dark => ThemePreference::System,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemePreference::System => "System",
+            ThemePreference::Light => "Light",
+            ThemePreference::Dark => "Dark",
+        }
+    }
+}
+
+/// Named combination of register filters, like "Shared expenses 2025".
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    /// Full paths of the accounts the register is filtered to, all when empty.
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// Full paths of the accounts left out of the register.
+    #[serde(default)]
+    pub excluded_accounts: Vec<String>,
+    pub period: Period,
+    /// Description transactions are filtered by.
+    pub payee: Option<String>,
+    /// Text of the register's query filter.
+    pub query: Option<String>,
+}
+
+impl SavedView {
+    /// Accounts the view's register is filtered by.
+    pub fn account_filter(&self) -> AccountFilter {
+        let parse = |accounts: &[String]| {
+            accounts
+                .iter()
+                .map(|account| Account::parse(account))
+                .collect()
+        };
+        AccountFilter {
+            include: parse(&self.accounts),
+            exclude: parse(&self.excluded_accounts),
+        }
+    }
+}
+
+/// Register column computed from each posting, like `value("EUR")` for the amount in
+/// euros. See `expr` for what can be written.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ComputedColumn {
+    pub name: String,
+    pub expression: String,
+}
+
+/// Journals kept in the recent list besides the pinned ones.
+const MAX_RECENT_JOURNALS: usize = 10;
+
+/// A journal opened before, listed on the welcome screen and in the File menu.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RecentJournal {
+    pub path: PathBuf,
+    /// Pinned journals stay at the top of the list and are never dropped from it.
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Zoom levels stepped through by zooming in and out, in percent.
+pub const ZOOM_LEVELS: [u16; 11] = [50, 67, 75, 80, 90, 100, 110, 125, 150, 175, 200];
+
+/// The next zoom level above `zoom`, or the largest.
+pub fn zoom_in(zoom: u16) -> u16 {
+    ZOOM_LEVELS
+        .into_iter()
+        .find(|level| *level > zoom)
+        .unwrap_or(ZOOM_LEVELS[ZOOM_LEVELS.len() - 1])
+}
+
+/// The next zoom level below `zoom`, or the smallest.
+pub fn zoom_out(zoom: u16) -> u16 {
+    ZOOM_LEVELS
+        .into_iter()
+        .rev()
+        .find(|level| *level < zoom)
+        .unwrap_or(ZOOM_LEVELS[0])
+}
+
+/// User preferences, stored as TOML in the platform config directory.
+// Each preference is its own switch
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Ledger executable, looked up in `PATH` when not absolute.
+    pub ledger_path: PathBuf,
+    /// Journal to open; ledger's own default (`LEDGER_FILE`, `~/.ledgerrc`) when unset.
+    pub journal: Option<PathBuf>,
+    /// Commodity reports are converted to, if any.
+    pub base_currency: Option<String>,
+    /// `strftime` format used to display dates.
+    pub date_format: String,
+    pub theme: ThemePreference,
+    /// Language of the menus and views, see `i18n`.
+    pub language: Language,
+    /// Decimal places shown for commodities the journal gives no precision for.
+    pub decimal_places: usize,
+    /// Separators, commodity placement and negative style for amounts.
+    pub amount_format: AmountFormat,
+    /// Names commodities are shown as, on top of the built-in currency symbols.
+    pub commodity_aliases: BTreeMap<String, String>,
+    /// Names accounts are shown as by full account path, on top of journal aliases.
+    pub account_names: BTreeMap<String, String>,
+    /// CSV file with a custom CPI series for inflation adjustment.
+    pub cpi_file: Option<PathBuf>,
+    /// Chart series color overrides, as `#rrggbb` by commodity.
+    pub series_colors: BTreeMap<String, String>,
+    /// Account colors as `#rrggbb` by full account path, also used for subaccounts.
+    pub account_colors: BTreeMap<String, String>,
+    /// Saved register views by journal path.
+    pub saved_views: BTreeMap<String, Vec<SavedView>>,
+    /// Keep loading past transactions that fail to parse, listing them as problems
+    /// instead of stopping at the first one.
+    pub lenient_parsing: bool,
+    /// Repeat the date and description, dimmed, on every posting row of the register
+    /// so they stay in view for transactions with many postings.
+    pub repeat_transaction_headers: bool,
+    /// Shade every other transaction of the register.
+    pub striped_rows: bool,
+    /// Size of text and rows in percent of the default, one of `ZOOM_LEVELS`.
+    pub zoom: u16,
+    /// Draw a line above the first posting of each transaction in the register.
+    pub transaction_lines: bool,
+    /// Columns added to the register after the amount.
+    pub computed_columns: Vec<ComputedColumn>,
+    /// Journals opened before, pinned ones first, then the most recently opened.
+    pub recent_journals: Vec<RecentJournal>,
+    /// Keystroke opening the quick-add window from any of the app's windows, like
+    /// `secondary-shift-a`; off when unset. Bound when the app starts.
+    pub quick_add_hotkey: Option<String>,
+    /// Account quick-added transactions are paid from, the last one used.
+    pub quick_add_from: String,
+    /// File fetched prices are appended to as `P` directives, also read for valuations.
+    pub price_db: Option<PathBuf>,
+    /// Shell commands printing the current price of a commodity in the base currency,
+    /// by commodity, with `*` for the rest. `{commodity}` and `{base}` stand for the
+    /// commodity and the base currency, also the script's `$1` and `$2`.
+    pub price_commands: BTreeMap<String, String>,
+    /// How balances, net worth and charts value amounts.
+    pub valuation: Valuation,
+    /// Order of accounts in the accounts tree.
+    pub account_sort: AccountSort,
+    /// Unix socket scripts can drive the app through, see `api`; off when unset.
+    /// Opened when the app starts.
+    pub api_socket: Option<PathBuf>,
+    /// Rules alerted about after each reload, see `alerts`.
+    pub alert_rules: Vec<String>,
+    /// Alerts not shown until after the date, by rule.
+    pub snoozed_alerts: BTreeMap<String, chrono::NaiveDate>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ledger_path: PathBuf::from("ledger"),
+            journal: None,
+            base_currency: None,
+            date_format: "%Y-%m-%d".to_string(),
+            theme: ThemePreference::default(),
+            language: Language::default(),
+            decimal_places: 2,
+            amount_format: AmountFormat::default(),
+            commodity_aliases: BTreeMap::new(),
+            account_names: BTreeMap::new(),
+            cpi_file: None,
+            series_colors: BTreeMap::new(),
+            account_colors: BTreeMap::new(),
+            saved_views: BTreeMap::new(),
+            lenient_parsing: false,
+            repeat_transaction_headers: false,
+            striped_rows: true,
+            zoom: 100,
+            transaction_lines: true,
+            computed_columns: Vec::new(),
+            recent_journals: Vec::new(),
+            quick_add_hotkey: None,
+            quick_add_from: "assets:checking".to_string(),
+            price_db: None,
+            price_commands: BTreeMap::new(),
+            valuation: Valuation::default(),
+            account_sort: AccountSort::default(),
+            api_socket: None,
+            alert_rules: Vec::new(),
+            snoozed_alerts: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Whether alerts of the rule are snoozed on the day.
+    pub fn is_snoozed(&self, rule: &str, day: chrono::NaiveDate) -> bool {
+        self.snoozed_alerts
+            .get(rule)
+            .is_some_and(|until| day <= *until)
+    }
+
+    /// Command fetching the price of the commodity, if one is set up for it.
+    pub fn price_command(&self, commodity: &str) -> Option<&str> {
+        self.price_commands
+            .get(commodity)
+            .or_else(|| self.price_commands.get("*"))
+            .map(String::as_str)
+    }
+
+    /// Location of the settings file, e.g. `~/.config/ledger-desktop/settings.toml`.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("ledger-desktop").join("settings.toml"))
+    }
+
+    /// Loads settings from the default location, or defaults if there is no file yet.
+    pub fn load() -> Result<Self, SettingsError> {
+        let path = Self::path().ok_or(SettingsError::NoConfigDir)?;
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, SettingsError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_toml(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(SettingsError::Read(path.to_path_buf(), e)),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), SettingsError> {
+        let path = Self::path().ok_or(SettingsError::NoConfigDir)?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), SettingsError> {
+        let text = self.to_toml()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| SettingsError::Write(dir.to_path_buf(), e))?;
+        }
+        std::fs::write(path, text).map_err(|e| SettingsError::Write(path.to_path_buf(), e))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, SettingsError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, SettingsError> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Moves the journal to the top of the recent ones, below the pinned ones.
+    ///
+    /// The oldest unpinned journals are forgotten past [`MAX_RECENT_JOURNALS`].
+    pub fn remember_journal(&mut self, path: &Path) {
+        let pinned = self
+            .recent_journals
+            .iter()
+            .any(|journal| journal.pinned && journal.path == path);
+        if pinned {
+            return;
+        }
+        self.forget_journal(path);
+        self.insert_recent(RecentJournal {
+            path: path.to_path_buf(),
+            pinned: false,
+        });
+
+        let mut unpinned = 0;
+        self.recent_journals.retain(|journal| {
+            unpinned += usize::from(!journal.pinned);
+            journal.pinned || unpinned <= MAX_RECENT_JOURNALS
+        });
+    }
+
+    /// Pins the journal to the end of the pinned ones, or unpins it to the top of the
+    /// rest.
+    pub fn pin_journal(&mut self, path: &Path, pinned: bool) {
+        let Some(ix) = self
+            .recent_journals
+            .iter()
+            .position(|journal| journal.path == path)
+        else {
+            return;
+        };
+        let mut journal = self.recent_journals.remove(ix);
+        journal.pinned = pinned;
+        self.insert_recent(journal);
+    }
+
+    pub fn forget_journal(&mut self, path: &Path) {
+        self.recent_journals.retain(|journal| journal.path != path);
+    }
+
+    fn insert_recent(&mut self, journal: RecentJournal) {
+        let pinned = self
+            .recent_journals
+            .iter()
+            .take_while(|journal| journal.pinned)
+            .count();
+        self.recent_journals.insert(pinned, journal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_file_uses_defaults() {
+        let settings =
+            Settings::from_toml("theme = \"dark\"\ndecimal_places = 4\n").expect("should parse");
+        assert_eq!(settings.theme, ThemePreference::Dark);
+        assert_eq!(settings.decimal_places, 4);
+        assert_eq!(settings.ledger_path, PathBuf::from("ledger"));
+        assert_eq!(settings.date_format, "%Y-%m-%d");
+        assert_eq!(settings.amount_format, AmountFormat::default());
+    }
+
+    #[test]
+    fn test_zoom() {
+        assert_eq!(zoom_in(100), 110);
+        assert_eq!(zoom_out(100), 90);
+        // Levels edited into the file step to the nearest one
+        assert_eq!(zoom_in(105), 110);
+        assert_eq!(zoom_out(105), 100);
+        assert_eq!(zoom_in(200), 200);
+        assert_eq!(zoom_out(50), 50);
+        assert_eq!(zoom_out(20), 50);
+    }
+
+    #[test]
+    fn test_amount_format_table() {
+        let settings = Settings::from_toml(
+            "[amount_format]\nthousands_separator = \" \"\nnegative_style = \"parentheses\"\n",
+        )
+        .expect("should parse");
+        assert_eq!(settings.amount_format.thousands_separator, " ");
+        assert_eq!(settings.amount_format.decimal_separator, ".");
+        assert_eq!(
+            settings.amount_format.negative_style,
+            crate::amount_format::NegativeStyle::Parentheses
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut settings = Settings {
+            journal: Some(PathBuf::from("/tmp/main.ledger")),
+            base_currency: Some("SEK".to_string()),
+            ..Settings::default()
+        };
+        settings
+            .series_colors
+            .insert("USD".to_string(), "#ff0000".to_string());
+        settings.saved_views.insert(
+            "/tmp/main.ledger".to_string(),
+            vec![SavedView {
+                name: "Shared expenses 2025".to_string(),
+                accounts: vec!["expenses:shared".to_string()],
+                excluded_accounts: vec!["expenses:shared:pending".to_string()],
+                period: Period::containing(
+                    chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+                    crate::period::PeriodKind::Year,
+                ),
+                payee: None,
+                query: Some("%shared".to_string()),
+            }],
+        );
+
+        let text = settings.to_toml().expect("should serialize");
+        assert_eq!(Settings::from_toml(&text).expect("should parse"), settings);
+    }
+
+    #[test]
+    fn test_recent_journals() {
+        let mut settings = Settings::default();
+        for ix in 0..12 {
+            settings.remember_journal(Path::new(&format!("/tmp/{ix}.ledger")));
+        }
+        settings.pin_journal(Path::new("/tmp/5.ledger"), true);
+        settings.remember_journal(Path::new("/tmp/3.ledger"));
+        settings.remember_journal(Path::new("/tmp/5.ledger"));
+        settings.remember_journal(Path::new("/tmp/12.ledger"));
+
+        let paths = settings
+            .recent_journals
+            .iter()
+            .map(|journal| journal.path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(paths.len(), MAX_RECENT_JOURNALS + 1);
+        assert_eq!(
+            paths[..3],
+            ["/tmp/5.ledger", "/tmp/12.ledger", "/tmp/3.ledger"]
+        );
+        assert_eq!(paths.last().map(String::as_str), Some("/tmp/2.ledger"));
+
+        settings.pin_journal(Path::new("/tmp/5.ledger"), false);
+        assert!(!settings.recent_journals[0].pinned);
+        settings.forget_journal(Path::new("/tmp/5.ledger"));
+        assert_eq!(
+            settings.recent_journals[0].path,
+            Path::new("/tmp/12.ledger")
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let path = std::env::temp_dir().join("ledger-desktop-missing-settings.toml");
+        let settings = Settings::load_from(&path).expect("should fall back to defaults");
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        assert!(matches!(
+            Settings::from_toml("decimal_places = \"two\""),
+            Err(SettingsError::Parse(_))
+        ));
+    }
+}
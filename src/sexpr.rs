@@ -2,8 +2,12 @@ use core::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
+    /// A symbol, like `t` or `pending`.
     Atom(String),
+    /// The `nil` symbol, false or an empty value.
+    Nil,
     I64(i64),
+    F64(f64),
     String(String),
     List(Vec<Value>),
 }
@@ -12,7 +16,9 @@ impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::Atom(s) => write!(f, "{s}"),
+            Value::Nil => write!(f, "nil"),
             Value::I64(n) => write!(f, "{n}"),
+            Value::F64(n) => write!(f, "{n}"),
             Value::String(s) => write!(f, "\"{s}\""),
             Value::List(list) => {
                 write!(f, "(")?;
@@ -28,37 +34,65 @@ impl fmt::Display for Value {
     }
 }
 
+/// Where in the input a character is, counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
 #[derive(Debug, PartialEq, thiserror::Error)]
 pub enum Error {
-    #[error("unmatched closing parenthesis")]
-    UnmatchedCloseParen,
-    #[error("unterminated string literal")]
-    UnterminatedString,
+    #[error("unmatched closing parenthesis at {0}")]
+    UnmatchedCloseParen(Position),
+    #[error("unterminated string literal starting at {0}")]
+    UnterminatedString(Position),
     #[error("unclosed parentheses: {0} unclosed")]
     UnclosedParens(usize),
-    #[error("multiple top-level forms not allowed")]
-    MultipleTopLevelForms,
-    #[error(transparent)]
-    InvalidInteger(std::num::ParseIntError),
+    #[error("invalid integer `{1}` at {0}: {2}")]
+    InvalidInteger(Position, String, std::num::ParseIntError),
+    #[error("invalid number `{1}` at {0}: {2}")]
+    InvalidFloat(Position, String, std::num::ParseFloatError),
 }
 
 #[derive(Debug)]
 enum State {
     Normal,
-    InString { buf: String, escaped: bool },
+    InString {
+        buf: String,
+        escaped: bool,
+        start: Position,
+    },
+    /// After `;`, up to the end of the line.
+    InComment,
 }
 
+/// Incremental parser that can be fed input in chunks of any size.
+///
+/// Every top-level value is returned by [`Parser::drain_output`] once complete. The
+/// children of a top-level list that is still open can be taken early with
+/// [`Parser::drain_open_list`], for consuming one long list as it comes in.
 #[derive(Debug)]
 pub struct Parser {
     state: State,
     current_atom: String,
+    /// Where `current_atom` starts.
+    atom_start: Position,
+    /// Position of the next character.
+    position: Position,
+    /// Lists being read, outermost first.
     stack: Vec<Vec<Value>>,
+    /// Completed top-level values.
     output: Vec<Value>,
-    /// Track if we're inside the outer list
-    /// True means we've opened the outer `(` and are streaming its children
-    in_outer_list: bool,
 }
 
+/// All top-level values of the input.
 #[cfg(test)]
 pub fn parse_sexpr(input: &str) -> Result<Vec<Value>, Error> {
     let mut parser = Parser::new();
@@ -66,21 +100,39 @@ pub fn parse_sexpr(input: &str) -> Result<Vec<Value>, Error> {
     parser.finish()
 }
 
+/// Children of the single list the input consists of.
+#[cfg(test)]
+pub fn parse_list(input: &str) -> Result<Vec<Value>, Error> {
+    match parse_sexpr(input)?.as_slice() {
+        [Value::List(list)] => Ok(list.clone()),
+        values => panic!("expected a single list, got {values:?}"),
+    }
+}
+
 impl Parser {
     pub fn new() -> Self {
         Self {
             state: State::Normal,
             current_atom: String::new(),
+            atom_start: Position { line: 1, column: 1 },
+            position: Position { line: 1, column: 1 },
             stack: Vec::new(),
             output: Vec::new(),
-            in_outer_list: false,
         }
     }
 
     pub fn take(&mut self, chunk: &str) -> Result<(), Error> {
         for ch in chunk.chars() {
+            let position = self.position;
+            if ch == '\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+
             match &mut self.state {
-                State::InString { buf, escaped } => {
+                State::InString { buf, escaped, .. } => {
                     if *escaped {
                         buf.push(match ch {
                             'n' => '\n',
@@ -100,44 +152,45 @@ impl Parser {
                         }
                     }
                 }
+                State::InComment => {
+                    if ch == '\n' {
+                        self.state = State::Normal;
+                    }
+                }
                 State::Normal => match ch {
+                    ';' => {
+                        self.flush_atom()?;
+                        self.state = State::InComment;
+                    }
                     '"' => {
                         self.flush_atom()?;
                         self.state = State::InString {
                             buf: String::new(),
                             escaped: false,
+                            start: position,
                         };
                     }
                     '(' => {
                         self.flush_atom()?;
-
-                        // Reject multiple top-level forms
-                        if self.stack.is_empty() && self.in_outer_list {
-                            return Err(Error::MultipleTopLevelForms);
-                        }
-
-                        // Track if this is the outer list
-                        if self.stack.is_empty() {
-                            self.in_outer_list = true;
-                        }
-
                         self.stack.push(Vec::new());
                     }
                     ')' => {
                         self.flush_atom()?;
-                        let list = self.stack.pop().ok_or(Error::UnmatchedCloseParen)?;
-
-                        // If we just closed the outer list, don't push it (we've streamed its children)
-                        if self.stack.is_empty() && self.in_outer_list {
-                            // Outer list closed - we're done
-                        } else {
-                            self.push_value(Value::List(list));
-                        }
+                        let list = self
+                            .stack
+                            .pop()
+                            .ok_or(Error::UnmatchedCloseParen(position))?;
+                        self.push_value(Value::List(list));
                     }
                     c if c.is_whitespace() => {
                         self.flush_atom()?;
                     }
-                    c => self.current_atom.push(c),
+                    c => {
+                        if self.current_atom.is_empty() {
+                            self.atom_start = position;
+                        }
+                        self.current_atom.push(c);
+                    }
                 },
             }
         }
@@ -145,47 +198,57 @@ impl Parser {
     }
 
     fn flush_atom(&mut self) -> Result<(), Error> {
-        if self.current_atom.is_empty() {
-            Ok(())
-        } else if matches!(self.current_atom.chars().next(), Some('-' | '0'..='9')) {
-            let num = self
-                .current_atom
-                .parse::<i64>()
-                .map_err(Error::InvalidInteger)?;
-            self.push_value(Value::I64(num));
-            self.current_atom.clear();
-            Ok(())
-        } else {
-            let atom = std::mem::take(&mut self.current_atom);
-            self.push_value(Value::Atom(atom));
-            Ok(())
-        }
+        let atom = std::mem::take(&mut self.current_atom);
+        let value = match atom.as_str() {
+            // The dot of a dotted pair `(a . b)` is dropped, reading it as the list `(a b)`
+            "" | "." => return Ok(()),
+            "nil" => Value::Nil,
+            number if number.starts_with(|c: char| c.is_ascii_digit() || "-+.".contains(c)) => {
+                let start = self.atom_start;
+                if number.contains(['.', 'e', 'E']) {
+                    let value = number
+                        .parse()
+                        .map_err(|e| Error::InvalidFloat(start, atom.clone(), e))?;
+                    Value::F64(value)
+                } else {
+                    let value = number
+                        .parse()
+                        .map_err(|e| Error::InvalidInteger(start, atom.clone(), e))?;
+                    Value::I64(value)
+                }
+            }
+            _ => Value::Atom(atom),
+        };
+        self.push_value(value);
+        Ok(())
     }
 
     fn push_value(&mut self, value: Value) {
-        // If we're at depth 1 (inside the outer list), yield immediately
-        if self.stack.len() == 1 && self.in_outer_list {
-            self.output.push(value);
-        } else {
-            match self.stack.last_mut() {
-                Some(parent) => parent.push(value),
-                None => self.output.push(value),
-            }
+        match self.stack.last_mut() {
+            Some(parent) => parent.push(value),
+            None => self.output.push(value),
         }
     }
 
-    /// Drain any completed s-expressions from the output
-    /// Returns them and clears the output buffer
-    /// This is useful for streaming parsing
+    /// Takes the top-level values completed since the last call.
     pub fn drain_output(&mut self) -> Vec<Value> {
         std::mem::take(&mut self.output)
     }
 
+    /// Takes the completed children of the top-level list still being read. The list
+    /// is returned by [`Parser::drain_output`] without them once it closes.
+    pub fn drain_open_list(&mut self) -> Vec<Value> {
+        self.stack
+            .first_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     /// Call when input is done to check for errors
     pub fn finish(mut self) -> Result<Vec<Value>, Error> {
         self.flush_atom()?;
-        if matches!(self.state, State::InString { .. }) {
-            return Err(Error::UnterminatedString);
+        if let State::InString { start, .. } = self.state {
+            return Err(Error::UnterminatedString(start));
         }
         if !self.stack.is_empty() {
             return Err(Error::UnclosedParens(self.stack.len()));
@@ -203,48 +266,112 @@ mod tests {
         let mut parser = Parser::new();
         parser.take("(foo)").expect("should succeed");
         let output = parser.finish();
-        assert_eq!(output, Ok(vec![Value::Atom("foo".into())]));
+        assert_eq!(
+            output,
+            Ok(vec![Value::List(vec![Value::Atom("foo".into())])])
+        );
     }
 
     #[test]
     fn test_parser_string() {
-        let mut parser = Parser::new();
-        parser.take("(\"bar baz\")").expect("should succeed");
-        let output = parser.finish();
-        assert_eq!(output, Ok(vec![Value::String("bar baz".into())]));
+        assert_eq!(
+            parse_list("(\"bar baz\")"),
+            Ok(vec![Value::String("bar baz".into())])
+        );
     }
 
     #[test]
     fn test_parser_escaped_string() {
-        let mut parser = Parser::new();
-        parser.take("(\"escaped \\\"\")").expect("should succeed");
-        let output = parser.finish();
-        assert_eq!(output, Ok(vec![Value::String("escaped \"".into())]));
+        assert_eq!(
+            parse_list("(\"escaped \\\"\")"),
+            Ok(vec![Value::String("escaped \"".into())])
+        );
     }
 
     #[test]
     fn test_parser_i64() {
-        let mut parser = Parser::new();
-        parser.take("(123 )").expect("should succeed");
-        let output = parser.finish();
-        assert_eq!(output, Ok(vec![Value::I64(123)]));
+        assert_eq!(parse_list("(123 )"), Ok(vec![Value::I64(123)]));
     }
 
     #[test]
     fn test_parser_negative_i64() {
-        let mut parser = Parser::new();
-        parser.take("(-123 )").expect("should succeed");
-        let output = parser.finish();
-        assert_eq!(output, Ok(vec![Value::I64(-123)]));
+        assert_eq!(parse_list("(-123 )"), Ok(vec![Value::I64(-123)]));
+    }
+
+    #[test]
+    fn test_parser_f64() {
+        let output = parse_list("(1.5 -0.25 2e3 .5)");
+        assert_eq!(
+            output,
+            Ok(vec![
+                Value::F64(1.5),
+                Value::F64(-0.25),
+                Value::F64(2000.0),
+                Value::F64(0.5),
+            ])
+        );
+        assert!(matches!(
+            parse_sexpr("(1.2.3)"),
+            Err(Error::InvalidFloat(_, _, _))
+        ));
+    }
+
+    #[test]
+    fn test_parser_nil_and_symbols() {
+        let output = parse_list("(nil t pending nilly)");
+        assert_eq!(
+            output,
+            Ok(vec![
+                Value::Nil,
+                Value::Atom("t".into()),
+                Value::Atom("pending".into()),
+                Value::Atom("nilly".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parser_dotted_pair() {
+        let output = parse_list("((price . 1.25) (\"note\" . nil))");
+        assert_eq!(
+            output,
+            Ok(vec![
+                Value::List(vec![Value::Atom("price".into()), Value::F64(1.25)]),
+                Value::List(vec![Value::String("note".into()), Value::Nil]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parser_ledger_lisp_output() {
+        // Shape of `ledger lisp` output: a list of transactions with postings,
+        // cleared marks as `t`, missing values as `nil`
+        let output = parse_list(
+            "((\"main.ledger\" 12 \"2025-01-05\" nil \"Shop\"
+               (13 \"expenses:food\" \"50 USD\" t)
+               (14 \"assets:cash\" \"-50 USD\" nil \" note\")))",
+        )
+        .expect("should parse");
+        assert_eq!(output.len(), 1);
+        let Value::List(transaction) = &output[0] else {
+            panic!("expected a list, got {}", output[0]);
+        };
+        assert_eq!(transaction[3], Value::Nil);
+        assert_eq!(
+            transaction[6],
+            Value::List(vec![
+                Value::I64(14),
+                Value::String("assets:cash".into()),
+                Value::String("-50 USD".into()),
+                Value::Nil,
+                Value::String(" note".into()),
+            ])
+        );
     }
 
     #[test]
     fn test_parser_nested() {
-        let mut parser = Parser::new();
-        parser
-            .take("(foo (bar 42) \"baz\")")
-            .expect("should succeed");
-        let output = parser.finish();
+        let output = parse_list("(foo (bar 42) \"baz\")");
         assert_eq!(
             output,
             Ok(vec![
@@ -257,23 +384,58 @@ mod tests {
 
     #[test]
     fn test_parser_list() {
+        assert_eq!(
+            parse_sexpr("(a)(1)"),
+            Ok(vec![
+                Value::List(vec![Value::Atom("a".into())]),
+                Value::List(vec![Value::I64(1)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parser_whitespace_and_comments() {
+        let output = parse_sexpr("\n  ; generated by ledger\n(a ; first\n b) t\n");
+        assert_eq!(
+            output,
+            Ok(vec![
+                Value::List(vec![Value::Atom("a".into()), Value::Atom("b".into())]),
+                Value::Atom("t".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parser_drain_open_list() {
         let mut parser = Parser::new();
-        let result = parser.take("(a)(1)");
-        assert_eq!(result, Err(Error::MultipleTopLevelForms));
+        parser.take("((1) (2").expect("should succeed");
+        assert_eq!(parser.drain_output(), []);
+        assert_eq!(parser.drain_open_list(), [Value::List(vec![Value::I64(1)])]);
+        parser.take(") (3))").expect("should succeed");
+        assert_eq!(
+            parser.drain_output(),
+            [Value::List(vec![
+                Value::List(vec![Value::I64(2)]),
+                Value::List(vec![Value::I64(3)]),
+            ])]
+        );
     }
 
     #[test]
     fn test_parser_invalid_integer() {
         let mut parser = Parser::new();
         let result = parser.take("(123abc)");
-        assert!(matches!(result, Err(Error::InvalidInteger(_))));
+        assert!(matches!(result, Err(Error::InvalidInteger(_, _, _))));
     }
 
     #[test]
     fn test_parser_unmatched_close_paren() {
         let mut parser = Parser::new();
         let result = parser.take(")");
-        assert_eq!(result, Err(Error::UnmatchedCloseParen));
+        assert_eq!(
+            result,
+            Err(Error::UnmatchedCloseParen(Position { line: 1, column: 1 }))
+        );
     }
 
     #[test]
@@ -281,6 +443,23 @@ mod tests {
         let mut parser = Parser::new();
         parser.take("(\"unterminated)").expect("should succeed");
         let output = parser.finish();
-        assert_eq!(output, Err(Error::UnterminatedString));
+        assert_eq!(
+            output,
+            Err(Error::UnterminatedString(Position { line: 1, column: 2 }))
+        );
+    }
+
+    #[test]
+    fn test_parser_error_position() {
+        let mut parser = Parser::new();
+        parser.take("((a 1)\n").expect("should succeed");
+        let result = parser.take(" (b 12x))");
+        let Err(error) = result else {
+            panic!("expected an error, got {result:?}");
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid integer `12x` at line 2, column 5: invalid digit found in string"
+        );
     }
 }
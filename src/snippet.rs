@@ -0,0 +1,192 @@
+//! Reading journal entries pasted or dropped into the app.
+//!
+//! Entries are read without ledger, so they can be previewed before they are in the
+//! journal. Only transactions are understood: a header line with a date and
+//! description, followed by indented postings. Comment lines are skipped.
+
+use std::path::PathBuf;
+
+use fastnum::D128;
+
+use crate::accounts::Account;
+use crate::balancing::{self, BalanceError};
+use crate::transactions::{
+    parse_header, Amount, CurrencyAmount, ParseAmounError, Posting, PostingStatus, Transaction,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnippetError {
+    #[error("line {0}: expected a date and description, got `{1}`")]
+    Header(usize, String),
+    #[error("line {0}: posting outside of a transaction")]
+    StrayPosting(usize),
+    #[error("line {0}: invalid amount: {1}")]
+    Amount(usize, ParseAmounError),
+    #[error("line {0}: transaction has no postings")]
+    NoPostings(usize),
+    #[error("line {0}: {1}")]
+    Unbalanced(usize, BalanceError),
+    #[error("no transactions found")]
+    Empty,
+}
+
+/// A transaction being read, with its header's 1-based line.
+struct Entry {
+    line: usize,
+    transaction: Transaction,
+    amounts: Vec<Option<Amount>>,
+}
+
+/// Reads the transactions in the text.
+///
+/// Postings without an amount get the one that balances the transaction. The
+/// transactions are not in any file yet, their line is the header's line in the text.
+pub fn parse(text: &str) -> Result<Vec<Transaction>, SnippetError> {
+    let mut entries = Vec::<Entry>::new();
+    for (ix, line) in text.lines().enumerate() {
+        let line_number = ix + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with([';', '#', '%', '|']) {
+            continue;
+        }
+        if !line.starts_with([' ', '\t']) {
+            let (time, description) = parse_header(line)
+                .ok_or_else(|| SnippetError::Header(line_number, trimmed.to_string()))?;
+            entries.push(Entry {
+                line: line_number,
+                transaction: Transaction {
+                    file: PathBuf::new(),
+                    line: i64::try_from(line_number).unwrap_or(i64::MAX),
+                    time,
+                    description: description.to_string(),
+                    postings: Vec::new(),
+                },
+                amounts: Vec::new(),
+            });
+            continue;
+        }
+
+        let entry = entries
+            .last_mut()
+            .ok_or(SnippetError::StrayPosting(line_number))?;
+        let (posting, amount) =
+            parse_posting(trimmed).map_err(|e| SnippetError::Amount(line_number, e))?;
+        entry.transaction.postings.push(posting);
+        entry.amounts.push(amount);
+    }
+    if entries.is_empty() {
+        return Err(SnippetError::Empty);
+    }
+
+    entries.into_iter().map(finish).collect()
+}
+
+/// Posting and its amount, `None` when it is left out.
+fn parse_posting(line: &str) -> Result<(Posting, Option<Amount>), ParseAmounError> {
+    let (line, note) = match line.split_once(';') {
+        Some((line, note)) => (line, Some(note.to_string())),
+        None => (line, None),
+    };
+    let (status, line) = if let Some(line) = line.strip_prefix('*') {
+        (PostingStatus::Cleared, line.trim_start())
+    } else if let Some(line) = line.strip_prefix('!') {
+        (PostingStatus::Pending, line.trim_start())
+    } else {
+        (PostingStatus::Uncleared, line)
+    };
+    // Accounts can have single spaces, the amount is after two or a tab
+    let (account, amount) = match line.find("  ").into_iter().chain(line.find('\t')).min() {
+        Some(end) => (&line[..end], line[end..].trim()),
+        None => (line, ""),
+    };
+    let amount = if amount.is_empty() {
+        None
+    } else {
+        Some(Amount::parse(amount)?)
+    };
+    let posting = Posting {
+        account: Account::parse(account.trim()),
+        amount: amount.clone().unwrap_or(Amount {
+            value: CurrencyAmount {
+                value: D128::ZERO,
+                commodity: String::new(),
+            },
+            price: None,
+            date: None,
+            cost: None,
+        }),
+        status,
+        note,
+    };
+    Ok((posting, amount))
+}
+
+/// Checks the entry balances, filling in the amount that was left out.
+fn finish(mut entry: Entry) -> Result<Transaction, SnippetError> {
+    if entry.transaction.postings.is_empty() {
+        return Err(SnippetError::NoPostings(entry.line));
+    }
+    let amounts = entry.amounts.iter().map(Option::as_ref).collect::<Vec<_>>();
+    let implied =
+        balancing::balance(&amounts).map_err(|e| SnippetError::Unbalanced(entry.line, e))?;
+    if let Some(implied) = implied {
+        let elided = entry.amounts.iter().position(Option::is_none);
+        if let Some(posting) = elided.and_then(|ix| entry.transaction.postings.get_mut(ix)) {
+            posting.amount.value = implied;
+        }
+    }
+    Ok(entry.transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "\
+; sent by the bank
+2025/03/01 * Coffee shop
+    expenses:eating out    4.50 EUR  ; :coffee:
+    * assets:bank
+
+2025-03-02 Rent
+  expenses:rent\t900 EUR
+  assets:bank  -900 EUR
+";
+        let transactions = parse(text).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].description, "Coffee shop");
+        assert_eq!(transactions[0].line, 2);
+        let coffee = &transactions[0].postings;
+        assert_eq!(coffee[0].account.to_string(), "expenses:eating out");
+        assert_eq!(coffee[0].note.as_deref(), Some(" :coffee:"));
+        assert_eq!(coffee[1].status, PostingStatus::Cleared);
+        assert_eq!(coffee[1].amount.value.to_string(), "-4.50 EUR");
+        assert_eq!(
+            transactions[1].postings[0].amount.value.to_string(),
+            "900 EUR"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(parse("\n; nothing\n"), Err(SnippetError::Empty)));
+        assert!(matches!(
+            parse("  assets:bank  1 EUR\n"),
+            Err(SnippetError::StrayPosting(1))
+        ));
+        assert!(matches!(
+            parse("account assets:bank\n"),
+            Err(SnippetError::Header(1, _))
+        ));
+        assert!(matches!(
+            parse("2025-03-01 Shop\n  expenses:food  1 EUR\n  assets:bank  -2 EUR\n"),
+            Err(SnippetError::Unbalanced(1, BalanceError::Unbalanced(_)))
+        ));
+        assert!(matches!(
+            parse("2025-03-01 Shop\n2025-03-02 Cafe\n  a  1 EUR\n  b\n"),
+            Err(SnippetError::NoPostings(1))
+        ));
+    }
+}
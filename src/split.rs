@@ -0,0 +1,163 @@
+//! Dividing one amount across several postings.
+//!
+//! Each posting gets a share: a percentage, a fixed amount, or whatever is left. Shares
+//! can also come from a `shared:: 35%` tag in a posting's note.
+
+use fastnum::D128;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum SplitError {
+    #[error("invalid share: {0}")]
+    InvalidShare(String),
+    #[error("only one posting can take the remainder")]
+    SeveralRemainders,
+    #[error("shares are off by {0}")]
+    Unbalanced(D128),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Share {
+    /// Percentage of the total, `35%`.
+    Percent(D128),
+    /// Fixed amount, `12.50`.
+    Fixed(D128),
+    /// What the other shares leave, an empty share.
+    Remainder,
+}
+
+impl Share {
+    pub fn parse(text: &str) -> Result<Self, SplitError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(Self::Remainder);
+        }
+        let invalid = |_| SplitError::InvalidShare(text.to_string());
+        match text.strip_suffix('%') {
+            Some(percent) => percent.trim().parse().map(Self::Percent).map_err(invalid),
+            None => text
+                .replace(',', "")
+                .parse()
+                .map(Self::Fixed)
+                .map_err(invalid),
+        }
+    }
+
+    /// Share from a `shared:: VALUE` tag in a posting note.
+    pub fn from_note(note: &str) -> Option<Self> {
+        let (_, value) = note.split_once("shared::")?;
+        let value = value.split(';').next().unwrap_or_default().trim();
+        (!value.is_empty())
+            .then(|| Self::parse(value).ok())
+            .flatten()
+    }
+}
+
+impl std::fmt::Display for Share {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Percent(percent) => write!(f, "{percent}%"),
+            Self::Fixed(value) => write!(f, "{value}"),
+            Self::Remainder => Ok(()),
+        }
+    }
+}
+
+/// Divides the total by the shares, rounding percentages to `places` decimals.
+///
+/// Without a remainder share the shares have to add up to the total. When they are
+/// all percentages adding up to 100, the last one absorbs the rounding difference.
+pub fn split(total: D128, shares: &[Share], places: i16) -> Result<Vec<D128>, SplitError> {
+    let hundred = D128::from(100);
+    let mut amounts = shares
+        .iter()
+        .map(|share| match share {
+            Share::Percent(percent) => (total * *percent / hundred).round(places),
+            Share::Fixed(value) => *value,
+            Share::Remainder => D128::ZERO,
+        })
+        .collect::<Vec<_>>();
+    let difference = total - amounts.iter().fold(D128::ZERO, |sum, amount| sum + *amount);
+
+    let mut remainders = shares
+        .iter()
+        .enumerate()
+        .filter(|(_, share)| **share == Share::Remainder);
+    match (remainders.next(), remainders.next()) {
+        (Some(_), Some(_)) => return Err(SplitError::SeveralRemainders),
+        (Some((ix, _)), None) => amounts[ix] = difference,
+        (None, _) if difference.is_zero() => {}
+        (None, _) => {
+            let percents = shares
+                .iter()
+                .map(|share| match share {
+                    Share::Percent(percent) => Some(*percent),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            let all_percent = percents.is_some_and(|percents| {
+                percents
+                    .iter()
+                    .fold(D128::ZERO, |sum, percent| sum + *percent)
+                    == hundred
+            });
+            match amounts.last_mut() {
+                Some(last) if all_percent => *last += difference,
+                _ => return Err(SplitError::Unbalanced(difference)),
+            }
+        }
+    }
+    Ok(amounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> D128 {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_share() {
+        assert_eq!(Share::parse(" 35% "), Ok(Share::Percent(dec("35"))));
+        assert_eq!(Share::parse("1,200.50"), Ok(Share::Fixed(dec("1200.50"))));
+        assert_eq!(Share::parse(""), Ok(Share::Remainder));
+        assert!(Share::parse("half").is_err());
+        assert_eq!(
+            Share::from_note(" shared:: 35% ; with Anna"),
+            Some(Share::Percent(dec("35")))
+        );
+        assert_eq!(Share::from_note(" groceries"), None);
+    }
+
+    #[test]
+    fn test_split_with_remainder() {
+        let shares = [
+            Share::Percent(dec("35")),
+            Share::Fixed(dec("10")),
+            Share::Remainder,
+        ];
+        assert_eq!(
+            split(dec("100"), &shares, 2),
+            Ok(vec![dec("35"), dec("10"), dec("55")])
+        );
+        assert_eq!(
+            split(dec("100"), &[Share::Remainder, Share::Remainder], 2),
+            Err(SplitError::SeveralRemainders)
+        );
+    }
+
+    #[test]
+    fn test_split_rounding() {
+        let third = Share::Percent(dec("33.33"));
+        let shares = [third, third, Share::Percent(dec("33.34"))];
+        let amounts = split(dec("10"), &shares, 2).unwrap();
+        assert_eq!(amounts, vec![dec("3.33"), dec("3.33"), dec("3.34")]);
+
+        let shares = [Share::Percent(dec("50")), Share::Fixed(dec("40"))];
+        assert_eq!(
+            split(dec("100"), &shares, 2),
+            Err(SplitError::Unbalanced(dec("10")))
+        );
+    }
+}
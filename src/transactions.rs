@@ -13,13 +13,16 @@ pub enum ParseTransactionError {
     ParseDateError(chrono::ParseError),
     #[error("expected a list of {0}, got {1}")]
     UnexpectedLength(usize, usize),
-    #[error("expected type {1} at position {0}")]
+    #[error("unexpected {1} at position {0}")]
     UnexpectedType(usize, sexpr::Value),
-    #[error("error parsing posting at index {0}: {1}")]
-    PostingError(usize, ParsePostingError),
+    #[error("error parsing posting at index {0}, `{1}`: {2}")]
+    PostingError(usize, sexpr::Value, ParsePostingError),
+    /// Any of the above, in the transaction at the journal file and line.
+    #[error("{}:{}: {}", .0.display(), .1, .2)]
+    At(path::PathBuf, i64, Box<ParseTransactionError>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct Transaction {
     pub file: path::PathBuf,
     pub line: i64,
@@ -29,12 +32,41 @@ pub struct Transaction {
 }
 
 impl Transaction {
+    /// Reads a transaction from ledger's `emacs` output.
+    ///
+    /// Once the file and line are known, errors are wrapped in
+    /// [`ParseTransactionError::At`] to point at the transaction in the journal.
     pub fn from_sexpr(value: &[sexpr::Value]) -> Result<Self, ParseTransactionError> {
+        let source = match value {
+            [sexpr::Value::String(file), sexpr::Value::I64(line), ..] => {
+                Some((path::PathBuf::from(file), *line))
+            }
+            _ => None,
+        };
+        Self::parse(value).map_err(|error| {
+            let Some((file, line)) = source else {
+                return error;
+            };
+            // Postings start with their own line number
+            let line = match &error {
+                ParseTransactionError::PostingError(_, sexpr::Value::List(posting), _) => {
+                    match posting.first() {
+                        Some(sexpr::Value::I64(line)) => *line,
+                        _ => line,
+                    }
+                }
+                _ => line,
+            };
+            ParseTransactionError::At(file, line, Box::new(error))
+        })
+    }
+
+    fn parse(value: &[sexpr::Value]) -> Result<Self, ParseTransactionError> {
         if value.len() < 5 {
             return Err(ParseTransactionError::UnexpectedLength(5, value.len()));
         }
         let sexpr::Value::String(file) = value[0].to_owned() else {
-            return Err(ParseTransactionError::UnexpectedType(1, value[1].clone()));
+            return Err(ParseTransactionError::UnexpectedType(0, value[0].clone()));
         };
         let sexpr::Value::I64(line) = value[1].to_owned() else {
             return Err(ParseTransactionError::UnexpectedType(1, value[1].clone()));
@@ -55,8 +87,9 @@ impl Transaction {
                         posting_value.clone(),
                     ));
                 };
-                Posting::from_sexpr(posting_list)
-                    .map_err(|e| ParseTransactionError::PostingError(i, e))
+                Posting::from_sexpr(posting_list).map_err(|e| {
+                    ParseTransactionError::PostingError(i, posting_value.clone(), e)
+                })
             })
             .collect::<Result<Vec<Posting>, ParseTransactionError>>()?;
         Ok(Transaction {
@@ -70,20 +103,181 @@ impl Transaction {
     }
 }
 
+#[cfg(test)]
+impl Transaction {
+    /// Transaction on the first line of `main.ledger` with an uncleared posting per
+    /// `(account, amount)`.
+    pub fn fixture(time: chrono::NaiveDate, description: &str, postings: &[(&str, &str)]) -> Self {
+        Self {
+            file: "main.ledger".into(),
+            line: 1,
+            time,
+            description: description.to_string(),
+            postings: postings
+                .iter()
+                .map(|(account, amount)| Posting {
+                    account: Account::parse(account),
+                    amount: Amount::parse(amount).unwrap(),
+                    status: PostingStatus::Uncleared,
+                    note: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Date and description of a transaction header line in the journal, like
+/// `2025-01-31 * (42) Shop ; note`.
+pub fn parse_header(line: &str) -> Option<(chrono::NaiveDate, &str)> {
+    let line = line.split_once(';').map_or(line, |(line, _)| line);
+    let (date, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    // Drop an auxiliary date, `2025-01-31=2025-02-01`
+    let date = date.split_once('=').map_or(date, |(date, _)| date);
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| chrono::NaiveDate::parse_from_str(date, "%Y/%m/%d"))
+        .ok()?;
+
+    let mut description = rest.trim_start().trim_start_matches(['*', '!']).trim_start();
+    if description.starts_with('(') {
+        if let Some((_, rest)) = description.split_once(')') {
+            description = rest.trim_start();
+        }
+    }
+    Some((date, description.trim_end()))
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", self.time.format("%Y-%m-%d"), self.description)?;
+        for posting in &self.postings {
+            writeln!(f, "{posting}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Text formats transactions can be copied as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFormat {
+    /// Entries as written in the journal.
+    Journal,
+    /// `date,description,account,amount,commodity` rows, one per posting.
+    Csv,
+    /// Table with a row per posting.
+    Markdown,
+    /// The parsed transactions, for scripts.
+    Json,
+    /// Beancount journal, with the accounts opened.
+    Beancount,
+}
+
+impl Transaction {
+    /// Writes the transactions as text in the format.
+    pub fn serialize(transactions: &[Transaction], format: TextFormat) -> String {
+        let rows = || {
+            transactions.iter().flat_map(|transaction| {
+                transaction.postings.iter().map(move |posting| (transaction, posting))
+            })
+        };
+        match format {
+            TextFormat::Journal => transactions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            TextFormat::Csv => rows().fold(String::new(), |mut csv, (transaction, posting)| {
+                let fields = [
+                    transaction.time.format("%Y-%m-%d").to_string(),
+                    transaction.description.clone(),
+                    posting.account.to_string(),
+                    posting.amount.value.value.to_string(),
+                    posting.amount.value.commodity.clone(),
+                ]
+                .map(|field| csv_field(&field));
+                csv.push_str(&fields.join(","));
+                csv.push('\n');
+                csv
+            }),
+            TextFormat::Markdown => {
+                let header = concat!(
+                    "| Date | Description | Account | Amount |\n",
+                    "| --- | --- | --- | ---: |\n",
+                );
+                rows()
+                    .map(|(transaction, posting)| {
+                        format!(
+                            "| {} | {} | {} | {} |\n",
+                            transaction.time.format("%Y-%m-%d"),
+                            markdown_cell(&transaction.description),
+                            markdown_cell(&posting.account.to_string()),
+                            markdown_cell(&posting.amount.value.to_string()),
+                        )
+                    })
+                    .fold(header.to_string(), |table, row| table + &row)
+            }
+            TextFormat::Json => serde_json::to_string_pretty(transactions)
+                .expect("transactions serialize to JSON"),
+            TextFormat::Beancount => crate::beancount::export(transactions),
+        }
+    }
+}
+
+/// Writes decimals as strings, numbers in JSON lose their precision.
+pub fn serialize_decimal<S: serde::Serializer>(
+    value: &D128,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Quotes a CSV field when it needs to be.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ParsePostingError {
     #[error("expected a list of {0}, got {1}")]
     UnexpectedLength(usize, usize),
-    #[error("expected type {1} at position {0}")]
+    #[error("unexpected {1} at position {0}")]
     UnexpectedType(usize, sexpr::Value),
     #[error("invalid amount: {0}")]
     InvalidAmount(ParseAmounError),
 }
 
-#[derive(Debug, Clone)]
+/// Clearing state of a posting, `*` and `!` in the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostingStatus {
+    #[default]
+    Uncleared,
+    Pending,
+    Cleared,
+}
+
+impl PostingStatus {
+    fn from_sexpr(value: &sexpr::Value) -> Self {
+        match value {
+            sexpr::Value::Atom(atom) if atom == "t" => PostingStatus::Cleared,
+            sexpr::Value::Atom(atom) if atom == "pending" => PostingStatus::Pending,
+            _ => PostingStatus::Uncleared,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct Posting {
     pub account: Account,
     pub amount: Amount,
+    pub status: PostingStatus,
     pub note: Option<String>,
 }
 
@@ -100,6 +294,7 @@ impl Posting {
             return Err(ParsePostingError::UnexpectedType(2, value[2].clone()));
         };
         let amount = Amount::parse(&amount).map_err(ParsePostingError::InvalidAmount)?;
+        let status = PostingStatus::from_sexpr(&value[3]);
         if value.len() == 5 {
             let sexpr::Value::String(note) = value[4].to_owned() else {
                 return Err(ParsePostingError::UnexpectedType(4, value[4].clone()));
@@ -107,18 +302,40 @@ impl Posting {
             Ok(Posting {
                 account,
                 amount,
+                status,
                 note: Some(note),
             })
         } else {
             Ok(Posting {
                 account,
                 amount,
+                status,
                 note: None,
             })
         }
     }
 }
 
+impl fmt::Display for Posting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            PostingStatus::Uncleared => "",
+            PostingStatus::Pending => "! ",
+            PostingStatus::Cleared => "* ",
+        };
+        write!(
+            f,
+            "    {status}{}  {}",
+            self.account,
+            self.amount.to_string().trim_end()
+        )?;
+        if let Some(note) = &self.note {
+            write!(f, "  ;{note}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ParseAmounError {
     #[error("invalid decimal: {0}")]
@@ -127,8 +344,9 @@ pub enum ParseAmounError {
     InvalidFormat,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 pub struct CurrencyAmount {
+    #[serde(serialize_with = "serialize_decimal")]
     pub value: D128,
     pub commodity: String,
 }
@@ -161,11 +379,31 @@ impl CurrencyAmount {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// What a posting was converted at, as opposed to the lot price it was bought at.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cost {
+    /// `@ 10 SEK`, the price of one unit.
+    PerUnit(CurrencyAmount),
+    /// `@@ 100 SEK`, the price of the whole amount.
+    Total(CurrencyAmount),
+}
+
+impl fmt::Display for Cost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cost::PerUnit(cost) => write!(f, "@ {cost}"),
+            Cost::Total(cost) => write!(f, "@@ {cost}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 pub struct Amount {
     pub value: CurrencyAmount,
     pub price: Option<CurrencyAmount>,
     pub date: Option<chrono::NaiveDate>,
+    pub cost: Option<Cost>,
 }
 
 impl fmt::Display for Amount {
@@ -177,12 +415,27 @@ impl fmt::Display for Amount {
         if let Some(date) = &self.date {
             write!(f, " [{}]", date.format("%Y/%m/%d"))?;
         }
+        if let Some(cost) = &self.cost {
+            write!(f, " {cost}")?;
+        }
         Ok(())
     }
 }
 
 impl Amount {
     pub fn parse(amount_str: &str) -> Result<Self, ParseAmounError> {
+        // The cost comes after the lot annotations
+        let (amount_str, cost) = if let Some(cost_start) = amount_str.find('@') {
+            let cost_str = &amount_str[cost_start + 1..];
+            let cost = if let Some(total) = cost_str.strip_prefix('@') {
+                Cost::Total(CurrencyAmount::parse(total)?)
+            } else {
+                Cost::PerUnit(CurrencyAmount::parse(cost_str)?)
+            };
+            (&amount_str[..cost_start], Some(cost))
+        } else {
+            (amount_str, None)
+        };
         let price_start = amount_str.find('{');
         let price = if let Some(price_start) = price_start {
             let price_end = amount_str.find('}').ok_or(ParseAmounError::InvalidFormat)?;
@@ -211,7 +464,12 @@ impl Amount {
             amount_str
         };
         let value = CurrencyAmount::parse(amount_str)?;
-        Ok(Amount { value, price, date })
+        Ok(Amount {
+            value,
+            price,
+            date,
+            cost,
+        })
     }
 }
 
@@ -222,22 +480,41 @@ mod tests {
     #[test]
     fn test_parse_posting() {
         let sexpr_str = "(8562 \"expenses:Pending\" \"148.95 SEK\" pending \" shared:: 35%\")";
-        let sexpr_value = sexpr::parse_sexpr(sexpr_str).expect("should sexpr");
+        let sexpr_value = sexpr::parse_list(sexpr_str).expect("should sexpr");
         let posting = Posting::from_sexpr(&sexpr_value).expect("should parse posting");
         assert_eq!(posting.account.to_string(), "expenses:Pending");
         assert_eq!(
             posting.amount,
             Amount::parse("148.95 SEK").expect("should parse amount")
         );
+        assert_eq!(posting.status, PostingStatus::Pending);
         assert!(posting.note.is_some());
         assert_eq!(posting.note.unwrap(), " shared:: 35%");
     }
 
+    #[test]
+    fn test_parse_posting_status() {
+        let status = |s: &str| {
+            let sexpr_value = sexpr::parse_list(s).expect("should sexpr");
+            Posting::from_sexpr(&sexpr_value)
+                .expect("should parse posting")
+                .status
+        };
+        assert_eq!(
+            status("(1 \"assets:bank\" \"1 SEK\" t)"),
+            PostingStatus::Cleared
+        );
+        assert_eq!(
+            status("(1 \"assets:bank\" \"1 SEK\" nil)"),
+            PostingStatus::Uncleared
+        );
+    }
+
     #[test]
     fn test_parse_transaction() {
         let sexpr_str  = "(\"/Users/nikita.galaiko/Developer/finance/transactions/2025.ledger\" 8561 \"2025-12-13\" nil \"Kop\"
   (8562 \"expenses:Pending\" \"148.95 SEK\" pending \" shared:: 35%\"))";
-        let sexpr_value = sexpr::parse_sexpr(sexpr_str).expect("should sexpr");
+        let sexpr_value = sexpr::parse_list(sexpr_str).expect("should sexpr");
         let transaction = Transaction::from_sexpr(&sexpr_value).expect("should parse transaction");
         assert_eq!(
             transaction.file,
@@ -260,6 +537,48 @@ mod tests {
         assert_eq!(posting.note.as_ref().unwrap(), " shared:: 35%");
     }
 
+    #[test]
+    fn test_parse_transaction_error() {
+        let sexpr_str = "(\"2025.ledger\" 10 \"2025-12-13\" nil \"Kop\"
+  (11 \"expenses:food\" \"148.95 SEK\" nil)
+  (12 \"assets:bank\" 42 nil))";
+        let sexpr_value = sexpr::parse_list(sexpr_str).expect("should sexpr");
+        let error = Transaction::from_sexpr(&sexpr_value).expect_err("should fail");
+        assert_eq!(
+            error.to_string(),
+            "2025.ledger:12: error parsing posting at index 1, \
+             `(12 \"assets:bank\" 42 nil)`: unexpected 42 at position 2"
+        );
+    }
+
+    #[test]
+    fn test_display_transaction() {
+        let transaction = Transaction {
+            file: path::PathBuf::new(),
+            line: 0,
+            time: chrono::NaiveDate::from_ymd_opt(2025, 12, 13).unwrap(),
+            description: "Kop".to_string(),
+            postings: vec![
+                Posting {
+                    account: Account::parse("expenses:groceries"),
+                    amount: Amount::parse("148.95 SEK").expect("should parse amount"),
+                    status: PostingStatus::Uncleared,
+                    note: Some(" shared:: 35%".to_string()),
+                },
+                Posting {
+                    account: Account::parse("assets:checking"),
+                    amount: Amount::parse("-148.95").expect("should parse amount"),
+                    status: PostingStatus::Cleared,
+                    note: None,
+                },
+            ],
+        };
+        assert_eq!(
+            transaction.to_string(),
+            "2025-12-13 Kop\n    expenses:groceries  148.95 SEK  ; shared:: 35%\n    * assets:checking  -148.95\n"
+        );
+    }
+
     #[test]
     fn test_parse_currency_amount_no_commodity() {
         let amount_str = "-1,020.48";
@@ -320,6 +639,37 @@ mod tests {
         assert_eq!(*date, chrono::NaiveDate::from_ymd_opt(2025, 12, 3).unwrap());
     }
 
+    #[test]
+    fn test_parse_amount_cost() {
+        let amount = Amount::parse("-20.48 GEL @ 3.60 SEK").expect("should parse amount");
+        assert_eq!(amount.value, CurrencyAmount::parse("-20.48 GEL").unwrap());
+        assert_eq!(amount.price, None);
+        assert_eq!(
+            amount.cost,
+            Some(Cost::PerUnit(CurrencyAmount::parse("3.60 SEK").unwrap()))
+        );
+        assert_eq!(amount.to_string(), "-20.48 GEL @ 3.60 SEK");
+
+        let amount = Amount::parse("10 AAPL {150 USD} [2025/01/02] @@ 16000 SEK")
+            .expect("should parse amount");
+        assert_eq!(amount.value, CurrencyAmount::parse("10 AAPL").unwrap());
+        assert_eq!(amount.price, Some(CurrencyAmount::parse("150 USD").unwrap()));
+        assert_eq!(
+            amount.date,
+            Some(chrono::NaiveDate::from_ymd_opt(2025, 1, 2).unwrap())
+        );
+        assert_eq!(
+            amount.cost,
+            Some(Cost::Total(CurrencyAmount::parse("16000 SEK").unwrap()))
+        );
+        assert_eq!(
+            amount.to_string(),
+            "10 AAPL {150 USD} [2025/01/02] @@ 16000 SEK"
+        );
+
+        assert!(Amount::parse("10 AAPL @").is_err());
+    }
+
     #[test]
     fn test_parse_amount_long_price() {
         let amount_str = "194.21240000 USDT {9.525653356840242950501615756769 SEK} [2025/09/17]";
@@ -341,4 +691,68 @@ mod tests {
         let date = amount.date.as_ref().unwrap();
         assert_eq!(*date, chrono::NaiveDate::from_ymd_opt(2025, 9, 17).unwrap());
     }
+
+    #[test]
+    fn test_parse_header() {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        assert_eq!(parse_header("2025-01-31 Shop"), Some((date, "Shop")));
+        assert_eq!(
+            parse_header("2025/01/31=2025/02/01 * (42) Shop  ; note"),
+            Some((date, "Shop"))
+        );
+        assert_eq!(parse_header("2025-01-31"), Some((date, "")));
+        assert_eq!(parse_header("account assets:cash"), None);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let transaction = Transaction {
+            file: path::PathBuf::new(),
+            line: 0,
+            time: chrono::NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+            description: "Shop, \"Corner\" | Co".to_string(),
+            postings: vec![
+                Posting {
+                    account: Account::parse("expenses:food"),
+                    amount: Amount::parse("10 USD").unwrap(),
+                    status: PostingStatus::Cleared,
+                    note: None,
+                },
+                Posting {
+                    account: Account::parse("assets:cash"),
+                    amount: Amount::parse("-10 USD").unwrap(),
+                    status: PostingStatus::Uncleared,
+                    note: None,
+                },
+            ],
+        };
+        let transactions = [transaction];
+
+        assert_eq!(
+            Transaction::serialize(&transactions, TextFormat::Journal),
+            "2025-01-31 Shop, \"Corner\" | Co\n    * expenses:food  10 USD\n    assets:cash  -10 USD\n"
+        );
+        assert_eq!(
+            Transaction::serialize(&transactions, TextFormat::Csv),
+            "2025-01-31,\"Shop, \"\"Corner\"\" | Co\",expenses:food,10,USD\n\
+             2025-01-31,\"Shop, \"\"Corner\"\" | Co\",assets:cash,-10,USD\n"
+        );
+        assert_eq!(
+            Transaction::serialize(&transactions, TextFormat::Markdown),
+            "| Date | Description | Account | Amount |\n| --- | --- | --- | ---: |\n\
+             | 2025-01-31 | Shop, \"Corner\" \\| Co | expenses:food | 10 USD |\n\
+             | 2025-01-31 | Shop, \"Corner\" \\| Co | assets:cash | -10 USD |\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&Transaction::serialize(&transactions, TextFormat::Json))
+                .unwrap();
+        assert_eq!(json[0]["time"], "2025-01-31");
+        assert_eq!(json[0]["postings"][0]["account"], "expenses:food");
+        assert_eq!(json[0]["postings"][0]["status"], "cleared");
+        assert_eq!(
+            json[0]["postings"][1]["amount"]["value"],
+            serde_json::json!({ "value": "-10", "commodity": "USD" })
+        );
+    }
 }
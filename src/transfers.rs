@@ -0,0 +1,270 @@
+//! Transfers between the journal owner's own accounts booked as two entries.
+//!
+//! Importing two statements books a transfer twice: once as money leaving one account
+//! and once as money arriving in another, each against an income or expense account.
+//! Entries with opposite amounts a few days apart are paired, and confirmed pairs are
+//! tagged `:transfer:` so income and expense reports leave them out.
+
+use fastnum::D128;
+
+use crate::{
+    accounts::Account,
+    transactions::{CurrencyAmount, Posting, Transaction},
+};
+
+/// Tag confirmed transfers carry on their postings.
+pub const TAG: &str = ":transfer:";
+/// Most days the two entries of a transfer are apart.
+const WINDOW_DAYS: i64 = 3;
+
+/// Two entries of one transfer.
+#[derive(Debug, Clone)]
+pub struct TransferPair {
+    /// Entry taking the money out.
+    pub outgoing: Transaction,
+    /// Entry putting it in.
+    pub incoming: Transaction,
+}
+
+impl TransferPair {
+    pub fn from(&self) -> &Account {
+        &own_posting(&self.outgoing)
+            .expect("paired entries have an own posting")
+            .account
+    }
+
+    pub fn to(&self) -> &Account {
+        &own_posting(&self.incoming)
+            .expect("paired entries have an own posting")
+            .account
+    }
+
+    pub fn amount(&self) -> &CurrencyAmount {
+        &own_posting(&self.incoming)
+            .expect("paired entries have an own posting")
+            .amount
+            .value
+    }
+}
+
+fn is_own(account: &Account) -> bool {
+    account.segments.first().is_some_and(|root| {
+        root.eq_ignore_ascii_case("assets") || root.eq_ignore_ascii_case("liabilities")
+    })
+}
+
+/// The only posting of the transaction to an asset or liability account. Entries moving
+/// money between two of them already book the transfer as one.
+fn own_posting(transaction: &Transaction) -> Option<&Posting> {
+    let mut own = transaction
+        .postings
+        .iter()
+        .filter(|posting| is_own(&posting.account));
+    let posting = own.next()?;
+    own.next().is_none().then_some(posting)
+}
+
+/// Whether the transaction is tagged as one half of a transfer.
+pub fn is_transfer(transaction: &Transaction) -> bool {
+    transaction
+        .postings
+        .iter()
+        .filter_map(|posting| posting.note.as_deref())
+        .any(|note| note.contains(TAG))
+}
+
+/// Pairs entries into transfers, oldest first. With `confirmed` the entries already
+/// tagged are paired, otherwise the ones that are not.
+///
+/// Each outgoing entry is paired with the incoming entry of the opposite amount in
+/// another account that is closest to it in time.
+pub fn find_pairs(transactions: &[&Transaction], confirmed: bool) -> Vec<TransferPair> {
+    let mut candidates = transactions
+        .iter()
+        .filter(|transaction| is_transfer(transaction) == confirmed)
+        .filter_map(|transaction| Some((*transaction, own_posting(transaction)?)))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(transaction, _)| transaction.time);
+
+    let mut paired = vec![false; candidates.len()];
+    let mut pairs = Vec::new();
+    for (i, (outgoing, from)) in candidates.iter().enumerate() {
+        let amount = &from.amount.value;
+        if paired[i] || amount.value >= D128::ZERO {
+            continue;
+        }
+        let incoming = candidates
+            .iter()
+            .enumerate()
+            .filter(|(j, (incoming, to))| {
+                !paired[*j]
+                    && to.account != from.account
+                    && to.amount.value.commodity == amount.commodity
+                    && to.amount.value.value == -amount.value
+                    && (incoming.time - outgoing.time).num_days().abs() <= WINDOW_DAYS
+            })
+            .min_by_key(|(_, (incoming, _))| (incoming.time - outgoing.time).num_days().abs());
+        if let Some((j, (incoming, _))) = incoming {
+            paired[i] = true;
+            paired[j] = true;
+            pairs.push(TransferPair {
+                outgoing: (*outgoing).clone(),
+                incoming: (*incoming).clone(),
+            });
+        }
+    }
+    pairs
+}
+
+/// Posting lines of an entry: indented and not comments.
+fn is_posting_line(line: &str) -> bool {
+    line.starts_with([' ', '\t']) && !line.trim().is_empty() && !line.trim().starts_with(';')
+}
+
+/// Tags every posting of the entry as a transfer.
+pub fn mark(entry: &str) -> String {
+    let mut marked = String::with_capacity(entry.len());
+    for line in entry.split_inclusive('\n') {
+        let (text, newline) = line.split_at(line.trim_end_matches('\n').len());
+        marked.push_str(text);
+        if is_posting_line(text) && !text.contains(TAG) {
+            marked.push_str(if text.contains(';') { " " } else { "  ; " });
+            marked.push_str(TAG);
+        }
+        marked.push_str(newline);
+    }
+    marked
+}
+
+/// Takes the transfer tag off the entry's postings.
+pub fn unmark(entry: &str) -> String {
+    let mut unmarked = String::with_capacity(entry.len());
+    for line in entry.split_inclusive('\n') {
+        let (text, newline) = line.split_at(line.trim_end_matches('\n').len());
+        let text = if is_posting_line(text) {
+            text.strip_suffix(&format!("  ; {TAG}"))
+                .or_else(|| text.strip_suffix(&format!(" {TAG}")))
+                .unwrap_or(text)
+        } else {
+            text
+        };
+        unmarked.push_str(text);
+        unmarked.push_str(newline);
+    }
+    unmarked
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn transaction(day: u32, description: &str, postings: &[(&str, &str)]) -> Transaction {
+        let date = NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        Transaction::fixture(date, description, postings)
+    }
+
+    #[test]
+    fn test_find_pairs() {
+        let tagged = |day, description, postings| {
+            let mut transaction = transaction(day, description, postings);
+            transaction.postings[0].note = Some(format!(" {TAG}"));
+            transaction
+        };
+        let transactions = [
+            transaction(
+                2,
+                "To savings",
+                &[
+                    ("Assets:Checking", "-200 USD"),
+                    ("Expenses:Unknown", "200 USD"),
+                ],
+            ),
+            transaction(
+                3,
+                "Grocer",
+                &[
+                    ("Assets:Checking", "-200 USD"),
+                    ("Expenses:Food", "200 USD"),
+                ],
+            ),
+            transaction(
+                4,
+                "From checking",
+                &[
+                    ("Assets:Savings", "200 USD"),
+                    ("Income:Unknown", "-200 USD"),
+                ],
+            ),
+            // Money coming back into the same account
+            transaction(
+                10,
+                "Refund",
+                &[
+                    ("Assets:Checking", "200 USD"),
+                    ("Income:Unknown", "-200 USD"),
+                ],
+            ),
+            // Already booked as a transfer in one entry
+            transaction(
+                12,
+                "Move",
+                &[("Assets:Checking", "-70 USD"), ("Assets:Savings", "70 USD")],
+            ),
+            tagged(
+                19,
+                "Pay card",
+                &[
+                    ("Assets:Checking", "-50 USD"),
+                    ("Expenses:Unknown", "50 USD"),
+                ],
+            ),
+            tagged(
+                20,
+                "Card payment",
+                &[
+                    ("Liabilities:Card", "50 USD"),
+                    ("Income:Unknown", "-50 USD"),
+                ],
+            ),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+
+        let describe = |pairs: Vec<TransferPair>| {
+            pairs
+                .iter()
+                .map(|pair| {
+                    format!(
+                        "{} -> {}: {} {} {}",
+                        pair.outgoing.description,
+                        pair.incoming.description,
+                        pair.from(),
+                        pair.to(),
+                        pair.amount()
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            describe(find_pairs(&transactions, false)),
+            ["To savings -> From checking: Assets:Checking Assets:Savings 200 USD"]
+        );
+        assert_eq!(
+            describe(find_pairs(&transactions, true)),
+            ["Pay card -> Card payment: Assets:Checking Liabilities:Card 50 USD"]
+        );
+    }
+
+    #[test]
+    fn test_mark() {
+        let entry = "2025-03-02 To savings\n    ; imported\n    assets:checking  -200 USD ; bank\n    expenses:unknown\n";
+        let marked = mark(entry);
+        assert_eq!(
+            marked,
+            "2025-03-02 To savings\n    ; imported\n    assets:checking  -200 USD ; bank :transfer:\n    expenses:unknown  ; :transfer:\n"
+        );
+        assert_eq!(mark(&marked), marked);
+        assert_eq!(unmark(&marked), entry);
+    }
+}
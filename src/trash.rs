@@ -0,0 +1,151 @@
+//! Soft deletion of journal entries.
+//!
+//! Deleting an entry comments it out by prefixing each of its lines with `; `, so the
+//! text stays in the journal and ledger ignores it. Commented-out entries are found
+//! again by their header line and can be restored by removing the prefix.
+
+use std::path::{Path, PathBuf};
+
+use crate::history::{lines_with_offsets, FileEdit};
+use crate::transactions::parse_header;
+
+const PREFIX: &str = "; ";
+
+/// An entry that was commented out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedEntry {
+    pub file: PathBuf,
+    /// Byte offset of the entry in the file.
+    pub offset: usize,
+    /// Commented-out text of the entry, including the trailing newline.
+    pub text: String,
+    pub date: chrono::NaiveDate,
+    pub description: String,
+}
+
+impl DeletedEntry {
+    /// Edit that uncomments the entry.
+    pub fn restore_edit(&self) -> FileEdit {
+        FileEdit {
+            file: self.file.clone(),
+            offset: self.offset,
+            removed: self.text.clone(),
+            inserted: self
+                .text
+                .split_inclusive('\n')
+                .map(|line| line.strip_prefix(PREFIX).unwrap_or(line))
+                .collect(),
+        }
+    }
+}
+
+/// The entry's text commented out.
+pub fn comment_out(entry: &str) -> String {
+    entry
+        .split_inclusive('\n')
+        .flat_map(|line| [PREFIX, line])
+        .collect()
+}
+
+/// Finds commented-out entries in a journal file's text.
+pub fn find_deleted(text: &str, file: &Path) -> Vec<DeletedEntry> {
+    let lines = lines_with_offsets(text).collect::<Vec<_>>();
+    let mut deleted = Vec::new();
+    let mut ix = 0;
+    while ix < lines.len() {
+        let (start, line) = lines[ix];
+        ix += 1;
+        let Some((date, description)) = line
+            .strip_prefix(PREFIX)
+            .filter(|header| !header.starts_with([' ', '\t']))
+            .and_then(parse_header)
+        else {
+            continue;
+        };
+
+        let mut end = start + line.len();
+        while let Some((offset, line)) = lines.get(ix) {
+            let commented_posting = line
+                .strip_prefix(PREFIX)
+                .is_some_and(|line| line.starts_with([' ', '\t']) && !line.trim().is_empty());
+            if !commented_posting {
+                break;
+            }
+            end = offset + line.len();
+            ix += 1;
+        }
+
+        deleted.push(DeletedEntry {
+            file: file.to_path_buf(),
+            offset: start,
+            text: text[start..end].to_string(),
+            date,
+            description: description.to_string(),
+        });
+    }
+    deleted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::entry_edit;
+
+    const JOURNAL: &str = "\
+2025-01-01 Opening
+    assets:checking  1000 USD
+    equity:opening
+
+2025-01-05 * Shop
+    expenses:food  50 USD
+    assets:checking
+2025-01-06 Rent
+    expenses:rent  500 USD
+    assets:checking
+";
+
+    #[test]
+    fn test_delete_and_restore() {
+        let file = Path::new("main.ledger");
+        let edit = entry_edit(file, JOURNAL, 5, comment_out).unwrap();
+        assert_eq!(
+            edit.inserted,
+            "; 2025-01-05 * Shop\n;     expenses:food  50 USD\n;     assets:checking\n"
+        );
+        let deleted_text = edit.apply(JOURNAL).unwrap();
+
+        let deleted = find_deleted(&deleted_text, file);
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].description, "Shop");
+        assert_eq!(
+            deleted[0].date,
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 5).unwrap()
+        );
+        assert_eq!(deleted[0].text, edit.inserted);
+
+        let restored = deleted[0].restore_edit().apply(&deleted_text).unwrap();
+        assert_eq!(restored, JOURNAL);
+    }
+
+    #[test]
+    fn test_delete_needs_header() {
+        let file = Path::new("main.ledger");
+        assert!(entry_edit(file, JOURNAL, 2, comment_out).is_none());
+        assert!(entry_edit(file, JOURNAL, 0, comment_out).is_none());
+        assert!(entry_edit(file, JOURNAL, 100, comment_out).is_none());
+        assert_eq!(
+            entry_edit(file, JOURNAL, 8, comment_out).unwrap().removed,
+            "2025-01-06 Rent\n    expenses:rent  500 USD\n    assets:checking\n"
+        );
+    }
+
+    #[test]
+    fn test_find_deleted_ignores_comments() {
+        let text =
+            "; just a note\n;     indented note\n; 2025-01-01 Old\n;     assets:cash  1 USD\n";
+        let deleted = find_deleted(text, Path::new("main.ledger"));
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].offset, 34);
+        assert_eq!(deleted[0].description, "Old");
+    }
+}
@@ -1,22 +1,654 @@
 #![allow(clippy::wildcard_imports)]
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use gpui::prelude::FluentBuilder as _;
 use gpui::*;
-use gpui_component::{v_flex, TitleBar};
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Root, Selectable as _, Sizable as _, TitleBar,
+};
+
+use crate::{
+    accounts::Account,
+    custom_reports::ReportRegistry,
+    deep_link::{self, DeepLink},
+    i18n::Message,
+    settings::{zoom_in, zoom_out, SavedView, Settings, ThemePreference},
+};
+
+use self::{
+    accounts_tree::{AccountsTreeEvent, AccountsTreeView},
+    budget::BudgetView,
+    components::{AlertsBar, StatusBar},
+    conflict::ConflictView,
+    custom_reports::CustomReportView,
+    dashboard::{DashboardEvent, DashboardView},
+    diagnostics::DiagnosticsView,
+    imports::ImportsView,
+    loans::LoansView,
+    receivables::ReceivablesView,
+    reports::ReportsView,
+    sections::Section,
+    state::{State, StateEvent},
+    welcome::{WelcomeEvent, WelcomeView},
+};
 
 mod accounts_tree;
+mod api;
+mod assertions;
 mod balance_chart;
+mod budget;
+mod changelog;
+mod colors;
 mod components;
+mod conflict;
+mod custom_reports;
+mod dashboard;
+mod diagnostics;
 mod file;
+mod history;
+mod imports;
+mod loans;
+mod new_journal;
+mod paste;
+mod quick_add;
+mod receivables;
+mod reports;
+mod saved_views;
+mod sections;
+mod settings;
+mod start_file;
 mod state;
+mod theme;
 mod transactions_register;
+mod transfers;
+mod trash;
+mod welcome;
+mod what_if;
+
+actions!(
+    ledger_desktop,
+    [
+        Quit,
+        ShowWelcome,
+        QuickAdd,
+        ToggleDiagnostics,
+        ZoomIn,
+        ZoomOut,
+        ResetZoom
+    ]
+);
+
+/// Opens the journal in a new window, from the recent journals in the File menu.
+#[derive(Debug, Clone, PartialEq, Action)]
+#[action(namespace = ledger_desktop, no_json)]
+pub struct OpenJournal {
+    pub path: PathBuf,
+}
+
+pub fn init(cx: &mut App) {
+    settings::init(cx);
+    file::init(cx);
+    cx.set_global(ReportRegistry::builtin());
+
+    cx.bind_keys([
+        KeyBinding::new("secondary-q", Quit, None),
+        KeyBinding::new("secondary-=", ZoomIn, None),
+        KeyBinding::new("secondary-+", ZoomIn, None),
+        KeyBinding::new("secondary--", ZoomOut, None),
+        KeyBinding::new("secondary-0", ResetZoom, None),
+    ]);
+    bind_quick_add(cx);
+    api::init(cx);
+    cx.on_action(|_: &Quit, cx| cx.quit());
+    cx.on_action(|_: &QuickAdd, cx| {
+        let state = journal_window(cx).map(|(_, view)| view.read(cx).state.clone());
+        quick_add::open_window(state, cx);
+    });
+    cx.on_action(|action: &OpenJournal, cx| open_window(Some(action.path.clone()), cx));
+    cx.on_action(|_: &ZoomIn, cx| set_zoom(zoom_in, cx));
+    cx.on_action(|_: &ZoomOut, cx| set_zoom(zoom_out, cx));
+    cx.on_action(|_: &ResetZoom, cx| set_zoom(|_| 100, cx));
+    set_menus(cx);
+    // Recent journals change as journals are opened
+    cx.observe_global::<Settings>(set_menus).detach();
+}
+
+/// Binds the quick-add hotkey from settings, when there is a valid one.
+fn bind_quick_add(cx: &mut App) {
+    let Some(hotkey) = cx.global::<Settings>().quick_add_hotkey.clone() else {
+        return;
+    };
+    // Binding an invalid keystroke panics
+    if let Err(e) = hotkey
+        .split_whitespace()
+        .try_for_each(|key| Keystroke::parse(key).map(drop))
+    {
+        eprintln!("Invalid quick add hotkey {hotkey:?}: {e}");
+        return;
+    }
+    cx.bind_keys([KeyBinding::new(&hotkey, QuickAdd, None)]);
+}
+
+/// Changes the zoom level of every window, remembering it for the next start.
+fn set_zoom(zoom: fn(u16) -> u16, cx: &mut App) {
+    settings::update(cx, |settings| settings.zoom = zoom(settings.zoom))
+        .map_err(|e| eprintln!("Error saving zoom: {e}"))
+        .ok();
+}
+
+/// Menu bar, with the recent journals under File.
+fn set_menus(cx: &mut App) {
+    let language = cx.global::<Settings>().language;
+    let text = |message| language.text(message);
+    let recent = cx
+        .global::<Settings>()
+        .recent_journals
+        .iter()
+        .map(|journal| {
+            MenuItem::action(
+                journal.path.display().to_string(),
+                OpenJournal {
+                    path: journal.path.clone(),
+                },
+            )
+        })
+        .collect();
+    cx.set_menus(vec![
+        Menu {
+            name: "ledger-desktop".into(),
+            items: vec![MenuItem::action(text(Message::Quit), Quit)],
+        },
+        Menu {
+            name: text(Message::File).into(),
+            items: vec![
+                MenuItem::action(text(Message::Welcome), ShowWelcome),
+                MenuItem::action(text(Message::QuickAdd), QuickAdd),
+                MenuItem::submenu(Menu {
+                    name: text(Message::OpenRecent).into(),
+                    items: recent,
+                }),
+            ],
+        },
+        Menu {
+            name: text(Message::View).into(),
+            items: vec![
+                MenuItem::action(text(Message::ZoomIn), ZoomIn),
+                MenuItem::action(text(Message::ZoomOut), ZoomOut),
+                MenuItem::action(text(Message::ActualSize), ResetZoom),
+            ],
+        },
+        Menu {
+            name: text(Message::Debug).into(),
+            items: vec![MenuItem::action(
+                text(Message::Diagnostics),
+                ToggleDiagnostics,
+            )],
+        },
+    ]);
+}
+
+/// Opens a window on the journal, or on the one in settings.
+pub fn open_window(journal: Option<PathBuf>, cx: &mut App) {
+    let title = window_title(journal.as_deref());
+    cx.open_window(
+        WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some(title.into()),
+                appears_transparent: true,
+                ..TitlebarOptions::default()
+            }),
+            ..WindowOptions::default()
+        },
+        |window, cx| {
+            let view = cx.new(|cx| Window::new(journal, window, cx));
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    )
+    .map_err(|e| eprintln!("Error opening window: {e}"))
+    .ok();
+}
+
+/// Registers the app for `ledger-desktop://` links and opens them as they arrive.
+pub fn open_deep_links(links: async_channel::Receiver<Vec<String>>, cx: &mut App) {
+    let register = cx.register_url_scheme(deep_link::SCHEME);
+    cx.spawn(async move |cx| {
+        if let Err(e) = register.await {
+            eprintln!("Error registering {} links: {e}", deep_link::SCHEME);
+        }
+        while let Ok(urls) = links.recv().await {
+            cx.update(|cx| {
+                for url in urls {
+                    open_deep_link(&url, cx);
+                }
+            })
+            .ok();
+        }
+    })
+    .detach();
+}
+
+/// Journal window links and API calls act on: the active one, or else any other.
+fn journal_window(cx: &App) -> Option<(WindowHandle<Root>, Entity<Window>)> {
+    cx.active_window()
+        .into_iter()
+        .chain(cx.windows())
+        .filter_map(|window| window.downcast::<Root>())
+        .find_map(|root| {
+            let view = root
+                .read(cx)
+                .ok()?
+                .view()
+                .clone()
+                .downcast::<Window>()
+                .ok()?;
+            Some((root, view))
+        })
+}
+
+/// Opens the link in a journal window, and a window for it when there is none.
+fn open_deep_link(url: &str, cx: &mut App) {
+    let today = chrono::Local::now().date_naive();
+    let link = match deep_link::parse(url, today) {
+        Ok(link) => link,
+        Err(e) => {
+            eprintln!("Error opening {url}: {e}");
+            return;
+        }
+    };
+    if journal_window(cx).is_none() {
+        open_window(None, cx);
+    }
+    let Some((root, view)) = journal_window(cx) else {
+        return;
+    };
+    root.update(cx, |_root, window, cx| {
+        window.activate_window();
+        view.update(cx, |view, cx| view.open_deep_link(link, window, cx));
+    })
+    .map_err(|e| eprintln!("Error opening {url}: {e}"))
+    .ok();
+    cx.activate(true);
+}
+
+/// Lists the journal under recent journals on the welcome screen and in the File menu.
+fn remember_journal(path: &Path, cx: &mut App) {
+    if let Err(e) = settings::update(cx, |settings| settings.remember_journal(path)) {
+        eprintln!("Error saving settings: {}", e);
+    }
+}
+
+/// The journal's file name when the window was opened on one.
+fn window_title(journal: Option<&Path>) -> String {
+    match journal {
+        Some(journal) => {
+            let name = journal.file_name().unwrap_or(journal.as_os_str());
+            format!("{} — ledger-desktop", name.to_string_lossy())
+        }
+        None => "ledger-desktop".to_string(),
+    }
+}
+
+/// What a file dropped on the window is taken as, by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DroppedFile {
+    /// Opened in a window of its own.
+    Journal,
+    /// Bank statement, handed to the imports section.
+    Statement,
+    /// Entries previewed before they are appended to the journal.
+    Entries,
+}
+
+impl DroppedFile {
+    fn of(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("ledger" | "journal") => DroppedFile::Journal,
+            Some("csv" | "ofx") => DroppedFile::Statement,
+            _ => DroppedFile::Entries,
+        }
+    }
+}
 
 pub struct Window {
-    file: Entity<file::LedgerFile>,
+    state: Entity<State>,
+    /// Journal the window was opened on, instead of the one in settings.
+    journal: Option<PathBuf>,
+    section: Section,
+    /// Kept apart from the other views so sections can open account registers in it.
+    register: Option<Entity<file::LedgerFile>>,
+    /// Kept apart so dropped statements can be handed to it.
+    imports: Option<Entity<ImportsView>>,
+    welcome: Option<Entity<WelcomeView>>,
+    /// True while recent journals are shown instead of a section.
+    show_welcome: bool,
+    /// Views of the other sections, created the first time they are shown.
+    views: HashMap<Section, AnyView>,
+    /// Overlay from the Debug menu, there while it is toggled on.
+    diagnostics: Option<Entity<DiagnosticsView>>,
+    /// Dialog shown while an edit conflicts with a change to the journal file.
+    conflict: Entity<ConflictView>,
+    theme: ThemePreference,
+    /// Zoom level applied to the window, in percent.
+    zoom: u16,
 }
 
 impl Window {
-    pub fn new(window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> Self {
+    pub fn new(
+        journal: Option<PathBuf>,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Self {
+        let theme = cx.global::<Settings>().theme;
+        let zoom = cx.global::<Settings>().zoom;
+        theme::apply(theme, window, cx);
+        theme::apply_zoom(zoom, window, cx);
+
+        cx.observe_window_appearance(window, |this, window, cx| {
+            if this.theme == ThemePreference::System {
+                theme::apply(this.theme, window, cx);
+            }
+        })
+        .detach();
+
+        cx.observe_global_in::<Settings>(window, |this, window, cx| {
+            let theme = cx.global::<Settings>().theme;
+            if this.theme != theme {
+                this.theme = theme;
+                theme::apply(theme, window, cx);
+                cx.notify();
+            }
+            let zoom = cx.global::<Settings>().zoom;
+            if this.zoom != zoom {
+                this.zoom = zoom;
+                theme::apply_zoom(zoom, window, cx);
+            }
+        })
+        .detach();
+
+        let state = cx.new(|cx| State::new(journal.clone(), cx));
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        // The welcome screen offers to locate ledger
+        cx.subscribe(&state, |this, _state, event: &StateEvent, cx| {
+            if let StateEvent::LedgerUnavailable = event {
+                this.show_welcome = true;
+                cx.notify();
+            }
+        })
+        .detach();
+
+        if let Some(opened) = state.read(cx).journal_file() {
+            remember_journal(&opened, cx);
+        }
+        let show_welcome = !state.read(cx).has_journal();
+        let conflict = cx.new(|cx| ConflictView::new(state.clone(), cx));
+
         Self {
-            file: cx.new(|cx| file::LedgerFile::new(window, cx)),
+            state,
+            journal,
+            section: Section::Register,
+            register: None,
+            imports: None,
+            welcome: None,
+            show_welcome,
+            views: HashMap::new(),
+            diagnostics: None,
+            conflict,
+            theme,
+            zoom,
+        }
+    }
+
+    fn register(
+        &mut self,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Entity<file::LedgerFile> {
+        if let Some(register) = &self.register {
+            return register.clone();
+        }
+        let state = self.state.clone();
+        let register = cx.new(|cx| file::LedgerFile::new(state, window, cx));
+        // For the status bar
+        cx.observe(&register, |_this, _register, cx| cx.notify())
+            .detach();
+        self.register = Some(register.clone());
+        register
+    }
+
+    fn welcome(
+        &mut self,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Entity<WelcomeView> {
+        if let Some(welcome) = &self.welcome {
+            return welcome.clone();
+        }
+        let welcome = cx.new(WelcomeView::new);
+        cx.subscribe_in(&welcome, window, |this, _, event, window, cx| match event {
+            WelcomeEvent::Open(path) => this.open_journal(path.clone(), window, cx),
+        })
+        .detach();
+        self.welcome = Some(welcome.clone());
+        welcome
+    }
+
+    /// Reads the journal in this window when it has none, or opens a new window on it.
+    fn open_journal(
+        &mut self,
+        path: PathBuf,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        if self.state.read(cx).has_journal() {
+            open_window(Some(path), cx);
+            return;
+        }
+        remember_journal(&path, cx);
+        window.set_window_title(&window_title(Some(&path)));
+        self.state
+            .update(cx, |state, cx| state.set_journal(path.clone(), cx));
+        self.journal = Some(path);
+        self.show_welcome = false;
+        cx.notify();
+    }
+
+    fn show_welcome(
+        &mut self,
+        _: &ShowWelcome,
+        _: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.show_welcome = true;
+        cx.notify();
+    }
+
+    fn toggle_diagnostics(
+        &mut self,
+        _: &ToggleDiagnostics,
+        _: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.diagnostics = match self.diagnostics {
+            Some(_) => None,
+            None => {
+                let state = self.state.clone();
+                Some(cx.new(|cx| DiagnosticsView::new(state, cx)))
+            }
+        };
+        cx.notify();
+    }
+
+    fn imports(&mut self, cx: &mut gpui::Context<Self>) -> Entity<ImportsView> {
+        self.imports
+            .get_or_insert_with(|| cx.new(|_| ImportsView::new()))
+            .clone()
+    }
+
+    fn section_view(
+        &mut self,
+        section: Section,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> AnyView {
+        if let Some(view) = self.views.get(&section) {
+            return view.clone();
+        }
+        let state = self.state.clone();
+        let view: AnyView = match section {
+            Section::Register => return self.register(window, cx).into(),
+            Section::Dashboard => {
+                let view = cx.new(|cx| DashboardView::new(state, cx));
+                cx.subscribe_in(&view, window, |this, _, event, window, cx| match event {
+                    DashboardEvent::Open(saved) => this.open_register_tab(saved, window, cx),
+                })
+                .detach();
+                view.into()
+            }
+            Section::Balances => {
+                let tree = cx.new(|cx| AccountsTreeView::new(state, cx));
+                cx.subscribe_in(&tree, window, |this, _, event, window, cx| match event {
+                    AccountsTreeEvent::Open(account) => {
+                        this.open_account_register(account, window, cx);
+                    }
+                })
+                .detach();
+                tree.into()
+            }
+            Section::Budget => cx.new(|cx| BudgetView::new(state, cx)).into(),
+            Section::Loans => cx.new(|cx| LoansView::new(state, cx)).into(),
+            Section::Receivables => cx.new(|cx| ReceivablesView::new(state, window, cx)).into(),
+            Section::Reports => cx.new(|cx| ReportsView::new(state, window, cx)).into(),
+            Section::Imports => return self.imports(cx).into(),
+            Section::Report(name) => cx.new(|cx| CustomReportView::new(state, name, cx)).into(),
+        };
+        self.views.insert(section, view.clone());
+        view
+    }
+
+    fn open_account_register(
+        &mut self,
+        account: &Account,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.register(window, cx).update(cx, |register, cx| {
+            register.open_account_register(account, window, cx);
+        });
+        self.section = Section::Register;
+        self.show_welcome = false;
+        cx.notify();
+    }
+
+    fn open_register_tab(
+        &mut self,
+        view: &SavedView,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.register(window, cx).update(cx, |register, cx| {
+            register.open_register_tab(view, window, cx);
+        });
+        self.section = Section::Register;
+        self.show_welcome = false;
+        cx.notify();
+    }
+
+    /// Filters the register like a saved view, from the automation API.
+    fn set_register_filter(
+        &mut self,
+        view: &SavedView,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.register(window, cx).update(cx, |register, cx| {
+            register.apply_view(view, window, cx);
+        });
+        self.section = Section::Register;
+        self.show_welcome = false;
+        cx.notify();
+    }
+
+    fn open_deep_link(
+        &mut self,
+        link: DeepLink,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        match link {
+            DeepLink::Register(view) => self.open_register_tab(&view, window, cx),
+        }
+    }
+
+    fn render_sidebar(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        let language = cx.global::<Settings>().language;
+        let reports = cx
+            .global::<ReportRegistry>()
+            .names()
+            .map(Section::Report)
+            .collect::<Vec<_>>();
+        v_flex()
+            .w(px(120.))
+            .h_full()
+            .gap_1()
+            .p_1()
+            .border_r_1()
+            .border_color(cx.theme().border)
+            .children(Section::ALL.into_iter().chain(reports).map(|section| {
+                Button::new(section.label(language))
+                    .label(section.label(language))
+                    .small()
+                    .ghost()
+                    .selected(!self.show_welcome && self.section == section)
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.section = section;
+                        this.show_welcome = false;
+                        cx.notify();
+                    }))
+            }))
+    }
+
+    /// Opens dropped journals in new windows and hands statements to the imports
+    /// section. Other files are read as journal entries to append.
+    fn drop_files(
+        &mut self,
+        paths: &ExternalPaths,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let mut statements = Vec::new();
+        let mut entries = Vec::new();
+        for path in paths.paths() {
+            match DroppedFile::of(path) {
+                DroppedFile::Journal => open_window(Some(path.clone()), cx),
+                DroppedFile::Statement => statements.push(path.clone()),
+                DroppedFile::Entries => entries.push(path.clone()),
+            }
+        }
+        if !statements.is_empty() {
+            self.imports(cx)
+                .update(cx, |imports, cx| imports.add_statements(&statements, cx));
+            self.section = Section::Imports;
+            self.show_welcome = false;
+        }
+        if !entries.is_empty() {
+            self.register(window, cx).update(cx, |register, cx| {
+                register.preview_entries(&entries, window, cx);
+            });
+            self.section = Section::Register;
+            self.show_welcome = false;
+        }
+        cx.notify();
+    }
+
+    fn cycle_theme(&mut self, cx: &mut gpui::Context<Self>) {
+        let theme = self.theme.next();
+        if let Err(e) = settings::update(cx, |settings| settings.theme = theme) {
+            eprintln!("Error saving settings: {}", e);
         }
     }
 }
@@ -24,12 +656,77 @@ impl Window {
 impl Render for Window {
     fn render(
         &mut self,
-        _window: &mut gpui::Window,
-        _cx: &mut gpui::Context<Self>,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
     ) -> impl IntoElement {
+        let content: AnyView = if self.show_welcome {
+            self.welcome(window, cx).into()
+        } else {
+            self.section_view(self.section, window, cx)
+        };
+        let summary = match (self.section, &self.register) {
+            (Section::Register, Some(register)) => register.read(cx).summary(cx),
+            _ => None,
+        };
+
+        let title = window_title(self.journal.as_deref());
+        let conflict = self
+            .state
+            .read(cx)
+            .conflict
+            .is_some()
+            .then(|| self.conflict.clone());
+
         v_flex()
+            .relative()
             .size_full()
-            .child(TitleBar::new().child(div().text_center().flex_1().child("ledger-desktop")))
-            .child(div().size_full().child(self.file.clone()))
+            .on_action(cx.listener(Self::show_welcome))
+            .on_action(cx.listener(Self::toggle_diagnostics))
+            .on_drop(cx.listener(Self::drop_files))
+            .child(
+                TitleBar::new().child(
+                    h_flex()
+                        .flex_1()
+                        .child(div().text_center().flex_1().child(title))
+                        .child(
+                            Button::new("theme")
+                                .label(self.theme.label())
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.cycle_theme(cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("settings")
+                                .label(cx.global::<Settings>().language.text(Message::Settings))
+                                .xsmall()
+                                .ghost()
+                                .on_click(|_, _window, cx| settings::open_window(cx)),
+                        ),
+                ),
+            )
+            .child(AlertsBar::new(self.state.clone()))
+            .child(
+                h_flex()
+                    .size_full()
+                    .child(self.render_sidebar(cx))
+                    .child(div().flex_1().h_full().child(content)),
+            )
+            .child(StatusBar::new(self.state.clone(), summary))
+            .when_some(self.diagnostics.clone(), |this, diagnostics| {
+                this.child(div().absolute().right_2().bottom_8().child(diagnostics))
+            })
+            .when_some(conflict, |this, conflict| {
+                this.child(
+                    div()
+                        .absolute()
+                        .size_full()
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .child(conflict),
+                )
+            })
     }
 }
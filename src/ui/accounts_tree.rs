@@ -1,50 +1,391 @@
 use std::collections::HashSet;
+use std::time::Instant;
 
+use fastnum::D128;
+
+use gpui::prelude::FluentBuilder;
 #[allow(clippy::wildcard_imports)]
 use gpui::*;
 use gpui_component::{
+    button::{Button, ButtonVariants as _},
     h_flex,
+    input::{Input, InputState},
     list::ListItem,
+    menu::ContextMenuExt,
+    tooltip::Tooltip,
     tree::{tree, TreeItem, TreeState},
-    IconName,
+    v_flex, ActiveTheme, IconName, Selectable as _, Sizable as _,
 };
 
-use crate::accounts::{Account, TreeNode};
+use crate::{
+    account_declarations::{is_declared, AccountDeclaration},
+    account_names::AccountNames,
+    accounts::{Account, AccountFilter, AccountLimit, Balance, LimitKind, TreeNode},
+    amount_format::{Alignment, AmountFormatter},
+    settings::Settings,
+    transactions::CurrencyAmount,
+};
 
 use super::{
-    components::{Checkbox, CheckboxState},
-    state::State,
+    colors::AccountColors,
+    components::{build_menu, Checkbox, CheckboxState, MenuEntry, RefreshBar},
+    settings,
+    state::{State, StateEvent},
 };
 
+pub enum AccountsTreeEvent {
+    /// An account was double-clicked to open its own register.
+    Open(Account),
+}
+
 pub struct AccountsTreeView {
     tree_state: Entity<TreeState>,
     state: Entity<State>,
     selected_accounts: HashSet<Account>,
+    /// Accounts left out of the register, toggled with alt-click.
+    excluded_accounts: HashSet<Account>,
+    /// Folders the user expanded, kept when the items are rebuilt.
+    expanded: HashSet<Account>,
+    limit_editor: Option<LimitEditor>,
+    rename_editor: Option<RenameEditor>,
+    /// Accounts deeper than this are folded into their ancestor.
+    depth: Option<usize>,
+    /// Leave out accounts whose balance is zero.
+    hide_zero: bool,
+}
+
+/// Inline form for setting an account's balance limit.
+struct LimitEditor {
+    account: Account,
+    kind: LimitKind,
+    input: Entity<InputState>,
+    error: Option<String>,
 }
 
+/// Inline form for renaming an account in the journal.
+struct RenameEditor {
+    account: Account,
+    input: Entity<InputState>,
+    error: Option<String>,
+}
+
+impl EventEmitter<AccountsTreeEvent> for AccountsTreeView {}
+
 impl AccountsTreeView {
     pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
         let tree_state = cx.new(|cx| TreeState::new(cx));
 
-        cx.observe(&state, |this, state, cx| {
-            let tree_items = build_items(&state.read(cx).accounts);
-            this.tree_state.update(cx, |tree_state, cx| {
-                tree_state.set_items(tree_items, cx);
-                cx.notify();
-            });
-            cx.notify();
+        // Loaded accounts are shown once the load is done, streamed batches and other
+        // changes to the state only repaint it
+        cx.subscribe(&state, |this, _state, event: &StateEvent, cx| {
+            if let StateEvent::ReloadStarted | StateEvent::AccountsChanged = event {
+                this.rebuild_items(cx);
+            }
         })
         .detach();
+        cx.observe_global::<Settings>(|this, cx| this.rebuild_items(cx))
+            .detach();
 
         Self {
             tree_state,
             state: state.clone(),
             selected_accounts: HashSet::new(),
+            excluded_accounts: HashSet::new(),
+            expanded: HashSet::new(),
+            limit_editor: None,
+            rename_editor: None,
+            depth: None,
+            hide_zero: false,
+        }
+    }
+
+    fn rebuild_items(&mut self, cx: &mut Context<Self>) {
+        let started = Instant::now();
+        let state = self.state.read(cx);
+        let mut accounts = state.accounts.pruned(self.depth, self.hide_zero);
+        accounts.sort(
+            cx.global::<Settings>().account_sort,
+            state.transactions.iter().map(AsRef::as_ref),
+        );
+        let tree_items = build_items(&accounts, &state.account_names(cx), &self.expanded);
+        let took = started.elapsed();
+        self.state
+            .update(cx, |state, _cx| state.record_rebuild("Balances", took));
+        self.tree_state.update(cx, |tree_state, cx| {
+            tree_state.set_items(tree_items, cx);
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    fn open_limit_editor(
+        &mut self,
+        account: Account,
+        kind: LimitKind,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let current = self
+            .state
+            .read(cx)
+            .limits
+            .get(&account)
+            .filter(|limit| limit.kind == kind)
+            .map(|limit| limit.amount.to_string());
+        let input = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("Amount, e.g. 500 USD");
+            if let Some(current) = current {
+                input.set_value(current.trim().to_string(), window, cx);
+            }
+            input
+        });
+        self.limit_editor = Some(LimitEditor {
+            account,
+            kind,
+            input,
+            error: None,
+        });
+        cx.notify();
+    }
+
+    fn save_limit(&mut self, cx: &mut Context<Self>) {
+        let Some(editor) = self.limit_editor.as_mut() else {
+            return;
+        };
+        match CurrencyAmount::parse(&editor.input.read(cx).value()) {
+            Ok(amount) => {
+                let account = editor.account.clone();
+                let limit = AccountLimit {
+                    kind: editor.kind,
+                    amount,
+                };
+                self.limit_editor = None;
+                self.state.update(cx, |state, cx| {
+                    state.set_limit(account, Some(limit), cx);
+                });
+            }
+            Err(e) => editor.error = Some(format!("Invalid amount: {e}")),
+        }
+        cx.notify();
+    }
+
+    fn render_limit_editor(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let editor = self.limit_editor.as_ref()?;
+        let title = match editor.kind {
+            LimitKind::MinimumBalance => "Minimum balance",
+            LimitKind::CreditLimit => "Credit limit",
+        };
+
+        Some(
+            v_flex()
+                .gap_1()
+                .p_2()
+                .border_b_1()
+                .border_color(cx.theme().border)
+                .child(
+                    div()
+                        .text_xs()
+                        .child(format!("{title} for {}", editor.account)),
+                )
+                .child(Input::new(&editor.input).small())
+                .children(
+                    editor
+                        .error
+                        .clone()
+                        .map(|error| div().text_xs().text_color(cx.theme().danger).child(error)),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Button::new("limit-save")
+                                .label("Save")
+                                .xsmall()
+                                .primary()
+                                .on_click(cx.listener(|this, _, _window, cx| this.save_limit(cx))),
+                        )
+                        .child(
+                            Button::new("limit-cancel")
+                                .label("Cancel")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.limit_editor = None;
+                                    cx.notify();
+                                })),
+                        ),
+                ),
+        )
+    }
+
+    fn open_rename_editor(
+        &mut self,
+        account: Account,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let name = account.to_string();
+        let input = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("New account name");
+            input.set_value(name, window, cx);
+            input
+        });
+        self.rename_editor = Some(RenameEditor {
+            account,
+            input,
+            error: None,
+        });
+        cx.notify();
+    }
+
+    fn save_rename(&mut self, cx: &mut Context<Self>) {
+        let Some(editor) = self.rename_editor.as_mut() else {
+            return;
+        };
+        let to = Account::parse(editor.input.read(cx).value().trim());
+        if to.segments.is_empty() {
+            editor.error = Some("Account name can't be empty".into());
+        } else if to == editor.account {
+            self.rename_editor = None;
+        } else {
+            let from = editor.account.clone();
+            self.rename_editor = None;
+            self.state
+                .update(cx, |state, cx| state.rename_account(from, to, cx));
         }
+        cx.notify();
+    }
+
+    fn render_rename_editor(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let editor = self.rename_editor.as_ref()?;
+
+        Some(
+            v_flex()
+                .gap_1()
+                .p_2()
+                .border_b_1()
+                .border_color(cx.theme().border)
+                .child(div().text_xs().child(format!("Rename {}", editor.account)))
+                .child(Input::new(&editor.input).small())
+                .children(
+                    editor
+                        .error
+                        .clone()
+                        .map(|error| div().text_xs().text_color(cx.theme().danger).child(error)),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Button::new("rename-save")
+                                .label("Rename")
+                                .xsmall()
+                                .primary()
+                                .on_click(cx.listener(|this, _, _window, cx| this.save_rename(cx))),
+                        )
+                        .child(
+                            Button::new("rename-cancel")
+                                .label("Cancel")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.rename_editor = None;
+                                    cx.notify();
+                                })),
+                        ),
+                ),
+        )
     }
 
-    pub fn selected_accounts(&self) -> &HashSet<Account> {
-        &self.selected_accounts
+    pub fn set_account_filter(&mut self, filter: AccountFilter, cx: &mut Context<Self>) {
+        self.selected_accounts = filter.include;
+        self.excluded_accounts = filter.exclude;
+        cx.notify();
+    }
+
+    /// Leaves the account, with its subaccounts, out of the register, or brings it back.
+    fn toggle_excluded(&mut self, account: &Account, cx: &mut Context<Self>) {
+        if !self.excluded_accounts.remove(account) {
+            self.excluded_accounts.insert(account.clone());
+        }
+        cx.notify();
+    }
+
+    /// Selects the account with its subaccounts, and nothing else.
+    pub fn select_only(&mut self, account: &Account, cx: &mut Context<Self>) {
+        let accounts = Self::find_node(&self.state.read(cx).accounts, account)
+            .map_or_else(|| vec![account.clone()], Self::collect_all_accounts);
+        self.selected_accounts = accounts.into_iter().collect();
+        cx.notify();
+    }
+
+    /// Collapses the account's folder and every folder below it.
+    fn collapse_subtree(&mut self, account: &Account, cx: &mut Context<Self>) {
+        self.expanded
+            .retain(|expanded| expanded != account && !account.is_parent_of(expanded));
+        self.rebuild_items(cx);
+    }
+
+    /// Actions on a tree node.
+    fn menu_entries(
+        view: &Entity<Self>,
+        account: &Account,
+        has_children: bool,
+        has_limit: bool,
+        excluded: bool,
+    ) -> Vec<MenuEntry> {
+        let action =
+            |label: &'static str, f: fn(&mut Self, Account, &mut Window, &mut Context<Self>)| {
+                let view = view.clone();
+                let account = account.clone();
+                MenuEntry::action(label, move |window, cx| {
+                    view.update(cx, |this, cx| f(this, account.clone(), window, cx));
+                })
+            };
+
+        let mut entries = vec![
+            action("Show only this account", |this, account, _window, cx| {
+                this.select_only(&account, cx);
+            }),
+            action(
+                if excluded {
+                    "Include in register"
+                } else {
+                    "Exclude from register"
+                },
+                |this, account, _window, cx| this.toggle_excluded(&account, cx),
+            ),
+            action("Rename…", Self::open_rename_editor),
+        ];
+        if has_children {
+            entries.push(action("Collapse subtree", |this, account, _window, cx| {
+                this.collapse_subtree(&account, cx);
+            }));
+        }
+        entries.extend([
+            MenuEntry::Separator,
+            action("Set minimum balance…", |this, account, window, cx| {
+                this.open_limit_editor(account, LimitKind::MinimumBalance, window, cx);
+            }),
+            action("Set credit limit…", |this, account, window, cx| {
+                this.open_limit_editor(account, LimitKind::CreditLimit, window, cx);
+            }),
+        ]);
+        if has_limit {
+            entries.push(action("Clear limit", |this, account, _window, cx| {
+                this.state
+                    .update(cx, |state, cx| state.set_limit(account, None, cx));
+            }));
+        }
+        entries
+    }
+
+    /// Accounts the register is filtered by: the selected ones, less the excluded.
+    pub fn account_filter(&self) -> AccountFilter {
+        AccountFilter {
+            include: self.selected_accounts.clone(),
+            exclude: self.excluded_accounts.clone(),
+        }
     }
 
     fn is_selected(&self, account: &Account) -> bool {
@@ -112,6 +453,75 @@ impl AccountsTreeView {
         }
     }
 
+    fn render_options(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let valuation = cx.global::<Settings>().valuation;
+        let sort = cx.global::<Settings>().account_sort;
+        let depth_button = |id: &'static str, label: &'static str, depth: Option<usize>| {
+            Button::new(id)
+                .label(label)
+                .xsmall()
+                .ghost()
+                .selected(self.depth == depth)
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                    this.depth = depth;
+                    this.rebuild_items(cx);
+                }))
+        };
+
+        h_flex()
+            .gap_1()
+            .px_2()
+            .items_center()
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("Depth"),
+            )
+            .child(depth_button("accounts-depth-all", "All", None))
+            .child(depth_button("accounts-depth-1", "1", Some(1)))
+            .child(depth_button("accounts-depth-2", "2", Some(2)))
+            .child(depth_button("accounts-depth-3", "3", Some(3)))
+            .child(div().flex_1())
+            .child(
+                Button::new("accounts-sort")
+                    .label(format!("Sort: {}", sort.label()))
+                    .xsmall()
+                    .ghost()
+                    .on_click(|_, _window, cx| {
+                        if let Err(e) = settings::update(cx, |settings| {
+                            settings.account_sort = settings.account_sort.next();
+                        }) {
+                            eprintln!("Error saving settings: {}", e);
+                        }
+                    }),
+            )
+            .child(
+                Button::new("accounts-valuation")
+                    .label(format!("Value: {}", valuation.label()))
+                    .xsmall()
+                    .ghost()
+                    .on_click(|_, _window, cx| {
+                        if let Err(e) = settings::update(cx, |settings| {
+                            settings.valuation = settings.valuation.next();
+                        }) {
+                            eprintln!("Error saving settings: {}", e);
+                        }
+                    }),
+            )
+            .child(
+                Button::new("accounts-hide-zero")
+                    .label("Hide zero")
+                    .xsmall()
+                    .ghost()
+                    .selected(self.hide_zero)
+                    .on_click(cx.listener(|this, _, _window, cx| {
+                        this.hide_zero = !this.hide_zero;
+                        this.rebuild_items(cx);
+                    })),
+            )
+    }
+
     /// Find a node in the tree by account
     fn find_node<'a>(node: &'a TreeNode, account: &Account) -> Option<&'a TreeNode> {
         if &node.account == account {
@@ -153,15 +563,140 @@ impl AccountsTreeView {
     }
 }
 
-fn build_items(node: &TreeNode) -> Vec<TreeItem> {
+/// Alignment lining up every balance in the tree.
+fn balances_alignment(node: &TreeNode, formatter: &AmountFormatter) -> Alignment {
+    fn collect<'a>(node: &'a TreeNode, amounts: &mut Vec<&'a CurrencyAmount>) {
+        amounts.extend(node.balance.amounts().chain(node.cleared_balance.amounts()));
+        for child in &node.children {
+            collect(child, amounts);
+        }
+    }
+    let mut amounts = Vec::new();
+    collect(node, &mut amounts);
+    formatter.alignment(amounts)
+}
+
+/// Cleared and total balance side by side, right-aligned and muted, with negative
+/// amounts in red. Amounts are in a monospace font and padded to `alignment`, so they
+/// line up on the decimal separator.
+///
+/// The cleared column is what a bank statement should show; it is left blank when it
+/// matches the total. Balances in several commodities are collapsed to how many there
+/// are, with the amounts in a tooltip.
+fn render_balances(
+    account: &Account,
+    cleared: &Balance,
+    total: &Balance,
+    formatter: &AmountFormatter,
+    alignment: Alignment,
+    cx: &App,
+) -> impl IntoElement {
+    let mut commodities = total
+        .amounts()
+        .map(|amount| amount.commodity.clone())
+        .collect::<Vec<_>>();
+    commodities.sort();
+
+    let amount = |balance: &Balance, commodity: &String| {
+        balance
+            .get(commodity)
+            .cloned()
+            .unwrap_or_else(|| CurrencyAmount {
+                value: D128::ZERO,
+                commodity: commodity.clone(),
+            })
+    };
+    let differs = commodities
+        .iter()
+        .any(|commodity| amount(cleared, commodity).value != amount(total, commodity).value);
+    let cell = |amount: Option<CurrencyAmount>| {
+        div()
+            .min_w(px(70.))
+            .text_right()
+            .when_some(amount, |this, amount| {
+                this.when(amount.value.is_negative(), |this| {
+                    this.text_color(cx.theme().danger)
+                })
+                .child(formatter.format_aligned(&amount, alignment))
+            })
+    };
+
+    let row = h_flex()
+        .id(SharedString::from(format!("balance-{account}")))
+        .font_family("monospace")
+        .gap_3()
+        .px_2()
+        .justify_end()
+        .text_xs()
+        .text_color(cx.theme().muted_foreground);
+    match commodities.as_slice() {
+        [] => row,
+        [commodity] => row
+            .child(cell(differs.then(|| amount(cleared, commodity))))
+            .child(cell(Some(amount(total, commodity)))),
+        _ => {
+            let lines = commodities
+                .iter()
+                .map(|commodity| {
+                    let total = formatter.format(&amount(total, commodity));
+                    if differs {
+                        let cleared = formatter.format(&amount(cleared, commodity));
+                        format!("{total} ({cleared} cleared)")
+                    } else {
+                        total
+                    }
+                })
+                .collect::<Vec<_>>();
+            let text = SharedString::from(lines.join("\n"));
+            row.child(format!("{} commodities", commodities.len()))
+                .tooltip(move |window, cx| Tooltip::new(text.clone()).build(window, cx))
+        }
+    }
+}
+
+/// Builds tree items keyed by the canonical account path and labelled with its display name.
+/// Notes and asserts of the account's declaration, or why it is flagged as undeclared.
+fn account_details(
+    declarations: &[AccountDeclaration],
+    account: &Account,
+    declared: bool,
+) -> Option<SharedString> {
+    if !declared {
+        return Some("Not declared with an account directive".into());
+    }
+    let declaration = declarations
+        .iter()
+        .find(|declaration| declaration.account == *account)?;
+    let lines = declaration
+        .notes
+        .iter()
+        .cloned()
+        .chain(
+            declaration
+                .asserts
+                .iter()
+                .map(|assert| format!("assert {assert}")),
+        )
+        .collect::<Vec<_>>();
+    (!lines.is_empty()).then(|| lines.join("\n").into())
+}
+
+fn build_items(
+    node: &TreeNode,
+    names: &AccountNames,
+    expanded: &HashSet<Account>,
+) -> Vec<TreeItem> {
     let mut items = Vec::new();
 
     for child in &node.children {
-        let mut item = TreeItem::new(child.account.to_string(), child.account.name().to_string());
+        let mut item = TreeItem::new(
+            child.account.to_string(),
+            names.short_name(&child.account).to_string(),
+        );
 
         if !child.children.is_empty() {
-            item = item.expanded(false);
-            for sub_child in build_items(child) {
+            item = item.expanded(expanded.contains(&child.account));
+            for sub_child in build_items(child, names, expanded) {
                 item = item.child(sub_child);
             }
         }
@@ -174,24 +709,99 @@ fn build_items(node: &TreeNode) -> Vec<TreeItem> {
 
 impl Render for AccountsTreeView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        tree(&self.tree_state, {
+        let tree = tree(&self.tree_state, {
             let view = cx.entity();
             let state_entity = self.state.clone();
+            let formatter = self.state.read(cx).amount_formatter(cx);
+            let alignment = balances_alignment(&self.state.read(cx).accounts, &formatter);
+            let colors = AccountColors::new(&cx.global::<Settings>().account_colors);
             move |ix, entry, _selected, _window, cx| {
                 view.update(cx, |this, cx| {
                     let item = entry.item();
                     let account = Account::parse(&item.id);
 
                     // Get the tree node to calculate state
-                    let tree_node = &state_entity.read(cx).accounts;
+                    let state = state_entity.read(cx);
+                    let tree_node = &state.accounts;
                     let checkbox_state = this.calculate_state(tree_node, &account);
+                    let over_limit = state.limits.get(&account).is_some_and(|limit| {
+                        Self::find_node(tree_node, &account)
+                            .is_some_and(|node| limit.is_violated_by(&node.balance))
+                    });
+
+                    let excluded = this.excluded_accounts.contains(&account);
+                    let declared = is_declared(&state.declarations, &account);
+                    let details = account_details(&state.declarations, &account, declared);
+
+                    let label = div()
+                        .id(SharedString::from(format!("label-{}", item.id)))
+                        .flex()
+                        .gap_1()
+                        .items_center()
+                        .when(over_limit, |this| this.text_color(cx.theme().danger))
+                        .when(excluded, |this| {
+                            this.line_through().text_color(cx.theme().muted_foreground)
+                        })
+                        .child(
+                            div()
+                                .size(px(8.))
+                                .rounded_full()
+                                .bg(colors.color(&account, cx.theme())),
+                        )
+                        .child(item.label.clone())
+                        .when(!declared, |this| {
+                            this.child(div().text_color(cx.theme().warning).child("⚠"))
+                        })
+                        .when_some(details, |this, details| {
+                            this.tooltip(move |window, cx| {
+                                Tooltip::new(details.clone()).build(window, cx)
+                            })
+                        })
+                        .on_mouse_down(MouseButton::Left, {
+                            let view = view.clone();
+                            let account = account.clone();
+                            move |event, _window, cx| {
+                                if event.modifiers.alt {
+                                    view.update(cx, |this, cx| this.toggle_excluded(&account, cx));
+                                    cx.stop_propagation();
+                                } else if event.click_count == 2 {
+                                    view.update(cx, |_, cx| {
+                                        cx.emit(AccountsTreeEvent::Open(account.clone()));
+                                    });
+                                }
+                            }
+                        })
+                        .context_menu({
+                            let view = view.clone();
+                            let entries = Self::menu_entries(
+                                &view,
+                                &account,
+                                entry.is_folder(),
+                                state.limits.contains_key(&account),
+                                excluded,
+                            );
+                            move |menu, _window, _cx| build_menu(menu, entries.clone())
+                        });
+
+                    let balances = Self::find_node(tree_node, &account).map(|node| {
+                        render_balances(
+                            &account,
+                            &node.cleared_balance,
+                            &node.balance,
+                            &formatter,
+                            alignment,
+                            cx,
+                        )
+                    });
 
                     let with_checkbox = div()
                         .flex()
                         .size_full()
                         .justify_between()
                         .items_center()
-                        .child(item.label.clone())
+                        .child(label)
+                        .child(div().flex_1())
+                        .children(balances)
                         .child(
                             div()
                                 .child({
@@ -219,6 +829,13 @@ impl Render for AccountsTreeView {
                                 ),
                         );
 
+                    // Remembered so rebuilding the items keeps folders open
+                    if entry.is_expanded() {
+                        this.expanded.insert(account.clone());
+                    } else {
+                        this.expanded.remove(&account);
+                    }
+
                     let with_icon = if !entry.is_folder() {
                         h_flex().gap_2().pl(px(24.)).child(with_checkbox)
                     } else if entry.is_expanded() {
@@ -240,6 +857,14 @@ impl Render for AccountsTreeView {
                         .child(with_icon)
                 })
             }
-        })
+        });
+
+        v_flex()
+            .size_full()
+            .child(RefreshBar::new("accounts", self.state.clone()))
+            .child(self.render_options(cx))
+            .children(self.render_limit_editor(cx))
+            .children(self.render_rename_editor(cx))
+            .child(tree)
     }
 }
@@ -0,0 +1,135 @@
+use std::io;
+use std::os::unix::fs::FileTypeExt as _;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+use async_channel::{bounded, Sender};
+use async_io::Async;
+use futures_lite::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use gpui::{App, BackgroundExecutor};
+use serde_json::Value;
+
+use crate::{
+    api::{self, ApiError, Method},
+    settings::Settings,
+};
+
+/// A request handed from a connection to the app, answered on `response_tx` like
+/// commands sent to the ledger actor.
+struct ApiCall {
+    method: Method,
+    response_tx: Sender<Result<Value, ApiError>>,
+}
+
+/// Serves the automation API on the socket in settings, when there is one.
+///
+/// Connections are read on the background executor, and their calls are carried out
+/// one at a time on the app's thread.
+pub fn init(cx: &mut App) {
+    let Some(socket) = cx.global::<Settings>().api_socket.clone() else {
+        return;
+    };
+    let (call_tx, call_rx) = bounded::<ApiCall>(16);
+    let executor = cx.background_executor().clone();
+    cx.background_executor()
+        .spawn(async move {
+            if let Err(e) = listen(&socket, call_tx, &executor).await {
+                eprintln!("Error serving the API on {}: {e}", socket.display());
+            }
+        })
+        .detach();
+    cx.spawn(async move |cx| {
+        while let Ok(call) = call_rx.recv().await {
+            let result = cx
+                .update(|cx| dispatch(call.method, cx))
+                .unwrap_or_else(|e| Err(ApiError::Failed(e.to_string())));
+            call.response_tx.send(result).await.ok();
+        }
+    })
+    .detach();
+}
+
+async fn listen(
+    socket: &Path,
+    calls: Sender<ApiCall>,
+    executor: &BackgroundExecutor,
+) -> io::Result<()> {
+    // Left behind when the app didn't quit cleanly, and binding fails while it's there
+    if std::fs::metadata(socket).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(socket)?;
+    }
+    let listener = Async::<UnixListener>::bind(socket)?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let calls = calls.clone();
+        executor
+            .spawn(async move {
+                if let Err(e) = serve(&stream, &calls).await {
+                    eprintln!("Error answering an API connection: {e}");
+                }
+            })
+            .detach();
+    }
+}
+
+/// Answers the requests of a connection until it is closed.
+async fn serve(stream: &Async<UnixStream>, calls: &Sender<ApiCall>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut writer = stream;
+    let mut line = String::new();
+    while reader.read_line(&mut line).await? > 0 {
+        if !line.trim().is_empty() {
+            if let Some(response) = answer(&line, calls).await {
+                writer.write_all(format!("{response}\n").as_bytes()).await?;
+            }
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+/// Response to a request line, none for a notification.
+async fn answer(line: &str, calls: &Sender<ApiCall>) -> Option<String> {
+    let today = chrono::Local::now().date_naive();
+    let request = match api::parse_request(line, today) {
+        Ok(request) => request,
+        Err((id, error)) => return Some(api::response(&id, Err(error))),
+    };
+    let (response_tx, response_rx) = bounded(1);
+    let call = ApiCall {
+        method: request.method,
+        response_tx,
+    };
+    let result = match calls.send(call).await {
+        Ok(()) => response_rx.recv().await.ok(),
+        Err(_) => None,
+    }
+    .unwrap_or_else(|| Err(ApiError::Failed("the app is quitting".into())));
+    Some(api::response(&request.id?, result))
+}
+
+/// Carries out the call in a journal window. Writes and reloads run in the background,
+/// and show their errors in the window like the ones started from it.
+fn dispatch(method: Method, cx: &mut App) -> Result<Value, ApiError> {
+    let (root, view) = super::journal_window(cx)
+        .ok_or_else(|| ApiError::Failed("no journal window is open".into()))?;
+    let state = view.read(cx).state.clone();
+    match method {
+        Method::Reload => state.update(cx, |state, cx| state.refresh(false, cx)),
+        Method::AddTransaction(transaction) => {
+            let label = format!("Add {}", transaction.description);
+            state.update(cx, |state, cx| {
+                state.append_transaction(&transaction, label, cx);
+            });
+        }
+        Method::Balances { account } => {
+            return api::balances(&state.read(cx).accounts, account.as_ref());
+        }
+        Method::SetFilter(filter) => root
+            .update(cx, |_root, window, cx| {
+                view.update(cx, |view, cx| view.set_register_filter(&filter, window, cx));
+            })
+            .map_err(|e| ApiError::Failed(e.to_string()))?,
+    }
+    Ok(Value::Null)
+}
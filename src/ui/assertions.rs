@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputState},
+    v_flex, ActiveTheme, Sizable as _,
+};
+
+use crate::{
+    account_names::AccountNames,
+    amount_format::AmountFormatter,
+    assertions::{AssertionSource, BalanceAssertion, Discrepancy},
+    transactions::{CurrencyAmount, Transaction},
+};
+
+use super::{
+    components::{AccountPicker, AccountPickerEvent},
+    state::State,
+};
+
+/// Checks balance assertions against the journal and lists the ones that fail.
+///
+/// Assertions come from the journal and from a form for entering the expected balance
+/// of an account on a date, like the one on a bank statement.
+pub struct AssertionsView {
+    state: Entity<State>,
+    account: Entity<AccountPicker>,
+    date: Entity<InputState>,
+    expected: Entity<InputState>,
+    error: Option<String>,
+}
+
+impl AssertionsView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let account = cx.new(|cx| AccountPicker::new(state.clone(), "Account", window, cx));
+        let date = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("As of date");
+            input.set_value(
+                chrono::Local::now()
+                    .date_naive()
+                    .format("%Y-%m-%d")
+                    .to_string(),
+                window,
+                cx,
+            );
+            input
+        });
+        let expected = cx.new(|cx| InputState::new(window, cx).placeholder("Expected balance"));
+
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        cx.subscribe_in(
+            &account,
+            window,
+            |this, _, _: &AccountPickerEvent, window, cx| {
+                let expected = this.expected.read(cx).focus_handle(cx);
+                window.focus(&expected);
+            },
+        )
+        .detach();
+
+        Self {
+            state,
+            account,
+            date,
+            expected,
+            error: None,
+        }
+    }
+
+    fn parse_assertion(&self, cx: &App) -> Result<BalanceAssertion, String> {
+        let account = self
+            .account
+            .read(cx)
+            .account(cx)
+            .ok_or("Account is required")?;
+        let date = self.date.read(cx).value();
+        let date = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date: {e}"))?;
+        let expected = CurrencyAmount::parse(&self.expected.read(cx).value())
+            .map_err(|e| format!("Invalid amount: {e}"))?;
+        Ok(BalanceAssertion {
+            account,
+            date,
+            expected,
+            source: AssertionSource::User,
+        })
+    }
+
+    fn add(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match self.parse_assertion(cx) {
+            Ok(assertion) => {
+                self.error = None;
+                self.state
+                    .update(cx, |state, cx| state.add_assertion(assertion, cx));
+                self.expected
+                    .update(cx, |input, cx| input.set_value("", window, cx));
+            }
+            Err(e) => self.error = Some(e),
+        }
+        cx.notify();
+    }
+
+    fn render_user_assertions(
+        &self,
+        names: &AccountNames,
+        formatter: &AmountFormatter,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let assertions = &self.state.read(cx).user_assertions;
+        v_flex().gap_1().children(
+            assertions
+                .iter()
+                .enumerate()
+                .map(|(ix, assertion)| {
+                    h_flex()
+                        .gap_2()
+                        .text_xs()
+                        .child(format!(
+                            "{} {} = {}",
+                            assertion.date.format("%Y-%m-%d"),
+                            names.full_name(&assertion.account),
+                            formatter.format(&assertion.expected)
+                        ))
+                        .child(
+                            Button::new(("assertion-remove", ix))
+                                .label("×")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.state
+                                        .update(cx, |state, cx| state.remove_assertion(ix, cx));
+                                })),
+                        )
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn render_discrepancy(
+        discrepancy: &Discrepancy,
+        transactions: &[Arc<Transaction>],
+        names: &AccountNames,
+        formatter: &AmountFormatter,
+        cx: &App,
+    ) -> impl IntoElement {
+        let assertion = &discrepancy.assertion;
+        let commodity = assertion.expected.commodity.clone();
+        let amount = |value| {
+            formatter.format(&CurrencyAmount {
+                value,
+                commodity: commodity.clone(),
+            })
+        };
+        let source = match &assertion.source {
+            AssertionSource::Journal { file, line } => format!("{}:{line}", file.display()),
+            AssertionSource::User => "entered".to_string(),
+        };
+        let diverging = discrepancy
+            .diverging
+            .and_then(|ix| transactions.get(ix))
+            .map(|transaction| {
+                let postings = transaction
+                    .postings
+                    .iter()
+                    .filter(|posting| posting.account == assertion.account)
+                    .map(|posting| formatter.format_amount(&posting.amount))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "{} {} ({postings}) — {}:{}",
+                    transaction.time.format("%Y-%m-%d"),
+                    transaction.description,
+                    transaction.file.display(),
+                    transaction.line
+                )
+            });
+
+        v_flex()
+            .gap_1()
+            .p_2()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_md()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .text_sm()
+                    .child(names.full_name(&assertion.account))
+                    .child(assertion.date.format("%Y-%m-%d").to_string()),
+            )
+            .child(div().text_xs().child(format!(
+                "Expected {}, actual {}, off by {}",
+                amount(assertion.expected.value),
+                amount(discrepancy.actual),
+                amount(discrepancy.difference())
+            )))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(source),
+            )
+            .children(diverging.map(|diverging| {
+                div()
+                    .text_xs()
+                    .px_1()
+                    .rounded_sm()
+                    .bg(cx.theme().warning.opacity(0.2))
+                    .child(format!("First diverging: {diverging}"))
+            }))
+    }
+}
+
+impl Render for AssertionsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let names = self.state.read(cx).account_names(cx);
+        let formatter = self.state.read(cx).amount_formatter(cx);
+        let user_assertions = self.render_user_assertions(&names, &formatter, cx);
+
+        let state = self.state.read(cx);
+        let total = state.journal_assertions.len() + state.user_assertions.len();
+        let discrepancies = state.check_assertions();
+        let summary = if discrepancies.is_empty() {
+            format!("All {total} assertions hold")
+        } else {
+            format!("{} of {total} assertions fail", discrepancies.len())
+        };
+        let cards = discrepancies
+            .iter()
+            .map(|discrepancy| {
+                Self::render_discrepancy(discrepancy, &state.transactions, &names, &formatter, cx)
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("assertions")
+            .size_full()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .child(div().text_sm().child("Balance checks"))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().flex_1().child(self.account.clone()))
+                    .child(div().w(px(110.)).child(Input::new(&self.date)))
+                    .child(div().w(px(140.)).child(Input::new(&self.expected)))
+                    .child(
+                        Button::new("assertion-add")
+                            .label("Add")
+                            .on_click(cx.listener(|this, _, window, cx| this.add(window, cx))),
+                    ),
+            )
+            .children(
+                self.error
+                    .clone()
+                    .map(|error| div().text_xs().text_color(cx.theme().danger).child(error)),
+            )
+            .child(user_assertions)
+            .child(
+                div()
+                    .text_xs()
+                    .when(!discrepancies.is_empty(), |this| {
+                        this.text_color(cx.theme().danger)
+                    })
+                    .child(summary),
+            )
+            .children(cards)
+    }
+}
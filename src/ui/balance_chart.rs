@@ -3,7 +3,14 @@
 //! This module provides an interactive chart that displays balance data for multiple
 //! commodities across time. Features include:
 //! - Multi-commodity line chart with color-coded lines
+//! - One line per account instead, converted to a single commodity
+//! - Monthly expenses stacked by category
+//! - Step, filled area and per-period rendering options
+//! - Optional inflation adjustment into today's money
+//! - Weekly or monthly aggregation for long date ranges
 //! - Interactive hover tooltips showing exact values
+//! - Click to pin the tooltip and copy its values to the clipboard
+//! - Hover events and a date marker for linking the chart with other views
 //! - Automatic scaling and grid lines
 //! - X and Y axis labels with smart tick spacing
 
@@ -11,26 +18,40 @@ use chrono::Datelike;
 use gpui::prelude::FluentBuilder;
 #[allow(clippy::wildcard_imports)]
 use gpui::*;
+use gpui_component::button::{Button, ButtonVariants as _};
 use gpui_component::plot::{
     scale::{Scale, ScaleLinear, ScalePoint},
-    shape::Line,
+    shape::{Area, Line},
     AxisText, Grid, IntoPlot, Plot, PlotAxis, StrokeStyle, AXIS_GAP,
 };
-use gpui_component::{h_flex, v_flex, ActiveTheme, PixelsExt, StyledExt};
+use gpui_component::{
+    h_flex, v_flex, ActiveTheme, PixelsExt, Selectable as _, Sizable as _, StyledExt,
+};
 use std::cell::Cell;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+use crate::accounts::Account;
+use crate::amount_format::AmountFormatter;
+use crate::inflation::CpiTable;
+use crate::settings::Settings;
+
+use super::colors::{AccountColors, SeriesColors};
+
 // Constants for chart layout
 /// Padding around the plot area in pixels
 const PLOT_PADDING: f32 = 10.0;
-/// Number of available chart colors for commodity lines
-const CHART_COLORS_COUNT: usize = 5;
 /// Minimum number of data points before skipping ticks on X-axis
 const MIN_TICK_SPACING: usize = 10;
 /// Number of horizontal grid lines to draw
 const GRID_LINE_COUNT: usize = 4;
 /// Number of Y-axis value labels to display
 const Y_AXIS_LABEL_COUNT: usize = 5;
+/// Longest range in days that is plotted with one point per day
+const DAILY_MAX_DAYS: i64 = 180;
+/// Longest range in days that is plotted with one point per week
+const WEEKLY_MAX_DAYS: i64 = 3 * 365;
 
 /// A single data point in the chart representing balances at a specific date.
 #[derive(Clone)]
@@ -41,6 +62,112 @@ pub struct DataPoint {
     pub balances: Vec<(String, f64)>,
 }
 
+/// How balances are drawn on the chart.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ChartStyle {
+    /// Draw piecewise-constant steps instead of straight lines between points
+    pub step: bool,
+    /// Fill the area between each line and zero
+    pub area: bool,
+    /// Show running balances instead of per-period changes
+    pub cumulative: bool,
+    /// Express historical amounts in today's money
+    pub inflation_adjusted: bool,
+}
+
+impl Default for ChartStyle {
+    fn default() -> Self {
+        Self {
+            step: false,
+            area: false,
+            cumulative: true,
+            inflation_adjusted: false,
+        }
+    }
+}
+
+/// What each line of the chart is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SeriesBy {
+    /// The balance in one commodity.
+    #[default]
+    Commodity,
+    /// The balance of one account, in a single commodity.
+    Account,
+    /// Monthly totals of expense categories, stacked on each other.
+    ExpenseCategory,
+}
+
+impl SeriesBy {
+    fn next(self) -> Self {
+        match self {
+            SeriesBy::Commodity => SeriesBy::Account,
+            SeriesBy::Account => SeriesBy::ExpenseCategory,
+            SeriesBy::ExpenseCategory => SeriesBy::Commodity,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SeriesBy::Commodity => "By commodity",
+            SeriesBy::Account => "By account",
+            SeriesBy::ExpenseCategory => "Expenses by category",
+        }
+    }
+}
+
+/// Size of the period each plotted point covers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Granularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// Picks the finest granularity that keeps the number of points manageable.
+    fn for_range(data: &[DataPoint]) -> Self {
+        let (Some(first), Some(last)) = (data.first(), data.last()) else {
+            return Granularity::Day;
+        };
+        let days = (last.date - first.date).num_days();
+        if days <= DAILY_MAX_DAYS {
+            Granularity::Day
+        } else if days <= WEEKLY_MAX_DAYS {
+            Granularity::Week
+        } else {
+            Granularity::Month
+        }
+    }
+
+    /// First day of the period containing the date.
+    fn period_start(self, date: chrono::NaiveDate) -> chrono::NaiveDate {
+        match self {
+            Granularity::Day => date,
+            Granularity::Week => {
+                date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            Granularity::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    fn axis_format(self) -> &'static str {
+        match self {
+            Granularity::Day => "%m-%d",
+            Granularity::Week => "W%V %y",
+            Granularity::Month => "%b %Y",
+        }
+    }
+
+    fn tooltip_format(self) -> &'static str {
+        match self {
+            Granularity::Day => "%B %d, %Y",
+            Granularity::Week => "Week of %B %d, %Y",
+            Granularity::Month => "%B %Y",
+        }
+    }
+}
+
 /// Inner plot structure that implements the Plot trait for custom rendering.
 ///
 /// This struct is wrapped by BalanceChart and handles the actual drawing
@@ -49,14 +176,37 @@ pub struct DataPoint {
 struct PlotInner {
     /// Time series data points to plot
     data: Vec<DataPoint>,
-    /// List of commodity names in the order they appear in each DataPoint
+    /// List of series names in the order they appear in each DataPoint: commodities,
+    /// or full account paths when `unit` is set
     commodities: Vec<String>,
+    /// Commodity of every series when the series are accounts
+    unit: Option<String>,
+    /// Whether series are drawn stacked on each other
+    stacked: bool,
+    /// Rendering options for the lines
+    style: ChartStyle,
+    /// Period covered by each data point
+    granularity: Granularity,
+    /// Color assignment for commodity lines
+    colors: SeriesColors,
+    /// Colors of account lines
+    account_colors: AccountColors,
+    /// Formats balances in the tooltip
+    formatter: AmountFormatter,
     /// Shared bounds reference that persists across clones.
     /// Updated during paint and read by parent for hover detection.
     /// Uses Rc<Cell<>> for interior mutability.
     cached_bounds: Rc<Cell<Option<Bounds<Pixels>>>>,
 }
 
+pub enum ChartEvent {
+    /// The hovered data point changed. Its dates run from the first date up to, but
+    /// not including, the second.
+    Hover(Option<(chrono::NaiveDate, chrono::NaiveDate)>),
+    /// The user switched what the lines are; the data has to be set again.
+    SeriesBy(SeriesBy),
+}
+
 /// Interactive balance chart component with hover tooltips.
 ///
 /// Displays multi-commodity balance data over time with:
@@ -66,23 +216,88 @@ struct PlotInner {
 pub struct BalanceChart {
     /// The inner plot component that renders the chart
     plot_inner: PlotInner,
+    /// Running balances as last passed to `set_data`
+    source: Vec<DataPoint>,
+    /// Price index used for inflation adjustment
+    cpi: CpiTable,
+    /// CPI file the table was loaded from, if not built in
+    cpi_file: Option<PathBuf>,
     /// Index of the currently hovered data point, if any
     hovered_index: Option<usize>,
     /// Mouse position for tooltip placement
     mouse_position: Option<Point<Pixels>>,
+    /// Whether the tooltip stays in place instead of following the mouse
+    pinned: bool,
+    /// Date another view points at, marked while nothing is hovered
+    marker: Option<chrono::NaiveDate>,
+    /// What the owner of the chart should plot
+    series_by: SeriesBy,
 }
 
 impl BalanceChart {
-    /// Creates a new empty balance chart.
-    pub fn new() -> Self {
+    /// Creates a new empty balance chart that follows the color and CPI settings.
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<Settings>(|this, cx| {
+            this.apply_settings(cx);
+            cx.notify();
+        })
+        .detach();
+
+        let settings = cx.global::<Settings>();
         Self {
             plot_inner: PlotInner {
                 data: vec![],
                 commodities: vec![],
+                unit: None,
+                stacked: false,
+                style: ChartStyle::default(),
+                granularity: Granularity::Day,
+                colors: SeriesColors::new(&settings.series_colors),
+                account_colors: AccountColors::new(&settings.account_colors),
+                formatter: AmountFormatter::default(),
                 cached_bounds: Rc::new(Cell::new(None)),
             },
+            source: vec![],
+            cpi: load_cpi_table(settings.cpi_file.as_deref()),
+            cpi_file: settings.cpi_file.clone(),
             hovered_index: None,
             mouse_position: None,
+            pinned: false,
+            marker: None,
+            series_by: SeriesBy::default(),
+        }
+    }
+
+    pub fn series_by(&self) -> SeriesBy {
+        self.series_by
+    }
+
+    /// Marks the data point covering `date`, like a hovered register row.
+    pub fn set_marker(&mut self, date: Option<chrono::NaiveDate>, cx: &mut Context<Self>) {
+        if self.marker != date {
+            self.marker = date;
+            cx.notify();
+        }
+    }
+
+    /// Dates covered by the data point, up to the next one.
+    fn point_range(&self, index: usize) -> Option<(chrono::NaiveDate, chrono::NaiveDate)> {
+        let start = self.plot_inner.data.get(index)?.date;
+        let end = self
+            .plot_inner
+            .data
+            .get(index + 1)
+            .map_or(chrono::NaiveDate::MAX, |next| next.date);
+        Some((start, end))
+    }
+
+    fn set_hovered_index(&mut self, index: Option<usize>, cx: &mut Context<Self>) {
+        if index != self.hovered_index {
+            self.hovered_index = index;
+            cx.emit(ChartEvent::Hover(
+                index.and_then(|index| self.point_range(index)),
+            ));
+            cx.notify();
         }
     }
 
@@ -91,13 +306,192 @@ impl BalanceChart {
     /// # Arguments
     /// * `data` - Vector of data points containing dates and balance values
     /// * `commodities` - List of commodity names in the order they appear in data points
-    ///
-    /// # Note
-    /// Currently filters data to only show year 2025 for focused analysis.
-    pub fn set_data(&mut self, data: Vec<DataPoint>, commodities: Vec<String>) {
-        // Filter for year 2025
-        self.plot_inner.data = data.into_iter().filter(|d| d.date.year() == 2025).collect();
+    /// * `formatter` - How balances are written in the tooltip
+    pub fn set_data(
+        &mut self,
+        data: Vec<DataPoint>,
+        commodities: Vec<String>,
+        formatter: AmountFormatter,
+    ) {
+        self.source = data;
         self.plot_inner.commodities = commodities;
+        self.plot_inner.unit = None;
+        self.plot_inner.formatter = formatter;
+        self.refresh_plot();
+    }
+
+    /// Updates the chart with one series per account, all in the `unit` commodity.
+    ///
+    /// Accounts are full account paths, in the order they appear in data points.
+    pub fn set_account_data(
+        &mut self,
+        data: Vec<DataPoint>,
+        accounts: Vec<String>,
+        unit: String,
+        formatter: AmountFormatter,
+    ) {
+        self.source = data;
+        self.plot_inner.commodities = accounts;
+        self.plot_inner.unit = Some(unit);
+        self.plot_inner.formatter = formatter;
+        self.refresh_plot();
+    }
+
+    /// Updates how the chart is drawn.
+    pub fn set_style(&mut self, style: ChartStyle, cx: &mut Context<Self>) {
+        self.plot_inner.style = style;
+        self.refresh_plot();
+        cx.notify();
+    }
+
+    fn apply_settings(&mut self, cx: &mut Context<Self>) {
+        let settings = cx.global::<Settings>();
+        self.plot_inner.colors = SeriesColors::new(&settings.series_colors);
+        self.plot_inner.account_colors = AccountColors::new(&settings.account_colors);
+        if self.cpi_file != settings.cpi_file {
+            self.cpi = load_cpi_table(settings.cpi_file.as_deref());
+            self.cpi_file = settings.cpi_file.clone();
+            self.refresh_plot();
+        }
+    }
+
+    /// Recomputes plotted points from the source data and current style.
+    fn refresh_plot(&mut self) {
+        let stacked = self.series_by == SeriesBy::ExpenseCategory;
+        let (granularity, mut data) = if stacked {
+            // Already totals by month
+            (Granularity::Month, self.source.clone())
+        } else {
+            let granularity = Granularity::for_range(&self.source);
+            let sampled = downsample(&self.source, granularity);
+            if self.plot_inner.style.cumulative {
+                (granularity, sampled)
+            } else {
+                (granularity, per_period(&sampled))
+            }
+        };
+        if self.plot_inner.style.inflation_adjusted {
+            for point in &mut data {
+                for (_, balance) in &mut point.balances {
+                    *balance = self.cpi.to_today(*balance, point.date);
+                }
+            }
+        }
+        self.plot_inner.data = data;
+        self.plot_inner.granularity = granularity;
+        self.plot_inner.stacked = stacked;
+        self.hovered_index = None;
+        self.pinned = false;
+    }
+
+    /// Puts the date and balances of the hovered data point on the clipboard.
+    fn copy_values(&self, cx: &mut Context<Self>) {
+        let Some(data_point) = self
+            .hovered_index
+            .and_then(|idx| self.plot_inner.data.get(idx))
+        else {
+            return;
+        };
+        let mut text = data_point.date.format("%Y-%m-%d").to_string();
+        for (series, balance) in &data_point.balances {
+            let formatter = &self.plot_inner.formatter;
+            let commodity = self.plot_inner.unit.as_deref().unwrap_or(series);
+            let decimals = formatter.places(commodity);
+            let name = if self.plot_inner.unit.is_some() {
+                series
+            } else {
+                formatter.commodity_name(commodity)
+            };
+            let _ = write!(text, "\n{name}\t{balance:.decimals$}");
+        }
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let style = self.plot_inner.style;
+        let series_by = self.series_by;
+
+        h_flex()
+            .gap_1()
+            .px_2()
+            .child(
+                Button::new("chart-series-by")
+                    .label(series_by.label())
+                    .small()
+                    .ghost()
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.series_by = series_by.next();
+                        cx.emit(ChartEvent::SeriesBy(this.series_by));
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Button::new("chart-step")
+                    .label("Step")
+                    .small()
+                    .ghost()
+                    .selected(style.step)
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.set_style(
+                            ChartStyle {
+                                step: !style.step,
+                                ..style
+                            },
+                            cx,
+                        );
+                    })),
+            )
+            .child(
+                Button::new("chart-area")
+                    .label("Area")
+                    .small()
+                    .ghost()
+                    .selected(style.area)
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.set_style(
+                            ChartStyle {
+                                area: !style.area,
+                                ..style
+                            },
+                            cx,
+                        );
+                    })),
+            )
+            .child(
+                Button::new("chart-cumulative")
+                    .label(if style.cumulative {
+                        "Cumulative"
+                    } else {
+                        "Per period"
+                    })
+                    .small()
+                    .ghost()
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.set_style(
+                            ChartStyle {
+                                cumulative: !style.cumulative,
+                                ..style
+                            },
+                            cx,
+                        );
+                    })),
+            )
+            .child(
+                Button::new("chart-inflation")
+                    .label("Today's money")
+                    .small()
+                    .ghost()
+                    .selected(style.inflation_adjusted)
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.set_style(
+                            ChartStyle {
+                                inflation_adjusted: !style.inflation_adjusted,
+                                ..style
+                            },
+                            cx,
+                        );
+                    })),
+            )
     }
 
     /// Find the nearest data point to the given mouse position using proper scale calculations
@@ -144,13 +538,26 @@ impl Render for BalanceChart {
         let hovered_index = self.hovered_index;
         let cached_bounds = self.plot_inner.cached_bounds.clone();
         let mouse_position = self.mouse_position;
+        let pinned = self.pinned;
+        // Last data point on or before the marked date
+        let marker_index = self.marker.and_then(|marker| {
+            self.plot_inner
+                .data
+                .iter()
+                .rposition(|data_point| data_point.date <= marker)
+        });
 
-        div()
+        let chart = div()
             .id("balance_chart")
             .size_full()
             .relative()
             .on_mouse_move(
                 cx.listener(move |this, event: &MouseMoveEvent, _window, cx| {
+                    // A pinned tooltip ignores mouse movement
+                    if this.pinned {
+                        return;
+                    }
+
                     // Store mouse position
                     this.mouse_position = Some(event.position);
 
@@ -161,13 +568,14 @@ impl Render for BalanceChart {
                             this.find_nearest_data_point(event.position.x.as_f32(), &bounds);
 
                         // Only notify if the index actually changed to avoid unnecessary re-renders
-                        if new_index != this.hovered_index {
-                            this.hovered_index = new_index;
-                            cx.notify();
-                        }
+                        this.set_hovered_index(new_index, cx);
                     }
                 }),
             )
+            .on_click(cx.listener(|this, _, _window, cx| {
+                this.pinned = !this.pinned && this.hovered_index.is_some();
+                cx.notify();
+            }))
             .child(plot_inner.clone())
             .when_some(hovered_index, |this, idx| {
                 // Only render hover elements if index is valid
@@ -177,22 +585,147 @@ impl Render for BalanceChart {
                         idx,
                         cached_bounds.get(),
                         mouse_position,
+                        pinned,
                         cx,
                     ))
                 } else {
                     this
                 }
             })
+            .when(hovered_index.is_none(), |this| {
+                this.children(
+                    marker_index
+                        .map(|idx| Self::render_marker(&plot_inner, idx, cached_bounds.get(), cx)),
+                )
+            });
+
+        v_flex()
+            .size_full()
+            .child(self.render_toolbar(cx))
+            .child(chart)
+    }
+}
+
+/// Loads the CPI table from a CSV file, falling back to the built-in series.
+fn load_cpi_table(path: Option<&Path>) -> CpiTable {
+    let Some(path) = path else {
+        return CpiTable::builtin();
+    };
+    match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| CpiTable::parse_csv(&text).map_err(|e| e.to_string()))
+    {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Error loading CPI table from {}: {}", path.display(), e);
+            CpiTable::builtin()
+        }
+    }
+}
+
+/// Keeps the last running balance of each period, dated at the start of the period.
+fn downsample(data: &[DataPoint], granularity: Granularity) -> Vec<DataPoint> {
+    if granularity == Granularity::Day {
+        return data.to_vec();
+    }
+    let mut sampled: Vec<DataPoint> = Vec::new();
+    for point in data {
+        let date = granularity.period_start(point.date);
+        let balances = point.balances.clone();
+        match sampled.last_mut() {
+            Some(last) if last.date == date => last.balances = balances,
+            _ => sampled.push(DataPoint { date, balances }),
+        }
     }
+    sampled
 }
 
+/// Adds each series to the ones before it, so each is drawn on top of the previous.
+fn stack(data: &[DataPoint]) -> Vec<DataPoint> {
+    data.iter()
+        .map(|point| {
+            let mut total = 0.0;
+            let balances = point
+                .balances
+                .iter()
+                .map(|(name, value)| {
+                    total += value;
+                    (name.clone(), total)
+                })
+                .collect();
+            DataPoint {
+                date: point.date,
+                balances,
+            }
+        })
+        .collect()
+}
+
+/// Converts running balances into the change of each balance since the previous point.
+fn per_period(data: &[DataPoint]) -> Vec<DataPoint> {
+    let mut previous: Option<&DataPoint> = None;
+    data.iter()
+        .map(|point| {
+            let balances = point
+                .balances
+                .iter()
+                .enumerate()
+                .map(|(idx, (commodity, balance))| {
+                    let before = previous
+                        .and_then(|p| p.balances.get(idx))
+                        .map_or(0.0, |(_, v)| *v);
+                    (commodity.clone(), balance - before)
+                })
+                .collect();
+            previous = Some(point);
+            DataPoint {
+                date: point.date,
+                balances,
+            }
+        })
+        .collect()
+}
+
+impl EventEmitter<ChartEvent> for BalanceChart {}
+
 impl BalanceChart {
+    /// Renders the vertical line marking a data point for another view.
+    fn render_marker(
+        plot_inner: &PlotInner,
+        index: usize,
+        cached_bounds: Option<Bounds<Pixels>>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let (Some(bounds), Some(data_point)) = (cached_bounds, plot_inner.data.get(index)) else {
+            return div();
+        };
+        let chart_width = bounds.size.width.as_f32();
+        let chart_height = bounds.size.height.as_f32();
+        let x_scale = ScalePoint::new(
+            plot_inner.data.iter().map(|d| d.date.to_string()).collect(),
+            vec![PLOT_PADDING, chart_width - PLOT_PADDING],
+        );
+        let x = x_scale
+            .tick(&data_point.date.to_string())
+            .unwrap_or(PLOT_PADDING);
+
+        div()
+            .absolute()
+            .left(px(x - 1.0))
+            .top(px(PLOT_PADDING))
+            .w(px(2.0))
+            .h(px(chart_height - AXIS_GAP - PLOT_PADDING))
+            .bg(cx.theme().primary)
+            .opacity(0.6)
+    }
+
     /// Renders hover elements including vertical line, markers, and tooltip
     fn render_hover_elements(
         plot_inner: &PlotInner,
         hovered_index: usize,
         cached_bounds: Option<Bounds<Pixels>>,
         mouse_position: Option<Point<Pixels>>,
+        pinned: bool,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
         // Bounds check
@@ -202,15 +735,9 @@ impl BalanceChart {
 
         let bounds = cached_bounds.unwrap();
         let data_point = &plot_inner.data[hovered_index];
+        let plotted = plot_inner.plotted();
         let theme = cx.theme();
-
-        let colors = vec![
-            theme.chart_1,
-            theme.chart_2,
-            theme.chart_3,
-            theme.chart_4,
-            theme.chart_5,
-        ];
+        let colors = plot_inner.series_colors(theme);
 
         // Calculate scales using chart-relative coordinates
         let chart_width = bounds.size.width.as_f32();
@@ -221,8 +748,7 @@ impl BalanceChart {
             vec![PLOT_PADDING, chart_width - PLOT_PADDING],
         );
 
-        let all_values: Vec<f64> = plot_inner
-            .data
+        let all_values: Vec<f64> = plotted
             .iter()
             .flat_map(|d| d.balances.iter().map(|(_, v)| *v))
             .chain(std::iter::once(0.0))
@@ -271,13 +797,13 @@ impl BalanceChart {
             )
             .children(
                 // Circle markers on each line (at data point position)
-                data_point
+                plotted[hovered_index]
                     .balances
                     .iter()
                     .enumerate()
                     .filter_map(|(idx, (_, balance))| {
                         y_scale.tick(balance).map(|y_pos| {
-                            let color = colors[idx % CHART_COLORS_COUNT];
+                            let color = colors[idx];
                             div()
                                 .absolute()
                                 .left(px(data_point_x - 5.0))
@@ -305,29 +831,57 @@ impl BalanceChart {
                     .top(px(PLOT_PADDING + 20.0))
                     .min_w(px(180.0))
                     .child(
-                        div()
-                            .text_sm()
-                            .font_semibold()
-                            .text_color(theme.foreground)
-                            .child(data_point.date.format("%B %d, %Y").to_string()),
+                        h_flex()
+                            .justify_between()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_semibold()
+                                    .text_color(theme.foreground)
+                                    .child(
+                                        data_point
+                                            .date
+                                            .format(plot_inner.granularity.tooltip_format())
+                                            .to_string(),
+                                    ),
+                            )
+                            .when(pinned, |this| {
+                                this.child(
+                                    Button::new("chart-copy-values")
+                                        .label("Copy")
+                                        .xsmall()
+                                        .ghost()
+                                        .on_click(cx.listener(|this, _, _window, cx| {
+                                            this.copy_values(cx);
+                                            cx.stop_propagation();
+                                        })),
+                                )
+                            }),
                     )
                     .children(data_point.balances.iter().enumerate().map(
-                        |(_idx, (commodity, balance))| {
+                        |(idx, (series, balance))| {
+                            let commodity = plot_inner.unit.as_deref().unwrap_or(series);
                             h_flex()
                                 .gap_2()
                                 .items_center()
-                                .child(
-                                    div()
-                                        .text_xs()
-                                        .text_color(theme.muted_foreground)
-                                        .child("—"),
-                                )
+                                .child(div().text_xs().text_color(colors[idx]).child("—"))
+                                .when(plot_inner.unit.is_some(), |this| {
+                                    this.child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(theme.muted_foreground)
+                                            .child(series.clone()),
+                                    )
+                                })
                                 .child(
                                     div()
                                         .text_sm()
                                         .font_medium()
                                         .text_color(theme.foreground)
-                                        .child(format!("{} ${:.2}", commodity, balance)),
+                                        .child(
+                                            plot_inner.formatter.format_f64(*balance, commodity),
+                                        ),
                                 )
                         },
                     )),
@@ -335,9 +889,33 @@ impl BalanceChart {
     }
 }
 
+impl PlotInner {
+    /// Data points as drawn: stacked, or as they are.
+    fn plotted(&self) -> Vec<DataPoint> {
+        if self.stacked {
+            stack(&self.data)
+        } else {
+            self.data.clone()
+        }
+    }
+
+    /// One color per series, in the order of `commodities`.
+    fn series_colors(&self, theme: &gpui_component::Theme) -> Vec<Hsla> {
+        if self.unit.is_some() {
+            self.commodities
+                .iter()
+                .map(|account| self.account_colors.color(&Account::parse(account), theme))
+                .collect()
+        } else {
+            self.colors.assign(&self.commodities, theme)
+        }
+    }
+}
+
 impl Plot for PlotInner {
     fn paint(&mut self, bounds: Bounds<Pixels>, window: &mut Window, cx: &mut App) {
-        if self.data.is_empty() {
+        let data = self.plotted();
+        if data.is_empty() {
             return;
         }
 
@@ -349,13 +927,12 @@ impl Plot for PlotInner {
         let height = bounds.size.height.as_f32() - AXIS_GAP - PLOT_PADDING;
 
         // Create X scale for dates (categorical)
-        let date_strings: Vec<String> = self.data.iter().map(|d| d.date.to_string()).collect();
+        let date_strings: Vec<String> = data.iter().map(|d| d.date.to_string()).collect();
         let x_scale = ScalePoint::new(date_strings.clone(), vec![PLOT_PADDING, width]);
 
         // Create Y scale for balances (continuous)
         // Include 0 in the domain for proper baseline
-        let all_values: Vec<f64> = self
-            .data
+        let all_values: Vec<f64> = data
             .iter()
             .flat_map(|d| d.balances.iter().map(|(_, v)| *v))
             .chain(std::iter::once(0.0))
@@ -386,16 +963,15 @@ impl Plot for PlotInner {
             .collect();
 
         // Create X-axis labels (show every Nth date to avoid crowding)
-        let tick_margin = (self.data.len() / MIN_TICK_SPACING).max(1);
-        let x_labels: Vec<AxisText> = self
-            .data
+        let tick_margin = (data.len() / MIN_TICK_SPACING).max(1);
+        let x_labels: Vec<AxisText> = data
             .iter()
             .enumerate()
             .filter_map(|(i, d)| {
                 if i % tick_margin == 0 {
                     x_scale.tick(&d.date.to_string()).map(|tick| {
                         AxisText::new(
-                            d.date.format("%m-%d").to_string(),
+                            d.date.format(self.granularity.axis_format()).to_string(),
                             tick,
                             cx.theme().muted_foreground,
                         )
@@ -424,23 +1000,66 @@ impl Plot for PlotInner {
             .paint(&bounds, window);
 
         // Define colors for different commodities
-        let theme = cx.theme();
-        let colors = vec![
-            theme.chart_1,
-            theme.chart_2,
-            theme.chart_3,
-            theme.chart_4,
-            theme.chart_5,
-        ];
-
-        // Draw a line for each commodity
-        for (commodity_idx, _commodity) in self.commodities.iter().enumerate() {
-            let color = colors[commodity_idx % CHART_COLORS_COUNT];
+        let colors = self.series_colors(cx.theme());
+
+        let stroke_style = if self.style.step {
+            StrokeStyle::StepAfter
+        } else {
+            StrokeStyle::Linear
+        };
+        let baseline = y_scale.tick(&0.0).unwrap_or(height);
+
+        // Draw a line for each commodity, stacked ones from the top down so each
+        // area covers the ones above only where it reaches
+        let mut series = (0..self.commodities.len()).collect::<Vec<_>>();
+        if self.stacked {
+            series.reverse();
+        }
+        for commodity_idx in series {
+            let color = colors[commodity_idx];
+
+            if self.stacked {
+                let x_scale_clone = x_scale.clone();
+                let y_scale_clone = y_scale.clone();
+                Area::new()
+                    .data(data.clone())
+                    .x(move |d| x_scale_clone.tick(&d.date.to_string()))
+                    .y0(baseline)
+                    .y1(move |d| {
+                        d.balances
+                            .get(commodity_idx)
+                            .and_then(|(_, value)| y_scale_clone.tick(value))
+                    })
+                    .fill(color)
+                    .stroke(color)
+                    .paint(&bounds, window);
+                continue;
+            }
+
+            if self.style.area {
+                let x_scale_clone = x_scale.clone();
+                let y_scale_clone = y_scale.clone();
+                Area::new()
+                    .data(data.clone())
+                    .x(move |d| x_scale_clone.tick(&d.date.to_string()))
+                    .y0(baseline)
+                    .y1(move |d| {
+                        d.balances
+                            .get(commodity_idx)
+                            .and_then(|(_, value)| y_scale_clone.tick(value))
+                    })
+                    .fill(color.opacity(0.2))
+                    .stroke(color)
+                    .stroke_style(stroke_style)
+                    .paint(&bounds, window);
+                continue;
+            }
+
             let x_scale_clone = x_scale.clone();
             let y_scale_clone = y_scale.clone();
 
             Line::new()
-                .data(self.data.clone())
+                .data(data.clone())
                 .x(move |d| x_scale_clone.tick(&d.date.to_string()))
                 .y(move |d| {
                     // Find the balance for this commodity
@@ -451,7 +1070,7 @@ impl Plot for PlotInner {
                 })
                 .stroke(color)
                 .stroke_width(px(2.0))
-                .stroke_style(StrokeStyle::Linear)
+                .stroke_style(stroke_style)
                 .paint(&bounds, window);
         }
     }
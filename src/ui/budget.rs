@@ -0,0 +1,147 @@
+use fastnum::D128;
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{h_flex, v_flex, ActiveTheme};
+
+use crate::{
+    amount_format::AmountFormatter,
+    budget::{self, EnvelopeMonth},
+    transactions::CurrencyAmount,
+};
+
+use super::state::State;
+
+/// Envelopes of the journal's monthly budget as of the selected period: what is
+/// available in each, and how it got there month by month.
+pub struct BudgetView {
+    state: Entity<State>,
+    /// Envelope whose history is shown.
+    expanded: Option<usize>,
+}
+
+impl BudgetView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self {
+            state,
+            expanded: None,
+        }
+    }
+}
+
+fn amount_cells(
+    month: &EnvelopeMonth,
+    commodity: &str,
+    formatter: &AmountFormatter,
+    danger: Hsla,
+) -> Vec<Div> {
+    let format = |value: D128| {
+        formatter.format(&CurrencyAmount {
+            value,
+            commodity: commodity.to_string(),
+        })
+    };
+    let available = month.available();
+    vec![
+        div().w(px(110.)).text_right().child(format(month.budgeted)),
+        div().w(px(110.)).text_right().child(format(month.carried)),
+        div().w(px(110.)).text_right().child(format(month.spent)),
+        div()
+            .w(px(110.))
+            .text_right()
+            .when(available < D128::ZERO, |this| this.text_color(danger))
+            .child(format(available)),
+    ]
+}
+
+impl Render for BudgetView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let names = state.account_names(cx);
+        let period = state.period;
+        let until = period.end().pred_opt().unwrap_or(period.start);
+        let envelopes = budget::envelopes(&state.budget, &state.all_transactions(), until);
+        let muted = cx.theme().muted_foreground;
+        let danger = cx.theme().danger;
+
+        if envelopes.is_empty() {
+            return v_flex()
+                .id("budget")
+                .size_full()
+                .gap_1()
+                .p_2()
+                .child(div().text_sm().child("Budget"))
+                .child(div().text_xs().text_color(muted).child(
+                    "No budget is set up for this journal. Budgets are written as \
+                     ~ Monthly entries with an amount for each account.",
+                ));
+        }
+
+        let header = |first: SharedString| {
+            h_flex()
+                .gap_2()
+                .text_xs()
+                .text_color(muted)
+                .child(div().flex_1().child(first))
+                .children(
+                    ["Budgeted", "Carried over", "Spent", "Available"]
+                        .map(|title| div().w(px(110.)).text_right().child(title)),
+                )
+        };
+
+        v_flex()
+            .id("budget")
+            .size_full()
+            .gap_1()
+            .p_2()
+            .overflow_y_scroll()
+            .child(div().text_sm().child(format!("Budget for {period}")))
+            .child(header("Envelope".into()))
+            .children(envelopes.iter().enumerate().filter_map(|(ix, envelope)| {
+                let current = envelope.current()?;
+                let expanded = self.expanded == Some(ix);
+                let history = expanded.then(|| {
+                    v_flex()
+                        .pl_4()
+                        .pb_1()
+                        .child(header("Month".into()))
+                        .children(envelope.months.iter().rev().map(|month| {
+                            h_flex()
+                                .gap_2()
+                                .text_xs()
+                                .child(div().flex_1().child(month.month.to_string()))
+                                .children(amount_cells(
+                                    month,
+                                    &envelope.commodity,
+                                    &formatter,
+                                    danger,
+                                ))
+                        }))
+                });
+                Some(
+                    v_flex()
+                        .child(
+                            h_flex()
+                                .id(("budget-envelope", ix))
+                                .gap_2()
+                                .text_sm()
+                                .cursor_pointer()
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.expanded = if expanded { None } else { Some(ix) };
+                                    cx.notify();
+                                }))
+                                .child(div().flex_1().child(names.full_name(&envelope.account)))
+                                .children(amount_cells(
+                                    current,
+                                    &envelope.commodity,
+                                    &formatter,
+                                    danger,
+                                )),
+                        )
+                        .children(history),
+                )
+            }))
+    }
+}
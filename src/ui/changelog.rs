@@ -0,0 +1,209 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Sizable as _,
+};
+
+use crate::changelog::{self, Change};
+use crate::history::{self, DiffLine};
+
+use super::state::State;
+
+/// Every change the app wrote to journal files, newest first, read again from the
+/// changelog after each reload. A change shows the lines it changed when clicked.
+pub struct ChangelogView {
+    state: Entity<State>,
+    changes: Vec<Change>,
+    /// Index into `changes` of the change shown with its lines.
+    expanded: Option<usize>,
+    /// Error reading or exporting the changelog, or where it was exported to.
+    status: Option<Result<String, String>>,
+    /// When the journal was loaded the last time the changelog was read.
+    read_at: Option<chrono::DateTime<chrono::Local>>,
+}
+
+impl ChangelogView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        // Changes are written right before the journal reloads
+        cx.observe(&state, |this, state, cx| {
+            let loaded_at = state.read(cx).loaded_at;
+            if this.read_at != loaded_at {
+                this.read_at = loaded_at;
+                this.read(cx);
+            }
+        })
+        .detach();
+        let mut view = Self {
+            state,
+            changes: Vec::new(),
+            expanded: None,
+            status: None,
+            read_at: None,
+        };
+        view.read(cx);
+        view
+    }
+
+    fn read(&mut self, cx: &mut Context<Self>) {
+        let read = cx.background_executor().spawn(async move {
+            let path = changelog::path().ok_or("No data directory for the changelog")?;
+            changelog::read(&path).map_err(|e| e.to_string())
+        });
+        cx.spawn(async move |this, cx| {
+            let result = read.await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(changes) => {
+                        this.changes = changes;
+                        this.expanded = None;
+                    }
+                    Err(e) => this.status = Some(Err(e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Asks where to write the changelog as text, next to the journal by default.
+    fn export(&mut self, cx: &mut Context<Self>) {
+        let directory = self
+            .state
+            .read(cx)
+            .journal_file()
+            .and_then(|journal| journal.parent().map(std::path::Path::to_path_buf))
+            .unwrap_or_default();
+        let text = changelog::to_text(&self.changes);
+        let path = cx.prompt_for_new_path(&directory, Some("changelog.txt"));
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+            let status = match std::fs::write(&path, text) {
+                Ok(()) => Ok(format!("Exported to {}", path.display())),
+                Err(e) => Err(format!("Failed to write {}: {e}", path.display())),
+            };
+            this.update(cx, |this, cx| {
+                this.status = Some(status);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+fn render_edits(change: &Change, cx: &App) -> Div {
+    v_flex()
+        .pl_2()
+        .font_family("monospace")
+        .text_xs()
+        .children(change.edits.iter().map(|edit| {
+            v_flex()
+                .child(
+                    div()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(edit.file.display().to_string()),
+                )
+                .children(
+                    history::diff_lines(&edit.removed, &edit.inserted)
+                        .into_iter()
+                        .filter_map(|line| match line {
+                            DiffLine::Removed(line) => Some(
+                                div()
+                                    .text_color(cx.theme().danger)
+                                    .child(format!("- {line}")),
+                            ),
+                            DiffLine::Added(line) => Some(
+                                div()
+                                    .text_color(cx.theme().success)
+                                    .child(format!("+ {line}")),
+                            ),
+                            DiffLine::Same(_) => None,
+                        }),
+                )
+        }))
+}
+
+impl Render for ChangelogView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let empty = self.changes.is_empty();
+        let entries = self
+            .changes
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(ix, change)| {
+                let mut files = change
+                    .edits
+                    .iter()
+                    .map(|edit| edit.file.display().to_string())
+                    .collect::<Vec<_>>();
+                files.dedup();
+                let expanded = self.expanded == Some(ix);
+                v_flex()
+                    .id(("changelog-entry", ix))
+                    .px_2()
+                    .py_1()
+                    .cursor_pointer()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(change.time.format("%Y-%m-%d %H:%M:%S").to_string()),
+                            )
+                            .child(div().text_sm().child(change.label.clone())),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(files.join(", ")),
+                    )
+                    .when(expanded, |this| this.child(render_edits(change, cx)))
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.expanded = (this.expanded != Some(ix)).then_some(ix);
+                        cx.notify();
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("changelog")
+            .size_full()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().flex_1().text_sm().child("Changelog"))
+                    .child(
+                        Button::new("changelog-export")
+                            .label("Export…")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| this.export(cx))),
+                    ),
+            )
+            .children(self.status.clone().map(|status| match status {
+                Ok(message) => div().text_xs().child(message),
+                Err(e) => div().text_xs().text_color(cx.theme().danger).child(e),
+            }))
+            .when(empty, |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("No changes made through the app yet"),
+                )
+            })
+            .children(entries)
+    }
+}
@@ -0,0 +1,192 @@
+//! Color assignment for chart series and accounts.
+//!
+//! Each series name is mapped to a palette slot derived from a stable hash of the
+//! name, so a commodity keeps its color between sessions and filters. Collisions
+//! among the currently displayed series are resolved by probing the next free slot.
+//!
+//! Accounts get a color the same way, unless one is set for them or a parent in the
+//! settings, and keep it in every view that shows it.
+
+use std::collections::{BTreeMap, HashMap};
+
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::Theme;
+
+use crate::accounts::Account;
+
+/// Number of generated colors appended after the theme's chart colors
+const EXTRA_COLORS_COUNT: usize = 11;
+
+/// Assigns colors to chart series by name.
+#[derive(Clone, Default)]
+pub struct SeriesColors {
+    overrides: HashMap<String, Hsla>,
+}
+
+impl SeriesColors {
+    /// Creates an assignment with `#rrggbb` overrides by series name.
+    ///
+    /// Overrides that are not valid hex colors are ignored.
+    pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+        let overrides = overrides
+            .iter()
+            .filter_map(|(name, color)| Some((name.clone(), parse_hex(color)?)))
+            .collect();
+        Self { overrides }
+    }
+
+    /// Returns one color per name, in the same order.
+    pub fn assign(&self, names: &[String], theme: &Theme) -> Vec<Hsla> {
+        let palette = palette(theme);
+        let slots = assign_slots(names, palette.len());
+        names
+            .iter()
+            .zip(slots)
+            .map(|(name, slot)| self.overrides.get(name).copied().unwrap_or(palette[slot]))
+            .collect()
+    }
+}
+
+/// Colors of accounts, shown in the tree, the register and per-account charts.
+#[derive(Clone, Default)]
+pub struct AccountColors {
+    overrides: HashMap<Account, Hsla>,
+}
+
+impl AccountColors {
+    /// Creates the colors with `#rrggbb` overrides by full account path.
+    ///
+    /// Overrides that are not valid hex colors are ignored.
+    pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+        let overrides = overrides
+            .iter()
+            .filter_map(|(account, color)| Some((Account::parse(account), parse_hex(color)?)))
+            .collect();
+        Self { overrides }
+    }
+
+    /// The account's color: its own, its nearest parent's or one picked by its name.
+    pub fn color(&self, account: &Account, theme: &Theme) -> Hsla {
+        self.configured(account).unwrap_or_else(|| {
+            let palette = palette(theme);
+            let slot = usize::try_from(fnv1a(&account.to_string())).unwrap_or_default();
+            palette[slot % palette.len()]
+        })
+    }
+
+    /// Color set for the account or its nearest parent.
+    fn configured(&self, account: &Account) -> Option<Hsla> {
+        (1..=account.segments.len()).rev().find_map(|len| {
+            let ancestor = Account::from_segments(account.segments[..len].to_vec());
+            self.overrides.get(&ancestor).copied()
+        })
+    }
+}
+
+fn palette(theme: &Theme) -> Vec<Hsla> {
+    let mut colors = vec![
+        theme.chart_1,
+        theme.chart_2,
+        theme.chart_3,
+        theme.chart_4,
+        theme.chart_5,
+    ];
+    #[allow(clippy::cast_precision_loss)]
+    colors.extend((0..EXTRA_COLORS_COUNT).map(|i| {
+        // Golden-angle spacing keeps neighbouring slots far apart on the color wheel
+        let hue = (i as f32 * 0.381_966).fract();
+        hsla(hue, 0.65, 0.55, 1.0)
+    }));
+    colors
+}
+
+fn parse_hex(color: &str) -> Option<Hsla> {
+    let hex = u32::from_str_radix(color.trim().trim_start_matches('#'), 16).ok()?;
+    Some(rgb(hex).into())
+}
+
+/// Maps names to distinct palette slots, starting from each name's hash.
+///
+/// Names are processed in sorted order so the result does not depend on input order.
+/// When there are more names than slots, slots are reused.
+fn assign_slots(names: &[String], palette_len: usize) -> Vec<usize> {
+    let mut order = (0..names.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| &names[i]);
+
+    let mut used = vec![false; palette_len];
+    let mut slots = vec![0; names.len()];
+    for (n, i) in order.into_iter().enumerate() {
+        if n % palette_len == 0 {
+            used.fill(false);
+        }
+        let start = usize::try_from(fnv1a(&names[i])).unwrap_or_default() % palette_len;
+        let slot = (0..palette_len)
+            .map(|offset| (start + offset) % palette_len)
+            .find(|slot| !used[*slot])
+            .unwrap_or(start);
+        used[slot] = true;
+        slots[i] = slot;
+    }
+    slots
+}
+
+/// Stable 32-bit FNV-1a hash, independent of the std hasher's per-process seed.
+fn fnv1a(s: &str) -> u32 {
+    s.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_slots_distinct_and_stable() {
+        let names = ["USD", "EUR", "SEK", "GEL", "BTC", "ETH", "USDT", "AAPL"]
+            .map(String::from)
+            .to_vec();
+        let slots = assign_slots(&names, 16);
+
+        let mut unique = slots.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), names.len());
+
+        // Order of input does not change assignment
+        let mut reversed = names.clone();
+        reversed.reverse();
+        let mut reversed_slots = assign_slots(&reversed, 16);
+        reversed_slots.reverse();
+        assert_eq!(slots, reversed_slots);
+    }
+
+    #[test]
+    fn test_account_colors_inherited() {
+        let colors = AccountColors::new(&BTreeMap::from([
+            ("assets:bank".to_string(), "#ff0000".to_string()),
+            ("assets:bank:savings".to_string(), "#00ff00".to_string()),
+            ("expenses".to_string(), "not a color".to_string()),
+        ]));
+        let red = parse_hex("#ff0000");
+        assert_eq!(
+            colors.configured(&Account::parse("assets:bank:checking")),
+            red
+        );
+        assert_eq!(colors.configured(&Account::parse("assets:bank")), red);
+        assert_eq!(
+            colors.configured(&Account::parse("assets:bank:savings:rainy-day")),
+            parse_hex("#00ff00")
+        );
+        assert_eq!(colors.configured(&Account::parse("assets")), None);
+        assert_eq!(colors.configured(&Account::parse("expenses:food")), None);
+    }
+
+    #[test]
+    fn test_assign_slots_more_names_than_palette() {
+        let names = (0..5).map(|i| format!("C{i}")).collect::<Vec<_>>();
+        let slots = assign_slots(&names, 2);
+        assert!(slots.iter().all(|slot| *slot < 2));
+    }
+}
@@ -1,3 +1,15 @@
+pub mod account_picker;
+pub mod alerts_bar;
 pub mod checkbox;
+pub mod context_menu;
+pub mod refresh_bar;
+pub mod sparkline;
+pub mod status_bar;
 
+pub use account_picker::{AccountPicker, AccountPickerEvent};
+pub use alerts_bar::AlertsBar;
 pub use checkbox::{Checkbox, CheckboxState};
+pub use context_menu::{build_menu, MenuEntry};
+pub use refresh_bar::RefreshBar;
+pub use sparkline::Sparkline;
+pub use status_bar::StatusBar;
@@ -0,0 +1,224 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    input::{Input, InputEvent, InputState},
+    v_flex, ActiveTheme,
+};
+
+use crate::{accounts::Account, fuzzy, ui::state::State};
+
+/// Most suggestions shown at once.
+const MAX_SUGGESTIONS: usize = 8;
+
+pub enum AccountPickerEvent {
+    /// An account was picked from the list or typed and confirmed with enter.
+    Confirm,
+}
+
+enum Suggestion {
+    Existing(Account),
+    /// Account that is not in the journal yet.
+    Create(Account),
+}
+
+/// Text input that suggests known accounts as you type.
+///
+/// Suggestions are fuzzy matched against every account in the state's tree and can be
+/// picked with the mouse or with up, down and enter. A query that names no existing
+/// account is offered as a new one.
+pub struct AccountPicker {
+    state: Entity<State>,
+    input: Entity<InputState>,
+    suggestions: Vec<Suggestion>,
+    selected: usize,
+    open: bool,
+    /// Text last put into the input by picking a suggestion, so the change it causes
+    /// does not reopen the list.
+    picked: Option<SharedString>,
+}
+
+impl EventEmitter<AccountPickerEvent> for AccountPicker {}
+
+impl AccountPicker {
+    pub fn new(
+        state: Entity<State>,
+        placeholder: impl Into<SharedString>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let input = cx.new(|cx| InputState::new(window, cx).placeholder(placeholder));
+
+        cx.subscribe_in(
+            &input,
+            window,
+            |this, _input, event, _window, cx| match event {
+                InputEvent::Change => {
+                    let query = this.input.read(cx).value();
+                    if this.picked.as_ref() != Some(&query) {
+                        this.picked = None;
+                        this.update_suggestions(cx);
+                        this.open = true;
+                    }
+                    cx.notify();
+                }
+                InputEvent::Focus => {
+                    this.update_suggestions(cx);
+                    this.open = true;
+                    cx.notify();
+                }
+                InputEvent::Blur => {
+                    this.open = false;
+                    cx.notify();
+                }
+                InputEvent::PressEnter { .. } => {
+                    if this.account(cx).is_some() {
+                        cx.emit(AccountPickerEvent::Confirm);
+                    }
+                }
+                _ => {}
+            },
+        )
+        .detach();
+
+        Self {
+            state,
+            input,
+            suggestions: Vec::new(),
+            selected: 0,
+            open: false,
+            picked: None,
+        }
+    }
+
+    /// The account currently in the input, if any.
+    pub fn account(&self, cx: &App) -> Option<Account> {
+        let account = Account::parse(&self.input.read(cx).value());
+        (!account.segments.is_empty()).then_some(account)
+    }
+
+    pub fn set_account(
+        &mut self,
+        account: Option<&Account>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let text = SharedString::from(account.map(ToString::to_string).unwrap_or_default());
+        self.picked = Some(text.clone());
+        self.open = false;
+        self.input
+            .update(cx, |input, cx| input.set_value(text, window, cx));
+        cx.notify();
+    }
+
+    fn update_suggestions(&mut self, cx: &App) {
+        let query = self.input.read(cx).value().trim().to_string();
+        let accounts = self.state.read(cx).accounts.accounts();
+        let exists = accounts.iter().any(|account| account.to_string() == query);
+
+        let names = accounts
+            .into_iter()
+            .map(|account| (account.to_string(), account))
+            .collect::<Vec<_>>();
+        self.suggestions = fuzzy::rank(&query, names, |(name, _)| name.as_str())
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, account)| Suggestion::Existing(account))
+            .collect();
+        if !query.is_empty() && !exists {
+            self.suggestions
+                .push(Suggestion::Create(Account::parse(&query)));
+        }
+        self.selected = 0;
+    }
+
+    fn pick(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(suggestion) = self.suggestions.get(index) else {
+            return;
+        };
+        let account = match suggestion {
+            Suggestion::Existing(account) | Suggestion::Create(account) => account.clone(),
+        };
+        self.set_account(Some(&account), window, cx);
+        cx.emit(AccountPickerEvent::Confirm);
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.open || self.suggestions.is_empty() {
+            return;
+        }
+        match event.keystroke.key.as_str() {
+            "up" => {
+                self.selected = self
+                    .selected
+                    .checked_sub(1)
+                    .unwrap_or(self.suggestions.len() - 1);
+            }
+            "down" => self.selected = (self.selected + 1) % self.suggestions.len(),
+            "enter" => self.pick(self.selected, window, cx),
+            "escape" => self.open = false,
+            _ => return,
+        }
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    fn render_suggestions(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        v_flex()
+            .w_full()
+            .py_1()
+            .bg(theme.popover)
+            .border_1()
+            .border_color(theme.border)
+            .rounded_md()
+            .shadow_md()
+            .children(
+                self.suggestions
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, suggestion)| {
+                        let label = match suggestion {
+                            Suggestion::Existing(account) => account.to_string(),
+                            Suggestion::Create(account) => format!("Create “{account}”"),
+                        };
+                        div()
+                            .id(("account-picker-suggestion", ix))
+                            .px_2()
+                            .py_1()
+                            .text_sm()
+                            .when(ix == self.selected, |this| {
+                                this.bg(theme.accent).text_color(theme.accent_foreground)
+                            })
+                            .when(matches!(suggestion, Suggestion::Create(_)), |this| {
+                                this.italic()
+                            })
+                            .child(label)
+                            // Mouse down, so the pick happens before the input loses focus
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(move |this, _, window, cx| {
+                                    this.pick(ix, window, cx);
+                                    cx.stop_propagation();
+                                }),
+                            )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
+
+impl Render for AccountPicker {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let suggestions =
+            (self.open && !self.suggestions.is_empty()).then(|| self.render_suggestions(cx));
+
+        v_flex()
+            .w_full()
+            .capture_key_down(cx.listener(Self::on_key_down))
+            .child(Input::new(&self.input))
+            .children(suggestions.map(|suggestions| {
+                deferred(anchored().child(div().w(px(280.)).child(suggestions))).with_priority(1)
+            }))
+    }
+}
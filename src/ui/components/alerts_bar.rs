@@ -0,0 +1,83 @@
+use gpui::{
+    div, App, Entity, IntoElement, ParentElement, RenderOnce, SharedString, Styled, Window,
+};
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Sizable,
+};
+
+use crate::{
+    settings::Settings,
+    ui::{settings, state::State},
+};
+
+/// Strip along the top of the window with the alerts that are not snoozed, each with
+/// buttons snoozing its rule.
+#[derive(IntoElement)]
+pub struct AlertsBar {
+    state: Entity<State>,
+}
+
+impl AlertsBar {
+    pub fn new(state: Entity<State>) -> Self {
+        Self { state }
+    }
+}
+
+/// Hides the rule's alerts for `days` days, forgetting snoozes that are over.
+fn snooze(rule: &str, days: u64, cx: &mut App) {
+    let today = chrono::Local::now().date_naive();
+    let until = today + chrono::Days::new(days - 1);
+    let rule = rule.to_string();
+    settings::update(cx, |settings| {
+        settings.snoozed_alerts.retain(|_, until| *until >= today);
+        settings.snoozed_alerts.insert(rule, until);
+    })
+    .map_err(|e| {
+        eprintln!("Error snoozing alert: {}", e);
+    })
+    .ok();
+}
+
+impl RenderOnce for AlertsBar {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let settings = cx.global::<Settings>();
+        let formatter = state.amount_formatter(cx);
+        let today = chrono::Local::now().date_naive();
+
+        v_flex()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .children(
+                state
+                    .alerts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, alert)| !settings.is_snoozed(&alert.rule, today))
+                    .map(|(ix, alert)| {
+                        let snooze_button = |label: &'static str, days: u64| {
+                            let rule = alert.rule.clone();
+                            Button::new(SharedString::from(format!("alert-{ix}-snooze-{days}")))
+                                .label(label)
+                                .xsmall()
+                                .ghost()
+                                .on_click(move |_, _window, cx| snooze(&rule, days, cx))
+                        };
+                        h_flex()
+                            .gap_2()
+                            .px_2()
+                            .items_center()
+                            .text_xs()
+                            .child(
+                                div()
+                                    .text_color(cx.theme().warning)
+                                    .child(alert.rule.clone()),
+                            )
+                            .child(div().flex_1().child(alert.message(&formatter)))
+                            .child(snooze_button("Snooze a day", 1))
+                            .child(snooze_button("Snooze a week", 7))
+                    }),
+            )
+    }
+}
@@ -0,0 +1,50 @@
+use std::rc::Rc;
+
+use gpui::{App, SharedString, Window};
+use gpui_component::menu::{PopupMenu, PopupMenuItem};
+
+/// Entry of a right-click menu on a row.
+#[derive(Clone)]
+pub enum MenuEntry {
+    Action {
+        label: SharedString,
+        handler: Rc<dyn Fn(&mut Window, &mut App)>,
+    },
+    Separator,
+}
+
+impl MenuEntry {
+    pub fn action(
+        label: impl Into<SharedString>,
+        handler: impl Fn(&mut Window, &mut App) + 'static,
+    ) -> Self {
+        Self::Action {
+            label: label.into(),
+            handler: Rc::new(handler),
+        }
+    }
+}
+
+/// Adds the entries to a menu, leaving out separators that would not separate
+/// anything.
+pub fn build_menu(menu: PopupMenu, entries: impl IntoIterator<Item = MenuEntry>) -> PopupMenu {
+    let mut menu = menu;
+    let mut has_items = false;
+    let mut pending_separator = false;
+    for entry in entries {
+        match entry {
+            MenuEntry::Separator => pending_separator = has_items,
+            MenuEntry::Action { label, handler } => {
+                if pending_separator {
+                    menu = menu.separator();
+                    pending_separator = false;
+                }
+                menu = menu.item(
+                    PopupMenuItem::new(label).on_click(move |_, window, cx| handler(window, cx)),
+                );
+                has_items = true;
+            }
+        }
+    }
+    menu
+}
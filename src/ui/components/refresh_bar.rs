@@ -0,0 +1,101 @@
+use gpui::{
+    div, prelude::FluentBuilder as _, App, Entity, IntoElement, ParentElement, RenderOnce,
+    SharedString, Styled, Window,
+};
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, ActiveTheme, Disableable, Sizable,
+};
+
+use crate::{settings::Settings, ui::state::State};
+
+/// Header strip showing how fresh a view's data is, with refresh controls.
+#[derive(IntoElement)]
+pub struct RefreshBar {
+    id: SharedString,
+    state: Entity<State>,
+}
+
+impl RefreshBar {
+    pub fn new(id: impl Into<SharedString>, state: Entity<State>) -> Self {
+        Self {
+            id: id.into(),
+            state,
+        }
+    }
+}
+
+impl RenderOnce for RefreshBar {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let status: SharedString = if state.loading {
+            "Loading…".into()
+        } else if let Some(loaded_at) = state.loaded_at {
+            let age = chrono::Local::now() - loaded_at;
+            format!("Updated {}", format_age(age.num_seconds())).into()
+        } else {
+            "Not loaded".into()
+        };
+        let query = format!("ledger {}", state.source_query());
+        let loading = state.loading;
+        let fetching_prices = state.fetching_prices;
+        let fetches_prices = !cx.global::<Settings>().price_commands.is_empty();
+
+        h_flex()
+            .gap_2()
+            .px_2()
+            .items_center()
+            .text_xs()
+            .text_color(cx.theme().muted_foreground)
+            .child(div().flex_1().child(status))
+            .child(div().child(query))
+            .when(fetches_prices, |this| {
+                this.child(
+                    Button::new(SharedString::from(format!("{}-fetch-prices", self.id)))
+                        .label(if fetching_prices {
+                            "Fetching prices…"
+                        } else {
+                            "Fetch prices"
+                        })
+                        .xsmall()
+                        .ghost()
+                        .disabled(loading || fetching_prices)
+                        .on_click({
+                            let state = self.state.clone();
+                            move |_, _, cx| state.update(cx, |state, cx| state.fetch_prices(cx))
+                        }),
+                )
+            })
+            .child(
+                Button::new(SharedString::from(format!("{}-refresh", self.id)))
+                    .label("Refresh")
+                    .xsmall()
+                    .ghost()
+                    .disabled(loading)
+                    .on_click({
+                        let state = self.state.clone();
+                        move |_, _, cx| state.update(cx, |state, cx| state.refresh(false, cx))
+                    }),
+            )
+            .child(
+                Button::new(SharedString::from(format!("{}-force-refresh", self.id)))
+                    .label("Force")
+                    .xsmall()
+                    .ghost()
+                    .disabled(loading)
+                    .on_click({
+                        let state = self.state;
+                        move |_, _, cx| state.update(cx, |state, cx| state.refresh(true, cx))
+                    }),
+            )
+    }
+}
+
+fn format_age(seconds: i64) -> String {
+    match seconds {
+        ..=4 => "just now".to_string(),
+        5..=59 => format!("{seconds}s ago"),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        _ => format!("{}h ago", seconds / 3600),
+    }
+}
@@ -0,0 +1,43 @@
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::plot::{
+    scale::{Scale, ScaleLinear},
+    shape::Line,
+    IntoPlot, Plot,
+};
+use gpui_component::PixelsExt;
+
+/// Line of a series of values without axes or labels, small enough to show a trend
+/// next to a number.
+#[derive(IntoPlot, Clone)]
+pub struct Sparkline {
+    values: Vec<f64>,
+    color: Hsla,
+}
+
+impl Sparkline {
+    pub fn new(values: Vec<f64>, color: Hsla) -> Self {
+        Self { values, color }
+    }
+}
+
+impl Plot for Sparkline {
+    #[allow(clippy::cast_precision_loss)]
+    fn paint(&mut self, bounds: Bounds<Pixels>, window: &mut Window, _cx: &mut App) {
+        if self.values.len() < 2 {
+            return;
+        }
+        let width = bounds.size.width.as_f32();
+        let height = bounds.size.height.as_f32();
+        let x_scale = ScaleLinear::new(vec![0.0, (self.values.len() - 1) as f64], vec![0.0, width]);
+        let y_scale = ScaleLinear::new(self.values.clone(), vec![height, 0.0]);
+
+        Line::new()
+            .data(self.values.iter().copied().enumerate().collect::<Vec<_>>())
+            .x(move |(ix, _)| x_scale.tick(&(*ix as f64)))
+            .y(move |(_, value)| y_scale.tick(value))
+            .stroke(self.color)
+            .stroke_width(px(1.5))
+            .paint(&bounds, window);
+    }
+}
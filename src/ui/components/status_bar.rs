@@ -0,0 +1,86 @@
+use gpui::{
+    div, prelude::FluentBuilder as _, App, Entity, InteractiveElement as _, IntoElement,
+    ParentElement, RenderOnce, SharedString, StatefulInteractiveElement as _, Styled, Window,
+};
+use gpui_component::{h_flex, tooltip::Tooltip, ActiveTheme};
+
+use crate::ui::{state::State, transactions_register::RegisterSummary};
+
+/// Strip along the bottom of the window with the shown register's totals and filters,
+/// and what the ledger process is doing.
+#[derive(IntoElement)]
+pub struct StatusBar {
+    state: Entity<State>,
+    summary: Option<RegisterSummary>,
+}
+
+impl StatusBar {
+    pub fn new(state: Entity<State>, summary: Option<RegisterSummary>) -> Self {
+        Self { state, summary }
+    }
+}
+
+impl RenderOnce for StatusBar {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let (status, failed): (SharedString, bool) = if let Some(error) = &state.error {
+            (format!("Error: {error}").into(), true)
+        } else if state.loading {
+            ("Streaming from ledger…".into(), false)
+        } else {
+            ("Idle".into(), false)
+        };
+        let formatter = state.amount_formatter(cx);
+
+        // Transactions skipped by lenient parsing, listed in the tooltip
+        let problems = (!state.problems.is_empty()).then(|| {
+            let label = match state.problems.len() {
+                1 => "1 problem".to_string(),
+                n => format!("{n} problems"),
+            };
+            let details = SharedString::from(state.problems.join("\n"));
+            div()
+                .id("status-bar-problems")
+                .text_color(cx.theme().warning)
+                .child(label)
+                .tooltip(move |window, cx| Tooltip::new(details.clone()).build(window, cx))
+        });
+
+        let summary = self.summary.map(|summary| {
+            let count = match summary.transactions {
+                1 => "1 transaction".to_string(),
+                n => format!("{n} transactions"),
+            };
+            h_flex()
+                .flex_1()
+                .gap_4()
+                .overflow_hidden()
+                .child(div().child(count))
+                .child(div().child(formatter.format_balance(&summary.total)))
+                .children(summary.selection.map(|(count, total)| {
+                    div().text_color(cx.theme().foreground).child(format!(
+                        "{count} selected: {}",
+                        formatter.format_balance(&total)
+                    ))
+                }))
+                .child(div().truncate().child(summary.filters.join(" · ")))
+        });
+
+        h_flex()
+            .gap_4()
+            .px_2()
+            .py_1()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .text_xs()
+            .text_color(cx.theme().muted_foreground)
+            .children(summary)
+            .child(div().flex_1())
+            .children(problems)
+            .child(
+                div()
+                    .when(failed, |this| this.text_color(cx.theme().danger))
+                    .child(status),
+            )
+    }
+}
@@ -0,0 +1,160 @@
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Sizable as _,
+};
+
+use crate::history::{self, DiffLine, EntryConflict};
+
+use super::state::State;
+
+/// Dialog over the window while an edited entry was also changed in its file, to keep
+/// the app's version, take the file's, or compare the two first.
+pub struct ConflictView {
+    state: Entity<State>,
+    /// True while the line diff of the file's version against the app's is shown.
+    show_diff: bool,
+}
+
+impl ConflictView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self {
+            state,
+            show_diff: false,
+        }
+    }
+
+    fn resolve(&mut self, keep_mine: bool, cx: &mut Context<Self>) {
+        self.show_diff = false;
+        self.state.update(cx, |state, cx| {
+            if keep_mine {
+                state.keep_mine(cx);
+            } else {
+                state.take_file(cx);
+            }
+        });
+    }
+}
+
+fn entry_text(label: &'static str, text: &str, cx: &App) -> Div {
+    v_flex()
+        .flex_1()
+        .gap_1()
+        .child(
+            div()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .child(label),
+        )
+        .child(
+            div()
+                .p_1()
+                .rounded_md()
+                .bg(cx.theme().muted)
+                .font_family("monospace")
+                .text_xs()
+                .child(text.to_string()),
+        )
+}
+
+fn render_diff(conflict: &EntryConflict, cx: &App) -> Div {
+    let current = conflict.current.as_deref().unwrap_or_default();
+    v_flex().font_family("monospace").text_xs().children(
+        history::diff_lines(current, &conflict.mine)
+            .into_iter()
+            .map(|line| match line {
+                DiffLine::Same(line) => div().child(format!("  {line}")),
+                DiffLine::Removed(line) => div()
+                    .text_color(cx.theme().danger)
+                    .child(format!("- {line}")),
+                DiffLine::Added(line) => div()
+                    .text_color(cx.theme().success)
+                    .child(format!("+ {line}")),
+            }),
+    )
+}
+
+impl Render for ConflictView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(conflict) = self.state.read(cx).conflict.clone() else {
+            return div();
+        };
+        let body = if self.show_diff {
+            v_flex()
+                .gap_1()
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("File (-) against mine (+)"),
+                )
+                .child(render_diff(&conflict, cx))
+        } else {
+            h_flex()
+                .gap_2()
+                .items_start()
+                .child(entry_text(
+                    "In the file",
+                    conflict
+                        .current
+                        .as_deref()
+                        .unwrap_or("No entry on this line anymore"),
+                    cx,
+                ))
+                .child(entry_text("Mine", &conflict.mine, cx))
+        };
+
+        v_flex()
+            .w(px(640.))
+            .p_3()
+            .gap_2()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().popover)
+            .shadow_md()
+            .child(div().text_sm().child(format!(
+                "Line {} of {} changed since it was loaded",
+                conflict.line,
+                conflict.file.display()
+            )))
+            .child(body)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("conflict-keep-mine")
+                            .label("Keep mine")
+                            .small()
+                            .primary()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.resolve(true, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("conflict-take-file")
+                            .label("Take file")
+                            .small()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.resolve(false, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("conflict-diff")
+                            .label(if self.show_diff {
+                                "Hide diff"
+                            } else {
+                                "Open diff"
+                            })
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.show_diff = !this.show_diff;
+                                cx.notify();
+                            })),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,138 @@
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{h_flex, v_flex, ActiveTheme};
+
+use crate::{
+    amount_format::AmountFormatter,
+    custom_reports::{Cell, Chart, ReportInput, ReportOutput, ReportRegistry, Table},
+};
+
+use super::state::State;
+
+impl Global for ReportRegistry {}
+
+/// Section of a report from the registry, made again whenever the journal or the period
+/// changes.
+pub struct CustomReportView {
+    state: Entity<State>,
+    name: &'static str,
+}
+
+impl CustomReportView {
+    pub fn new(state: Entity<State>, name: &'static str, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self { state, name }
+    }
+}
+
+fn render_table(table: &Table, formatter: &AmountFormatter, cx: &App) -> Div {
+    let row = |cells: Vec<Div>| h_flex().gap_2().px_2().children(cells);
+    v_flex()
+        .gap_1()
+        .child(
+            row(table
+                .columns
+                .iter()
+                .map(|column| div().flex_1().child(column.clone()))
+                .collect())
+            .text_xs()
+            .text_color(cx.theme().muted_foreground),
+        )
+        .children(table.rows.iter().map(|cells| {
+            row(cells
+                .iter()
+                .map(|cell| match cell {
+                    Cell::Text(text) => div().flex_1().child(text.clone()),
+                    Cell::Amount(amount) => div().flex_1().child(formatter.format(amount)),
+                })
+                .collect())
+            .text_sm()
+        }))
+}
+
+fn render_chart(chart: &Chart, formatter: &AmountFormatter, cx: &App) -> Div {
+    let largest = chart
+        .bars
+        .iter()
+        .map(|(_, amount)| amount.value.abs())
+        .max()
+        .unwrap_or_default();
+    v_flex()
+        .gap_1()
+        .children(chart.bars.iter().map(|(label, amount)| {
+            let share = if largest.is_zero() {
+                0.0
+            } else {
+                (amount.value.abs() / largest)
+                    .to_string()
+                    .parse::<f32>()
+                    .unwrap_or(0.0)
+            };
+            let color = if amount.value.is_negative() {
+                cx.theme().danger
+            } else {
+                cx.theme().chart_1
+            };
+            h_flex()
+                .gap_2()
+                .px_2()
+                .text_sm()
+                .child(div().w(px(90.)).child(label.clone()))
+                .child(
+                    div()
+                        .flex_1()
+                        .h(px(12.))
+                        .child(div().h_full().w(relative(share)).bg(color)),
+                )
+                .child(
+                    div()
+                        .w(px(110.))
+                        .text_right()
+                        .child(formatter.format(amount)),
+                )
+        }))
+}
+
+impl Render for CustomReportView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let transactions = state.reportable_transactions();
+        let commodity = state.main_commodity(cx).unwrap_or_default();
+        let input = ReportInput {
+            transactions: &transactions,
+            period: state.period,
+            commodity: &commodity,
+        };
+        let output = cx
+            .global::<ReportRegistry>()
+            .get(self.name)
+            .map(|provider| provider.report(&input));
+
+        let body = match &output {
+            Some(ReportOutput::Table(table)) if table.rows.is_empty() => div()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .child("Nothing to report in this period"),
+            Some(ReportOutput::Table(table)) => render_table(table, &formatter, cx),
+            Some(ReportOutput::Chart(chart)) => render_chart(chart, &formatter, cx),
+            None => div()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .child("Unknown report"),
+        };
+
+        v_flex()
+            .id("custom-report")
+            .size_full()
+            .gap_1()
+            .p_2()
+            .overflow_y_scroll()
+            .child(
+                div()
+                    .text_sm()
+                    .child(format!("{}, {}", self.name, input.period)),
+            )
+            .child(body)
+    }
+}
@@ -0,0 +1,385 @@
+use fastnum::D128;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{h_flex, v_flex, ActiveTheme};
+
+use crate::{
+    accounts::{Account, Balance, TreeNode},
+    amount_format::AmountFormatter,
+    insights::{self, SpikeSubject},
+    period::{Period, PeriodKind},
+    reports::{self, period_totals, MonthlyFlow},
+    settings::SavedView,
+    transactions::CurrencyAmount,
+};
+
+use super::{components::Sparkline, state::State};
+
+/// Top-level accounts that add up to net worth.
+const NET_WORTH: [&str; 2] = ["assets", "liabilities"];
+
+/// Months the sparklines cover.
+const TREND_MONTHS: usize = 12;
+
+pub enum DashboardEvent {
+    /// Open a register tab with the transactions behind an insight.
+    Open(SavedView),
+}
+
+/// Overview of the journal: net worth, the period's income and expenses, and what needs
+/// attention.
+pub struct DashboardView {
+    state: Entity<State>,
+}
+
+impl EventEmitter<DashboardEvent> for DashboardView {}
+
+impl DashboardView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self { state }
+    }
+
+    /// Spending spikes of the last month of the period, each opening the transactions
+    /// behind it when clicked.
+    fn render_insights(&self, cx: &mut Context<Self>) -> Option<Div> {
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let names = state.account_names(cx);
+        let commodity = state.main_commodity(cx)?;
+        let until = last_day(state.period);
+        let spikes = insights::find_spikes(&state.reportable_transactions(), until, &commodity);
+        let month = Period::containing(until, PeriodKind::Month);
+        let format = |value: D128| {
+            formatter.format(&CurrencyAmount {
+                value,
+                commodity: commodity.clone(),
+            })
+        };
+        let muted = cx.theme().muted_foreground;
+
+        let rows = spikes
+            .into_iter()
+            .enumerate()
+            .map(|(ix, spike)| {
+                let (name, view) = match &spike.subject {
+                    SpikeSubject::Account(account) => {
+                        let name = names.full_name(account);
+                        let view = SavedView {
+                            name: name.clone(),
+                            accounts: vec![account.to_string()],
+                            excluded_accounts: Vec::new(),
+                            period: spike.month,
+                            payee: None,
+                            query: None,
+                        };
+                        (name, view)
+                    }
+                    SpikeSubject::Payee(payee) => {
+                        let view = SavedView {
+                            name: payee.clone(),
+                            accounts: Vec::new(),
+                            excluded_accounts: Vec::new(),
+                            period: spike.month,
+                            payee: Some(payee.clone()),
+                            query: None,
+                        };
+                        (payee.clone(), view)
+                    }
+                };
+                v_flex()
+                    .id(("dashboard-spike", ix))
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |_this, _, _window, cx| {
+                        cx.emit(DashboardEvent::Open(view.clone()));
+                    }))
+                    .child(div().text_sm().child(format!(
+                        "{name}: {}, {:.1}× the usual {}",
+                        format(spike.spent),
+                        spike.ratio(),
+                        format(spike.average),
+                    )))
+                    .children(spike.drivers.iter().map(|driver| {
+                        div().pl_2().text_xs().text_color(muted).child(format!(
+                            "{} {} {}",
+                            driver.date.format("%Y-%m-%d"),
+                            driver.description,
+                            format(driver.amount),
+                        ))
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        let body = if rows.is_empty() {
+            v_flex().child(
+                div()
+                    .text_sm()
+                    .child(format!("Spending in {month} is as usual")),
+            )
+        } else {
+            v_flex().gap_1().children(rows)
+        };
+        Some(card("Insights", body, cx))
+    }
+}
+
+/// Day the figures are shown as of: the end of the period, or today for the current one.
+fn last_day(period: Period) -> chrono::NaiveDate {
+    period
+        .end()
+        .pred_opt()
+        .unwrap_or(period.start)
+        .min(chrono::Local::now().date_naive())
+}
+
+fn find_node<'a>(node: &'a TreeNode, account: &Account) -> Option<&'a TreeNode> {
+    if &node.account == account {
+        return Some(node);
+    }
+    node.children
+        .iter()
+        .find_map(|child| find_node(child, account))
+}
+
+fn decimal_to_f64(value: D128) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+/// A figure with an arrow for the way it last moved and a sparkline of its recent
+/// values. `change` is how much it moved, `None` when it can't be told.
+fn trend_row(
+    label: String,
+    value: String,
+    change: Option<f64>,
+    higher_is_better: bool,
+    history: Vec<f64>,
+    cx: &App,
+) -> Div {
+    let theme = cx.theme();
+    let (rise, fall) = if higher_is_better {
+        (theme.success, theme.danger)
+    } else {
+        (theme.danger, theme.success)
+    };
+    let (arrow, color) = match change {
+        Some(change) if change > 0.0 => ("▲", rise),
+        Some(change) if change < 0.0 => ("▼", fall),
+        _ => ("–", theme.muted_foreground),
+    };
+    h_flex()
+        .gap_2()
+        .items_center()
+        .child(div().flex_1().text_sm().child(label))
+        .child(div().text_sm().child(value))
+        .child(div().w(px(12.)).text_xs().text_color(color).child(arrow))
+        .child(
+            div()
+                .w(px(80.))
+                .h(px(20.))
+                .child(Sparkline::new(history, theme.chart_1)),
+        )
+}
+
+/// Savings rate, rolling averages of expenses and income growth over the months up to
+/// the last of `flows`.
+fn trend_rows(
+    flows: &[MonthlyFlow],
+    commodity: &str,
+    formatter: &AmountFormatter,
+    cx: &App,
+) -> Vec<Div> {
+    let recent = &flows[flows.len().saturating_sub(TREND_MONTHS)..];
+    let format = |value: D128| {
+        formatter.format(&CurrencyAmount {
+            value,
+            commodity: commodity.to_string(),
+        })
+    };
+    let mut rows = Vec::new();
+
+    let rates = recent
+        .iter()
+        .map(MonthlyFlow::savings_rate)
+        .collect::<Vec<_>>();
+    let (current, previous) = match rates.as_slice() {
+        [.., previous, current] => (*current, *previous),
+        [current] => (*current, None),
+        [] => (None, None),
+    };
+    rows.push(trend_row(
+        "Savings rate".into(),
+        current.map_or("–".into(), |rate| format!("{:.0}%", rate * 100.0)),
+        current
+            .zip(previous)
+            .map(|(current, previous)| current - previous),
+        true,
+        rates.iter().map(|rate| rate.unwrap_or(0.0)).collect(),
+        cx,
+    ));
+
+    let expenses = flows.iter().map(|flow| flow.expenses).collect::<Vec<_>>();
+    for months in [3, 6, 12] {
+        // Average as of each of the recent months
+        let averages = (expenses.len().saturating_sub(TREND_MONTHS)..expenses.len())
+            .filter_map(|end| reports::trailing_average(&expenses[..=end], months))
+            .collect::<Vec<_>>();
+        let change = match averages.as_slice() {
+            [.., previous, current] => Some(decimal_to_f64(*current - *previous)),
+            _ => None,
+        };
+        rows.push(trend_row(
+            format!("Expenses, {months}-month average"),
+            averages
+                .last()
+                .map_or("–".into(), |average| format(*average)),
+            change,
+            false,
+            averages.iter().copied().map(decimal_to_f64).collect(),
+            cx,
+        ));
+    }
+
+    let income = flows.iter().map(|flow| flow.income).collect::<Vec<_>>();
+    let (before, last_year) = income.split_at(income.len().saturating_sub(12));
+    let growth = reports::growth(
+        last_year
+            .iter()
+            .fold(D128::ZERO, |total, value| total + *value),
+        before
+            .iter()
+            .fold(D128::ZERO, |total, value| total + *value),
+    );
+    rows.push(trend_row(
+        "Income growth, last 12 months".into(),
+        growth.map_or("–".into(), |growth| format!("{:+.0}%", growth * 100.0)),
+        growth,
+        true,
+        recent
+            .iter()
+            .map(|flow| decimal_to_f64(flow.income))
+            .collect(),
+        cx,
+    ));
+
+    rows
+}
+
+fn card(title: &'static str, body: impl IntoElement, cx: &App) -> Div {
+    v_flex()
+        .gap_1()
+        .p_2()
+        .border_1()
+        .border_color(cx.theme().border)
+        .rounded_md()
+        .child(
+            div()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .child(title),
+        )
+        .child(body)
+}
+
+impl Render for DashboardView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let insights = self.render_insights(cx);
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let names = state.account_names(cx);
+
+        let mut net_worth = Balance::new();
+        state
+            .accounts
+            .children
+            .iter()
+            .filter(|node| {
+                node.account
+                    .segments
+                    .first()
+                    .is_some_and(|root| NET_WORTH.iter().any(|r| root.eq_ignore_ascii_case(r)))
+            })
+            .for_each(|node| net_worth += &node.balance);
+
+        let transactions = state.reportable_transactions();
+        let totals = period_totals(&transactions, state.period, &["income", "expenses"], 1);
+
+        let until = last_day(state.period);
+        let trends = state.main_commodity(cx).map(|commodity| {
+            // Two years, so the 12-month figures have a year before them to compare to
+            let flows = reports::monthly_flows(&transactions, until, 2 * TREND_MONTHS, &commodity);
+            trend_rows(&flows, &commodity, &formatter, cx)
+        });
+
+        let failing_checks = state.check_assertions().len();
+        let mut over_limit = state
+            .limits
+            .iter()
+            .filter(|(account, limit)| {
+                find_node(&state.accounts, account)
+                    .is_some_and(|node| limit.is_violated_by(&node.balance))
+            })
+            .map(|(account, _)| names.full_name(account))
+            .collect::<Vec<_>>();
+        over_limit.sort();
+
+        let muted = cx.theme().muted_foreground;
+        let line = |text: String| div().text_sm().child(text);
+
+        v_flex()
+            .id("dashboard")
+            .size_full()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .child(card(
+                "Net worth",
+                line(formatter.format_balance(&net_worth)),
+                cx,
+            ))
+            .child(card(
+                "Income and expenses",
+                v_flex()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(muted)
+                            .child(state.period.to_string()),
+                    )
+                    .children(
+                        totals
+                            .iter()
+                            .map(|(account, balance)| {
+                                h_flex()
+                                    .gap_2()
+                                    .child(div().flex_1().text_sm().child(names.full_name(account)))
+                                    .child(line(formatter.format_balance(balance)))
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                cx,
+            ))
+            .children(trends.map(|rows| card("Trends", v_flex().gap_1().children(rows), cx)))
+            .children(insights)
+            .child(card(
+                "Needs attention",
+                v_flex()
+                    .child(line(match failing_checks {
+                        0 => "All balance checks pass".to_string(),
+                        1 => "1 balance check fails".to_string(),
+                        n => format!("{n} balance checks fail"),
+                    }))
+                    .children(
+                        over_limit
+                            .into_iter()
+                            .map(|account| {
+                                div()
+                                    .text_sm()
+                                    .text_color(cx.theme().danger)
+                                    .child(format!("{account} is over its limit"))
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                cx,
+            ))
+    }
+}
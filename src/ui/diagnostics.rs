@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{h_flex, v_flex, ActiveTheme};
+
+use crate::diagnostics;
+
+use super::state::State;
+
+/// How often the ledger process's memory is read again.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Overlay with the numbers asked for when reporting a slow journal: how many
+/// transactions there are, the memory they and the ledger process take, and how long
+/// loading and rebuilding the views took.
+pub struct DiagnosticsView {
+    state: Entity<State>,
+    /// Resident memory of the ledger process, read with `ps`.
+    ledger_rss: Option<u64>,
+    _poll: Task<()>,
+}
+
+impl DiagnosticsView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        let poll = cx.spawn(async move |this, cx| loop {
+            let Ok(pid) = this.update(cx, |this, cx| this.state.read(cx).ledger_pid()) else {
+                break;
+            };
+            let rss = match pid {
+                Some(pid) => {
+                    cx.background_executor()
+                        .spawn(async move { diagnostics::process_rss(pid) })
+                        .await
+                }
+                None => None,
+            };
+            let updated = this.update(cx, |this, cx| {
+                this.ledger_rss = rss;
+                cx.notify();
+            });
+            if updated.is_err() {
+                break;
+            }
+            cx.background_executor().timer(POLL_INTERVAL).await;
+        });
+        Self {
+            state,
+            ledger_rss: None,
+            _poll: poll,
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.)
+}
+
+impl Render for DiagnosticsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let model_size = diagnostics::model_size(&state.transactions)
+            + diagnostics::model_size(&state.hypothetical);
+        let reload = match (state.loading, state.loaded_at, state.load_duration) {
+            (true, _, _) => "Loading…".to_string(),
+            (false, Some(loaded_at), Some(took)) => format!(
+                "{} in {}",
+                loaded_at.format("%H:%M:%S"),
+                format_duration(took)
+            ),
+            _ => "Not loaded".to_string(),
+        };
+        let ledger = match (state.ledger_pid(), self.ledger_rss) {
+            (Some(pid), Some(rss)) => format!("{} (pid {pid})", diagnostics::format_bytes(rss)),
+            (Some(pid), None) => format!("pid {pid}"),
+            (None, _) => "Not running".to_string(),
+        };
+
+        let mut rows = vec![
+            (
+                "Transactions".to_string(),
+                format!(
+                    "{} + {} what-if",
+                    state.transactions.len(),
+                    state.hypothetical.len()
+                ),
+            ),
+            (
+                "Model memory".to_string(),
+                diagnostics::format_bytes(model_size as u64),
+            ),
+            ("Last reload".to_string(), reload),
+            ("Ledger process".to_string(), ledger),
+        ];
+        rows.extend(
+            state
+                .rebuild_timings
+                .iter()
+                .map(|(view, took)| (format!("{view} rebuild"), format_duration(*took))),
+        );
+        let muted = cx.theme().muted_foreground;
+
+        v_flex()
+            .w(px(280.))
+            .p_2()
+            .gap_1()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().popover)
+            .shadow_md()
+            .text_xs()
+            .child(div().text_sm().child("Diagnostics"))
+            .children(rows.into_iter().map(|(label, value)| {
+                h_flex()
+                    .gap_2()
+                    .child(div().flex_1().text_color(muted).child(label))
+                    .child(value)
+            }))
+    }
+}
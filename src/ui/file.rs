@@ -1,52 +1,458 @@
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 use gpui::*;
-use gpui_component::resizable::{h_resizable, resizable_panel};
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    resizable::{h_resizable, resizable_panel},
+    v_flex, Selectable as _, Sizable as _,
+};
+
+use crate::{accounts::Account, settings::SavedView};
 
 use super::{
-    accounts_tree::{self, AccountsTreeView},
+    accounts_tree::{AccountsTreeEvent, AccountsTreeView},
+    assertions::AssertionsView,
+    changelog::ChangelogView,
+    history::HistoryView,
+    paste::PasteView,
+    saved_views::{SavedViewsEvent, SavedViewsView},
     state::State,
-    transactions_register::RegisterView,
+    transactions_register::{RegisterEvent, RegisterSummary, RegisterView},
+    trash::TrashView,
+    what_if::WhatIfView,
 };
 
+const CONTEXT: &str = "LedgerFile";
+
+actions!(ledger_file, [PreviousPeriod, NextPeriod, Undo, Redo]);
+
+pub fn init(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("secondary-left", PreviousPeriod, Some(CONTEXT)),
+        KeyBinding::new("secondary-right", NextPeriod, Some(CONTEXT)),
+        KeyBinding::new("secondary-z", Undo, Some(CONTEXT)),
+        KeyBinding::new("secondary-shift-z", Redo, Some(CONTEXT)),
+    ]);
+}
+
+/// Tab shown in the middle panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Register,
+    /// Index into `tabs`.
+    Workspace(usize),
+    Assertions,
+    Trash,
+    History,
+    Changelog,
+    Paste,
+}
+
+/// A register opened next to the main one, with its own filters and period.
+struct RegisterTab {
+    /// Account the tab was opened for from the tree.
+    account: Option<Account>,
+    title: String,
+    view: Entity<RegisterView>,
+}
+
 pub struct LedgerFile {
     register_view: Entity<RegisterView>,
+    /// Registers shown as tabs next to the main one.
+    tabs: Vec<RegisterTab>,
+    active_tab: Tab,
+    assertions: Entity<AssertionsView>,
+    history: Entity<HistoryView>,
+    changelog: Entity<ChangelogView>,
+    paste: Entity<PasteView>,
+    trash: Entity<TrashView>,
     accounts_tree: Entity<AccountsTreeView>,
+    saved_views: Entity<SavedViewsView>,
+    what_if: Entity<WhatIfView>,
+    focus_handle: FocusHandle,
 
-    _state: Entity<State>,
+    state: Entity<State>,
 }
 
 impl LedgerFile {
-    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let state = cx.new(|cx| State::new(cx));
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
         let accounts_tree = cx.new(|cx| AccountsTreeView::new(state.clone(), cx));
         let register_view = cx.new(|cx| RegisterView::new(state.clone(), window, cx));
+        let what_if = cx.new(|cx| WhatIfView::new(state.clone(), window, cx));
+        let assertions = cx.new(|cx| AssertionsView::new(state.clone(), window, cx));
+        let history = cx.new(|cx| HistoryView::new(state.clone(), cx));
+        let changelog = cx.new(|cx| ChangelogView::new(state.clone(), cx));
+        let paste = cx.new(|cx| PasteView::new(state.clone(), window, cx));
+        let trash = cx.new(|cx| TrashView::new(state.clone(), cx));
+        let saved_views = cx.new(|cx| SavedViewsView::new(state.clone(), window, cx));
 
         cx.observe(&accounts_tree, |this, accounts_tree, cx| {
             accounts_tree.update(cx, |accounts_tree, cx| {
                 this.register_view.update(cx, |state, cx| {
-                    state.set_account_filter(accounts_tree.selected_accounts().clone(), cx);
+                    state.set_account_filter(accounts_tree.account_filter(), cx);
                 });
             })
         })
         .detach();
 
+        cx.subscribe_in(
+            &accounts_tree,
+            window,
+            |this, _, event, window, cx| match event {
+                AccountsTreeEvent::Open(account) => this.open_account_register(account, window, cx),
+            },
+        )
+        .detach();
+
+        Self::subscribe_register(&register_view, window, cx);
+
+        cx.subscribe_in(
+            &saved_views,
+            window,
+            |this, _, event, window, cx| match event {
+                SavedViewsEvent::Save(name) => this.save_view(name.clone(), cx),
+                SavedViewsEvent::Apply(view) => this.apply_view(view, window, cx),
+            },
+        )
+        .detach();
+
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+
         Self {
             accounts_tree,
+            saved_views,
             register_view,
-            _state: state,
+            tabs: Vec::new(),
+            active_tab: Tab::Register,
+            assertions,
+            history,
+            changelog,
+            paste,
+            trash,
+            what_if,
+            focus_handle,
+            state,
+        }
+    }
+
+    fn subscribe_register(
+        register: &Entity<RegisterView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // The window's status bar summarizes the active register
+        cx.observe(register, |_this, _register, cx| cx.notify())
+            .detach();
+        cx.subscribe_in(register, window, |this, _, event, window, cx| match event {
+            RegisterEvent::Duplicate(transaction) => {
+                this.what_if.update(cx, |what_if, cx| {
+                    what_if.fill_from(transaction, window, cx);
+                });
+            }
+            RegisterEvent::Edit(transaction) => {
+                this.what_if.update(cx, |what_if, cx| {
+                    what_if.edit(transaction, window, cx);
+                });
+            }
+            RegisterEvent::FilterAccount(account) => {
+                this.accounts_tree.update(cx, |accounts_tree, cx| {
+                    accounts_tree.select_only(account, cx);
+                });
+                // The tree filters the main register
+                this.active_tab = Tab::Register;
+                cx.notify();
+            }
+        })
+        .detach();
+    }
+
+    /// Saves the accounts selected and excluded in the tree, the period and the payee
+    /// and query filters of the main register.
+    fn save_view(&mut self, name: String, cx: &mut Context<Self>) {
+        let accounts = self.accounts_tree.read(cx).account_filter();
+        let register = self.register_view.read(cx);
+        let query = register.query_text(cx);
+        let view = SavedView {
+            name,
+            accounts: sorted_paths(&accounts.include),
+            excluded_accounts: sorted_paths(&accounts.exclude),
+            period: self.state.read(cx).period,
+            payee: register.payee_filter().map(str::to_string),
+            query: (!query.is_empty()).then_some(query),
+        };
+        self.saved_views
+            .update(cx, |saved_views, cx| saved_views.save(view, cx));
+    }
+
+    pub fn apply_view(&mut self, view: &SavedView, window: &mut Window, cx: &mut Context<Self>) {
+        self.accounts_tree.update(cx, |accounts_tree, cx| {
+            accounts_tree.set_account_filter(view.account_filter(), cx);
+        });
+        self.state
+            .update(cx, |state, cx| state.set_period(view.period, cx));
+        self.register_view.update(cx, |register, cx| {
+            register.set_payee_filter(view.payee.clone(), cx);
+            register.set_query_text(view.query.as_deref().unwrap_or_default(), window, cx);
+        });
+        self.active_tab = Tab::Register;
+        cx.notify();
+    }
+
+    pub fn open_account_register(
+        &mut self,
+        account: &Account,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let index = if let Some(index) = self
+            .tabs
+            .iter()
+            .position(|tab| tab.account.as_ref() == Some(account))
+        {
+            index
+        } else {
+            let state = self.state.clone();
+            let view = cx.new(|cx| RegisterView::for_account(state, account.clone(), window, cx));
+            Self::subscribe_register(&view, window, cx);
+            self.tabs.push(RegisterTab {
+                account: Some(account.clone()),
+                title: self.state.read(cx).account_names(cx).full_name(account),
+                view,
+            });
+            self.tabs.len() - 1
+        };
+        self.active_tab = Tab::Workspace(index);
+        cx.notify();
+    }
+
+    /// Opens a tab with the filters and period of the main register, to keep working
+    /// with them while the main register moves on.
+    fn open_workspace(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let main = self.register_view.read(cx);
+        let accounts = main.account_filter().clone();
+        let payee = main.payee_filter().map(str::to_string);
+        let query = main.query_text(cx);
+        let period = main.period(cx);
+
+        let names = self.state.read(cx).account_names(cx);
+        let mut title = accounts
+            .include
+            .iter()
+            .map(|account| names.full_name(account))
+            .collect::<Vec<_>>();
+        title.sort();
+        let title = match (title.is_empty(), &payee) {
+            (true, Some(payee)) => payee.clone(),
+            (true, None) => "All".to_string(),
+            (false, _) => title.join(", "),
+        };
+
+        let view = SavedView {
+            name: title,
+            accounts: sorted_paths(&accounts.include),
+            excluded_accounts: sorted_paths(&accounts.exclude),
+            period,
+            payee,
+            query: (!query.is_empty()).then_some(query),
+        };
+        self.open_register_tab(&view, window, cx);
+    }
+
+    /// Opens a tab named after the view, with its filters and period.
+    pub fn open_register_tab(
+        &mut self,
+        view: &SavedView,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let state = self.state.clone();
+        let register = cx.new(|cx| {
+            let mut register = RegisterView::new(state, window, cx);
+            register.set_account_filter(view.account_filter(), cx);
+            register.set_payee_filter(view.payee.clone(), cx);
+            register.set_query_text(view.query.as_deref().unwrap_or_default(), window, cx);
+            register.pin_period(view.period, cx);
+            register
+        });
+        Self::subscribe_register(&register, window, cx);
+        self.tabs.push(RegisterTab {
+            account: None,
+            title: view.name.clone(),
+            view: register,
+        });
+        self.active_tab = Tab::Workspace(self.tabs.len() - 1);
+        cx.notify();
+    }
+
+    fn close_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        self.tabs.remove(index);
+        self.active_tab = match self.active_tab {
+            Tab::Workspace(active) if active == index => Tab::Register,
+            Tab::Workspace(active) if active > index => Tab::Workspace(active - 1),
+            active => active,
+        };
+        cx.notify();
+    }
+
+    /// Register of the active tab, the main one when another kind of tab is shown.
+    fn active_register(&self) -> &Entity<RegisterView> {
+        match self.active_tab {
+            Tab::Workspace(ix) => &self.tabs[ix].view,
+            _ => &self.register_view,
+        }
+    }
+
+    /// Summary of the register shown, if a register tab is active.
+    pub fn summary(&self, cx: &App) -> Option<RegisterSummary> {
+        match self.active_tab {
+            Tab::Register | Tab::Workspace(_) => Some(self.active_register().read(cx).summary(cx)),
+            Tab::Assertions | Tab::Trash | Tab::History | Tab::Changelog | Tab::Paste => None,
         }
     }
+
+    fn render_register_tabs(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let tab = |id: &'static str, label: &'static str, tab: Tab| {
+            Button::new(id)
+                .label(label)
+                .xsmall()
+                .ghost()
+                .selected(self.active_tab == tab)
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                    this.active_tab = tab;
+                    cx.notify();
+                }))
+        };
+        h_flex()
+            .gap_1()
+            .px_2()
+            .child(tab("register-tab-all", "All", Tab::Register))
+            .children(
+                self.tabs
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, register_tab)| {
+                        h_flex()
+                            .child(
+                                Button::new(("register-tab", ix))
+                                    .label(register_tab.title.clone())
+                                    .xsmall()
+                                    .ghost()
+                                    .selected(self.active_tab == Tab::Workspace(ix))
+                                    .on_click(cx.listener(move |this, _, _window, cx| {
+                                        this.active_tab = Tab::Workspace(ix);
+                                        cx.notify();
+                                    })),
+                            )
+                            .child(
+                                Button::new(("register-tab-close", ix))
+                                    .label("×")
+                                    .xsmall()
+                                    .ghost()
+                                    .on_click(cx.listener(move |this, _, _window, cx| {
+                                        this.close_tab(ix, cx);
+                                    })),
+                            )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .child(
+                Button::new("register-tab-new")
+                    .label("+")
+                    .xsmall()
+                    .ghost()
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.open_workspace(window, cx);
+                    })),
+            )
+            .child(div().flex_1())
+            .child(tab("register-tab-assertions", "Checks", Tab::Assertions))
+            .child(tab("register-tab-trash", "Deleted", Tab::Trash))
+            .child(tab("register-tab-history", "History", Tab::History))
+            .child(tab("register-tab-changelog", "Changelog", Tab::Changelog))
+            .child(tab("register-tab-paste", "Paste", Tab::Paste))
+    }
+
+    fn previous_period(&mut self, _: &PreviousPeriod, _: &mut Window, cx: &mut Context<Self>) {
+        self.active_register().update(cx, |register, cx| {
+            register.set_period(register.period(cx).prev(), cx);
+        });
+    }
+
+    fn next_period(&mut self, _: &NextPeriod, _: &mut Window, cx: &mut Context<Self>) {
+        self.active_register().update(cx, |register, cx| {
+            register.set_period(register.period(cx).next(), cx);
+        });
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        self.state.update(cx, |state, cx| state.undo(cx));
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        self.state.update(cx, |state, cx| state.redo(cx));
+    }
+
+    /// Previews the entries in the files before they are appended to the journal.
+    pub fn preview_entries(
+        &mut self,
+        paths: &[PathBuf],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.paste.update(cx, |paste, cx| {
+            paste.load_files(paths, window, cx);
+        });
+        self.active_tab = Tab::Paste;
+        cx.notify();
+    }
+}
+
+/// Full paths of the accounts, sorted, as views keep them.
+fn sorted_paths(accounts: &HashSet<Account>) -> Vec<String> {
+    let mut paths = accounts.iter().map(ToString::to_string).collect::<Vec<_>>();
+    paths.sort();
+    paths
 }
 
 impl Render for LedgerFile {
-    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
-        h_resizable("ledger-register")
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let active: AnyView = match self.active_tab {
+            Tab::Register => self.register_view.clone().into(),
+            Tab::Workspace(ix) => self.tabs[ix].view.clone().into(),
+            Tab::Assertions => self.assertions.clone().into(),
+            Tab::Trash => self.trash.clone().into(),
+            Tab::History => self.history.clone().into(),
+            Tab::Changelog => self.changelog.clone().into(),
+            Tab::Paste => self.paste.clone().into(),
+        };
+        let register = v_flex()
+            .size_full()
+            .child(self.render_register_tabs(cx))
+            .child(active);
+
+        let panels = h_resizable("ledger-register")
             .child(
-                resizable_panel()
-                    .size(px(250.))
-                    .child(self.accounts_tree.clone()),
+                resizable_panel().size(px(250.)).child(
+                    v_flex()
+                        .size_full()
+                        .child(self.saved_views.clone())
+                        .child(div().flex_1().child(self.accounts_tree.clone())),
+                ),
             )
-            .child(resizable_panel().child(self.register_view.clone()))
+            .child(resizable_panel().child(register))
+            .child(resizable_panel().size(px(250.)).child(self.what_if.clone()));
+
+        div()
+            .size_full()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::previous_period))
+            .on_action(cx.listener(Self::next_period))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .child(panels)
     }
 }
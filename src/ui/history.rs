@@ -0,0 +1,100 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{button::Button, h_flex, v_flex, ActiveTheme, Disableable, Sizable as _};
+
+use super::state::State;
+
+/// Lists changes made to the journal files, newest first, with undone ones greyed out
+/// above them.
+pub struct HistoryView {
+    state: Entity<State>,
+}
+
+impl HistoryView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self { state }
+    }
+}
+
+impl Render for HistoryView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let history = &self.state.read(cx).history;
+        let can_undo = !history.undo_stack().is_empty();
+        let can_redo = !history.redo_stack().is_empty();
+        let entries = history
+            .redo_stack()
+            .iter()
+            .map(|operation| (operation, false))
+            .chain(
+                history
+                    .undo_stack()
+                    .iter()
+                    .rev()
+                    .map(|operation| (operation, true)),
+            )
+            .map(|(operation, applied)| {
+                let files = operation
+                    .edits
+                    .iter()
+                    .map(|edit| edit.file.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                v_flex()
+                    .px_2()
+                    .py_1()
+                    .when(!applied, |this| {
+                        this.text_color(cx.theme().muted_foreground)
+                    })
+                    .child(div().text_sm().child(operation.label.clone()))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(files),
+                    )
+            })
+            .collect::<Vec<_>>();
+        let empty = entries.is_empty();
+
+        v_flex()
+            .id("history")
+            .size_full()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().flex_1().text_sm().child("History"))
+                    .child(
+                        Button::new("history-undo")
+                            .label("Undo")
+                            .xsmall()
+                            .disabled(!can_undo)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.state.update(cx, |state, cx| state.undo(cx));
+                            })),
+                    )
+                    .child(
+                        Button::new("history-redo")
+                            .label("Redo")
+                            .xsmall()
+                            .disabled(!can_redo)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.state.update(cx, |state, cx| state.redo(cx));
+                            })),
+                    ),
+            )
+            .when(empty, |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("No changes to the journal yet"),
+                )
+            })
+            .children(entries)
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{button::Button, h_flex, v_flex, ActiveTheme, Sizable as _};
+
+/// Bank statements dropped on the window, waiting to be imported.
+pub struct ImportsView {
+    statements: Vec<PathBuf>,
+}
+
+impl ImportsView {
+    pub fn new() -> Self {
+        Self {
+            statements: Vec::new(),
+        }
+    }
+
+    pub fn add_statements(&mut self, paths: &[PathBuf], cx: &mut Context<Self>) {
+        for path in paths {
+            if !self.statements.contains(path) {
+                self.statements.push(path.clone());
+            }
+        }
+        cx.notify();
+    }
+}
+
+impl Render for ImportsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let empty = self.statements.is_empty();
+
+        v_flex()
+            .id("imports")
+            .size_full()
+            .gap_1()
+            .p_2()
+            .overflow_y_scroll()
+            .child(div().text_sm().child("Imports"))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(if empty {
+                        "Drop .csv or .ofx statements on the window to import them"
+                    } else {
+                        "Statements can't be turned into transactions yet"
+                    }),
+            )
+            .when(!empty, |this| {
+                this.children(
+                    self.statements
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, path)| {
+                            h_flex()
+                                .gap_2()
+                                .px_2()
+                                .py_1()
+                                .child(div().flex_1().text_sm().child(path.display().to_string()))
+                                .child(
+                                    Button::new(("imports-remove", ix))
+                                        .label("Remove")
+                                        .xsmall()
+                                        .on_click(cx.listener(move |this, _, _window, cx| {
+                                            this.statements.remove(ix);
+                                            cx.notify();
+                                        })),
+                                )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+    }
+}
@@ -0,0 +1,152 @@
+use fastnum::D128;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{h_flex, v_flex, ActiveTheme};
+
+use crate::{
+    loans::{self, RECENT_MONTHS},
+    transactions::CurrencyAmount,
+};
+
+use super::{components::Sparkline, state::State};
+
+/// Months of each loan's history listed.
+const HISTORY_MONTHS: usize = 12;
+
+/// Loans as of the selected period: principal left, interest paid and when they are
+/// paid off at the pace of recent payments.
+pub struct LoansView {
+    state: Entity<State>,
+}
+
+impl LoansView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self { state }
+    }
+}
+
+fn decimal_to_f64(value: D128) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+impl Render for LoansView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let names = state.account_names(cx);
+        let transactions = state.all_transactions();
+        let until = state.period.end().pred_opt().unwrap_or(state.period.start);
+        let loans = loans::loan_accounts(&transactions)
+            .iter()
+            .filter_map(|account| loans::track(&transactions, account, until))
+            .collect::<Vec<_>>();
+        let theme = cx.theme();
+
+        if loans.is_empty() {
+            return v_flex()
+                .id("loans")
+                .size_full()
+                .gap_1()
+                .p_2()
+                .child(div().text_sm().child("Loans"))
+                .child(div().text_xs().text_color(theme.muted_foreground).child(
+                    "No loans are tracked. Tag interest postings with the loan they belong \
+                     to, like loan: liabilities:mortgage.",
+                ));
+        }
+
+        v_flex()
+            .id("loans")
+            .size_full()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .children(loans.iter().map(|loan| {
+                let format = |value: D128| {
+                    formatter.format(&CurrencyAmount {
+                        value,
+                        commodity: loan.commodity.clone(),
+                    })
+                };
+                let payoff = if loan.principal() <= D128::ZERO {
+                    "Paid off".to_string()
+                } else if let Some(month) = loan.projected_payoff() {
+                    month.to_string()
+                } else {
+                    format!("No payments in the last {RECENT_MONTHS} months")
+                };
+                let figure = |label: &'static str, value: String| {
+                    v_flex()
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(theme.muted_foreground)
+                                .child(label),
+                        )
+                        .child(div().text_sm().child(value))
+                };
+                let history = loan.months.iter().rev().take(HISTORY_MONTHS).map(|month| {
+                    h_flex()
+                        .gap_2()
+                        .text_xs()
+                        .child(div().flex_1().child(month.month.to_string()))
+                        .child(div().w(px(110.)).text_right().child(format(month.repaid)))
+                        .child(div().w(px(110.)).text_right().child(format(month.interest)))
+                        .child(
+                            div()
+                                .w(px(110.))
+                                .text_right()
+                                .child(format(month.principal)),
+                        )
+                });
+
+                v_flex()
+                    .gap_1()
+                    .p_2()
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded_md()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .text_sm()
+                                    .child(names.full_name(&loan.account)),
+                            )
+                            .child(
+                                div().w(px(120.)).h(px(24.)).child(Sparkline::new(
+                                    loan.months
+                                        .iter()
+                                        .map(|month| decimal_to_f64(month.principal))
+                                        .collect(),
+                                    theme.chart_1,
+                                )),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_4()
+                            .child(figure("Principal left", format(loan.principal())))
+                            .child(figure("Interest paid", format(loan.interest_paid())))
+                            .child(figure("Projected payoff", payoff)),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .pt_1()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child(div().flex_1().child("Month"))
+                            .children(
+                                ["Repaid", "Interest", "Principal left"]
+                                    .map(|title| div().w(px(110.)).text_right().child(title)),
+                            ),
+                    )
+                    .children(history)
+            }))
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use fastnum::D128;
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputState},
+    v_flex, ActiveTheme, Selectable as _, Sizable as _,
+};
+
+use crate::{
+    new_journal::{NewJournal, Template},
+    settings::Settings,
+};
+
+pub enum NewJournalEvent {
+    /// The journal was written to the path.
+    Created(PathBuf),
+    Cancel,
+}
+
+/// Form picking the chart of accounts, commodity and opening balances of a new
+/// journal, then where to write it.
+pub struct NewJournalView {
+    template: Template,
+    commodity: Entity<InputState>,
+    /// Opening balance inputs of the template's asset and liability accounts.
+    balances: Vec<(&'static str, Entity<InputState>)>,
+    error: Option<String>,
+}
+
+impl EventEmitter<NewJournalEvent> for NewJournalView {}
+
+impl NewJournalView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let base_currency = cx.global::<Settings>().base_currency.clone();
+        let commodity = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("Commodity");
+            input.set_value(base_currency.unwrap_or_else(|| "USD".into()), window, cx);
+            input
+        });
+        let mut view = Self {
+            template: Template::default(),
+            commodity,
+            balances: Vec::new(),
+            error: None,
+        };
+        view.set_template(Template::default(), window, cx);
+        view
+    }
+
+    fn set_template(&mut self, template: Template, window: &mut Window, cx: &mut Context<Self>) {
+        self.template = template;
+        self.balances = template
+            .balance_accounts()
+            .map(|account| {
+                let input = cx.new(|cx| InputState::new(window, cx).placeholder("0"));
+                (account, input)
+            })
+            .collect();
+        cx.notify();
+    }
+
+    fn journal(&self, cx: &App) -> Result<NewJournal, String> {
+        let commodity = self.commodity.read(cx).value().trim().to_string();
+        if commodity.is_empty() {
+            return Err("A commodity is required".into());
+        }
+        let opening_balances = self
+            .balances
+            .iter()
+            .filter_map(|(account, input)| {
+                let value = input.read(cx).value().trim().replace(',', "");
+                if value.is_empty() {
+                    return None;
+                }
+                Some(
+                    value
+                        .parse::<D128>()
+                        .map(|balance| ((*account).to_string(), balance))
+                        .map_err(|e| format!("Invalid balance of {account}: {e}")),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(NewJournal {
+            template: self.template,
+            commodity,
+            date: chrono::Local::now().date_naive(),
+            opening_balances,
+        })
+    }
+
+    /// Asks where to write the journal and writes it there.
+    fn create(&mut self, cx: &mut Context<Self>) {
+        let journal = match self.journal(cx) {
+            Ok(journal) => journal,
+            Err(e) => {
+                self.error = Some(e);
+                cx.notify();
+                return;
+            }
+        };
+        self.error = None;
+
+        let directory = dirs::home_dir().unwrap_or_default();
+        let path = cx.prompt_for_new_path(&directory, Some("main.ledger"));
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+            let result = std::fs::write(&path, journal.scaffold());
+            this.update(cx, |this, cx| match result {
+                Ok(()) => cx.emit(NewJournalEvent::Created(path)),
+                Err(e) => {
+                    this.error = Some(format!("Failed to write {}: {e}", path.display()));
+                    cx.notify();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl Render for NewJournalView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let template_buttons = Template::ALL.into_iter().map(|template| {
+            Button::new(("new-journal-template", template as usize))
+                .label(template.label())
+                .xsmall()
+                .ghost()
+                .selected(self.template == template)
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    this.set_template(template, window, cx);
+                }))
+        });
+        let theme = cx.theme();
+
+        v_flex()
+            .gap_2()
+            .child(div().text_sm().child("New journal"))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().w(px(120.)).text_xs().child("Accounts for"))
+                    .children(template_buttons),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().w(px(120.)).text_xs().child("Commodity"))
+                    .child(div().w(px(90.)).child(Input::new(&self.commodity).small())),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(theme.muted_foreground)
+                    .child("Opening balances, as of today"),
+            )
+            .children(self.balances.iter().map(|(account, input)| {
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().w(px(200.)).text_xs().child(*account))
+                    .child(div().w(px(120.)).child(Input::new(input).small()))
+            }))
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().text_xs().text_color(theme.danger).child(error))
+            })
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("new-journal-create")
+                            .label("Create…")
+                            .small()
+                            .primary()
+                            .on_click(cx.listener(|this, _, _window, cx| this.create(cx))),
+                    )
+                    .child(
+                        Button::new("new-journal-cancel")
+                            .label("Cancel")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|_this, _, _window, cx| {
+                                cx.emit(NewJournalEvent::Cancel);
+                            })),
+                    ),
+            )
+    }
+}
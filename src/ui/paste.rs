@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex, ActiveTheme, Disableable, Sizable as _,
+};
+
+use crate::{settings::Settings, snippet};
+
+use super::state::State;
+
+/// Journal entries pasted or dropped into the app, previewed before they are appended
+/// to the journal.
+pub struct PasteView {
+    state: Entity<State>,
+    input: Entity<InputState>,
+    /// Why dropped files could not be read.
+    error: Option<String>,
+}
+
+impl PasteView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .rows(8)
+                .placeholder("Paste journal entries, or drop a file with them")
+        });
+        // The preview follows the text as it is typed
+        cx.subscribe(&input, |_this, _, event: &InputEvent, cx| {
+            if matches!(event, InputEvent::Change) {
+                cx.notify();
+            }
+        })
+        .detach();
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self {
+            state,
+            input,
+            error: None,
+        }
+    }
+
+    fn set_text(&mut self, text: String, window: &mut Window, cx: &mut Context<Self>) {
+        self.input
+            .update(cx, |input, cx| input.set_value(text, window, cx));
+        cx.notify();
+    }
+
+    fn paste(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) {
+            self.error = None;
+            self.set_text(text, window, cx);
+        }
+    }
+
+    /// Fills the text with the contents of the files, one after another.
+    pub fn load_files(&mut self, paths: &[PathBuf], window: &mut Window, cx: &mut Context<Self>) {
+        let texts = paths
+            .iter()
+            .map(|path| {
+                std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read {}: {e}", path.display()))
+            })
+            .collect::<Result<Vec<_>, _>>();
+        match texts {
+            Ok(texts) => {
+                self.error = None;
+                self.set_text(texts.join("\n"), window, cx);
+            }
+            Err(e) => {
+                self.error = Some(e);
+                cx.notify();
+            }
+        }
+    }
+
+    fn append(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.input.read(cx).value().to_string();
+        let Ok(transactions) = snippet::parse(&text) else {
+            return;
+        };
+        self.state.update(cx, |state, cx| {
+            state.append_entries(&text, transactions.len(), cx);
+        });
+        self.set_text(String::new(), window, cx);
+    }
+}
+
+impl Render for PasteView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let date_format = cx.global::<Settings>().date_format.clone();
+        let formatter = self.state.read(cx).amount_formatter(cx);
+        let text = self.input.read(cx).value();
+        let parsed = (!text.trim().is_empty()).then(|| snippet::parse(&text));
+        let ready = matches!(parsed, Some(Ok(_)));
+        let theme = cx.theme();
+
+        let preview = match parsed {
+            None => None,
+            Some(Err(e)) => Some(
+                div()
+                    .text_xs()
+                    .text_color(theme.danger)
+                    .child(e.to_string())
+                    .into_any_element(),
+            ),
+            Some(Ok(transactions)) => Some(
+                v_flex()
+                    .gap_2()
+                    .children(transactions.iter().map(|transaction| {
+                        v_flex()
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .text_sm()
+                                    .child(transaction.time.format(&date_format).to_string())
+                                    .child(transaction.description.clone()),
+                            )
+                            .children(transaction.postings.iter().map(|posting| {
+                                h_flex()
+                                    .gap_2()
+                                    .pl_4()
+                                    .text_xs()
+                                    .child(div().flex_1().child(posting.account.to_string()))
+                                    .child(formatter.format_amount(&posting.amount))
+                            }))
+                    }))
+                    .into_any_element(),
+            ),
+        };
+
+        v_flex()
+            .id("paste")
+            .size_full()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().flex_1().text_sm().child("Paste entries"))
+                    .child(
+                        Button::new("paste-clipboard")
+                            .label("Paste from clipboard")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, window, cx| this.paste(window, cx))),
+                    )
+                    .child(
+                        Button::new("paste-append")
+                            .label("Append to journal")
+                            .xsmall()
+                            .primary()
+                            .disabled(!ready)
+                            .on_click(cx.listener(|this, _, window, cx| this.append(window, cx))),
+                    ),
+            )
+            .child(Input::new(&self.input))
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().text_xs().text_color(theme.danger).child(error))
+            })
+            .children(preview)
+    }
+}
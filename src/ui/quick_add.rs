@@ -0,0 +1,270 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex, ActiveTheme, Root, Sizable as _,
+};
+
+use crate::{
+    accounts::Account,
+    settings::Settings,
+    transactions::{Amount, CurrencyAmount, Posting, PostingStatus, Transaction},
+    xact,
+};
+
+use super::{
+    components::{AccountPicker, AccountPickerEvent},
+    settings,
+    state::{State, StateEvent},
+};
+
+/// Opens the small quick-add window on top of the others, adding to the journal of
+/// `state` or, without one, to the default journal.
+pub fn open_window(state: Option<Entity<State>>, cx: &mut App) {
+    cx.open_window(
+        WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("Quick add".into()),
+                ..TitlebarOptions::default()
+            }),
+            window_bounds: Some(WindowBounds::centered(size(px(420.), px(240.)), cx)),
+            kind: WindowKind::PopUp,
+            ..WindowOptions::default()
+        },
+        |window, cx| {
+            let state = state.unwrap_or_else(|| cx.new(|cx| State::new(None, cx)));
+            let view = cx.new(|cx| QuickAddView::new(state, window, cx));
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    )
+    .map_err(|e| {
+        eprintln!("Error opening quick add window: {}", e);
+    })
+    .ok();
+}
+
+/// Payee, amount and account of a transaction dated today, appended to the journal
+/// before the window closes. A known payee fills the form from their last transaction,
+/// like `ledger xact`.
+pub struct QuickAddView {
+    /// Journal the transaction goes to, and accounts to suggest.
+    state: Entity<State>,
+    payee: Entity<InputState>,
+    amount: Entity<InputState>,
+    account: Entity<AccountPicker>,
+    /// Account paying the amount.
+    from: Entity<AccountPicker>,
+    /// Payee as typed and the draft made from their last transaction.
+    draft: Option<(String, Transaction)>,
+    /// Account the transaction being written is paid from, remembered once it is.
+    adding: Option<String>,
+    error: Option<String>,
+}
+
+impl QuickAddView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let payee = cx.new(|cx| InputState::new(window, cx).placeholder("Payee"));
+        let amount = cx.new(|cx| InputState::new(window, cx).placeholder("Amount"));
+        let account = cx.new(|cx| AccountPicker::new(state.clone(), "Account", window, cx));
+        let from = cx.new(|cx| AccountPicker::new(state.clone(), "Paid from", window, cx));
+        let paid_from = Account::parse(&cx.global::<Settings>().quick_add_from);
+        from.update(cx, |picker, cx| {
+            picker.set_account(Some(&paid_from), window, cx);
+        });
+
+        for input in [&payee, &amount] {
+            cx.subscribe(input, |this, _, event, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    this.add(cx);
+                }
+            })
+            .detach();
+        }
+        cx.subscribe_in(&payee, window, |this, _, event, window, cx| {
+            if matches!(event, InputEvent::Blur) {
+                this.fill_from_history(window, cx);
+            }
+        })
+        .detach();
+        for picker in [&account, &from] {
+            cx.subscribe(picker, |this, _, _: &AccountPickerEvent, cx| this.add(cx))
+                .detach();
+        }
+        cx.subscribe_in(
+            &state,
+            window,
+            |this, _, event: &StateEvent, window, cx| match event {
+                StateEvent::JournalWritten => {
+                    if let Some(from) = this.adding.take() {
+                        if let Err(e) =
+                            settings::update(cx, |settings| settings.quick_add_from = from)
+                        {
+                            eprintln!("Error saving settings: {}", e);
+                        }
+                        window.remove_window();
+                    }
+                }
+                StateEvent::Error(e) => {
+                    if this.adding.take().is_some() {
+                        this.error = Some(e.clone());
+                        cx.notify();
+                    }
+                }
+                _ => {}
+            },
+        )
+        .detach();
+        window.focus(&payee.read(cx).focus_handle(cx));
+
+        Self {
+            state,
+            payee,
+            amount,
+            account,
+            from,
+            draft: None,
+            adding: None,
+            error: None,
+        }
+    }
+
+    /// Fills the accounts, and the amount when it is empty, from the payee's most recent
+    /// transaction.
+    fn fill_from_history(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let payee = self.payee.read(cx).value().trim().to_string();
+        if self
+            .draft
+            .as_ref()
+            .is_some_and(|(typed, _)| *typed == payee)
+        {
+            return;
+        }
+        let today = chrono::Local::now().date_naive();
+        let draft = xact::draft(&self.state.read(cx).all_transactions(), &payee, today);
+        if let Some(draft) = &draft {
+            if let (Some(first), Some(last)) = (draft.postings.first(), draft.postings.last()) {
+                self.account.update(cx, |picker, cx| {
+                    picker.set_account(Some(&first.account), window, cx);
+                });
+                self.from.update(cx, |picker, cx| {
+                    picker.set_account(Some(&last.account), window, cx);
+                });
+                if self.amount.read(cx).value().trim().is_empty() {
+                    let amount = first.amount.value.to_string();
+                    self.amount
+                        .update(cx, |input, cx| input.set_value(amount, window, cx));
+                }
+            }
+        }
+        self.draft = draft.map(|draft| (payee, draft));
+        cx.notify();
+    }
+
+    fn transaction(&self, cx: &App) -> Result<Transaction, String> {
+        let payee = self.payee.read(cx).value().trim().to_string();
+        if payee.is_empty() {
+            return Err("A payee is required".into());
+        }
+        let amount = Amount::parse(&self.amount.read(cx).value())
+            .map_err(|e| format!("Invalid amount: {e}"))?;
+        let account = self
+            .account
+            .read(cx)
+            .account(cx)
+            .ok_or("An account is required")?;
+        let from = self
+            .from
+            .read(cx)
+            .account(cx)
+            .ok_or("An account to pay from is required")?;
+        let today = chrono::Local::now().date_naive();
+        // The payee's split is kept as long as its accounts are
+        if let Some((_, draft)) = self.draft.as_ref().filter(|(typed, draft)| {
+            *typed == payee
+                && draft.postings.first().map(|posting| &posting.account) == Some(&account)
+                && draft.postings.last().map(|posting| &posting.account) == Some(&from)
+        }) {
+            return Ok(Transaction {
+                time: today,
+                ..xact::with_amount(draft, &amount.value)
+            });
+        }
+        let paid = Amount {
+            value: CurrencyAmount {
+                value: -amount.value.value,
+                commodity: amount.value.commodity.clone(),
+            },
+            price: None,
+            date: None,
+            cost: None,
+        };
+        let posting = |account, amount| Posting {
+            account,
+            amount,
+            status: PostingStatus::Uncleared,
+            note: None,
+        };
+        Ok(Transaction {
+            file: std::path::PathBuf::new(),
+            line: 0,
+            time: today,
+            description: payee,
+            postings: vec![posting(account, amount), posting(from, paid)],
+        })
+    }
+
+    /// Appends the transaction to the journal and closes the window once it is written.
+    fn add(&mut self, cx: &mut Context<Self>) {
+        if self.adding.is_some() {
+            return;
+        }
+        let transaction = match self.transaction(cx) {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                self.error = Some(e);
+                cx.notify();
+                return;
+            }
+        };
+        self.error = None;
+        self.adding = Some(transaction.postings[1].account.to_string());
+        let label = format!("Add {}", transaction.description);
+        self.state.update(cx, |state, cx| {
+            state.append_transaction(&transaction, label, cx);
+        });
+        cx.notify();
+    }
+}
+
+impl Render for QuickAddView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .gap_2()
+            .p_3()
+            .bg(cx.theme().background)
+            .child(Input::new(&self.payee))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().w(px(120.)).child(Input::new(&self.amount)))
+                    .child(div().flex_1().child(self.account.clone())),
+            )
+            .child(self.from.clone())
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().text_xs().text_color(cx.theme().danger).child(error))
+            })
+            .child(
+                h_flex().justify_end().child(
+                    Button::new("quick-add")
+                        .label("Add")
+                        .small()
+                        .primary()
+                        .on_click(cx.listener(|this, _, _, cx| this.add(cx))),
+                ),
+            )
+    }
+}
@@ -0,0 +1,161 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Sizable as _,
+};
+
+use crate::{
+    accounts::Balance,
+    receivables::{self, AgeBucket, OpenReceivable},
+};
+
+use super::{components::AccountPicker, state::State};
+
+/// Open receivables by how long they have been owed, each with a button appending the
+/// payment that settles it.
+pub struct ReceivablesView {
+    state: Entity<State>,
+    /// Account payments go to.
+    deposit: Entity<AccountPicker>,
+    error: Option<String>,
+}
+
+impl ReceivablesView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        let deposit = cx.new(|cx| AccountPicker::new(state.clone(), "Paid into", window, cx));
+        Self {
+            state,
+            deposit,
+            error: None,
+        }
+    }
+
+    fn mark_paid(&mut self, receivable: &OpenReceivable, cx: &mut Context<Self>) {
+        let Some(deposit) = self.deposit.read(cx).account(cx) else {
+            self.error = Some("Pick the account the payment went to".into());
+            cx.notify();
+            return;
+        };
+        self.error = None;
+        let today = chrono::Local::now().date_naive();
+        let settlement = receivable.settlement(today, &deposit);
+        let label = format!("Mark {} paid", receivable.description);
+        self.state.update(cx, |state, cx| {
+            state.append_transaction(&settlement, label, cx);
+        });
+        cx.notify();
+    }
+}
+
+impl Render for ReceivablesView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let names = state.account_names(cx);
+        let open = receivables::open_receivables(&state.all_transactions());
+        let today = chrono::Local::now().date_naive();
+        let muted = cx.theme().muted_foreground;
+        let danger = cx.theme().danger;
+
+        let totals = AgeBucket::ALL.map(|bucket| {
+            let mut total = Balance::new();
+            for receivable in open.iter().filter(|r| r.bucket(today) == bucket) {
+                total.add_amount(receivable.amount.clone());
+            }
+            v_flex()
+                .child(div().text_xs().text_color(muted).child(bucket.label()))
+                .child(div().text_sm().child(formatter.format_balance(&total)))
+        });
+
+        let mut rows = Vec::new();
+        for bucket in AgeBucket::ALL {
+            let in_bucket = open
+                .iter()
+                .enumerate()
+                .filter(|(_, receivable)| receivable.bucket(today) == bucket)
+                .collect::<Vec<_>>();
+            if in_bucket.is_empty() {
+                continue;
+            }
+            rows.push(
+                div()
+                    .pt_2()
+                    .text_xs()
+                    .text_color(muted)
+                    .child(bucket.label()),
+            );
+            for (ix, receivable) in in_bucket {
+                let paid = receivable.clone();
+                rows.push(
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .text_sm()
+                        .child(
+                            div()
+                                .w(px(90.))
+                                .child(receivable.date.format("%Y-%m-%d").to_string()),
+                        )
+                        .child(
+                            div()
+                                .w(px(180.))
+                                .child(names.full_name(&receivable.account)),
+                        )
+                        .child(div().flex_1().child(receivable.description.clone()))
+                        .child(
+                            div()
+                                .w(px(70.))
+                                .text_right()
+                                .text_color(muted)
+                                .child(format!("{} days", receivable.age(today))),
+                        )
+                        .child(
+                            div()
+                                .w(px(110.))
+                                .text_right()
+                                .child(formatter.format(&receivable.amount)),
+                        )
+                        .child(
+                            Button::new(("receivable-paid", ix))
+                                .label("Mark paid")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.mark_paid(&paid, cx);
+                                })),
+                        ),
+                );
+            }
+        }
+
+        v_flex()
+            .id("receivables")
+            .size_full()
+            .gap_1()
+            .p_2()
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(div().flex_1().text_sm().child("Receivables"))
+                    .child(div().w(px(240.)).child(self.deposit.clone())),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().text_xs().text_color(danger).child(error))
+            })
+            .child(h_flex().gap_4().children(totals))
+            .when(open.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(muted)
+                        .child("Nothing is owed under assets:receivable"),
+                )
+            })
+            .children(rows)
+    }
+}
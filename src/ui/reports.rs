@@ -0,0 +1,129 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Sizable as _,
+};
+
+use crate::{accounts::Balance, reports::period_totals};
+
+use super::{start_file::StartFileView, state::State, transfers::TransfersView};
+
+/// Accounts shown in the income statement, by their top-level name.
+const INCOME_STATEMENT: [&str; 2] = ["income", "expenses"];
+
+/// Income and expenses of the selected period, by account, the transfers left out of
+/// them, and closing the year into a new file.
+pub struct ReportsView {
+    state: Entity<State>,
+    transfers: Entity<TransfersView>,
+    start_file: Entity<StartFileView>,
+}
+
+impl ReportsView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        let transfers = cx.new(|cx| TransfersView::new(state.clone(), cx));
+        let start_file = cx.new(|cx| StartFileView::new(state.clone(), window, cx));
+        Self {
+            state,
+            transfers,
+            start_file,
+        }
+    }
+}
+
+impl Render for ReportsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let names = state.account_names(cx);
+        let period = state.period;
+        let transactions = state.reportable_transactions();
+        let accounts = period_totals(&transactions, period, &INCOME_STATEMENT, 2);
+        let totals = period_totals(&transactions, period, &INCOME_STATEMENT, 1);
+
+        let row = |label: String, balance: &Balance| {
+            h_flex()
+                .gap_2()
+                .px_2()
+                .child(div().flex_1().text_sm().child(label))
+                .child(div().text_sm().child(formatter.format_balance(balance)))
+        };
+
+        v_flex()
+            .id("reports")
+            .size_full()
+            .gap_1()
+            .p_2()
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(div().flex_1().text_sm().child("Income and expenses"))
+                    .child(
+                        Button::new("reports-previous-period")
+                            .label("‹")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.state.update(cx, |state, cx| {
+                                    state.set_period(state.period.prev(), cx);
+                                });
+                            })),
+                    )
+                    .child(div().text_sm().child(period.to_string()))
+                    .child(
+                        Button::new("reports-next-period")
+                            .label("›")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.state.update(cx, |state, cx| {
+                                    state.set_period(state.period.next(), cx);
+                                });
+                            })),
+                    ),
+            )
+            .when(accounts.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("No income or expenses in this period"),
+                )
+            })
+            .children(
+                accounts
+                    .iter()
+                    .map(|(account, balance)| row(names.full_name(account), balance))
+                    .collect::<Vec<_>>(),
+            )
+            .child(div().border_t_1().border_color(cx.theme().border))
+            .children(
+                totals
+                    .iter()
+                    .map(|(account, balance)| {
+                        row(format!("Total {}", names.full_name(account)), balance)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .child(
+                div()
+                    .pt_4()
+                    .mt_2()
+                    .border_t_1()
+                    .border_color(cx.theme().border)
+                    .child(self.transfers.clone()),
+            )
+            .child(
+                div()
+                    .pt_4()
+                    .mt_2()
+                    .border_t_1()
+                    .border_color(cx.theme().border)
+                    .child(self.start_file.clone()),
+            )
+    }
+}
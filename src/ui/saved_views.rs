@@ -0,0 +1,165 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex, ActiveTheme, Disableable as _, Sizable as _,
+};
+
+use crate::settings::{SavedView, Settings};
+
+use super::state::State;
+
+pub enum SavedViewsEvent {
+    /// Save the current filters under this name.
+    Save(String),
+    /// Restore the filters of a saved view.
+    Apply(SavedView),
+}
+
+/// Named register filters saved for the open journal, listed above the accounts.
+pub struct SavedViewsView {
+    state: Entity<State>,
+    name: Entity<InputState>,
+    error: Option<String>,
+}
+
+impl EventEmitter<SavedViewsEvent> for SavedViewsView {}
+
+impl SavedViewsView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let name = cx.new(|cx| InputState::new(window, cx).placeholder("Name of this view"));
+
+        cx.subscribe_in(&name, window, |this, _, event, window, cx| match event {
+            InputEvent::PressEnter { .. } => this.request_save(window, cx),
+            InputEvent::Change => cx.notify(),
+            _ => {}
+        })
+        .detach();
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        cx.observe_global::<Settings>(|_this, cx| cx.notify())
+            .detach();
+
+        Self {
+            state,
+            name,
+            error: None,
+        }
+    }
+
+    /// Views are saved by the journal they were made for.
+    fn journal_key(&self, cx: &App) -> String {
+        self.state
+            .read(cx)
+            .journal_file()
+            .map(|journal| journal.display().to_string())
+            .unwrap_or_default()
+    }
+
+    fn views(&self, cx: &App) -> Vec<SavedView> {
+        cx.global::<Settings>()
+            .saved_views
+            .get(&self.journal_key(cx))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn request_save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.name.read(cx).value().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        self.name
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        cx.emit(SavedViewsEvent::Save(name));
+    }
+
+    /// Stores the view, replacing one with the same name.
+    pub fn save(&mut self, view: SavedView, cx: &mut Context<Self>) {
+        self.update_views(cx, |views| {
+            match views.iter_mut().find(|v| v.name == view.name) {
+                Some(existing) => *existing = view,
+                None => views.push(view),
+            }
+        });
+    }
+
+    fn remove(&mut self, name: &str, cx: &mut Context<Self>) {
+        self.update_views(cx, |views| views.retain(|view| view.name != name));
+    }
+
+    fn update_views(&mut self, cx: &mut Context<Self>, f: impl FnOnce(&mut Vec<SavedView>)) {
+        let journal = self.journal_key(cx);
+        self.error = super::settings::update(cx, |settings| {
+            let views = settings.saved_views.entry(journal.clone()).or_default();
+            f(views);
+            if views.is_empty() {
+                settings.saved_views.remove(&journal);
+            }
+        })
+        .err();
+        cx.notify();
+    }
+}
+
+impl Render for SavedViewsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let views = self.views(cx);
+        let can_save = !self.name.read(cx).value().trim().is_empty();
+
+        v_flex()
+            .gap_1()
+            .p_2()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(div().text_sm().child("Views"))
+            .children(
+                views
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, view)| {
+                        let name = view.name.clone();
+                        h_flex()
+                            .child(
+                                Button::new(("saved-view", ix))
+                                    .label(view.name.clone())
+                                    .xsmall()
+                                    .ghost()
+                                    .flex_1()
+                                    .on_click(cx.listener(move |_this, _, _window, cx| {
+                                        cx.emit(SavedViewsEvent::Apply(view.clone()));
+                                    })),
+                            )
+                            .child(
+                                Button::new(("saved-view-remove", ix))
+                                    .label("×")
+                                    .xsmall()
+                                    .ghost()
+                                    .on_click(cx.listener(move |this, _, _window, cx| {
+                                        this.remove(&name, cx);
+                                    })),
+                            )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(div().flex_1().child(Input::new(&self.name).small()))
+                    .child(
+                        Button::new("saved-view-save")
+                            .label("Save")
+                            .xsmall()
+                            .disabled(!can_save)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.request_save(window, cx);
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().text_xs().text_color(cx.theme().danger).child(error))
+            })
+    }
+}
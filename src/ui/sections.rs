@@ -0,0 +1,45 @@
+use crate::i18n::{Language, Message};
+
+/// Section of the window picked in the sidebar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Dashboard,
+    Register,
+    Balances,
+    Budget,
+    Loans,
+    Receivables,
+    Reports,
+    Imports,
+    /// A report from the registry, by name.
+    Report(&'static str),
+}
+
+impl Section {
+    pub const ALL: [Section; 8] = [
+        Section::Dashboard,
+        Section::Register,
+        Section::Balances,
+        Section::Budget,
+        Section::Loans,
+        Section::Receivables,
+        Section::Reports,
+        Section::Imports,
+    ];
+
+    pub fn label(self, language: Language) -> &'static str {
+        let message = match self {
+            Section::Dashboard => Message::Dashboard,
+            Section::Register => Message::Register,
+            Section::Balances => Message::Balances,
+            Section::Budget => Message::Budget,
+            Section::Loans => Message::Loans,
+            Section::Receivables => Message::Receivables,
+            Section::Reports => Message::Reports,
+            Section::Imports => Message::Imports,
+            // Reports are named by whoever wrote them
+            Section::Report(name) => return name,
+        };
+        language.text(message)
+    }
+}
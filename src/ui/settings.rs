@@ -0,0 +1,512 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputState},
+    v_flex, ActiveTheme, Root,
+};
+
+use crate::alerts::AlertRule;
+use crate::amount_format::{NegativeStyle, SymbolPosition};
+use crate::expr::Expr;
+use crate::i18n::Language;
+use crate::settings::{ComputedColumn, Settings, ThemePreference};
+
+impl Global for Settings {}
+
+/// Loads settings from disk into the global, falling back to defaults.
+pub fn init(cx: &mut App) {
+    let settings = Settings::load().unwrap_or_else(|e| {
+        eprintln!("Error loading settings: {}", e);
+        Settings::default()
+    });
+    cx.set_global(settings);
+}
+
+/// Changes the global settings and writes them to disk.
+///
+/// Views observing the `Settings` global are notified.
+pub fn update(cx: &mut App, f: impl FnOnce(&mut Settings)) -> Result<(), String> {
+    let mut settings = cx.global::<Settings>().clone();
+    f(&mut settings);
+    let result = settings.save().map_err(|e| e.to_string());
+    cx.set_global(settings);
+    result
+}
+
+pub fn open_window(cx: &mut App) {
+    cx.open_window(
+        WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("Settings".into()),
+                ..TitlebarOptions::default()
+            }),
+            window_bounds: Some(WindowBounds::centered(size(px(520.), px(760.)), cx)),
+            ..WindowOptions::default()
+        },
+        |window, cx| {
+            let view = cx.new(|cx| SettingsView::new(window, cx));
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    )
+    .map_err(|e| {
+        eprintln!("Error opening settings window: {}", e);
+    })
+    .ok();
+}
+
+/// Form editing the persistent settings.
+pub struct SettingsView {
+    ledger_path: Entity<InputState>,
+    journal: Entity<InputState>,
+    base_currency: Entity<InputState>,
+    date_format: Entity<InputState>,
+    decimal_places: Entity<InputState>,
+    commodity_decimal_places: Entity<InputState>,
+    thousands_separator: Entity<InputState>,
+    decimal_separator: Entity<InputState>,
+    symbol_position: SymbolPosition,
+    negative_style: NegativeStyle,
+    red_negatives: bool,
+    lenient_parsing: bool,
+    repeat_transaction_headers: bool,
+    striped_rows: bool,
+    transaction_lines: bool,
+    commodity_aliases: Entity<InputState>,
+    account_names: Entity<InputState>,
+    cpi_file: Entity<InputState>,
+    price_db: Entity<InputState>,
+    series_colors: Entity<InputState>,
+    account_colors: Entity<InputState>,
+    quick_add_hotkey: Entity<InputState>,
+    api_socket: Entity<InputState>,
+    computed_columns: Entity<InputState>,
+    alert_rules: Entity<InputState>,
+    theme: ThemePreference,
+    language: Language,
+    status: Option<Result<(), String>>,
+}
+
+impl SettingsView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let settings = cx.global::<Settings>().clone();
+        let mut input = |value: String, placeholder: &'static str| {
+            cx.new(|cx| {
+                let mut input = InputState::new(window, cx).placeholder(placeholder);
+                input.set_value(value, window, cx);
+                input
+            })
+        };
+        let path_string =
+            |path: Option<&PathBuf>| path.map(|p| p.display().to_string()).unwrap_or_default();
+
+        Self {
+            ledger_path: input(path_string(Some(&settings.ledger_path)), "ledger"),
+            journal: input(
+                path_string(settings.journal.as_ref()),
+                "ledger default (LEDGER_FILE, ~/.ledgerrc)",
+            ),
+            base_currency: input(settings.base_currency.clone().unwrap_or_default(), "None"),
+            date_format: input(settings.date_format.clone(), "%Y-%m-%d"),
+            decimal_places: input(settings.decimal_places.to_string(), "2"),
+            commodity_decimal_places: input(
+                format_pairs(&settings.amount_format.decimal_places),
+                "BTC=8, JPY=0",
+            ),
+            thousands_separator: input(settings.amount_format.thousands_separator.clone(), "None"),
+            decimal_separator: input(settings.amount_format.decimal_separator.clone(), "."),
+            symbol_position: settings.amount_format.symbol_position,
+            negative_style: settings.amount_format.negative_style,
+            red_negatives: settings.amount_format.red_negatives,
+            lenient_parsing: settings.lenient_parsing,
+            repeat_transaction_headers: settings.repeat_transaction_headers,
+            striped_rows: settings.striped_rows,
+            transaction_lines: settings.transaction_lines,
+            commodity_aliases: input(
+                format_pairs(&settings.commodity_aliases),
+                "USD=USD, VWCE=All-World",
+            ),
+            account_names: input(
+                format_pairs(&settings.account_names),
+                "assets:bank:nordea:1234=Nordea checking",
+            ),
+            cpi_file: input(path_string(settings.cpi_file.as_ref()), "Built-in US CPI"),
+            price_db: input(path_string(settings.price_db.as_ref()), "None"),
+            series_colors: input(
+                format_pairs(&settings.series_colors),
+                "USD=#4e79a7, EUR=#f28e2b",
+            ),
+            account_colors: input(
+                format_pairs(&settings.account_colors),
+                "assets:bank=#4e79a7, expenses=#e15759",
+            ),
+            quick_add_hotkey: input(
+                settings.quick_add_hotkey.clone().unwrap_or_default(),
+                "Off, e.g. secondary-shift-a",
+            ),
+            api_socket: input(
+                path_string(settings.api_socket.as_ref()),
+                "Off, e.g. /tmp/ledger-desktop.sock",
+            ),
+            computed_columns: input(
+                format_columns(&settings.computed_columns),
+                "In EUR=value(\"EUR\"); Weekday=weekday",
+            ),
+            alert_rules: input(
+                settings.alert_rules.join("; "),
+                "expenses:dining > budget; assets:checking < 500 SEK",
+            ),
+            theme: settings.theme,
+            language: settings.language,
+            status: None,
+        }
+    }
+
+    /// Switches to the next language, filling in its usual date and amount formats.
+    fn cycle_language(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.language = self.language.next();
+        let format = self
+            .language
+            .amount_format(&cx.global::<Settings>().amount_format);
+        let values = [
+            (&self.date_format, self.language.date_format().to_string()),
+            (&self.thousands_separator, format.thousands_separator),
+            (&self.decimal_separator, format.decimal_separator),
+        ];
+        for (input, value) in values {
+            input.update(cx, |input, cx| input.set_value(value, window, cx));
+        }
+        cx.notify();
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let text = |input: &Entity<InputState>| input.read(cx).value().trim().to_string();
+        let optional = |value: String| (!value.is_empty()).then_some(value);
+
+        let Ok(decimal_places) = text(&self.decimal_places).parse::<usize>() else {
+            self.status = Some(Err("Decimal places must be a whole number".into()));
+            cx.notify();
+            return;
+        };
+        let Ok(commodity_decimal_places) = parse_pairs(&text(&self.commodity_decimal_places))
+            .into_iter()
+            .map(|(commodity, places)| Ok((commodity, places.parse::<usize>()?)))
+            .collect::<Result<BTreeMap<_, _>, std::num::ParseIntError>>()
+        else {
+            self.status = Some(Err("Commodity decimal places must be whole numbers".into()));
+            cx.notify();
+            return;
+        };
+
+        let ledger_path = optional(text(&self.ledger_path)).unwrap_or_else(|| "ledger".into());
+        let journal = optional(text(&self.journal)).map(PathBuf::from);
+        let base_currency = optional(text(&self.base_currency));
+        let date_format = optional(text(&self.date_format)).unwrap_or_else(|| "%Y-%m-%d".into());
+        let cpi_file = optional(text(&self.cpi_file)).map(PathBuf::from);
+        let price_db = optional(text(&self.price_db)).map(PathBuf::from);
+        let commodity_aliases = parse_pairs(&text(&self.commodity_aliases));
+        let account_names = parse_pairs(&text(&self.account_names));
+        let series_colors = parse_pairs(&text(&self.series_colors));
+        let account_colors = parse_pairs(&text(&self.account_colors));
+        let quick_add_hotkey = optional(text(&self.quick_add_hotkey));
+        let api_socket = optional(text(&self.api_socket)).map(PathBuf::from);
+        if let Some(Err(e)) = quick_add_hotkey.as_ref().map(|hotkey| {
+            hotkey
+                .split_whitespace()
+                .try_for_each(|key| Keystroke::parse(key).map(drop))
+        }) {
+            self.status = Some(Err(format!("Invalid quick add hotkey: {e}")));
+            cx.notify();
+            return;
+        }
+        // Formatting a date with an invalid pattern panics
+        if chrono::format::StrftimeItems::new(&date_format)
+            .any(|item| item == chrono::format::Item::Error)
+        {
+            self.status = Some(Err(format!("Invalid date format: {date_format}")));
+            cx.notify();
+            return;
+        }
+        let computed_columns = parse_columns(&text(&self.computed_columns));
+        if let Some((column, e)) = computed_columns.iter().find_map(|column| {
+            Expr::parse(&column.expression)
+                .err()
+                .map(|e| (&column.name, e))
+        }) {
+            self.status = Some(Err(format!("Invalid column {column}: {e}")));
+            cx.notify();
+            return;
+        }
+        // Amounts may have commas, so rules are separated by semicolons
+        let alert_rules = text(&self.alert_rules)
+            .split(';')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if let Some(e) = alert_rules
+            .iter()
+            .find_map(|rule| AlertRule::parse(rule).err())
+        {
+            self.status = Some(Err(format!("Invalid alert: {e}")));
+            cx.notify();
+            return;
+        }
+        // Separators are not trimmed, a space is a common thousands separator
+        let thousands_separator = self.thousands_separator.read(cx).value().to_string();
+        let decimal_separator =
+            optional(text(&self.decimal_separator)).unwrap_or_else(|| ".".into());
+        let symbol_position = self.symbol_position;
+        let negative_style = self.negative_style;
+        let red_negatives = self.red_negatives;
+        let lenient_parsing = self.lenient_parsing;
+        let repeat_transaction_headers = self.repeat_transaction_headers;
+        let striped_rows = self.striped_rows;
+        let transaction_lines = self.transaction_lines;
+        let theme = self.theme;
+        let language = self.language;
+
+        self.status = Some(update(cx, |settings| {
+            settings.ledger_path = PathBuf::from(ledger_path);
+            settings.journal = journal;
+            settings.base_currency = base_currency;
+            settings.date_format = date_format;
+            settings.decimal_places = decimal_places;
+            settings.amount_format.decimal_places = commodity_decimal_places;
+            settings.amount_format.thousands_separator = thousands_separator;
+            settings.amount_format.decimal_separator = decimal_separator;
+            settings.amount_format.symbol_position = symbol_position;
+            settings.amount_format.negative_style = negative_style;
+            settings.amount_format.red_negatives = red_negatives;
+            settings.commodity_aliases = commodity_aliases;
+            settings.account_names = account_names;
+            settings.cpi_file = cpi_file;
+            settings.price_db = price_db;
+            settings.series_colors = series_colors;
+            settings.account_colors = account_colors;
+            settings.quick_add_hotkey = quick_add_hotkey;
+            settings.api_socket = api_socket;
+            settings.computed_columns = computed_columns;
+            settings.alert_rules = alert_rules;
+            settings.theme = theme;
+            settings.language = language;
+            settings.lenient_parsing = lenient_parsing;
+            settings.repeat_transaction_headers = repeat_transaction_headers;
+            settings.striped_rows = striped_rows;
+            settings.transaction_lines = transaction_lines;
+        }));
+        cx.notify();
+    }
+}
+
+/// Writes `NAME=value` pairs separated by commas.
+fn format_pairs<T: std::fmt::Display>(pairs: &BTreeMap<String, T>) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn parse_pairs(text: &str) -> BTreeMap<String, String> {
+    text.split(',')
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Writes `Name=expression` columns separated by semicolons, as expressions may have
+/// commas.
+fn format_columns(columns: &[ComputedColumn]) -> String {
+    columns
+        .iter()
+        .map(|column| format!("{}={}", column.name, column.expression))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn parse_columns(text: &str) -> Vec<ComputedColumn> {
+    text.split(';')
+        .filter_map(|column| {
+            let (name, expression) = column.split_once('=')?;
+            Some(ComputedColumn {
+                name: name.trim().to_string(),
+                expression: expression.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Labelled button cycling through the values of a setting.
+fn toggle(
+    id: &'static str,
+    label: &'static str,
+    value: &'static str,
+    on_click: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    h_flex()
+        .gap_2()
+        .items_center()
+        .child(div().text_sm().child(label))
+        .child(Button::new(id).label(value).ghost().on_click(on_click))
+}
+
+impl Render for SettingsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let field = |label: &'static str, input: &Entity<InputState>| {
+            v_flex()
+                .gap_1()
+                .child(div().text_sm().child(label))
+                .child(Input::new(input))
+        };
+
+        v_flex()
+            .size_full()
+            .gap_3()
+            .p_4()
+            .bg(cx.theme().background)
+            .child(field("Ledger binary", &self.ledger_path))
+            .child(field("Default journal", &self.journal))
+            .child(field("Base currency", &self.base_currency))
+            .child(toggle(
+                "settings-language",
+                "Language",
+                self.language.label(),
+                cx.listener(|this, _, window, cx| this.cycle_language(window, cx)),
+            ))
+            .child(field("Date format", &self.date_format))
+            .child(field("Decimal places", &self.decimal_places))
+            .child(field(
+                "Decimal places by commodity",
+                &self.commodity_decimal_places,
+            ))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(field("Thousands separator", &self.thousands_separator))
+                    .child(field("Decimal separator", &self.decimal_separator)),
+            )
+            .child(
+                h_flex()
+                    .gap_4()
+                    .child(toggle(
+                        "settings-symbol-position",
+                        "Commodity",
+                        self.symbol_position.label(),
+                        cx.listener(|this, _, _window, cx| {
+                            this.symbol_position = this.symbol_position.next();
+                            cx.notify();
+                        }),
+                    ))
+                    .child(toggle(
+                        "settings-negative-style",
+                        "Negative",
+                        self.negative_style.label(),
+                        cx.listener(|this, _, _window, cx| {
+                            this.negative_style = this.negative_style.next();
+                            cx.notify();
+                        }),
+                    ))
+                    .child(toggle(
+                        "settings-red-negatives",
+                        "Red negatives",
+                        if self.red_negatives { "On" } else { "Off" },
+                        cx.listener(|this, _, _window, cx| {
+                            this.red_negatives = !this.red_negatives;
+                            cx.notify();
+                        }),
+                    ))
+                    .child(toggle(
+                        "settings-repeat-transaction-headers",
+                        "Date on every posting",
+                        if self.repeat_transaction_headers {
+                            "On"
+                        } else {
+                            "Off"
+                        },
+                        cx.listener(|this, _, _window, cx| {
+                            this.repeat_transaction_headers = !this.repeat_transaction_headers;
+                            cx.notify();
+                        }),
+                    )),
+            )
+            .child(field("Commodity names", &self.commodity_aliases))
+            .child(field("Account names", &self.account_names))
+            .child(field("CPI table (CSV)", &self.cpi_file))
+            .child(field("Price database", &self.price_db))
+            .child(field("Chart colors", &self.series_colors))
+            .child(field("Account colors", &self.account_colors))
+            .child(field(
+                "Quick add hotkey (after restarting)",
+                &self.quick_add_hotkey,
+            ))
+            .child(field("API socket (after restarting)", &self.api_socket))
+            .child(field("Register columns", &self.computed_columns))
+            .child(field("Alerts", &self.alert_rules))
+            .child(
+                h_flex()
+                    .gap_4()
+                    .child(toggle(
+                        "settings-theme",
+                        "Theme",
+                        self.theme.label(),
+                        cx.listener(|this, _, _window, cx| {
+                            this.theme = this.theme.next();
+                            cx.notify();
+                        }),
+                    ))
+                    .child(toggle(
+                        "settings-striped-rows",
+                        "Striped rows",
+                        if self.striped_rows { "On" } else { "Off" },
+                        cx.listener(|this, _, _window, cx| {
+                            this.striped_rows = !this.striped_rows;
+                            cx.notify();
+                        }),
+                    ))
+                    .child(toggle(
+                        "settings-transaction-lines",
+                        "Lines between transactions",
+                        if self.transaction_lines { "On" } else { "Off" },
+                        cx.listener(|this, _, _window, cx| {
+                            this.transaction_lines = !this.transaction_lines;
+                            cx.notify();
+                        }),
+                    )),
+            )
+            .child(toggle(
+                "settings-lenient-parsing",
+                "Unparseable transactions",
+                if self.lenient_parsing {
+                    "Skip"
+                } else {
+                    "Stop loading"
+                },
+                cx.listener(|this, _, _window, cx| {
+                    this.lenient_parsing = !this.lenient_parsing;
+                    cx.notify();
+                }),
+            ))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        Button::new("settings-save")
+                            .label("Save")
+                            .primary()
+                            .on_click(cx.listener(|this, _, _window, cx| this.save(cx))),
+                    )
+                    .children(self.status.clone().map(|status| match status {
+                        Ok(()) => div().text_sm().child("Saved"),
+                        Err(e) => div().text_sm().text_color(cx.theme().danger).child(e),
+                    })),
+            )
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use chrono::Datelike as _;
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex, ActiveTheme, Disableable, Selectable as _, Sizable as _,
+};
+
+use crate::new_journal::FileStart;
+
+use super::state::State;
+
+/// Closes the year and starts the next one's file: the journal's balances are carried
+/// into a new file as of a date, which the journal includes. The changes are shown as
+/// a diff before anything is written.
+pub struct StartFileView {
+    state: Entity<State>,
+    date: Entity<InputState>,
+    /// Also close income and expenses into equity, as at the end of a year.
+    close_income: bool,
+    /// Changes waiting to be confirmed.
+    preview: Option<FileStart>,
+}
+
+impl StartFileView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let year = chrono::Local::now().date_naive().year();
+        let date = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("YYYY-MM-DD");
+            input.set_value(format!("{year}-01-01"), window, cx);
+            input
+        });
+        cx.subscribe(&date, |this, _, event: &InputEvent, cx| {
+            if matches!(event, InputEvent::Change) {
+                this.preview = None;
+                cx.notify();
+            }
+        })
+        .detach();
+        Self {
+            state,
+            date,
+            close_income: true,
+            preview: None,
+        }
+    }
+
+    fn date(&self, cx: &App) -> Option<chrono::NaiveDate> {
+        let date = self.date.read(cx).value();
+        chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+    }
+
+    /// Asks where to write the new file, next to the journal by default, and previews
+    /// the changes.
+    fn prepare(&mut self, cx: &mut Context<Self>) {
+        let Some(date) = self.date(cx) else {
+            return;
+        };
+        let Some(journal) = self.state.read(cx).journal_file() else {
+            return;
+        };
+        let directory = journal
+            .parent()
+            .map(std::path::Path::to_path_buf)
+            .unwrap_or_default();
+        let name = format!("{}.ledger", date.year());
+        let path = cx.prompt_for_new_path(&directory, Some(&name));
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                let state = this.state.read(cx);
+                let transactions = state
+                    .transactions
+                    .iter()
+                    .map(Arc::as_ref)
+                    .collect::<Vec<_>>();
+                this.preview = Some(FileStart::new(
+                    &transactions,
+                    &journal,
+                    &path,
+                    date,
+                    this.close_income,
+                ));
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn write(&mut self, cx: &mut Context<Self>) {
+        if let Some(start) = self.preview.take() {
+            self.state
+                .update(cx, |state, cx| state.start_file(start, cx));
+        }
+        cx.notify();
+    }
+}
+
+impl Render for StartFileView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let date = self.date(cx);
+        let theme = cx.theme();
+
+        let preview = self.preview.as_ref().map(|start| {
+            v_flex()
+                .gap_1()
+                .child(
+                    div()
+                        .p_2()
+                        .border_1()
+                        .border_color(theme.border)
+                        .font_family("monospace")
+                        .text_xs()
+                        .children(start.diff().lines().map(|line| {
+                            let added = line.starts_with('+') && !line.starts_with("+++");
+                            div()
+                                .text_color(if added {
+                                    theme.foreground
+                                } else {
+                                    theme.muted_foreground
+                                })
+                                .child(line.to_string())
+                        })),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Button::new("start-file-write")
+                                .label("Write files")
+                                .xsmall()
+                                .primary()
+                                .on_click(cx.listener(|this, _, _window, cx| this.write(cx))),
+                        )
+                        .child(
+                            Button::new("start-file-cancel")
+                                .label("Cancel")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.preview = None;
+                                    cx.notify();
+                                })),
+                        ),
+                )
+        });
+
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().flex_1().text_sm().child("Start a new file"))
+                    .child(div().w(px(110.)).child(Input::new(&self.date).small()))
+                    .child(
+                        Button::new("start-file-close-income")
+                            .label("Close income and expenses")
+                            .xsmall()
+                            .ghost()
+                            .selected(self.close_income)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.close_income = !this.close_income;
+                                this.preview = None;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("start-file")
+                            .label("Preview…")
+                            .xsmall()
+                            .primary()
+                            .disabled(date.is_none())
+                            .on_click(cx.listener(|this, _, _window, cx| this.prepare(cx))),
+                    ),
+            )
+            .child(div().text_xs().text_color(theme.muted_foreground).child(
+                "The new file opens with the asset and liability balances before the \
+                 date. The journal closes them the day before, and income and expenses \
+                 into equity:retained earnings, then includes the new file.",
+            ))
+            .when(date.is_none(), |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.danger)
+                        .child("Dates are written as YYYY-MM-DD"),
+                )
+            })
+            .children(preview)
+    }
+}
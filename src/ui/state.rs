@@ -1,44 +1,1015 @@
 #[allow(clippy::wildcard_imports)]
 use gpui::*;
 
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use futures_lite::StreamExt;
 
-use crate::{accounts::TreeNode, ledger::LedgerHandle, transactions::Transaction};
+use crate::{
+    account_declarations::{self, AccountDeclaration},
+    account_names::{self, AccountNames},
+    accounts::{Account, AccountLimit, TreeNode},
+    alerts::{self, Alert, AlertRule},
+    amount_format::{AmountFormatter, CommodityPrecision},
+    assertions::{self, BalanceAssertion, Discrepancy},
+    budget::{self, BudgetLine},
+    changelog::{self, Change},
+    commodities::CommodityRegistry,
+    history::{self, EntryConflict, FileEdit, History, HistoryError, Operation},
+    ledger::{self, LedgerHandle},
+    new_journal::FileStart,
+    period::{Period, PeriodKind},
+    prices::{self, PriceDirective, PriceHistory, Valuation},
+    rename,
+    settings::Settings,
+    transactions::Transaction,
+    transfers,
+    trash::{self, DeletedEntry},
+};
+
+/// How often transactions streamed from ledger are added while loading, so views
+/// rebuild about 20 times a second instead of once per transaction.
+const LOAD_BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What changed in the state, for views that only need to rebuild for some changes.
+/// Observers are still notified of every change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateEvent {
+    /// The journal is being loaded again and its transactions were cleared.
+    ReloadStarted,
+    /// Transactions streamed from ledger were added to the end of `transactions`.
+    TransactionsAdded,
+    /// The journal is loaded, with its directives and prices.
+    ReloadFinished,
+    /// The accounts tree was rebuilt, other than by adding loaded transactions.
+    AccountsChanged,
+    /// What-if transactions were added or discarded.
+    HypotheticalChanged,
+    /// The shared period changed.
+    PeriodChanged,
+    /// An edit made through the app was written to the journal.
+    JournalWritten,
+    /// The ledger binary could not be started, the error says why.
+    LedgerUnavailable,
+    Error(String),
+}
 
 pub struct State {
     pub accounts: TreeNode,
-    pub transactions: Vec<Transaction>,
+    /// Shared with the views listing them, so they are not copied into each one.
+    pub transactions: Vec<Arc<Transaction>>,
+    /// What-if transactions that only exist for the current session.
+    pub hypothetical: Vec<Arc<Transaction>>,
+    /// Balance limits the accounts tree highlights violations of.
+    pub limits: HashMap<Account, AccountLimit>,
+    /// Decimal places commodities are written with in the journal.
+    pub precision: CommodityPrecision,
+    /// `alias` directives found in the journal files.
+    pub aliases: Vec<(String, Account)>,
+    /// `account` directives found in the journal files.
+    pub declarations: Vec<AccountDeclaration>,
+    /// Balance assertions found in the journal files.
+    pub journal_assertions: Vec<BalanceAssertion>,
+    /// Expected balances entered in the app for the current session.
+    pub user_assertions: Vec<BalanceAssertion>,
+    /// Entries commented out in the journal files.
+    pub deleted: Vec<DeletedEntry>,
+    /// `P` directives found in the journal files and the price database.
+    pub prices: Vec<PriceDirective>,
+    /// True while price commands are running.
+    pub fetching_prices: bool,
+    /// Postings of the journal's `~ Monthly` entries.
+    pub budget: Vec<BudgetLine>,
+    /// Alert rules from settings that hold, including snoozed ones.
+    pub alerts: Vec<Alert>,
+    /// Date range the register and chart are showing.
+    pub period: Period,
     pub error: Option<String>,
+    /// Transactions skipped during the last load because they failed to parse.
+    pub problems: Vec<String>,
+    /// True while transactions are being streamed from ledger.
+    pub loading: bool,
+    /// When the last load finished.
+    pub loaded_at: Option<chrono::DateTime<chrono::Local>>,
+    /// How long the last load took.
+    pub load_duration: Option<Duration>,
+    /// How long each view took to rebuild from the transactions the last time, by view.
+    pub rebuild_timings: BTreeMap<&'static str, Duration>,
+    /// Changes made to the journal files that can be undone.
+    pub history: History,
+    /// Entry edit that found the entry changed outside the app, waiting for the user
+    /// to pick a version.
+    pub conflict: Option<EntryConflict>,
 
+    /// Journal the window was opened with, used instead of the one in settings.
+    journal: Option<PathBuf>,
+    /// Text of the journal files as they were read after the last load, which entries
+    /// are expected to still match when they are edited.
+    loaded_texts: HashMap<PathBuf, String>,
+    /// Binary and journal the ledger process was started with.
+    ledger_source: (PathBuf, Option<PathBuf>),
     ledger_handle: LedgerHandle,
+    /// Load streaming transactions in, dropped to cancel it when another one starts.
+    load_task: Option<Task<()>>,
 }
 
+impl EventEmitter<StateEvent> for State {}
+
 impl State {
-    pub fn new(cx: &mut Context<Self>) -> Self {
-        let ledger_handle = LedgerHandle::spawn(cx, None);
+    pub fn new(journal: Option<PathBuf>, cx: &mut Context<Self>) -> Self {
+        let source = ledger_source(cx.global::<Settings>(), journal.as_ref());
+        let ledger_handle = spawn_ledger(&source, cx);
+
+        cx.observe_global::<Settings>(|this, cx| {
+            let settings = cx.global::<Settings>();
+            if this.ledger_source != ledger_source(settings, this.journal.as_ref()) {
+                this.refresh(true, cx);
+            } else {
+                // The valuation and base currency change what balances are worth
+                this.rebuild_accounts(cx);
+                this.check_alerts(cx);
+                cx.notify();
+            }
+        })
+        .detach();
+
         let mut ledger_state = Self {
             accounts: TreeNode::new(),
             transactions: Vec::new(),
+            hypothetical: Vec::new(),
+            limits: HashMap::new(),
+            precision: CommodityPrecision::default(),
+            aliases: Vec::new(),
+            declarations: Vec::new(),
+            journal_assertions: Vec::new(),
+            user_assertions: Vec::new(),
+            deleted: Vec::new(),
+            prices: Vec::new(),
+            budget: Vec::new(),
+            alerts: Vec::new(),
+            fetching_prices: false,
+            period: Period::containing(chrono::Local::now().date_naive(), PeriodKind::Year),
             error: None,
+            problems: Vec::new(),
+            loading: false,
+            loaded_at: None,
+            load_duration: None,
+            rebuild_timings: BTreeMap::new(),
+            history: History::default(),
+            conflict: None,
+            loaded_texts: HashMap::new(),
+            journal,
+            ledger_source: source,
             ledger_handle,
+            load_task: None,
         };
         ledger_state.reload_state(cx);
         ledger_state
     }
 
+    /// Journal and what-if transactions, ordered by date.
+    pub fn all_transactions(&self) -> Vec<&Transaction> {
+        let mut transactions = self
+            .transactions
+            .iter()
+            .chain(self.hypothetical.iter())
+            .map(Arc::as_ref)
+            .collect::<Vec<_>>();
+        if !self.hypothetical.is_empty() {
+            transactions.sort_by_key(|transaction| transaction.time);
+        }
+        transactions
+    }
+
+    /// Journal and what-if transactions, ordered by date, for views that keep them.
+    pub fn shared_transactions(&self) -> Vec<Arc<Transaction>> {
+        let mut transactions = self
+            .transactions
+            .iter()
+            .chain(self.hypothetical.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        if !self.hypothetical.is_empty() {
+            transactions.sort_by_key(|transaction| transaction.time);
+        }
+        transactions
+    }
+
+    /// Transactions income and expense reports are made of: all but the ones tagged as
+    /// halves of transfers between own accounts.
+    pub fn reportable_transactions(&self) -> Vec<&Transaction> {
+        let mut transactions = self.all_transactions();
+        transactions.retain(|transaction| !transfers::is_transfer(transaction));
+        transactions
+    }
+
+    pub fn add_hypothetical(&mut self, transaction: Transaction, cx: &mut Context<Self>) {
+        record_precision(&mut self.precision, &transaction);
+        self.hypothetical.push(Arc::new(transaction));
+        self.rebuild_accounts(cx);
+        cx.emit(StateEvent::HypotheticalChanged);
+        cx.notify();
+    }
+
+    pub fn discard_hypothetical(&mut self, cx: &mut Context<Self>) {
+        self.hypothetical.clear();
+        self.rebuild_accounts(cx);
+        cx.emit(StateEvent::HypotheticalChanged);
+        cx.notify();
+    }
+
+    /// Appends what-if transactions to the journal and reloads it.
+    pub fn commit_hypothetical(&mut self, cx: &mut Context<Self>) {
+        if self.hypothetical.is_empty() {
+            return;
+        }
+
+        let Some(journal) = self.journal_file() else {
+            self.set_error(
+                "No journal file to commit to, choose one in settings".into(),
+                cx,
+            );
+            cx.notify();
+            return;
+        };
+
+        let text = self
+            .hypothetical
+            .iter()
+            .map(|transaction| format!("\n{transaction}"))
+            .collect::<String>();
+
+        let label = match self.hypothetical.len() {
+            1 => "Commit 1 what-if transaction".to_string(),
+            count => format!("Commit {count} what-if transactions"),
+        };
+        self.write_journal(
+            move || {
+                let operation = Operation::new(label, vec![FileEdit::append(&journal, text)?]);
+                operation.apply()?;
+                Ok(operation)
+            },
+            |this, operation| {
+                this.hypothetical.clear();
+                this.history.record(operation);
+            },
+            cx,
+        );
+    }
+
+    /// Appends entry text pasted or dropped into the app to the journal, as one step.
+    ///
+    /// `count` is the number of transactions in the text, for the history label.
+    pub fn append_entries(&mut self, text: &str, count: usize, cx: &mut Context<Self>) {
+        let label = match count {
+            1 => "Append 1 pasted transaction".to_string(),
+            count => format!("Append {count} pasted transactions"),
+        };
+        self.append_text(text, label, cx);
+    }
+
+    /// Appends a transaction made in the app to the journal.
+    pub fn append_transaction(
+        &mut self,
+        transaction: &Transaction,
+        label: String,
+        cx: &mut Context<Self>,
+    ) {
+        self.append_text(&transaction.to_string(), label, cx);
+    }
+
+    fn append_text(&mut self, text: &str, label: String, cx: &mut Context<Self>) {
+        let Some(journal) = self.journal_file() else {
+            self.set_error(
+                "No journal file to append to, choose one in settings".into(),
+                cx,
+            );
+            cx.notify();
+            return;
+        };
+
+        let text = format!("\n{}\n", text.trim());
+        self.write_journal(
+            move || {
+                let operation = Operation::new(label, vec![FileEdit::append(&journal, text)?]);
+                operation.apply()?;
+                Ok(operation)
+            },
+            |this, operation| this.history.record(operation),
+            cx,
+        );
+    }
+
+    /// Writes the new file of `start` and appends its closing entries and `include` to
+    /// the journal, as one step.
+    pub fn start_file(&mut self, start: FileStart, cx: &mut Context<Self>) {
+        let label = format!("Start {}", start.file.display());
+        self.write_journal(
+            move || {
+                std::fs::File::create_new(&start.file)
+                    .map_err(|e| HistoryError::Write(start.file.clone(), e))?;
+                let operation = Operation::new(
+                    label,
+                    vec![
+                        FileEdit::append(&start.file, start.opening)?,
+                        FileEdit::append(&start.journal, start.closing)?,
+                    ],
+                );
+                operation.apply()?;
+                Ok(operation)
+            },
+            |this, operation| this.history.record(operation),
+            cx,
+        );
+    }
+
+    /// Runs the price commands for the journal's commodities and appends the prices
+    /// they print to the price database, then reloads.
+    ///
+    /// Commodities whose command fails are listed in the error; the prices of the
+    /// others are still written.
+    pub fn fetch_prices(&mut self, cx: &mut Context<Self>) {
+        let settings = cx.global::<Settings>();
+        let Some(base) = settings.base_currency.clone() else {
+            self.set_error("Set a base currency to fetch prices in".into(), cx);
+            cx.notify();
+            return;
+        };
+        let Some(price_db) = settings.price_db.clone() else {
+            self.set_error("Set a price database to write fetched prices to".into(), cx);
+            cx.notify();
+            return;
+        };
+        let mut commodities = self
+            .transactions
+            .iter()
+            .flat_map(|transaction| &transaction.postings)
+            .map(|posting| posting.amount.value.commodity.as_str())
+            .filter(|commodity| !commodity.is_empty() && *commodity != base)
+            .collect::<Vec<_>>();
+        commodities.sort_unstable();
+        commodities.dedup();
+        let commands = commodities
+            .into_iter()
+            .filter_map(|commodity| {
+                let command = settings.price_command(commodity)?;
+                Some((
+                    commodity.to_string(),
+                    prices::quote_command(command, commodity, &base),
+                ))
+            })
+            .collect::<Vec<_>>();
+        if commands.is_empty() {
+            self.set_error(
+                "No price commands are set up for the journal's commodities".into(),
+                cx,
+            );
+            cx.notify();
+            return;
+        }
+
+        self.fetching_prices = true;
+        cx.notify();
+        let date = chrono::Local::now().date_naive();
+        let fetch = cx.background_executor().spawn(async move {
+            let mut directives = Vec::new();
+            let mut failed = Vec::new();
+            for (commodity, mut command) in commands {
+                let output = command.output();
+                let price = output
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .and_then(|output| {
+                        prices::parse_quote(&String::from_utf8_lossy(&output.stdout), &base)
+                    });
+                match price {
+                    Some(price) => directives.push(PriceDirective {
+                        date,
+                        commodity,
+                        price,
+                    }),
+                    None => failed.push(commodity),
+                }
+            }
+            (directives, failed)
+        });
+
+        cx.spawn(async move |this, cx| {
+            let (directives, failed) = fetch.await;
+            this.update(cx, |this, cx| {
+                this.fetching_prices = false;
+                if !failed.is_empty() {
+                    this.set_error(
+                        format!("Failed to fetch prices of {}", failed.join(", ")),
+                        cx,
+                    );
+                }
+                if directives.is_empty() {
+                    cx.notify();
+                    return;
+                }
+                let text = directives
+                    .iter()
+                    .map(|directive| format!("{directive}\n"))
+                    .collect::<String>();
+                let label = match directives.len() {
+                    1 => "Fetch 1 price".to_string(),
+                    count => format!("Fetch {count} prices"),
+                };
+                this.write_journal(
+                    move || {
+                        std::fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(&price_db)
+                            .map_err(|e| HistoryError::Write(price_db.clone(), e))?;
+                        let operation =
+                            Operation::new(label, vec![FileEdit::append(&price_db, text)?]);
+                        operation.apply()?;
+                        Ok(operation)
+                    },
+                    |this, operation| this.history.record(operation),
+                    cx,
+                );
+            })
+            .map_err(|e| {
+                eprintln!("Error updating state with fetched prices: {}", e);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Comments the transactions out in their journal files.
+    pub fn delete_transactions(&mut self, transactions: &[Transaction], cx: &mut Context<Self>) {
+        let label = match transactions {
+            [transaction] => format!("Delete {}", transaction.description),
+            _ => format!("Delete {} transactions", transactions.len()),
+        };
+        self.edit_entries(label, transactions, trash::comment_out, cx);
+    }
+
+    /// Changes the transactions' entries in their journal files as one step, into what
+    /// `edit` makes of each entry's text. Transactions that are not in the journal are
+    /// left out.
+    ///
+    /// Entries changed outside the app since the journal was loaded are not edited; the
+    /// first of them becomes the `conflict` to resolve instead.
+    pub fn edit_entries(
+        &mut self,
+        label: String,
+        transactions: &[Transaction],
+        edit: impl Fn(&str) -> String + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        let mut entries = transactions
+            .iter()
+            .filter(|transaction| !transaction.file.as_os_str().is_empty())
+            .map(|transaction| (transaction.file.clone(), transaction.line))
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries.dedup();
+        let mut loaded_texts = entries
+            .iter()
+            .filter_map(|(file, _)| Some((file.clone(), self.loaded_texts.get(file)?.clone())))
+            .collect::<HashMap<_, _>>();
+        self.write_journal(
+            move || {
+                let mut edits = Vec::new();
+                for entries in entries.chunk_by(|a, b| a.0 == b.0) {
+                    let file = &entries[0].0;
+                    let text = std::fs::read_to_string(file)
+                        .map_err(|e| HistoryError::Read(file.clone(), e))?;
+                    let loaded = loaded_texts.remove(file);
+                    // Bottom entries first, so the offsets of the ones above stay valid
+                    for (_, line) in entries.iter().rev() {
+                        edits.push(match &loaded {
+                            Some(loaded) => {
+                                history::checked_entry_edit(file, loaded, &text, *line, &edit)?
+                            }
+                            None => history::entry_edit(file, &text, *line, &edit)
+                                .ok_or_else(|| HistoryError::Conflict(file.clone()))?,
+                        });
+                    }
+                }
+                let operation = Operation::new(label, edits);
+                operation.apply()?;
+                Ok(operation)
+            },
+            |this, operation| this.history.record(operation),
+            cx,
+        );
+    }
+
+    /// Replaces the transaction's entry in its journal file with `replacement`.
+    pub fn replace_transaction(
+        &mut self,
+        transaction: &Transaction,
+        replacement: Transaction,
+        cx: &mut Context<Self>,
+    ) {
+        self.edit_entries(
+            format!("Edit {}", transaction.description),
+            std::slice::from_ref(transaction),
+            move |_| replacement.to_string(),
+            cx,
+        );
+    }
+
+    /// Renames the account and its subaccounts in every journal file.
+    pub fn rename_account(&mut self, from: Account, to: Account, cx: &mut Context<Self>) {
+        let files = self.journal_files();
+        let label = format!("Rename {from} to {to}");
+        self.write_journal(
+            {
+                let (from, to) = (from.clone(), to.clone());
+                move || {
+                    let mut edits = Vec::new();
+                    for file in &files {
+                        let text = std::fs::read_to_string(file)
+                            .map_err(|e| HistoryError::Read(file.clone(), e))?;
+                        edits.extend(rename::rename_edits(file, &text, &from, &to));
+                    }
+                    let operation = Operation::new(label, edits);
+                    operation.apply()?;
+                    Ok(operation)
+                }
+            },
+            move |this, operation| {
+                if !operation.edits.is_empty() {
+                    this.history.record(operation);
+                }
+                // Limits follow the accounts they were set on
+                let renamed = this
+                    .limits
+                    .keys()
+                    .filter(|account| **account == from || from.is_parent_of(account))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for account in renamed {
+                    if let Some(limit) = this.limits.remove(&account) {
+                        let mut segments = to.segments.clone();
+                        segments.extend_from_slice(&account.segments[from.segments.len()..]);
+                        this.limits.insert(Account::from_segments(segments), limit);
+                    }
+                }
+            },
+            cx,
+        );
+    }
+
+    /// Uncomments a deleted entry.
+    pub fn restore_deleted(&mut self, entry: &DeletedEntry, cx: &mut Context<Self>) {
+        let operation = Operation::new(
+            format!("Restore {}", entry.description),
+            vec![entry.restore_edit()],
+        );
+        self.write_journal(
+            move || operation.apply().map(|()| operation),
+            |this, operation| this.history.record(operation),
+            cx,
+        );
+    }
+
+    /// Reverts the last change made to the journal files.
+    pub fn undo(&mut self, cx: &mut Context<Self>) {
+        let Some(operation) = self.history.next_undo().cloned() else {
+            return;
+        };
+        let undo = Operation::new(
+            format!("Undo {}", operation.label),
+            operation.inverse().edits,
+        );
+        self.write_journal(
+            move || undo.apply().map(|()| undo),
+            move |this, _| this.history.undone(&operation),
+            cx,
+        );
+    }
+
+    /// Applies the last undone change again.
+    pub fn redo(&mut self, cx: &mut Context<Self>) {
+        let Some(operation) = self.history.next_redo().cloned() else {
+            return;
+        };
+        let redo = Operation::new(format!("Redo {}", operation.label), operation.edits.clone());
+        self.write_journal(
+            move || redo.apply().map(|()| redo),
+            move |this, _| this.history.redone(&operation),
+            cx,
+        );
+    }
+
+    /// Changes the journal files in the background and reloads them.
+    ///
+    /// `then` runs with the applied operation once it succeeds. A conflict means the
+    /// files were changed outside the app, so the history no longer matches them and
+    /// is dropped. Written operations are added to the changelog.
+    fn write_journal(
+        &mut self,
+        write: impl FnOnce() -> Result<Operation, HistoryError> + Send + 'static,
+        then: impl FnOnce(&mut Self, Operation) + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        let write = cx.background_executor().spawn(async move {
+            let operation = write()?;
+            if let Some(path) = changelog::path() {
+                let change = Change::new(&operation, chrono::Local::now());
+                if let Err(e) = changelog::append(&path, &change) {
+                    eprintln!("Error recording change: {}", e);
+                }
+            }
+            Ok(operation)
+        });
+
+        cx.spawn(async move |this, cx| {
+            let result = write.await;
+            this.update(cx, |this, cx| match result {
+                Ok(operation) => {
+                    // Later edits expect the entries as the app left them, not as loaded
+                    for edit in &operation.edits {
+                        if let Some(text) = this.loaded_texts.get_mut(&edit.file) {
+                            match edit.apply(text) {
+                                Ok(edited) => *text = edited,
+                                Err(_) => {
+                                    this.loaded_texts.remove(&edit.file);
+                                }
+                            }
+                        }
+                    }
+                    then(this, operation);
+                    cx.emit(StateEvent::JournalWritten);
+                    this.refresh(true, cx);
+                }
+                Err(HistoryError::EntryConflict(conflict)) => {
+                    this.conflict = Some(*conflict);
+                    cx.notify();
+                }
+                Err(e) => {
+                    if matches!(e, HistoryError::Conflict(_)) {
+                        this.history.clear();
+                    }
+                    this.set_error(format!("Failed to write journal: {e}"), cx);
+                    cx.notify();
+                }
+            })
+            .map_err(|e| {
+                eprintln!("Error updating state after writing journal: {}", e);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Writes the app's version of the conflicting entry over the file's.
+    pub fn keep_mine(&mut self, cx: &mut Context<Self>) {
+        let Some(conflict) = self.conflict.take() else {
+            return;
+        };
+        self.write_journal(
+            move || {
+                let text = std::fs::read_to_string(&conflict.file)
+                    .map_err(|e| HistoryError::Read(conflict.file.clone(), e))?;
+                let operation = Operation::new("Keep my edit", vec![conflict.keep_mine(&text)?]);
+                operation.apply()?;
+                Ok(operation)
+            },
+            |this, operation| this.history.record(operation),
+            cx,
+        );
+    }
+
+    /// Drops the app's version of the conflicting entry and reloads the journal with
+    /// the file's.
+    pub fn take_file(&mut self, cx: &mut Context<Self>) {
+        self.conflict = None;
+        self.refresh(true, cx);
+    }
+
+    pub fn set_period(&mut self, period: Period, cx: &mut Context<Self>) {
+        self.period = period;
+        cx.emit(StateEvent::PeriodChanged);
+        cx.notify();
+    }
+
+    pub fn set_limit(
+        &mut self,
+        account: Account,
+        limit: Option<AccountLimit>,
+        cx: &mut Context<Self>,
+    ) {
+        match limit {
+            Some(limit) => self.limits.insert(account, limit),
+            None => self.limits.remove(&account),
+        };
+        cx.notify();
+    }
+
+    pub fn add_assertion(&mut self, assertion: BalanceAssertion, cx: &mut Context<Self>) {
+        self.user_assertions.push(assertion);
+        cx.notify();
+    }
+
+    pub fn remove_assertion(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index < self.user_assertions.len() {
+            self.user_assertions.remove(index);
+            cx.notify();
+        }
+    }
+
+    /// Journal and user assertions that do not hold for the journal transactions.
+    pub fn check_assertions(&self) -> Vec<Discrepancy> {
+        let assertions = self
+            .journal_assertions
+            .iter()
+            .chain(&self.user_assertions)
+            .cloned()
+            .collect::<Vec<_>>();
+        assertions::check(&assertions, &self.transactions)
+    }
+
+    /// Reloads transactions from ledger.
+    ///
+    /// A forced refresh restarts the ledger process, so nothing it has cached
+    /// about the journal survives.
+    pub fn refresh(&mut self, force: bool, cx: &mut Context<Self>) {
+        if force {
+            self.ledger_source = ledger_source(cx.global::<Settings>(), self.journal.as_ref());
+            self.ledger_handle = spawn_ledger(&self.ledger_source, cx);
+        }
+        self.reload_state(cx);
+    }
+
+    /// Prices of commodities in the base currency, or in the commodity most postings
+    /// are in when there is none.
+    pub fn price_history(&self, cx: &App) -> PriceHistory {
+        let unit = self.main_commodity(cx).unwrap_or_default();
+        PriceHistory::new(&self.all_transactions(), &self.prices, &unit)
+    }
+
+    /// The base currency, or else the commodity most postings are in.
+    pub fn main_commodity(&self, cx: &App) -> Option<String> {
+        cx.global::<Settings>()
+            .base_currency
+            .clone()
+            .or_else(|| prices::most_used_commodity(&self.all_transactions()))
+    }
+
+    /// Rebuilds the accounts tree from the journal and what-if transactions, valued as
+    /// settings say.
+    fn rebuild_accounts(&mut self, cx: &mut Context<Self>) {
+        let started = Instant::now();
+        let valuation = cx.global::<Settings>().valuation;
+        let prices = (valuation != Valuation::Native).then(|| self.price_history(cx));
+        self.accounts.clear();
+        for transaction in self.transactions.iter().chain(&self.hypothetical) {
+            match &prices {
+                Some(prices) => apply_to_accounts(
+                    &mut self.accounts,
+                    &prices.value_transaction(transaction, valuation),
+                ),
+                None => apply_to_accounts(&mut self.accounts, transaction),
+            }
+        }
+        self.record_rebuild("Accounts", started.elapsed());
+        cx.emit(StateEvent::AccountsChanged);
+    }
+
+    /// Shows the error in the window. The caller notifies.
+    fn set_error(&mut self, error: String, cx: &mut Context<Self>) {
+        cx.emit(StateEvent::Error(error.clone()));
+        self.error = Some(error);
+    }
+
+    /// Checks the alert rules against the journal transactions. Alerts that were not
+    /// raised before and are not snoozed also become system notifications.
+    fn check_alerts(&mut self, cx: &App) {
+        // Balances of a journal still loading are not what they will be
+        if self.loading {
+            return;
+        }
+        let settings = cx.global::<Settings>();
+        let rules = settings
+            .alert_rules
+            .iter()
+            .filter_map(|rule| {
+                AlertRule::parse(rule)
+                    .map_err(|e| eprintln!("Error parsing alert rule: {}", e))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        let transactions = self
+            .transactions
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<_>>();
+        let today = chrono::Local::now().date_naive();
+        let alerts = alerts::check(&rules, &self.budget, &transactions, today);
+
+        let formatter = self.amount_formatter(cx);
+        let raised = alerts
+            .iter()
+            .filter(|alert| {
+                !settings.is_snoozed(&alert.rule, today)
+                    && !self.alerts.iter().any(|raised| raised.rule == alert.rule)
+            })
+            .map(|alert| (alert.rule.clone(), alert.message(&formatter)))
+            .collect::<Vec<_>>();
+        if !raised.is_empty() {
+            cx.background_executor()
+                .spawn(async move {
+                    for (title, body) in raised {
+                        show_system_notification(&title, &body);
+                    }
+                })
+                .detach();
+        }
+        self.alerts = alerts;
+    }
+
+    /// Keeps how long `view` took to rebuild, for the diagnostics overlay. Does not
+    /// notify, the overlay refreshes on its own.
+    pub fn record_rebuild(&mut self, view: &'static str, took: Duration) {
+        self.rebuild_timings.insert(view, took);
+    }
+
+    /// Id of the ledger process the transactions are read from, once it has started.
+    pub fn ledger_pid(&self) -> Option<u32> {
+        self.ledger_handle.pid()
+    }
+
+    /// Formatter combining the amount settings with the journal's precision.
+    pub fn amount_formatter(&self, cx: &App) -> AmountFormatter {
+        let settings = cx.global::<Settings>();
+        AmountFormatter::new(
+            settings.amount_format.clone(),
+            settings.decimal_places,
+            self.precision.clone(),
+            CommodityRegistry::new(&settings.commodity_aliases),
+        )
+    }
+
+    /// Display names from journal aliases and settings.
+    pub fn account_names(&self, cx: &App) -> AccountNames {
+        AccountNames::new(&self.aliases, &cx.global::<Settings>().account_names)
+    }
+
+    /// The ledger query transactions are loaded with.
+    pub fn source_query(&self) -> &'static str {
+        ledger::TRANSACTIONS_QUERY
+    }
+
+    /// Journal new transactions are written to: the root journal ledger was started
+    /// with, never a file it includes. `None` when ledger finds it in `~/.ledgerrc`.
+    pub fn journal_file(&self) -> Option<PathBuf> {
+        self.ledger_source
+            .1
+            .clone()
+            // Ledger reads it when started without `--file`
+            .or_else(|| std::env::var_os("LEDGER_FILE").map(PathBuf::from))
+    }
+
+    /// Whether ledger has a journal to read: one given to the window, in settings or
+    /// `LEDGER_FILE`, or whatever `~/.ledgerrc` points at.
+    pub fn has_journal(&self) -> bool {
+        self.journal_file().is_some()
+            || dirs::home_dir().is_some_and(|home| home.join(".ledgerrc").exists())
+    }
+
+    /// Reads the journal instead of the one in settings.
+    pub fn set_journal(&mut self, journal: PathBuf, cx: &mut Context<Self>) {
+        self.journal = Some(journal);
+        self.refresh(true, cx);
+    }
+
+    /// Files transactions came from, and the journal new ones are written to.
+    fn journal_files(&self) -> Vec<PathBuf> {
+        let mut files = self
+            .transactions
+            .iter()
+            .map(|transaction| transaction.file.clone())
+            .chain(self.journal_file())
+            .collect::<Vec<_>>();
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Reads `alias` and `account` directives, balance assertions and deleted entries
+    /// from the journal files transactions came from.
+    fn load_journal_directives(&self, cx: &mut Context<Self>) {
+        let files = self.journal_files();
+        let price_db = cx.global::<Settings>().price_db.clone();
+
+        let read = cx.background_executor().spawn(async move {
+            let mut aliases = Vec::new();
+            let mut declarations = Vec::new();
+            let mut assertions = Vec::new();
+            let mut deleted = Vec::new();
+            let mut prices = Vec::new();
+            let mut budget = Vec::new();
+            let mut texts = HashMap::new();
+            for file in &files {
+                let Ok(text) = std::fs::read_to_string(file) else {
+                    continue;
+                };
+                aliases.extend(account_names::parse_aliases(&text));
+                declarations.extend(account_declarations::parse_declarations(&text));
+                assertions.extend(assertions::parse_assertions(&text, file));
+                deleted.extend(trash::find_deleted(&text, file));
+                prices.extend(prices::parse_price_directives(&text));
+                budget.extend(budget::parse_budget(&text));
+                texts.insert(file.clone(), text);
+            }
+            if let Some(text) = price_db.and_then(|db| std::fs::read_to_string(db).ok()) {
+                prices.extend(prices::parse_price_directives(&text));
+            }
+            (
+                aliases,
+                declarations,
+                assertions,
+                deleted,
+                prices,
+                budget,
+                texts,
+            )
+        });
+
+        cx.spawn(async move |this, cx| {
+            let (aliases, declarations, assertions, deleted, prices, budget, texts) = read.await;
+            this.update(cx, |this, cx| {
+                this.loaded_texts = texts;
+                this.aliases = aliases;
+                this.declarations = declarations;
+                this.journal_assertions = assertions;
+                this.deleted = deleted;
+                this.prices = prices;
+                this.budget = budget;
+                // Valuation needs the prices, so balances are only valued now
+                this.rebuild_accounts(cx);
+                this.check_alerts(cx);
+                cx.emit(StateEvent::ReloadFinished);
+                cx.notify();
+            })
+            .map_err(|e| {
+                eprintln!("Error updating state with journal directives: {}", e);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Adds a batch of transactions streamed from ledger, and the problems reading
+    /// the ones that were skipped.
+    fn add_loaded(
+        &mut self,
+        transactions: Vec<Transaction>,
+        problems: Vec<String>,
+        cx: &mut Context<Self>,
+    ) {
+        let added = !transactions.is_empty();
+        for transaction in transactions {
+            apply_to_accounts(&mut self.accounts, &transaction);
+            record_precision(&mut self.precision, &transaction);
+            self.transactions.push(Arc::new(transaction));
+        }
+        self.problems.extend(problems);
+        if added {
+            cx.emit(StateEvent::TransactionsAdded);
+        }
+    }
+
     fn reload_state(&mut self, cx: &mut Context<Self>) {
         let ledger = self.ledger_handle.clone();
 
         self.accounts.clear();
         self.transactions.clear();
+        self.precision.clear();
         self.error = None;
+        self.problems.clear();
+        cx.emit(StateEvent::ReloadStarted);
+        // Batches of the previous load would mix with the new one's
+        self.load_task = None;
+        // Without a journal ledger only fails, the window offers to open one instead
+        if !self.has_journal() {
+            self.loading = false;
+            cx.notify();
+            return;
+        }
+        self.loading = true;
+        let load_started = Instant::now();
+        let lenient = cx.global::<Settings>().lenient_parsing;
 
         cx.notify();
 
-        cx.spawn(async move |this, cx| {
+        self.load_task = Some(cx.spawn(async move |this, cx| {
             let Ok(mut stream) = ledger.transactions().await else {
                 this.update(cx, |this, cx| {
-                    this.error = Some("Failed to start ledger process".into());
+                    this.set_error("Failed to start ledger process".into(), cx);
+                    this.loading = false;
                     cx.notify();
                 })
                 .map_err(|e| {
@@ -48,25 +1019,41 @@ impl State {
                 return;
             };
 
+            let mut batch = Vec::new();
+            let mut problems = Vec::new();
+            let mut batch_started = Instant::now();
             loop {
                 match stream.next().await {
                     Some(Ok(transaction)) => {
-                        this.update(cx, |this, _cx| {
-                            for posting in transaction.postings.iter() {
-                                this.accounts.add_account(&posting.account);
-                                this.accounts
-                                    .add_amount_to_account(&posting.account, &posting.amount.value);
-                            }
-
-                            this.transactions.push(transaction.clone());
+                        batch.push(transaction);
+                        if batch_started.elapsed() < LOAD_BATCH_INTERVAL {
+                            continue;
+                        }
+                        let (transactions, problems) =
+                            (std::mem::take(&mut batch), std::mem::take(&mut problems));
+                        this.update(cx, |this, cx| {
+                            this.add_loaded(transactions, problems, cx);
+                            cx.notify();
                         })
                         .map_err(|e| {
                             eprintln!("Error updating state: {}", e);
                         })
                         .ok();
+                        batch_started = Instant::now();
                     }
                     None => {
-                        this.update(cx, |_this, cx| {
+                        let (transactions, problems) =
+                            (std::mem::take(&mut batch), std::mem::take(&mut problems));
+                        this.update(cx, |this, cx| {
+                            this.add_loaded(transactions, problems, cx);
+                            for transaction in &this.hypothetical {
+                                apply_to_accounts(&mut this.accounts, transaction);
+                                record_precision(&mut this.precision, transaction);
+                            }
+                            this.loading = false;
+                            this.loaded_at = Some(chrono::Local::now());
+                            this.load_duration = Some(load_started.elapsed());
+                            this.load_journal_directives(cx);
                             cx.notify();
                         })
                         .map_err(|e| {
@@ -75,10 +1062,31 @@ impl State {
                         .ok();
                         break;
                     }
+                    Some(Err(ledger::LedgerError::Parse(problem))) if lenient => {
+                        problems.push(problem);
+                    }
+                    // Only a process that never started has no id
+                    Some(Err(e)) if ledger.pid().is_none() => {
+                        this.update(cx, |this, cx| {
+                            this.set_error(e.to_string(), cx);
+                            cx.emit(StateEvent::LedgerUnavailable);
+                            this.loading = false;
+                            cx.notify();
+                        })
+                        .map_err(|e| {
+                            eprintln!("Error updating state with error: {}", e);
+                        })
+                        .ok();
+                        break;
+                    }
                     Some(Err(e)) => {
                         eprintln!("Error parsing transaction: {}", e);
+                        let (transactions, problems) =
+                            (std::mem::take(&mut batch), std::mem::take(&mut problems));
                         this.update(cx, |this, cx| {
-                            this.error = Some(format!("Error parsing transaction: {}", e));
+                            this.add_loaded(transactions, problems, cx);
+                            this.set_error(format!("Error parsing transaction: {}", e), cx);
+                            this.loading = false;
                             cx.notify();
                         })
                         .map_err(|e| {
@@ -89,7 +1097,61 @@ impl State {
                     }
                 }
             }
-        })
-        .detach();
+        }));
+    }
+}
+
+/// Shows a notification through the desktop's own notifier, `osascript` on macOS and
+/// `notify-send` elsewhere.
+fn show_system_notification(title: &str, body: &str) {
+    let output = if cfg!(target_os = "macos") {
+        let quote = |text: &str| format!("{text:?}");
+        std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification {} with title {}",
+                quote(body),
+                quote(title)
+            ))
+            .output()
+    } else {
+        std::process::Command::new("notify-send")
+            .arg("--app-name=ledger-desktop")
+            .arg(title)
+            .arg(body)
+            .output()
+    };
+    match output {
+        Ok(output) if !output.status.success() => eprintln!(
+            "Error showing notification: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(_) => {}
+        Err(e) => eprintln!("Error showing notification: {}", e),
+    }
+}
+
+fn ledger_source(settings: &Settings, journal: Option<&PathBuf>) -> (PathBuf, Option<PathBuf>) {
+    (
+        settings.ledger_path.clone(),
+        journal.or(settings.journal.as_ref()).cloned(),
+    )
+}
+
+fn spawn_ledger(source: &(PathBuf, Option<PathBuf>), cx: &mut Context<State>) -> LedgerHandle {
+    let (binary, journal) = source.clone();
+    LedgerHandle::spawn(cx, binary, journal)
+}
+
+fn apply_to_accounts(accounts: &mut TreeNode, transaction: &Transaction) {
+    for posting in &transaction.postings {
+        accounts.add_account(&posting.account);
+        accounts.add_posting(posting);
+    }
+}
+
+fn record_precision(precision: &mut CommodityPrecision, transaction: &Transaction) {
+    for posting in &transaction.postings {
+        precision.record(&posting.amount.value);
     }
 }
@@ -0,0 +1,26 @@
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{Theme, ThemeMode};
+
+/// Text size at 100% zoom, which the rest of the UI is sized in.
+const DEFAULT_FONT_SIZE: f32 = 16.;
+
+use crate::settings::ThemePreference;
+
+/// Applies the preference to the global theme.
+pub fn apply(preference: ThemePreference, window: &mut gpui::Window, cx: &mut App) {
+    // Changing the mode would reset the zoom
+    let font_size = Theme::global(cx).font_size;
+    match preference {
+        ThemePreference::System => Theme::sync_system_appearance(Some(window), cx),
+        ThemePreference::Light => Theme::change(ThemeMode::Light, Some(window), cx),
+        ThemePreference::Dark => Theme::change(ThemeMode::Dark, Some(window), cx),
+    }
+    Theme::global_mut(cx).font_size = font_size;
+}
+
+/// Scales text, and everything sized relative to it, by the zoom level in percent.
+pub fn apply_zoom(zoom: u16, window: &mut gpui::Window, cx: &mut App) {
+    Theme::global_mut(cx).font_size = px(DEFAULT_FONT_SIZE * f32::from(zoom) / 100.);
+    window.refresh();
+}
@@ -1,35 +1,211 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::Write as _;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
+use chrono::Datelike as _;
+use fastnum::D128;
+use gpui::prelude::FluentBuilder as _;
 #[allow(clippy::wildcard_imports)]
 use gpui::*;
 use gpui_component::{
+    button::{Button, ButtonVariants as _},
     h_flex,
+    input::{Input, InputEvent, InputState},
+    menu::ContextMenuExt,
     table::{Column, Table, TableDelegate, TableState},
-    v_flex,
+    tooltip::Tooltip,
+    v_flex, ActiveTheme, Disableable as _, Selectable as _, Sizable as _,
 };
 
-use crate::{accounts::Account, transactions::Transaction};
+use crate::{
+    account_names::AccountNames,
+    accounts::{Account, AccountFilter, Balance},
+    amount_format::{Alignment, AmountFormatter},
+    bulk,
+    expr::{Env, Expr},
+    grouping::{group_postings, Group, Grouping},
+    period::{Period, PeriodKind},
+    prices::{self, PriceDirective, PriceHistory, Valuation},
+    print,
+    query::Query,
+    settings::Settings,
+    transactions::{CurrencyAmount, Posting, TextFormat, Transaction},
+};
 
 use super::{
-    balance_chart::{BalanceChart, DataPoint},
-    state::State,
+    balance_chart::{BalanceChart, ChartEvent, DataPoint, SeriesBy},
+    colors::AccountColors,
+    components::{build_menu, AccountPicker, MenuEntry, RefreshBar},
+    state::{State, StateEvent},
 };
 
+pub enum RegisterEvent {
+    /// Start a new transaction like this one.
+    Duplicate(Transaction),
+    /// Change this transaction in the journal.
+    Edit(Transaction),
+    /// Show only transactions with postings to this account.
+    FilterAccount(Account),
+}
+
+/// What a register shows, for the status bar.
+pub struct RegisterSummary {
+    pub transactions: usize,
+    /// Sum of the shown postings by commodity.
+    pub total: Balance,
+    /// Period and filters narrowing the register, described for display.
+    pub filters: Vec<String>,
+    /// Number of selected transactions and the sum of their postings, when more than
+    /// one is selected.
+    pub selection: Option<(usize, Balance)>,
+}
+
 pub struct RegisterView {
     state: Entity<State>,
     chart_state: Entity<BalanceChart>,
     table_state: Entity<TableState<TransactionTableDelegate>>,
-    filter_accounts: HashSet<Account>,
+    filter_accounts: AccountFilter,
+    /// Only transactions with this description are shown when set.
+    filter_payee: Option<String>,
+    query_input: Entity<InputState>,
+    /// Last query from the filter bar that parsed.
+    query: Option<Query>,
+    query_error: Option<String>,
+    bulk_form: Option<BulkForm>,
+    /// Date range of this register alone; the shared period is used when unset.
+    period: Option<Period>,
+    /// Rebuild running in the background, and the flag cancelling it.
+    rebuild: Option<(Task<()>, Arc<AtomicBool>)>,
+}
+
+/// What the register is built from, taken from the state and the view so the build
+/// can run off the UI thread.
+struct RegisterInput {
+    /// Journal and what-if transactions.
+    transactions: Vec<Arc<Transaction>>,
+    /// Journal transactions alone, for the prices they imply.
+    journal: Vec<Arc<Transaction>>,
+    prices: Vec<PriceDirective>,
+    filter_accounts: AccountFilter,
+    filter_payee: Option<String>,
+    query: Option<Query>,
+    period: Period,
+    valuation: Valuation,
+    /// Commodity the chart values amounts in, unless it shows them as written.
+    price_unit: Option<String>,
+    series_by: SeriesBy,
+    base_currency: Option<String>,
+    /// Commodities computed columns value amounts in.
+    value_commodities: Vec<String>,
+}
+
+/// Shown transactions with their balances, and the chart of them.
+struct RegisterData {
+    transactions: Vec<Listed>,
+    balances: Vec<Vec<Option<CurrencyAmount>>>,
+    day_balances: Vec<Option<Balance>>,
+    chart_data_points: Vec<DataPoint>,
+    series: Vec<String>,
+    /// Commodity account lines are converted to.
+    unit: Option<String>,
+    /// Prices computed columns value amounts at, by the commodity they are in.
+    prices: HashMap<String, PriceHistory>,
+}
+
+/// Input for a bulk action on the selected transactions.
+enum BulkForm {
+    /// Move postings from one account to another.
+    ChangeAccount {
+        from: Entity<AccountPicker>,
+        to: Entity<AccountPicker>,
+    },
+    /// Add a tag or `key: value` metadata.
+    Tag(Entity<InputState>),
 }
 
 impl RegisterView {
     pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let table_state =
-            cx.new(|cx| TableState::new(TransactionTableDelegate::new(vec![]), window, cx));
-        let chart_state = cx.new(|_cx| BalanceChart::new());
+        Self::build(state, false, window, cx)
+    }
 
-        cx.observe(&state, |this, _state, cx| {
+    /// Register of a single account and its subaccounts, with a running balance column.
+    pub fn for_account(
+        state: Entity<State>,
+        account: Account,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut view = Self::build(state, true, window, cx);
+        view.filter_accounts.include = HashSet::from([account]);
+        view.period = Some(view.state.read(cx).period);
+        view.rebuild_visible_transactions(cx);
+        view
+    }
+
+    fn build(
+        state: Entity<State>,
+        running_balance: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let register = cx.weak_entity();
+        let table_state = cx.new(|cx| {
+            TableState::new(
+                TransactionTableDelegate::new(state.clone(), register, running_balance),
+                window,
+                cx,
+            )
+        });
+        let chart_state = cx.new(BalanceChart::new);
+        let query_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Filter, e.g. expenses and @amazon and amount>100")
+        });
+
+        // Hovering the chart highlights its transactions in the table
+        cx.subscribe(
+            &chart_state,
+            |this, _, event: &ChartEvent, cx| match event {
+                ChartEvent::Hover(range) => this.highlight_dates(*range, cx),
+                ChartEvent::SeriesBy(_) => {
+                    this.rebuild_visible_transactions(cx);
+                    cx.notify();
+                }
+            },
+        )
+        .detach();
+
+        cx.subscribe(&query_input, |this, _, event: &InputEvent, cx| {
+            if matches!(event, InputEvent::Change) {
+                this.update_query(cx);
+            }
+        })
+        .detach();
+
+        // Only rebuilt when what it lists changes, other changes only repaint it
+        cx.subscribe(&state, |this, _state, event: &StateEvent, cx| match event {
+            StateEvent::ReloadStarted
+            | StateEvent::TransactionsAdded
+            | StateEvent::ReloadFinished
+            | StateEvent::HypotheticalChanged => this.rebuild_visible_transactions(cx),
+            // A register with a period of its own does not follow the shared one
+            StateEvent::PeriodChanged if this.period.is_none() => {
+                this.rebuild_visible_transactions(cx);
+            }
+            StateEvent::PeriodChanged
+            | StateEvent::AccountsChanged
+            | StateEvent::JournalWritten
+            | StateEvent::LedgerUnavailable
+            | StateEvent::Error(_) => {}
+        })
+        .detach();
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+
+        cx.observe_global::<Settings>(|this, cx| {
             this.rebuild_visible_transactions(cx);
+            cx.notify();
         })
         .detach();
 
@@ -37,71 +213,988 @@ impl RegisterView {
             state,
             chart_state,
             table_state,
-            filter_accounts: HashSet::new(),
+            filter_accounts: AccountFilter::default(),
+            filter_payee: None,
+            query_input,
+            query: None,
+            query_error: None,
+            bulk_form: None,
+            period: None,
+            rebuild: None,
         }
     }
 
+    /// Filters the transactions and builds the chart and table from them in the
+    /// background. A rebuild still running when the next one starts is cancelled.
     fn rebuild_visible_transactions(&mut self, cx: &mut Context<Self>) {
-        let visible_transactions = self
-            .state
-            .read(cx)
-            .transactions
+        if let Some((_, cancelled)) = self.rebuild.take() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+        let state = self.state.read(cx);
+        let settings = cx.global::<Settings>();
+        let valuation = settings.valuation;
+        let computed_columns = settings
+            .computed_columns
             .iter()
-            .filter_map(|transaction| {
-                if self.filter_accounts.is_empty() {
-                    Some(transaction.clone())
-                } else {
-                    let matching_postings = transaction
-                        .postings
-                        .iter()
-                        .filter(|posting| {
-                            self.filter_accounts.iter().any(|filter| {
-                                posting.account.eq(filter) || filter.is_parent_of(&posting.account)
-                            })
-                        })
-                        .collect::<Vec<_>>();
+            .filter_map(|column| match Expr::parse(&column.expression) {
+                Ok(expr) => Some((column.name.clone(), expr)),
+                Err(e) => {
+                    eprintln!("Error parsing column {}: {}", column.name, e);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut value_commodities = computed_columns
+            .iter()
+            .flat_map(|(_, expr)| expr.value_commodities())
+            .collect::<Vec<_>>();
+        value_commodities.sort();
+        value_commodities.dedup();
+        let input = RegisterInput {
+            transactions: state.shared_transactions(),
+            journal: state.transactions.clone(),
+            prices: state.prices.clone(),
+            filter_accounts: self.filter_accounts.clone(),
+            filter_payee: self.filter_payee.clone(),
+            query: self.query.clone(),
+            period: self.period.unwrap_or(state.period),
+            valuation,
+            price_unit: (valuation != Valuation::Native)
+                .then(|| state.main_commodity(cx).unwrap_or_default()),
+            series_by: self.chart_state.read(cx).series_by(),
+            base_currency: settings.base_currency.clone(),
+            value_commodities,
+        };
+        let formatter = state.amount_formatter(cx);
+        let account_names = state.account_names(cx);
+        let account_colors = AccountColors::new(&settings.account_colors);
 
-                    if matching_postings.is_empty() {
-                        // No matching postings, skip this transaction
-                        None
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let build = cx.background_executor().spawn({
+            let cancelled = cancelled.clone();
+            async move {
+                let started = Instant::now();
+                let data = build_register(input, &cancelled)?;
+                Some((data, started.elapsed()))
+            }
+        });
+        let apply = cx.spawn(async move |this, cx| {
+            let Some((data, took)) = build.await else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.state
+                    .update(cx, |state, _cx| state.record_rebuild("Register", took));
+                this.chart_state.update(cx, |chart_state, _cx| {
+                    if let Some(unit) = data.unit {
+                        chart_state.set_account_data(
+                            data.chart_data_points,
+                            data.series,
+                            unit,
+                            formatter.clone(),
+                        );
                     } else {
-                        Some(Transaction {
-                            postings: matching_postings.into_iter().cloned().collect(),
-                            ..transaction.clone()
-                        })
+                        chart_state.set_data(
+                            data.chart_data_points,
+                            data.series,
+                            formatter.clone(),
+                        );
                     }
-                }
+                });
+                this.table_state.update(cx, |table_state, cx| {
+                    let delegate = table_state.delegate_mut();
+                    delegate.formatter = formatter;
+                    delegate.account_names = account_names;
+                    delegate.account_colors = account_colors;
+                    delegate.transactions = data.transactions;
+                    delegate.balances = data.balances;
+                    delegate.alignment = delegate.formatter.alignment(
+                        delegate
+                            .transactions
+                            .iter()
+                            .flat_map(Listed::postings)
+                            .map(|(_, posting)| &posting.amount.value)
+                            .chain(delegate.balances.iter().flatten().flatten()),
+                    );
+                    delegate.day_balances = data.day_balances;
+                    delegate.set_computed_columns(computed_columns, data.prices);
+                    // Rows moved, so the selected indices no longer mean the same transactions
+                    delegate.selected.clear();
+                    delegate.anchor = None;
+                    delegate.rebuild_rows();
+                    table_state.refresh(cx);
+                });
+                cx.notify();
             })
-            .collect::<Vec<_>>();
-        let (chart_data_points, commodities) = build_chart_data_points(&visible_transactions);
-        self.chart_state.update(cx, |chart_state, _cx| {
-            chart_state.set_data(chart_data_points, commodities);
+            .ok();
         });
+        self.rebuild = Some((apply, cancelled));
+    }
+
+    /// Highlights the rows of transactions from the first date up to the second and
+    /// scrolls to the first of them.
+    fn highlight_dates(
+        &mut self,
+        range: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
+        cx: &mut Context<Self>,
+    ) {
         self.table_state.update(cx, |table_state, cx| {
             let delegate = table_state.delegate_mut();
-            delegate.transactions = visible_transactions;
+            delegate.highlighted = range;
+            let first_row = range.and_then(|range| {
+                (0..delegate.rows.len()).find(|&row_ix| {
+                    delegate
+                        .get_row_data(row_ix)
+                        .is_some_and(|(tx_ix, _, _)| delegate.is_highlighted(tx_ix))
+                })
+            });
+            if let Some(row_ix) = first_row {
+                table_state.scroll_to_row(row_ix, cx);
+            }
+            cx.notify();
+        });
+    }
+
+    /// Marks the date of a hovered row on the chart.
+    fn mark_chart_date(&mut self, date: Option<chrono::NaiveDate>, cx: &mut Context<Self>) {
+        self.chart_state
+            .update(cx, |chart, cx| chart.set_marker(date, cx));
+    }
+
+    fn set_grouping(&mut self, grouping: Grouping, cx: &mut Context<Self>) {
+        self.table_state.update(cx, |table_state, cx| {
+            let delegate = table_state.delegate_mut();
+            delegate.grouping = grouping;
+            delegate.collapsed.clear();
+            delegate.rebuild_rows();
             table_state.refresh(cx);
         });
+        cx.notify();
+    }
+
+    pub fn period(&self, cx: &App) -> Period {
+        self.period.unwrap_or(self.state.read(cx).period)
     }
 
-    pub fn set_account_filter(&mut self, accounts: HashSet<Account>, cx: &mut Context<Self>) {
+    /// Changes the register's own period, or the shared one if it has none.
+    pub fn set_period(&mut self, period: Period, cx: &mut Context<Self>) {
+        if self.period.is_some() {
+            self.pin_period(period, cx);
+        } else {
+            self.state
+                .update(cx, |state, cx| state.set_period(period, cx));
+        }
+    }
+
+    /// Gives the register a period of its own, no longer following the shared one.
+    pub fn pin_period(&mut self, period: Period, cx: &mut Context<Self>) {
+        self.period = Some(period);
+        self.rebuild_visible_transactions(cx);
+        cx.notify();
+    }
+
+    pub fn account_filter(&self) -> &AccountFilter {
+        &self.filter_accounts
+    }
+
+    pub fn summary(&self, cx: &App) -> RegisterSummary {
+        let delegate = self.table_state.read(cx).delegate();
+        let total = delegate.total(0..delegate.transactions.len());
+        let selection = (delegate.selected.len() > 1).then(|| {
+            (
+                delegate.selected.len(),
+                delegate.total(delegate.selected.iter().copied()),
+            )
+        });
+
+        let mut filters = vec![self.period(cx).to_string()];
+        let names = |accounts: &HashSet<Account>| {
+            let mut names = accounts
+                .iter()
+                .map(|account| delegate.account_names.full_name(account))
+                .collect::<Vec<_>>();
+            names.sort();
+            names.join(", ")
+        };
+        if !self.filter_accounts.include.is_empty() {
+            filters.push(format!("account: {}", names(&self.filter_accounts.include)));
+        }
+        if !self.filter_accounts.exclude.is_empty() {
+            filters.push(format!(
+                "not account: {}",
+                names(&self.filter_accounts.exclude)
+            ));
+        }
+        if let Some(payee) = &self.filter_payee {
+            filters.push(format!("payee: {payee}"));
+        }
+        let query = self.query_text(cx);
+        if !query.is_empty() {
+            filters.push(format!("query: {query}"));
+        }
+
+        RegisterSummary {
+            transactions: delegate.transactions.len(),
+            total,
+            filters,
+            selection,
+        }
+    }
+
+    fn render_period_navigation(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let period = self.period(cx);
+        let kind_button = |id: &'static str, label: &'static str, kind: PeriodKind| {
+            Button::new(id)
+                .label(label)
+                .xsmall()
+                .ghost()
+                .selected(period.kind == kind)
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                    this.set_period(period.with_kind(kind), cx);
+                }))
+        };
+
+        h_flex()
+            .gap_1()
+            .px_2()
+            .items_center()
+            .child(
+                Button::new("period-prev")
+                    .label("‹")
+                    .xsmall()
+                    .ghost()
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.set_period(period.prev(), cx);
+                    })),
+            )
+            .child(
+                div()
+                    .min_w(px(110.))
+                    .text_center()
+                    .text_sm()
+                    .child(period.to_string()),
+            )
+            .child(
+                Button::new("period-next")
+                    .label("›")
+                    .xsmall()
+                    .ghost()
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.set_period(period.next(), cx);
+                    })),
+            )
+            .child(div().flex_1())
+            .child(kind_button("period-month", "Month", PeriodKind::Month))
+            .child(kind_button(
+                "period-quarter",
+                "Quarter",
+                PeriodKind::Quarter,
+            ))
+            .child(kind_button("period-year", "Year", PeriodKind::Year))
+    }
+
+    pub fn set_account_filter(&mut self, accounts: AccountFilter, cx: &mut Context<Self>) {
         self.filter_accounts = accounts;
         self.rebuild_visible_transactions(cx);
     }
+
+    /// Parses the filter bar, keeping the last valid query while the text has errors.
+    fn update_query(&mut self, cx: &mut Context<Self>) {
+        match Query::parse(&self.query_input.read(cx).value()) {
+            Ok(query) => {
+                self.query = query;
+                self.query_error = None;
+                self.rebuild_visible_transactions(cx);
+            }
+            Err(e) => self.query_error = Some(e.to_string()),
+        }
+        cx.notify();
+    }
+
+    pub fn query_text(&self, cx: &App) -> String {
+        self.query_input.read(cx).value().trim().to_string()
+    }
+
+    pub fn set_query_text(&mut self, text: &str, window: &mut Window, cx: &mut Context<Self>) {
+        self.query_input.update(cx, |input, cx| {
+            input.set_value(text.to_string(), window, cx)
+        });
+        self.update_query(cx);
+    }
+
+    fn render_query_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let grouping = self.table_state.read(cx).delegate().grouping;
+        let grouping_buttons = Grouping::ALL.into_iter().map(|option| {
+            Button::new(("grouping", option as usize))
+                .label(option.label())
+                .xsmall()
+                .ghost()
+                .selected(grouping == option)
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                    this.set_grouping(option, cx);
+                }))
+        });
+        // Copies the transactions the register shows
+        let export_button = |id: &'static str, label: &'static str, format: TextFormat| {
+            Button::new(id)
+                .label(label)
+                .xsmall()
+                .ghost()
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                    let delegate = this.table_state.read(cx).delegate();
+                    let transactions = delegate
+                        .transactions
+                        .iter()
+                        .map(Listed::to_transaction)
+                        .collect::<Vec<_>>();
+                    let text = Transaction::serialize(&transactions, format);
+                    cx.write_to_clipboard(ClipboardItem::new_string(text));
+                }))
+        };
+
+        v_flex()
+            .px_2()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().flex_1().child(Input::new(&self.query_input).small()))
+                    .child(div().pl_1().text_xs().child("Group by"))
+                    .children(grouping_buttons)
+                    .child(export_button(
+                        "copy-register-json",
+                        "Copy JSON",
+                        TextFormat::Json,
+                    ))
+                    .child(export_button(
+                        "copy-register-beancount",
+                        "Copy beancount",
+                        TextFormat::Beancount,
+                    ))
+                    .child(
+                        Button::new("print-register")
+                            .label("Print…")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| this.print(cx))),
+                    ),
+            )
+            .children(
+                self.query_error
+                    .clone()
+                    .map(|error| div().text_xs().text_color(cx.theme().danger).child(error)),
+            )
+    }
+
+    /// Opens the register laid out for paper in the browser, which shows its print
+    /// dialog as soon as the page loads.
+    fn print(&mut self, cx: &mut Context<Self>) {
+        let summary = self.summary(cx);
+        let delegate = self.table_state.read(cx).delegate();
+        let transactions = delegate
+            .transactions
+            .iter()
+            .map(Listed::to_transaction)
+            .collect::<Vec<_>>();
+        let html = print::register_html(
+            &format!("Register, {}", self.period(cx)),
+            &summary.filters,
+            &transactions.iter().collect::<Vec<_>>(),
+            &delegate.formatter,
+            &cx.global::<Settings>().date_format,
+            print::ROWS_PER_PAGE,
+        );
+        // A new file each time, so no other page or process is printed or overwritten
+        let name = format!(
+            "ledger-desktop-register-{}-{}.html",
+            std::process::id(),
+            PRINTED_FILES.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        let written = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(html.as_bytes()));
+        if let Err(e) = written {
+            eprintln!("Error writing {}: {e}", path.display());
+            return;
+        }
+        cx.open_with_system(&path);
+        // The browser has the page by then
+        let remove = cx.background_executor().timer(PRINT_FILE_LIFETIME);
+        cx.background_executor()
+            .spawn(async move {
+                remove.await;
+                std::fs::remove_file(&path).ok();
+            })
+            .detach();
+    }
+
+    pub fn payee_filter(&self) -> Option<&str> {
+        self.filter_payee.as_deref()
+    }
+
+    pub fn set_payee_filter(&mut self, payee: Option<String>, cx: &mut Context<Self>) {
+        self.filter_payee = payee;
+        self.rebuild_visible_transactions(cx);
+        cx.notify();
+    }
+
+    /// Selected transactions as they are in the journal, with all their postings.
+    fn selected_transactions(&self, cx: &App) -> Vec<Transaction> {
+        let delegate = self.table_state.read(cx).delegate();
+        delegate
+            .selected
+            .iter()
+            .filter_map(|ix| delegate.transactions.get(*ix))
+            .map(|listed| Transaction::clone(&listed.transaction))
+            .collect()
+    }
+
+    fn clear_selection(&mut self, cx: &mut Context<Self>) {
+        self.bulk_form = None;
+        self.table_state.update(cx, |table_state, cx| {
+            let delegate = table_state.delegate_mut();
+            delegate.selected.clear();
+            delegate.anchor = None;
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    /// Rewrites the selected transactions' entries with `edit` as one undoable step.
+    fn edit_selected(
+        &mut self,
+        label: impl FnOnce(&str) -> String,
+        edit: impl Fn(&str) -> String + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        let transactions = self.selected_transactions(cx);
+        let label = label(&count_label(transactions.len()));
+        self.state.update(cx, |state, cx| {
+            state.edit_entries(label, &transactions, edit, cx);
+        });
+        self.clear_selection(cx);
+    }
+
+    fn delete_selected(&mut self, cx: &mut Context<Self>) {
+        let transactions = self.selected_transactions(cx);
+        self.state
+            .update(cx, |state, cx| state.delete_transactions(&transactions, cx));
+        self.clear_selection(cx);
+    }
+
+    fn open_bulk_form(&mut self, tag: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let form = if tag {
+            let input = cx.new(|cx| InputState::new(window, cx).placeholder("Tag or key: value"));
+            cx.subscribe(&input, |_this, _input, _: &InputEvent, cx| cx.notify())
+                .detach();
+            BulkForm::Tag(input)
+        } else {
+            let picker =
+                |placeholder: &'static str, window: &mut Window, cx: &mut Context<Self>| {
+                    let picker = cx
+                        .new(|cx| AccountPicker::new(self.state.clone(), placeholder, window, cx));
+                    cx.observe(&picker, |_this, _picker, cx| cx.notify())
+                        .detach();
+                    picker
+                };
+            BulkForm::ChangeAccount {
+                from: picker("From account", window, cx),
+                to: picker("To account", window, cx),
+            }
+        };
+        self.bulk_form = Some(form);
+        cx.notify();
+    }
+
+    fn apply_bulk_form(&mut self, cx: &mut Context<Self>) {
+        match &self.bulk_form {
+            Some(BulkForm::ChangeAccount { from, to }) => {
+                let (Some(from), Some(to)) = (from.read(cx).account(cx), to.read(cx).account(cx))
+                else {
+                    return;
+                };
+                let accounts = format!("from {from} to {to}");
+                self.edit_selected(
+                    |count| format!("Move {count} {accounts}"),
+                    move |entry| bulk::change_account(entry, &from, &to),
+                    cx,
+                );
+            }
+            Some(BulkForm::Tag(input)) => {
+                let tag = input.read(cx).value().trim().to_string();
+                if tag.is_empty() {
+                    return;
+                }
+                self.edit_selected(
+                    |count| format!("Tag {count} with {tag}"),
+                    {
+                        let tag = tag.clone();
+                        move |entry| bulk::add_tag(entry, &tag)
+                    },
+                    cx,
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn render_bulk_form(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let form = self.bulk_form.as_ref()?;
+        let (inputs, ready) = match form {
+            BulkForm::ChangeAccount { from, to } => (
+                h_flex()
+                    .flex_1()
+                    .gap_1()
+                    .child(div().flex_1().child(from.clone()))
+                    .child("→")
+                    .child(div().flex_1().child(to.clone())),
+                from.read(cx).account(cx).is_some() && to.read(cx).account(cx).is_some(),
+            ),
+            BulkForm::Tag(input) => (
+                h_flex().flex_1().child(Input::new(input).small()),
+                !input.read(cx).value().trim().is_empty(),
+            ),
+        };
+
+        Some(
+            h_flex()
+                .gap_1()
+                .px_2()
+                .items_center()
+                .child(inputs)
+                .child(
+                    Button::new("bulk-apply")
+                        .label("Apply")
+                        .xsmall()
+                        .primary()
+                        .disabled(!ready)
+                        .on_click(cx.listener(|this, _, _window, cx| this.apply_bulk_form(cx))),
+                )
+                .child(
+                    Button::new("bulk-cancel")
+                        .label("Cancel")
+                        .xsmall()
+                        .ghost()
+                        .on_click(cx.listener(|this, _, _window, cx| {
+                            this.bulk_form = None;
+                            cx.notify();
+                        })),
+                ),
+        )
+    }
+
+    /// Actions on the selected transactions, shown while there is a selection.
+    fn render_bulk_actions(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let count = self.table_state.read(cx).delegate().selected.len();
+        if count == 0 {
+            return None;
+        }
+
+        let copy_button = |id: &'static str, label: &'static str, format: TextFormat| {
+            Button::new(id)
+                .label(label)
+                .xsmall()
+                .ghost()
+                .on_click(cx.listener(move |this, _, _window, cx| {
+                    let text = Transaction::serialize(&this.selected_transactions(cx), format);
+                    cx.write_to_clipboard(ClipboardItem::new_string(text));
+                }))
+        };
+
+        Some(
+            v_flex()
+                .gap_1()
+                .py_1()
+                .border_b_1()
+                .border_color(cx.theme().border)
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .px_2()
+                        .items_center()
+                        .text_xs()
+                        .child(
+                            div()
+                                .flex_1()
+                                .child(format!("{} selected", count_label(count))),
+                        )
+                        .child(
+                            Button::new("bulk-cleared")
+                                .label("Mark cleared")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.edit_selected(
+                                        |count| format!("Mark {count} cleared"),
+                                        bulk::mark_cleared,
+                                        cx,
+                                    );
+                                })),
+                        )
+                        .child(
+                            Button::new("bulk-account")
+                                .label("Change account…")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.open_bulk_form(false, window, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("bulk-tag")
+                                .label("Add tag…")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.open_bulk_form(true, window, cx);
+                                })),
+                        )
+                        .child(copy_button(
+                            "bulk-copy-journal",
+                            "Copy journal",
+                            TextFormat::Journal,
+                        ))
+                        .child(copy_button("bulk-copy-csv", "Copy CSV", TextFormat::Csv))
+                        .child(copy_button(
+                            "bulk-copy-markdown",
+                            "Copy Markdown",
+                            TextFormat::Markdown,
+                        ))
+                        .child(copy_button("bulk-copy-json", "Copy JSON", TextFormat::Json))
+                        .child(copy_button(
+                            "bulk-copy-beancount",
+                            "Copy beancount",
+                            TextFormat::Beancount,
+                        ))
+                        .child(
+                            Button::new("bulk-delete")
+                                .label("Delete")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.delete_selected(cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("bulk-clear-selection")
+                                .label("×")
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.clear_selection(cx);
+                                })),
+                        ),
+                )
+                .children(self.render_bulk_form(cx)),
+        )
+    }
+
+    fn render_payee_filter(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let payee = self.filter_payee.as_ref()?;
+        Some(
+            h_flex()
+                .gap_1()
+                .px_2()
+                .items_center()
+                .text_xs()
+                .child(format!("Payee: {payee}"))
+                .child(
+                    Button::new("clear-payee-filter")
+                        .label("×")
+                        .xsmall()
+                        .ghost()
+                        .on_click(cx.listener(|this, _, _window, cx| {
+                            this.set_payee_filter(None, cx);
+                        })),
+                ),
+        )
+    }
+}
+
+/// Filters the transactions, charts them and works out their balances. `None` when
+/// `cancelled` is set before it is done.
+fn build_register(input: RegisterInput, cancelled: &AtomicBool) -> Option<RegisterData> {
+    let visible_transactions = input
+        .transactions
+        .iter()
+        .filter(|transaction| {
+            input
+                .filter_payee
+                .as_ref()
+                .is_none_or(|payee| &transaction.description == payee)
+        })
+        .map(|transaction| Listed::new(transaction.clone()))
+        .filter_map(|listed| {
+            if input.filter_accounts.is_empty() {
+                return Some(listed);
+            }
+            listed.keep_postings(|_, posting| input.filter_accounts.matches(&posting.account))
+        })
+        .filter_map(|listed| {
+            let Some(query) = &input.query else {
+                return Some(listed);
+            };
+            // Like ledger, only the postings the query matches are shown
+            listed.keep_postings(|transaction, posting| query.matches_posting(transaction, posting))
+        })
+        .collect::<Vec<_>>();
+    if cancelled.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    // The chart values amounts as settings say, the table shows them as written
+    let all = input
+        .transactions
+        .iter()
+        .map(Arc::as_ref)
+        .collect::<Vec<_>>();
+    let valued_transactions;
+    let charted_transactions = if let Some(unit) = &input.price_unit {
+        let prices = PriceHistory::new(&all, &input.prices, unit);
+        valued_transactions = visible_transactions
+            .iter()
+            .map(|listed| Listed {
+                transaction: Arc::new(
+                    prices.value_transaction(&listed.transaction, input.valuation),
+                ),
+                postings: listed.postings.clone(),
+            })
+            .collect::<Vec<_>>();
+        &valued_transactions
+    } else {
+        &visible_transactions
+    };
+    // Account lines are converted to one commodity, at the journal's own prices and
+    // then the latest price directives
+    let unit = match input.series_by {
+        SeriesBy::Commodity => None,
+        SeriesBy::Account | SeriesBy::ExpenseCategory => {
+            chart_unit(charted_transactions, input.base_currency.as_deref())
+        }
+    };
+    let rates = unit
+        .as_ref()
+        .map(|unit| {
+            let mut rates = prices::implied_rates(&input.journal, unit);
+            rates.extend(prices::latest_rates(&input.prices, unit));
+            rates
+        })
+        .unwrap_or_default();
+    let convert = |amount: &CurrencyAmount| {
+        let unit = unit.as_ref()?;
+        let value = if amount.commodity == *unit {
+            amount.value
+        } else {
+            amount.value * *rates.get(&amount.commodity)?
+        };
+        Some(decimal_to_f64(value))
+    };
+    let (mut chart_data_points, series) = match (input.series_by, unit.is_some()) {
+        (SeriesBy::Account, true) => build_chart_data_points(charted_transactions, |posting| {
+            let account = series_account(&input.filter_accounts.include, &posting.account)?;
+            Some((account.to_string(), convert(&posting.amount.value)?))
+        }),
+        (SeriesBy::ExpenseCategory, true) => {
+            build_expense_data_points(charted_transactions, convert)
+        }
+        _ => build_chart_data_points(charted_transactions, |posting| {
+            let amount = &posting.amount.value;
+            Some((amount.commodity.clone(), decimal_to_f64(amount.value)))
+        }),
+    };
+    // Balances are accumulated from the start of the journal, then cut to the period
+    chart_data_points.retain(|data_point| input.period.contains(data_point.date));
+    if cancelled.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let prices = input
+        .value_commodities
+        .iter()
+        .map(|target| {
+            let prices = PriceHistory::new(&all, &input.prices, target);
+            (target.clone(), prices)
+        })
+        .collect();
+
+    let balances = running_balances(&visible_transactions);
+    let day_balances = end_of_day_balances(&visible_transactions, &input.filter_accounts);
+    let (transactions, (balances, day_balances)) = visible_transactions
+        .into_iter()
+        .zip(balances.into_iter().zip(day_balances))
+        .filter(|(listed, _)| input.period.contains(listed.transaction.time))
+        .unzip();
+    Some(RegisterData {
+        transactions,
+        balances,
+        day_balances,
+        chart_data_points,
+        series,
+        unit,
+        prices,
+    })
 }
 
-fn build_chart_data_points(transactions: &[Transaction]) -> (Vec<DataPoint>, Vec<String>) {
-    use std::collections::{HashMap, HashSet};
+/// Balance in the posting's commodity after each shown posting, by transaction and
+/// posting index. Postings that are not shown have none.
+fn running_balances(transactions: &[Listed]) -> Vec<Vec<Option<CurrencyAmount>>> {
+    let mut balance = Balance::new();
+    transactions
+        .iter()
+        .map(|listed| {
+            let mut balances = vec![None; listed.transaction.postings.len()];
+            for (posting_ix, posting) in listed.postings() {
+                balance.add_amount(posting.amount.value.clone());
+                balances[posting_ix] = balance.get(&posting.amount.value.commodity).cloned();
+            }
+            balances
+        })
+        .collect()
+}
+
+/// Balance of the filter's accounts at the end of each transaction's day, looked up
+/// when hovering a date instead of summing the transactions again. `None` for every
+/// transaction when the register is not scoped to accounts, as both sides of each
+/// transaction would sum to nothing.
+fn end_of_day_balances(transactions: &[Listed], filter: &AccountFilter) -> Vec<Option<Balance>> {
+    if filter.include.is_empty() {
+        return vec![None; transactions.len()];
+    }
+    let mut balance = Balance::new();
+    let mut balances = Vec::with_capacity(transactions.len());
+    for (ix, listed) in transactions.iter().enumerate() {
+        for (_, posting) in listed.postings() {
+            if filter.matches(&posting.account) {
+                balance.add_amount(posting.amount.value.clone());
+            }
+        }
+        let day_ends = transactions
+            .get(ix + 1)
+            .is_none_or(|next| next.transaction.time != listed.transaction.time);
+        if day_ends {
+            // Every transaction of the day gets the balance after the last one
+            balances.resize(ix + 1, Some(balance.clone()));
+        }
+    }
+    balances
+}
+
+/// Commodity account lines are converted to: the base currency, or the one most
+/// postings are in.
+fn chart_unit(transactions: &[Listed], base_currency: Option<&str>) -> Option<String> {
+    if let Some(base) = base_currency {
+        return Some(base.to_string());
+    }
+    let mut counts = std::collections::HashMap::<&str, usize>::new();
+    for (_, posting) in transactions.iter().flat_map(Listed::postings) {
+        *counts.entry(&posting.amount.value.commodity).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(commodity, count)| (*count, std::cmp::Reverse(*commodity)))
+        .map(|(commodity, _)| commodity.to_string())
+}
+
+/// Line a posting counts towards when charting accounts: the filtered account it is
+/// in, or its top-level account when the register is not filtered.
+fn series_account(filter: &HashSet<Account>, account: &Account) -> Option<Account> {
+    if filter.is_empty() {
+        let root = account.segments.first()?.clone();
+        return Some(Account::from_segments(vec![root]));
+    }
+    filter
+        .iter()
+        .find(|filter| *filter == account || filter.is_parent_of(account))
+        .cloned()
+}
+
+/// Monthly totals of the expense categories, the accounts one level under
+/// `expenses`, dated at the start of each month from the first transaction to the last.
+fn build_expense_data_points(
+    transactions: &[Listed],
+    convert: impl Fn(&CurrencyAmount) -> Option<f64>,
+) -> (Vec<DataPoint>, Vec<String>) {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    let (Some(first), Some(last)) = (transactions.first(), transactions.last()) else {
+        return (vec![], vec![]);
+    };
+    let month_start = |date: chrono::NaiveDate| date.with_day(1).unwrap_or(date);
+
+    let mut totals = BTreeMap::<(chrono::NaiveDate, String), f64>::new();
+    let mut categories = BTreeSet::new();
+    for listed in transactions {
+        for (_, posting) in listed.postings() {
+            let segments = &posting.account.segments;
+            if segments.len() < 2 || !segments[0].eq_ignore_ascii_case("expenses") {
+                continue;
+            }
+            let Some(value) = convert(&posting.amount.value) else {
+                continue;
+            };
+            let category = Account::from_segments(segments[..2].to_vec()).to_string();
+            *totals
+                .entry((month_start(listed.transaction.time), category.clone()))
+                .or_default() += value;
+            categories.insert(category);
+        }
+    }
+    let categories = categories.into_iter().collect::<Vec<_>>();
+
+    let mut data_points = Vec::new();
+    let mut month = month_start(first.transaction.time);
+    while month <= last.transaction.time {
+        let balances = categories
+            .iter()
+            .map(|category| {
+                let total = totals
+                    .get(&(month, category.clone()))
+                    .copied()
+                    .unwrap_or(0.0);
+                (category.clone(), total)
+            })
+            .collect();
+        data_points.push(DataPoint {
+            date: month,
+            balances,
+        });
+        let Some(next) = month.checked_add_months(chrono::Months::new(1)) else {
+            break;
+        };
+        month = next;
+    }
+    (data_points, categories)
+}
+
+fn decimal_to_f64(value: D128) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+/// Running balance of each series at the end of every day, from the first
+/// transaction to the last.
+///
+/// `series` gives the series a posting counts towards and its value there, or `None`
+/// to leave it out. Series are sorted by name.
+fn build_chart_data_points(
+    transactions: &[Listed],
+    series: impl Fn(&Posting) -> Option<(String, f64)>,
+) -> (Vec<DataPoint>, Vec<String>) {
+    use std::collections::HashMap;
 
     if transactions.is_empty() {
         return (vec![], vec![]);
     }
 
-    // First pass: collect all unique commodities
+    // First pass: collect all unique series
     let mut all_commodities = HashSet::new();
-    for transaction in transactions {
-        for posting in &transaction.postings {
-            all_commodities.insert(posting.amount.value.commodity.clone());
+    for listed in transactions {
+        for (_, posting) in listed.postings() {
+            if let Some((name, _)) = series(posting) {
+                all_commodities.insert(name);
+            }
         }
     }
 
@@ -111,11 +1204,11 @@ fn build_chart_data_points(transactions: &[Transaction]) -> (Vec<DataPoint>, Vec
 
     let min_date = transactions
         .first()
-        .map(|t| t.time)
+        .map(|t| t.transaction.time)
         .expect("transactions are not empty");
     let max_date = transactions
         .last()
-        .map(|t| t.time)
+        .map(|t| t.transaction.time)
         .expect("transactions are not empty");
 
     let mut data_points = Vec::new();
@@ -133,19 +1226,12 @@ fn build_chart_data_points(transactions: &[Transaction]) -> (Vec<DataPoint>, Vec
     while current_date <= max_date {
         // Process all transactions on this date
         while transaction_idx < transactions.len()
-            && transactions[transaction_idx].time == current_date
+            && transactions[transaction_idx].transaction.time == current_date
         {
-            for posting in &transactions[transaction_idx].postings {
-                let commodity = posting.amount.value.commodity.clone();
-                let value: f64 = posting
-                    .amount
-                    .value
-                    .value
-                    .to_string()
-                    .parse()
-                    .unwrap_or(0.0);
-
-                *balances.entry(commodity).or_insert(0.0) += value;
+            for (_, posting) in transactions[transaction_idx].postings() {
+                if let Some((name, value)) = series(posting) {
+                    *balances.entry(name).or_insert(0.0) += value;
+                }
             }
             transaction_idx += 1;
         }
@@ -167,23 +1253,94 @@ fn build_chart_data_points(transactions: &[Transaction]) -> (Vec<DataPoint>, Vec
     (data_points, commodities)
 }
 
+impl EventEmitter<RegisterEvent> for RegisterView {}
+
 impl Render for RegisterView {
-    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         v_flex()
             .size_full()
+            .child(RefreshBar::new("register", self.state.clone()))
+            .child(self.render_period_navigation(cx))
+            .child(self.render_query_bar(cx))
+            .children(self.render_payee_filter(cx))
             .child(self.chart_state.clone())
+            .children(self.render_bulk_actions(cx))
             .child(Table::new(&self.table_state))
     }
 }
 
 struct TransactionTableDelegate {
-    transactions: Vec<Transaction>,
+    state: Entity<State>,
+    register: WeakEntity<RegisterView>,
+    /// Shared with the state, with the postings the filters keep.
+    transactions: Vec<Listed>,
+    /// Running balance after each shown posting of `transactions`
+    balances: Vec<Vec<Option<CurrencyAmount>>>,
+    /// Balance of the register's accounts at the end of the day of each of
+    /// `transactions`, when it is scoped to accounts.
+    day_balances: Vec<Option<Balance>>,
+    formatter: AmountFormatter,
+    /// Padding lining up the amounts and balances of the shown postings.
+    alignment: Alignment,
+    account_names: AccountNames,
+    account_colors: AccountColors,
     columns: Vec<Column>,
+    /// Columns always shown, before the computed ones.
+    base_columns: usize,
+    /// Expressions of the columns after `base_columns`.
+    computed_columns: Vec<Expr>,
+    /// Prices the computed columns value amounts at.
+    prices: HashMap<String, PriceHistory>,
+    /// Indices into `transactions` of the selected rows.
+    selected: BTreeSet<usize>,
+    /// Transaction a shift-click selects from.
+    anchor: Option<usize>,
+    grouping: Grouping,
+    groups: Vec<Group>,
+    /// Labels of the groups whose postings are hidden.
+    collapsed: HashSet<String>,
+    /// What each line of the table shows.
+    rows: Vec<Row>,
+    /// Sum of all shown postings, for the last row.
+    grand_total: Balance,
+    /// Dates of the chart's hovered data point, from the first up to the second.
+    highlighted: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
+}
+
+/// How long the page opened for printing is kept in the temporary directory.
+const PRINT_FILE_LIFETIME: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Pages opened for printing so far, numbering their files.
+static PRINTED_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Estimated width of a character in the table, for fitting columns to their text.
+const CHAR_WIDTH: f32 = 7.5;
+/// Room around a cell's text.
+const CELL_PADDING: f32 = 24.;
+const MIN_FIT_WIDTH: f32 = 60.;
+const MAX_FIT_WIDTH: f32 = 600.;
+
+/// A line of the register table.
+#[derive(Debug, Clone, Copy)]
+enum Row {
+    /// Header of `groups[ix]` with its subtotal.
+    Group(usize),
+    Posting {
+        tx_ix: usize,
+        posting_ix: usize,
+        /// Whether the row above is of another transaction.
+        is_first: bool,
+    },
+    Total,
 }
 
 impl TransactionTableDelegate {
-    fn new(transactions: Vec<Transaction>) -> Self {
-        let columns = vec![
+    fn new(
+        state: Entity<State>,
+        register: WeakEntity<RegisterView>,
+        running_balance: bool,
+    ) -> Self {
+        let mut columns = vec![
             Column::new("date", "Date").width(px(100.0)),
             Column::new("description", "Description").width(px(300.0)),
             Column::new("account", "Account").width(px(250.0)),
@@ -191,58 +1348,582 @@ impl TransactionTableDelegate {
                 .width(px(120.0))
                 .text_right(),
         ];
+        if running_balance {
+            columns.push(
+                Column::new("balance", "Balance")
+                    .width(px(140.0))
+                    .text_right(),
+            );
+        }
         Self {
-            transactions,
+            state,
+            register,
+            transactions: Vec::new(),
+            balances: Vec::new(),
+            day_balances: Vec::new(),
+            formatter: AmountFormatter::default(),
+            alignment: Alignment::default(),
+            account_names: AccountNames::default(),
+            account_colors: AccountColors::default(),
+            base_columns: columns.len(),
             columns,
+            computed_columns: Vec::new(),
+            prices: HashMap::new(),
+            selected: BTreeSet::new(),
+            anchor: None,
+            grouping: Grouping::None,
+            groups: Vec::new(),
+            collapsed: HashSet::new(),
+            rows: Vec::new(),
+            grand_total: Balance::new(),
+            highlighted: None,
         }
     }
 
+    fn is_highlighted(&self, tx_ix: usize) -> bool {
+        let (Some((start, end)), Some(listed)) = (self.highlighted, self.transactions.get(tx_ix))
+        else {
+            return false;
+        };
+        (start..end).contains(&listed.transaction.time)
+    }
+
+    /// Lays out the rows for the transactions, grouping and collapsed groups.
+    fn rebuild_rows(&mut self) {
+        let postings = self
+            .transactions
+            .iter()
+            .enumerate()
+            .flat_map(|(tx_ix, listed)| {
+                listed
+                    .postings
+                    .iter()
+                    .map(move |posting_ix| (tx_ix, *posting_ix, listed.transaction.as_ref()))
+            });
+        self.groups = group_postings(postings, self.grouping, |account| {
+            self.account_names.full_name(account)
+        });
+        self.grand_total = self.total(0..self.transactions.len());
+
+        let mut rows = Vec::new();
+        if self.groups.is_empty() {
+            rows.extend(posting_rows(self.transactions.iter().enumerate().flat_map(
+                |(tx_ix, listed)| {
+                    listed
+                        .postings
+                        .iter()
+                        .map(move |posting_ix| (tx_ix, *posting_ix))
+                },
+            )));
+        } else {
+            for (ix, group) in self.groups.iter().enumerate() {
+                rows.push(Row::Group(ix));
+                if !self.collapsed.contains(&group.label) {
+                    rows.extend(posting_rows(group.postings.iter().copied()));
+                }
+            }
+        }
+        if !self.transactions.is_empty() {
+            rows.push(Row::Total);
+        }
+        self.rows = rows;
+    }
+
+    fn toggle_group(&mut self, group_ix: usize) {
+        let Some(group) = self.groups.get(group_ix) else {
+            return;
+        };
+        if !self.collapsed.remove(&group.label) {
+            self.collapsed.insert(group.label.clone());
+        }
+        self.rebuild_rows();
+    }
+
+    /// Updates the selection for a click on a transaction's row: a plain click selects
+    /// just it, the secondary modifier toggles it and shift selects the range from the
+    /// last clicked one.
+    fn click(&mut self, tx_ix: usize, modifiers: &Modifiers) {
+        match self.anchor {
+            Some(anchor) if modifiers.shift => {
+                self.selected = (anchor.min(tx_ix)..=anchor.max(tx_ix)).collect();
+                return;
+            }
+            _ if modifiers.secondary() => {
+                if !self.selected.remove(&tx_ix) {
+                    self.selected.insert(tx_ix);
+                }
+            }
+            _ => self.selected = BTreeSet::from([tx_ix]),
+        }
+        self.anchor = Some(tx_ix);
+    }
+
+    /// Replaces the computed columns with these named expressions.
+    fn set_computed_columns(
+        &mut self,
+        columns: Vec<(String, Expr)>,
+        prices: HashMap<String, PriceHistory>,
+    ) {
+        self.columns.truncate(self.base_columns);
+        self.computed_columns.clear();
+        for (name, expr) in columns {
+            self.columns.push(
+                Column::new(name.clone(), name)
+                    .width(px(120.0))
+                    .text_right(),
+            );
+            self.computed_columns.push(expr);
+        }
+        self.prices = prices;
+    }
+
+    /// Amounts of the postings shown for a transaction, as opposed to the formatted
+    /// cells.
+    fn amounts(&self, tx_ix: usize) -> impl Iterator<Item = &CurrencyAmount> {
+        self.transactions
+            .get(tx_ix)
+            .into_iter()
+            .flat_map(Listed::postings)
+            .map(|(_, posting)| &posting.amount.value)
+    }
+
+    /// Sum of the shown postings of these transactions, by commodity.
+    fn total(&self, tx_ixs: impl IntoIterator<Item = usize>) -> Balance {
+        let mut total = Balance::new();
+        for tx_ix in tx_ixs {
+            for amount in self.amounts(tx_ix) {
+                total.add_amount(amount.clone());
+            }
+        }
+        total
+    }
+
     // Helper to get the transaction and posting index for a given row
     fn get_row_data(&self, row_ix: usize) -> Option<(usize, usize, bool)> {
-        let mut current_row = 0;
-        for (tx_ix, transaction) in self.transactions.iter().enumerate() {
-            for (posting_ix, _) in transaction.postings.iter().enumerate() {
-                if current_row == row_ix {
-                    return Some((tx_ix, posting_ix, posting_ix == 0));
+        match self.rows.get(row_ix)? {
+            Row::Posting {
+                tx_ix,
+                posting_ix,
+                is_first,
+            } => Some((*tx_ix, *posting_ix, *is_first)),
+            Row::Group(_) | Row::Total => None,
+        }
+    }
+
+    /// Cell of a group header or the total row. `collapsed` is set for group headers.
+    fn render_summary_cell(
+        &self,
+        collapsed: Option<bool>,
+        label: &str,
+        total: &Balance,
+        col_ix: usize,
+        cx: &App,
+    ) -> Div {
+        let cell = div().font_semibold().text_color(cx.theme().foreground);
+        match col_ix {
+            0 => cell.children(collapsed.map(|collapsed| if collapsed { "▸" } else { "▾" })),
+            1 => cell.child(label.to_string()),
+            3 => cell.child(self.formatter.format_balance(total)),
+            _ => div(),
+        }
+    }
+
+    /// Text of a posting's cell in the columns before the computed ones.
+    fn cell_text(
+        &self,
+        tx_ix: usize,
+        posting_ix: usize,
+        col_ix: usize,
+        cx: &App,
+    ) -> Option<String> {
+        let transaction = &self.transactions[tx_ix].transaction;
+        let posting = &transaction.postings[posting_ix];
+        match col_ix {
+            0 => Some(
+                transaction
+                    .time
+                    .format(&cx.global::<Settings>().date_format)
+                    .to_string(),
+            ),
+            1 => Some(transaction.description.clone()),
+            2 => Some(self.account_names.full_name(&posting.account)),
+            3 => {
+                let amount = &posting.amount;
+                if amount.price.is_none() && amount.date.is_none() && amount.cost.is_none() {
+                    Some(self.formatter.format_aligned(&amount.value, self.alignment))
+                } else {
+                    // Lot prices and costs run past the aligned columns
+                    Some(self.formatter.format_amount(amount))
                 }
-                current_row += 1;
             }
+            4 => {
+                let balance = self.balances.get(tx_ix)?.get(posting_ix)?.as_ref()?;
+                Some(self.formatter.format_aligned(balance, self.alignment))
+            }
+            _ => None,
+        }
+    }
+
+    /// Text of a posting's cell as it is copied, without the padding that aligns
+    /// amounts.
+    fn copy_text(
+        &self,
+        tx_ix: usize,
+        posting_ix: usize,
+        col_ix: usize,
+        cx: &App,
+    ) -> Option<String> {
+        let transaction = &self.transactions[tx_ix].transaction;
+        let posting = &transaction.postings[posting_ix];
+        if let Some(expr) = col_ix
+            .checked_sub(self.base_columns)
+            .and_then(|ix| self.computed_columns.get(ix))
+        {
+            let env = Env {
+                transaction,
+                posting,
+                prices: &self.prices,
+            };
+            return expr.eval(&env).ok().map(|value| value.to_string());
+        }
+        match col_ix {
+            3 => Some(self.formatter.format_amount(&posting.amount)),
+            4 => {
+                let balance = self.balances.get(tx_ix)?.get(posting_ix)?.as_ref()?;
+                Some(self.formatter.format(balance))
+            }
+            _ => self.cell_text(tx_ix, posting_ix, col_ix, cx),
+        }
+    }
+
+    /// Sizes the column to the longest text in it, estimated from its characters.
+    fn fit_column(&mut self, col_ix: usize, cx: &App) {
+        let longest = (0..self.rows.len())
+            .filter_map(|row_ix| {
+                let (tx_ix, posting_ix, _) = self.get_row_data(row_ix)?;
+                self.cell_text(tx_ix, posting_ix, col_ix, cx)
+            })
+            .map(|text| text.chars().count())
+            .chain([self.columns[col_ix].name.chars().count()])
+            .max()
+            .unwrap_or_default();
+        let chars = f32::from(u16::try_from(longest).unwrap_or(u16::MAX));
+        self.columns[col_ix].width =
+            px((chars * CHAR_WIDTH + CELL_PADDING).clamp(MIN_FIT_WIDTH, MAX_FIT_WIDTH));
+    }
+
+    fn render_cell(
+        &self,
+        tx_ix: usize,
+        posting_ix: usize,
+        is_first: bool,
+        col_ix: usize,
+        cx: &App,
+    ) -> Div {
+        let transaction = &self.transactions[tx_ix].transaction;
+        let posting = &transaction.postings[posting_ix];
+        let settings = cx.global::<Settings>();
+        // Subsequent postings are empty or repeat the header dimmed
+        let header = |text: String| {
+            if is_first {
+                div().child(text)
+            } else if settings.repeat_transaction_headers {
+                div().text_color(cx.theme().muted_foreground).child(text)
+            } else {
+                div()
+            }
+        };
+
+        if let Some(expr) = col_ix
+            .checked_sub(self.base_columns)
+            .and_then(|ix| self.computed_columns.get(ix))
+        {
+            let env = Env {
+                transaction,
+                posting,
+                prices: &self.prices,
+            };
+            return match expr.eval(&env) {
+                Ok(value) => div().child(value.to_string()),
+                Err(e) => div().text_color(cx.theme().danger).child(e.to_string()),
+            };
+        }
+
+        let text = self
+            .cell_text(tx_ix, posting_ix, col_ix, cx)
+            .unwrap_or_default();
+        match col_ix {
+            // Date and description
+            0 | 1 => header(text),
+            2 => {
+                // Account, with its color as an accent
+                div()
+                    .pl_1()
+                    .border_l_2()
+                    .border_color(self.account_colors.color(&posting.account, cx.theme()))
+                    .text_color(cx.theme().warning)
+                    .child(text)
+            }
+            3 => {
+                // Amount
+                let color = if self.formatter.is_highlighted(posting.amount.value.value) {
+                    cx.theme().danger
+                } else {
+                    cx.theme().success
+                };
+                div().font_family("monospace").text_color(color).child(text)
+            }
+            4 => {
+                // Running balance
+                let balance = self
+                    .balances
+                    .get(tx_ix)
+                    .and_then(|balances| balances.get(posting_ix))
+                    .cloned()
+                    .flatten();
+                div().children(balance.map(|balance| {
+                    let color = if self.formatter.is_highlighted(balance.value) {
+                        cx.theme().danger
+                    } else {
+                        cx.theme().foreground
+                    };
+                    div().font_family("monospace").text_color(color).child(text)
+                }))
+            }
+            _ => div(),
+        }
+    }
+}
+
+/// A transaction the register shows, shared with the state, and which of its
+/// postings are shown.
+#[derive(Clone)]
+struct Listed {
+    transaction: Arc<Transaction>,
+    /// Indices of the shown postings, in order.
+    postings: Vec<usize>,
+}
+
+impl Listed {
+    /// Lists the transaction with all its postings.
+    fn new(transaction: Arc<Transaction>) -> Self {
+        let postings = (0..transaction.postings.len()).collect();
+        Self {
+            transaction,
+            postings,
+        }
+    }
+
+    /// Shown postings with their indices.
+    fn postings(&self) -> impl Iterator<Item = (usize, &Posting)> {
+        self.postings
+            .iter()
+            .map(|ix| (*ix, &self.transaction.postings[*ix]))
+    }
+
+    /// Shows only the shown postings `keep` is true for, `None` when none are left.
+    fn keep_postings(mut self, keep: impl Fn(&Transaction, &Posting) -> bool) -> Option<Self> {
+        let transaction = &self.transaction;
+        self.postings
+            .retain(|ix| keep(transaction, &transaction.postings[*ix]));
+        (!self.postings.is_empty()).then_some(self)
+    }
+
+    /// The transaction with only its shown postings, for copying it out as it is shown.
+    fn to_transaction(&self) -> Transaction {
+        Transaction {
+            file: self.transaction.file.clone(),
+            line: self.transaction.line,
+            time: self.transaction.time,
+            description: self.transaction.description.clone(),
+            postings: self
+                .postings()
+                .map(|(_, posting)| posting.clone())
+                .collect(),
         }
-        None
     }
 }
 
+/// Rows of postings, marking the first of each run of a transaction's postings.
+fn posting_rows(postings: impl IntoIterator<Item = (usize, usize)>) -> impl Iterator<Item = Row> {
+    let mut previous = None;
+    postings.into_iter().map(move |(tx_ix, posting_ix)| {
+        let is_first = previous != Some(tx_ix);
+        previous = Some(tx_ix);
+        Row::Posting {
+            tx_ix,
+            posting_ix,
+            is_first,
+        }
+    })
+}
+
+fn count_label(count: usize) -> String {
+    match count {
+        1 => "1 transaction".to_string(),
+        count => format!("{count} transactions"),
+    }
+}
+
+/// Actions on a register row, for the posting to `account` of the transaction.
+fn row_menu_entries(
+    state: &Entity<State>,
+    register: &WeakEntity<RegisterView>,
+    transaction: &Transaction,
+    account: &Account,
+    cx: &App,
+) -> Vec<MenuEntry> {
+    // What-if transactions are not in any file
+    let in_journal = !transaction.file.as_os_str().is_empty();
+    let full = transaction.clone();
+    let account_name = state.read(cx).account_names(cx).full_name(account);
+    let emit = |event: fn(Transaction) -> RegisterEvent| {
+        let register = register.clone();
+        let full = full.clone();
+        move |_: &mut Window, cx: &mut App| {
+            register
+                .update(cx, |_, cx| cx.emit(event(full.clone())))
+                .ok();
+        }
+    };
+    let copy = |label: &'static str, format: TextFormat| {
+        let text = Transaction::serialize(std::slice::from_ref(&full), format);
+        MenuEntry::action(label, move |_, cx| {
+            cx.write_to_clipboard(ClipboardItem::new_string(text.clone()));
+        })
+    };
+
+    let mut entries = Vec::new();
+    if in_journal {
+        entries.push(MenuEntry::action("Edit", emit(RegisterEvent::Edit)));
+    }
+    entries.push(MenuEntry::action(
+        "Duplicate",
+        emit(RegisterEvent::Duplicate),
+    ));
+    if in_journal {
+        let file = full.file.clone();
+        entries.push(MenuEntry::action("Open in editor", move |_, cx| {
+            cx.open_with_system(&file);
+        }));
+    }
+    entries.extend([
+        MenuEntry::Separator,
+        MenuEntry::action(format!("Filter by payee “{}”", full.description), {
+            let register = register.clone();
+            let payee = full.description.clone();
+            move |_, cx| {
+                register
+                    .update(cx, |register, cx| {
+                        register.set_payee_filter(Some(payee.clone()), cx);
+                    })
+                    .ok();
+            }
+        }),
+        MenuEntry::action(format!("Filter by account {account_name}"), {
+            let register = register.clone();
+            let account = account.clone();
+            move |_, cx| {
+                register
+                    .update(cx, |_, cx| {
+                        cx.emit(RegisterEvent::FilterAccount(account.clone()));
+                    })
+                    .ok();
+            }
+        }),
+        MenuEntry::Separator,
+        copy("Copy as journal text", TextFormat::Journal),
+        copy("Copy as CSV", TextFormat::Csv),
+        copy("Copy as Markdown", TextFormat::Markdown),
+        copy("Copy as JSON", TextFormat::Json),
+        copy("Copy as beancount", TextFormat::Beancount),
+    ]);
+    if in_journal {
+        let state = state.clone();
+        entries.extend([
+            MenuEntry::Separator,
+            MenuEntry::action("Delete", move |_, cx| {
+                state.update(cx, |state, cx| {
+                    state.delete_transactions(std::slice::from_ref(&full), cx);
+                });
+            }),
+        ]);
+    }
+    entries
+}
+
 impl TableDelegate for TransactionTableDelegate {
     fn columns_count(&self, _cx: &App) -> usize {
-        4
+        self.columns.len()
     }
 
     fn rows_count(&self, _cx: &App) -> usize {
-        self.transactions.iter().map(|t| t.postings.len()).sum()
+        self.rows.len()
     }
 
     fn column(&self, col_ix: usize, _cx: &App) -> &Column {
         &self.columns[col_ix]
     }
 
+    /// Header with the column's name. Double-clicking it fits the column to its text.
+    fn render_th(
+        &mut self,
+        col_ix: usize,
+        _window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) -> impl IntoElement {
+        div()
+            .size_full()
+            .truncate()
+            // Amounts and computed columns are right-aligned
+            .when(col_ix >= 3, |this| this.text_right())
+            .child(self.columns[col_ix].name.clone())
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |table, event: &MouseDownEvent, _window, cx| {
+                    if event.click_count == 2 && col_ix < table.delegate().base_columns {
+                        table.delegate_mut().fit_column(col_ix, cx);
+                        table.refresh(cx);
+                    }
+                }),
+            )
+    }
+
     fn render_tr(
         &mut self,
         row_ix: usize,
         _window: &mut Window,
-        _cx: &mut Context<TableState<Self>>,
+        cx: &mut Context<TableState<Self>>,
     ) -> Stateful<Div> {
+        let settings = cx.global::<Settings>();
+        let row = self.get_row_data(row_ix);
         // Get the transaction index for this row to determine background color
-        let bg_color = if let Some((tx_ix, _, _)) = self.get_row_data(row_ix) {
-            if tx_ix % 2 == 0 {
-                rgb(0x000d_0d0d) // Same as table background for even transactions
+        let bg_color = if matches!(self.rows.get(row_ix), Some(Row::Group(_) | Row::Total)) {
+            cx.theme().muted
+        } else if let Some((tx_ix, _, _)) = row {
+            if self.selected.contains(&tx_ix) {
+                cx.theme().table_active
+            } else if self.is_highlighted(tx_ix) {
+                cx.theme().table_hover
+            } else if tx_ix % 2 == 0 || !settings.striped_rows {
+                cx.theme().table // Same as table background for even transactions
             } else {
-                rgb(0x0015_1515) // Slightly lighter for odd transactions
+                cx.theme().table_even // Alternate shade for odd transactions
             }
         } else {
-            rgb(0x000d_0d0d)
+            cx.theme().table
         };
+        // A line above each transaction but the first
+        let line = settings.transaction_lines
+            && row_ix > 0
+            && row.is_some_and(|(_, _, is_first)| is_first);
 
-        h_flex().id(("row", row_ix)).bg(bg_color)
+        h_flex()
+            .id(("row", row_ix))
+            .bg(bg_color)
+            .when(line, |this| {
+                this.border_t_1().border_color(cx.theme().border)
+            })
     }
 
     fn render_td(
@@ -250,45 +1931,125 @@ impl TableDelegate for TransactionTableDelegate {
         row_ix: usize,
         col_ix: usize,
         _window: &mut Window,
-        _cx: &mut Context<TableState<Self>>,
+        cx: &mut Context<TableState<Self>>,
     ) -> impl IntoElement {
-        if let Some((tx_ix, posting_ix, is_first)) = self.get_row_data(row_ix) {
-            let transaction = &self.transactions[tx_ix];
-            let posting = &transaction.postings[posting_ix];
-
-            match col_ix {
-                0 => {
-                    // Date
-                    if is_first {
-                        div().child(transaction.time.format("%Y-%m-%d").to_string())
-                    } else {
-                        div() // Empty for subsequent postings
-                    }
-                }
-                1 => {
-                    // Description
-                    if is_first {
-                        div().child(transaction.description.clone())
-                    } else {
-                        div() // Empty for subsequent postings
-                    }
-                }
-                2 => {
-                    // Account
-                    div()
-                        .text_color(rgb(0x00ff_ff80))
-                        .child(posting.account.to_string())
-                }
-                3 => {
-                    // Amount
-                    div()
-                        .text_color(rgb(0x0080_ff80))
-                        .child(posting.amount.to_string())
-                }
-                _ => div(),
+        let id = ("cell", row_ix * self.columns.len() + col_ix);
+        match self.rows.get(row_ix) {
+            Some(&Row::Group(group_ix)) => {
+                let group = &self.groups[group_ix];
+                let collapsed = self.collapsed.contains(&group.label);
+                let table = cx.entity().downgrade();
+                return div()
+                    .id(id)
+                    .size_full()
+                    .child(self.render_summary_cell(
+                        Some(collapsed),
+                        &group.label,
+                        &group.subtotal,
+                        col_ix,
+                        cx,
+                    ))
+                    .on_mouse_down(MouseButton::Left, move |_, _window, cx| {
+                        table
+                            .update(cx, |table, cx| {
+                                table.delegate_mut().toggle_group(group_ix);
+                                table.refresh(cx);
+                            })
+                            .ok();
+                    })
+                    .into_any_element();
             }
-        } else {
-            div()
+            Some(Row::Total) => {
+                return self
+                    .render_summary_cell(None, "Total", &self.grand_total, col_ix, cx)
+                    .into_any_element();
+            }
+            Some(Row::Posting { .. }) | None => {}
         }
+        let Some((tx_ix, posting_ix, is_first)) = self.get_row_data(row_ix) else {
+            return div().into_any_element();
+        };
+
+        let transaction = self.transactions[tx_ix].transaction.clone();
+        let account = transaction.postings[posting_ix].account.clone();
+        let state = self.state.clone();
+        let register = self.register.clone();
+        let table = cx.entity().downgrade();
+        let copy_cell = self.copy_text(tx_ix, posting_ix, col_ix, cx).map(|text| {
+            let label = format!("Copy {}", self.columns[col_ix].name.to_lowercase());
+            MenuEntry::action(label, move |_, cx| {
+                cx.write_to_clipboard(ClipboardItem::new_string(text.clone()));
+            })
+        });
+        let day_balance = (col_ix == 0 && is_first)
+            .then(|| self.day_balances.get(tx_ix))
+            .flatten()
+            .and_then(Option::as_ref)
+            .map(|balance| -> SharedString {
+                let date_format = &cx.global::<Settings>().date_format;
+                format!(
+                    "Balance at end of {}: {}",
+                    transaction.time.format(date_format),
+                    self.formatter.format_balance(balance)
+                )
+                .into()
+            });
+        // Clipped text is shown in full on hover
+        let repeated = is_first || cx.global::<Settings>().repeat_transaction_headers;
+        let full_text = (col_ix == 2 || (col_ix == 1 && repeated))
+            .then(|| self.cell_text(tx_ix, posting_ix, col_ix, cx))
+            .flatten()
+            .map(SharedString::from);
+        div()
+            .id(id)
+            .size_full()
+            .child(
+                self.render_cell(tx_ix, posting_ix, is_first, col_ix, cx)
+                    .w_full()
+                    .truncate(),
+            )
+            .when_some(day_balance.or(full_text), |this, text| {
+                this.tooltip(move |window, cx| Tooltip::new(text.clone()).build(window, cx))
+            })
+            .on_hover({
+                let register = register.clone();
+                let date = transaction.time;
+                move |hovered, _window, cx| {
+                    register
+                        .update(cx, |register, cx| {
+                            register.mark_chart_date(hovered.then_some(date), cx);
+                        })
+                        .ok();
+                }
+            })
+            .on_mouse_down(MouseButton::Left, {
+                let register = register.clone();
+                move |event, _window, cx| {
+                    table
+                        .update(cx, |table, cx| {
+                            table.delegate_mut().click(tx_ix, &event.modifiers);
+                            cx.notify();
+                        })
+                        .ok();
+                    // The register shows actions for the selection
+                    register.update(cx, |_, cx| cx.notify()).ok();
+                }
+            })
+            .context_menu(move |menu, _window, cx| {
+                // The cell's own text comes before the row's actions
+                let entries = copy_cell
+                    .clone()
+                    .into_iter()
+                    .chain([MenuEntry::Separator])
+                    .chain(row_menu_entries(
+                        &state,
+                        &register,
+                        &transaction,
+                        &account,
+                        cx,
+                    ));
+                build_menu(menu, entries)
+            })
+            .into_any_element()
     }
 }
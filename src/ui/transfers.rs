@@ -0,0 +1,120 @@
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Sizable as _,
+};
+
+use crate::transfers::{self, TransferPair};
+
+use super::state::State;
+
+/// Transfers between own accounts in the selected period: pairs that look like one, to
+/// confirm, and confirmed ones, to unpair.
+pub struct TransfersView {
+    state: Entity<State>,
+}
+
+impl TransfersView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self { state }
+    }
+
+    /// Tags both entries of the pair, or takes the tag off them with `confirm` unset.
+    fn set_confirmed(&mut self, pair: &TransferPair, confirm: bool, cx: &mut Context<Self>) {
+        let label = if confirm {
+            format!("Pair {} as a transfer", pair.outgoing.description)
+        } else {
+            format!("Unpair {}", pair.outgoing.description)
+        };
+        let edit = if confirm {
+            transfers::mark
+        } else {
+            transfers::unmark
+        };
+        let entries = [pair.outgoing.clone(), pair.incoming.clone()];
+        self.state.update(cx, |state, cx| {
+            state.edit_entries(label, &entries, edit, cx);
+        });
+    }
+}
+
+impl Render for TransfersView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let formatter = state.amount_formatter(cx);
+        let names = state.account_names(cx);
+        let period = state.period;
+        let transactions = state.all_transactions();
+        let in_period = |pairs: Vec<TransferPair>| {
+            pairs
+                .into_iter()
+                .filter(|pair| period.contains(pair.outgoing.time))
+                .collect::<Vec<_>>()
+        };
+        let suggested = in_period(transfers::find_pairs(&transactions, false));
+        let confirmed = in_period(transfers::find_pairs(&transactions, true));
+        let muted = cx.theme().muted_foreground;
+
+        let mut rows = Vec::new();
+        for (id, title, pairs, confirm) in [
+            ("transfer-confirm", "Suggested", &suggested, true),
+            ("transfer-unpair", "Confirmed", &confirmed, false),
+        ] {
+            if pairs.is_empty() {
+                continue;
+            }
+            rows.push(div().pt_2().text_xs().text_color(muted).child(title));
+            for (ix, pair) in pairs.iter().enumerate() {
+                let clicked = pair.clone();
+                rows.push(
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .text_sm()
+                        .child(
+                            div()
+                                .w(px(90.))
+                                .child(pair.outgoing.time.format("%Y-%m-%d").to_string()),
+                        )
+                        .child(div().flex_1().child(format!(
+                            "{} → {}",
+                            names.full_name(pair.from()),
+                            names.full_name(pair.to())
+                        )))
+                        .child(div().flex_1().text_color(muted).child(format!(
+                            "{} / {}",
+                            pair.outgoing.description, pair.incoming.description
+                        )))
+                        .child(
+                            div()
+                                .w(px(110.))
+                                .text_right()
+                                .child(formatter.format(pair.amount())),
+                        )
+                        .child(
+                            Button::new((id, ix))
+                                .label(if confirm { "Confirm" } else { "Unpair" })
+                                .xsmall()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.set_confirmed(&clicked, confirm, cx);
+                                })),
+                        ),
+                );
+            }
+        }
+
+        v_flex()
+            .gap_1()
+            .child(div().text_sm().child("Transfers"))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(muted)
+                    .child("Entries tagged :transfer: are left out of income and expenses."),
+            )
+            .children(rows)
+    }
+}
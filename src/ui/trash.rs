@@ -0,0 +1,84 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{button::Button, h_flex, v_flex, ActiveTheme, Sizable as _};
+
+use crate::settings::Settings;
+
+use super::state::State;
+
+/// Lists entries commented out in the journal, with a way to bring them back.
+pub struct TrashView {
+    state: Entity<State>,
+}
+
+impl TrashView {
+    pub fn new(state: Entity<State>, cx: &mut Context<Self>) -> Self {
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+        Self { state }
+    }
+}
+
+impl Render for TrashView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let date_format = cx.global::<Settings>().date_format.clone();
+        let deleted = self.state.read(cx).deleted.clone();
+        let empty = deleted.is_empty();
+
+        v_flex()
+            .id("trash")
+            .size_full()
+            .gap_1()
+            .p_2()
+            .overflow_y_scroll()
+            .child(div().text_sm().child("Deleted"))
+            .when(empty, |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("No deleted transactions"),
+                )
+            })
+            .children(
+                deleted
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, entry)| {
+                        let location = entry.file.display().to_string();
+                        h_flex()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .child(
+                                div()
+                                    .w(px(100.))
+                                    .text_sm()
+                                    .child(entry.date.format(&date_format).to_string()),
+                            )
+                            .child(
+                                v_flex()
+                                    .flex_1()
+                                    .child(div().text_sm().child(entry.description.clone()))
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child(location),
+                                    ),
+                            )
+                            .child(
+                                Button::new(("trash-restore", ix))
+                                    .label("Restore")
+                                    .xsmall()
+                                    .on_click(cx.listener(move |this, _, _window, cx| {
+                                        this.state.update(cx, |state, cx| {
+                                            state.restore_deleted(&entry, cx)
+                                        });
+                                    })),
+                            )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
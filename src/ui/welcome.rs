@@ -0,0 +1,268 @@
+use std::path::PathBuf;
+
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, Disableable, Sizable as _,
+};
+
+use crate::settings::Settings;
+
+use super::{
+    new_journal::{NewJournalEvent, NewJournalView},
+    settings,
+};
+
+pub enum WelcomeEvent {
+    /// A journal was picked, created or chosen from the recent ones.
+    Open(PathBuf),
+}
+
+/// Shown when there is no journal to read: ways to get one, and the journals opened
+/// before to open again, pin or forget.
+pub struct WelcomeView {
+    /// Form for a new journal, while it is shown.
+    new_journal: Option<Entity<NewJournalView>>,
+}
+
+impl EventEmitter<WelcomeEvent> for WelcomeView {}
+
+impl WelcomeView {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<Settings>(|_this, cx| cx.notify())
+            .detach();
+        Self { new_journal: None }
+    }
+
+    /// Asks for a file and hands it to `then`.
+    fn pick_file(
+        &mut self,
+        cx: &mut Context<Self>,
+        then: impl FnOnce(&mut Self, PathBuf, &mut Context<Self>) + 'static,
+    ) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: None,
+        });
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(paths))) = paths.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            this.update(cx, |this, cx| then(this, path, cx)).ok();
+        })
+        .detach();
+    }
+
+    fn show_new_journal(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let form = cx.new(|cx| NewJournalView::new(window, cx));
+        cx.subscribe(&form, |this, _, event: &NewJournalEvent, cx| {
+            if let NewJournalEvent::Created(path) = event {
+                cx.emit(WelcomeEvent::Open(path.clone()));
+            }
+            this.new_journal = None;
+            cx.notify();
+        })
+        .detach();
+        self.new_journal = Some(form);
+        cx.notify();
+    }
+
+    fn render_actions(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let ledger_path = cx.global::<Settings>().ledger_path.display().to_string();
+
+        v_flex()
+            .gap_1()
+            .pb_3()
+            .child(div().text_sm().child("No journal to open"))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(
+                        "Pick a journal, start a new one, or set LEDGER_FILE. Journals can also \
+                         be dropped on the window.",
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("welcome-open-file")
+                            .label("Open journal…")
+                            .small()
+                            .primary()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.pick_file(cx, |_this, path, cx| {
+                                    cx.emit(WelcomeEvent::Open(path));
+                                });
+                            })),
+                    )
+                    .child(
+                        Button::new("welcome-new-journal")
+                            .label("New journal…")
+                            .small()
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.show_new_journal(window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("welcome-ledger-path")
+                            .label("Locate ledger…")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.pick_file(cx, |_this, path, cx| {
+                                    update_settings(cx, |settings| settings.ledger_path = path);
+                                });
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(format!("Ledger binary: {ledger_path}")),
+            )
+            .children(
+                self.new_journal
+                    .clone()
+                    .map(|form| div().pt_2().child(form)),
+            )
+    }
+}
+
+fn update_settings(cx: &mut App, f: impl FnOnce(&mut Settings)) {
+    if let Err(e) = settings::update(cx, f) {
+        eprintln!("Error saving settings: {}", e);
+    }
+}
+
+impl Render for WelcomeView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let recent = cx.global::<Settings>().recent_journals.clone();
+        let missing = recent.iter().any(|journal| !journal.path.exists());
+        let actions = self.render_actions(cx);
+        let theme = cx.theme();
+
+        v_flex()
+            .id("welcome")
+            .size_full()
+            .gap_1()
+            .p_2()
+            .overflow_y_scroll()
+            .child(actions)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().flex_1().text_sm().child("Recent journals"))
+                    .when(missing, |this| {
+                        this.child(
+                            Button::new("welcome-forget-missing")
+                                .label("Remove missing")
+                                .xsmall()
+                                .ghost()
+                                .on_click(|_, _window, cx| {
+                                    update_settings(cx, |settings| {
+                                        settings
+                                            .recent_journals
+                                            .retain(|journal| journal.path.exists());
+                                    });
+                                }),
+                        )
+                    }),
+            )
+            .when(recent.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child("Journals you open, or drop on the window, are listed here"),
+                )
+            })
+            .children(
+                recent
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, journal)| {
+                        let exists = journal.path.exists();
+                        let name = journal
+                            .path
+                            .file_name()
+                            .unwrap_or(journal.path.as_os_str())
+                            .to_string_lossy()
+                            .into_owned();
+                        let path = journal.path.clone();
+                        h_flex()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .child(
+                                v_flex()
+                                    .flex_1()
+                                    .child(
+                                        Button::new(("welcome-open", ix))
+                                            .label(name)
+                                            .small()
+                                            .ghost()
+                                            .disabled(!exists)
+                                            .on_click(cx.listener({
+                                                let path = path.clone();
+                                                move |_this, _, _window, cx| {
+                                                    cx.emit(WelcomeEvent::Open(path.clone()));
+                                                }
+                                            })),
+                                    )
+                                    .child(
+                                        div()
+                                            .px_2()
+                                            .text_xs()
+                                            .text_color(if exists {
+                                                theme.muted_foreground
+                                            } else {
+                                                theme.danger
+                                            })
+                                            .child(if exists {
+                                                journal.path.display().to_string()
+                                            } else {
+                                                format!("Missing: {}", journal.path.display())
+                                            }),
+                                    ),
+                            )
+                            .child(
+                                Button::new(("welcome-pin", ix))
+                                    .label(if journal.pinned { "Unpin" } else { "Pin" })
+                                    .xsmall()
+                                    .ghost()
+                                    .on_click({
+                                        let path = path.clone();
+                                        move |_, _window, cx| {
+                                            update_settings(cx, |settings| {
+                                                settings.pin_journal(&path, !journal.pinned);
+                                            });
+                                        }
+                                    }),
+                            )
+                            .child(
+                                Button::new(("welcome-forget", ix))
+                                    .label("Remove")
+                                    .xsmall()
+                                    .ghost()
+                                    .on_click(move |_, _window, cx| {
+                                        update_settings(cx, |settings| {
+                                            settings.forget_journal(&path);
+                                        });
+                                    }),
+                            )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
@@ -0,0 +1,612 @@
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use gpui_component::{
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex, ActiveTheme, Disableable, Selectable as _, Sizable as _,
+};
+
+use crate::{
+    balancing,
+    split::{self, Share},
+    transactions::{Amount, CurrencyAmount, Posting, PostingStatus, Transaction},
+};
+
+use super::{
+    components::{AccountPicker, AccountPickerEvent},
+    state::State,
+};
+
+/// Account and amount inputs of one posting.
+struct PostingRow {
+    account: Entity<AccountPicker>,
+    amount: Entity<InputState>,
+    /// Part of the split total the posting gets, used while splitting.
+    share: Entity<InputState>,
+}
+
+/// Form for adding hypothetical transactions to the session, or editing one from the
+/// journal.
+pub struct WhatIfView {
+    state: Entity<State>,
+    date: Entity<InputState>,
+    description: Entity<InputState>,
+    postings: Vec<PostingRow>,
+    /// True while the first posting's amount is divided across the others.
+    splitting: bool,
+    /// Amount divided across the postings while splitting.
+    total: Entity<InputState>,
+    /// Journal transaction the form replaces instead of adding a what-if one.
+    editing: Option<Transaction>,
+    error: Option<String>,
+}
+
+impl WhatIfView {
+    pub fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let date = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("Date");
+            input.set_value(today(), window, cx);
+            input
+        });
+        let description = cx.new(|cx| InputState::new(window, cx).placeholder("Description"));
+        let total = cx.new(|cx| InputState::new(window, cx).placeholder("Total to split"));
+
+        cx.observe(&state, |_this, _state, cx| cx.notify()).detach();
+
+        let mut view = Self {
+            state,
+            date,
+            description,
+            postings: Vec::new(),
+            splitting: false,
+            total,
+            editing: None,
+            error: None,
+        };
+        view.add_posting_row(window, cx);
+        view.add_posting_row(window, cx);
+        view
+    }
+
+    fn add_posting_row(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let placeholder = if self.postings.is_empty() {
+            "Account"
+        } else {
+            "Balancing account"
+        };
+        let account = cx.new(|cx| AccountPicker::new(self.state.clone(), placeholder, window, cx));
+        let amount = cx.new(|cx| InputState::new(window, cx).placeholder("Amount"));
+        let share = cx.new(|cx| InputState::new(window, cx).placeholder("Rest"));
+
+        cx.subscribe_in(
+            &account,
+            window,
+            |this, account, _: &AccountPickerEvent, window, cx| {
+                let Some(row) = this.postings.iter().find(|row| &row.account == account) else {
+                    return;
+                };
+                let amount = row.amount.read(cx).focus_handle(cx);
+                window.focus(&amount);
+            },
+        )
+        .detach();
+        cx.subscribe_in(&amount, window, |this, _, event, window, cx| match event {
+            InputEvent::PressEnter { .. } => this.add(window, cx),
+            // Validation is shown as amounts are typed
+            InputEvent::Change => cx.notify(),
+            _ => {}
+        })
+        .detach();
+        cx.observe(&account, |_this, _account, cx| cx.notify())
+            .detach();
+
+        self.postings.push(PostingRow {
+            account,
+            amount,
+            share,
+        });
+        cx.notify();
+    }
+
+    fn remove_posting_row(&mut self, index: usize, cx: &mut Context<Self>) {
+        if self.postings.len() > 2 {
+            self.postings.remove(index);
+            cx.notify();
+        }
+    }
+
+    /// Fills the form with the transaction's description and postings, dated today.
+    pub fn fill_from(
+        &mut self,
+        transaction: &Transaction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.postings.clear();
+        for posting in &transaction.postings {
+            self.add_posting_row(window, cx);
+            let row = self.postings.last().expect("row was just added");
+            row.account.update(cx, |picker, cx| {
+                picker.set_account(Some(&posting.account), window, cx);
+            });
+            // Lot dates belong to the original purchase
+            let amount = Amount {
+                date: None,
+                ..posting.amount.clone()
+            };
+            row.amount.update(cx, |input, cx| {
+                input.set_value(amount.to_string(), window, cx)
+            });
+            let share = posting.note.as_deref().and_then(Share::from_note);
+            row.share.update(cx, |input, cx| {
+                input.set_value(
+                    share.map(|share| share.to_string()).unwrap_or_default(),
+                    window,
+                    cx,
+                );
+            });
+        }
+
+        // Postings tagged `shared::` divide what the first one pays
+        self.splitting = transaction
+            .postings
+            .iter()
+            .any(|posting| posting.note.as_deref().and_then(Share::from_note).is_some());
+        if let (true, Some(first)) = (self.splitting, transaction.postings.first()) {
+            let total = CurrencyAmount {
+                value: -first.amount.value.value,
+                commodity: first.amount.value.commodity.clone(),
+            };
+            self.total.update(cx, |input, cx| {
+                input.set_value(total.to_string(), window, cx)
+            });
+        }
+        while self.postings.len() < 2 {
+            self.add_posting_row(window, cx);
+        }
+
+        self.date
+            .update(cx, |input, cx| input.set_value(today(), window, cx));
+        self.description.update(cx, |input, cx| {
+            input.set_value(transaction.description.clone(), window, cx);
+        });
+        self.editing = None;
+        self.error = None;
+        cx.notify();
+    }
+
+    /// Fills the form with the transaction to change it in the journal.
+    pub fn edit(&mut self, transaction: &Transaction, window: &mut Window, cx: &mut Context<Self>) {
+        self.fill_from(transaction, window, cx);
+        let date = transaction.time.format("%Y-%m-%d").to_string();
+        self.date
+            .update(cx, |input, cx| input.set_value(date, window, cx));
+        self.editing = Some(transaction.clone());
+    }
+
+    fn clear(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.description
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        for row in &self.postings {
+            row.amount
+                .update(cx, |input, cx| input.set_value("", window, cx));
+        }
+    }
+
+    /// Fills the form from the latest transaction in the journal.
+    fn repeat_last(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let last = self
+            .state
+            .read(cx)
+            .all_transactions()
+            .into_iter()
+            .max_by_key(|transaction| transaction.time)
+            .cloned();
+        if let Some(last) = last {
+            self.fill_from(&last, window, cx);
+        }
+    }
+
+    /// Divides the total across the postings after the first by their shares, and
+    /// sets the first to pay it.
+    fn apply_split(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match self.split_amounts(cx) {
+            Ok(amounts) => {
+                self.error = None;
+                for (row, amount) in self.postings.iter().zip(amounts) {
+                    row.amount.update(cx, |input, cx| {
+                        input.set_value(amount.to_string(), window, cx)
+                    });
+                }
+            }
+            Err(e) => self.error = Some(e),
+        }
+        cx.notify();
+    }
+
+    fn split_amounts(&self, cx: &App) -> Result<Vec<CurrencyAmount>, String> {
+        let total = CurrencyAmount::parse(&self.total.read(cx).value())
+            .map_err(|e| format!("Invalid total: {e}"))?;
+        let shares = self.postings[1..]
+            .iter()
+            .map(|row| Share::parse(&row.share.read(cx).value()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let places = self
+            .state
+            .read(cx)
+            .amount_formatter(cx)
+            .places(&total.commodity);
+        let amounts = split::split(
+            total.value,
+            &shares,
+            i16::try_from(places).unwrap_or(i16::MAX),
+        )
+        .map_err(|e| e.to_string())?;
+
+        let amount = |value| CurrencyAmount {
+            value,
+            commodity: total.commodity.clone(),
+        };
+        Ok(std::iter::once(amount(-total.value))
+            .chain(amounts.into_iter().map(amount))
+            .collect())
+    }
+
+    /// Checks the posting rows as they are typed.
+    fn validate(&self, cx: &App) -> Validation {
+        let mut validation = Validation::default();
+        let mut amounts = Vec::new();
+        for (ix, row) in self.postings.iter().enumerate() {
+            if row.account.read(cx).account(cx).is_none() {
+                continue;
+            }
+            let amount = row.amount.read(cx).value();
+            if amount.trim().is_empty() {
+                amounts.push((ix, None));
+                continue;
+            }
+            match Amount::parse(&amount) {
+                Ok(amount) => amounts.push((ix, Some(amount))),
+                Err(e) => validation
+                    .invalid
+                    .push((ix, format!("Invalid amount: {e}"))),
+            }
+        }
+        if !validation.invalid.is_empty() {
+            return validation;
+        }
+        // Nothing to balance until there are two postings
+        if amounts.len() < 2 {
+            return validation;
+        }
+
+        let values = amounts
+            .iter()
+            .map(|(_, amount)| amount.as_ref())
+            .collect::<Vec<_>>();
+        match balancing::balance(&values) {
+            Ok(implied) => {
+                validation.implied = implied.and_then(|implied| {
+                    let (ix, _) = amounts.iter().find(|(_, amount)| amount.is_none())?;
+                    Some((*ix, implied))
+                });
+            }
+            Err(e) => validation.error = Some(e.to_string()),
+        }
+        validation
+    }
+
+    fn parse_transaction(&self, cx: &App) -> Result<Transaction, String> {
+        let date = self.date.read(cx).value();
+        let time = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date: {e}"))?;
+
+        let validation = self.validate(cx);
+        if let Some((_, error)) = validation.invalid.first() {
+            return Err(error.clone());
+        }
+        if let Some(error) = &validation.error {
+            return Err(error.clone());
+        }
+
+        let postings = self
+            .postings
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, row)| {
+                let account = row.account.read(cx).account(cx)?;
+                let amount = match &validation.implied {
+                    Some((implied_ix, implied)) if *implied_ix == ix => Amount {
+                        value: implied.clone(),
+                        price: None,
+                        date: None,
+                        cost: None,
+                    },
+                    _ => Amount::parse(&row.amount.read(cx).value()).ok()?,
+                };
+                Some(Posting {
+                    account,
+                    amount,
+                    status: PostingStatus::Uncleared,
+                    note: None,
+                })
+            })
+            .collect::<Vec<_>>();
+        if postings.len() < 2 {
+            return Err("At least two accounts are required".into());
+        }
+
+        Ok(Transaction {
+            file: std::path::PathBuf::new(),
+            line: 0,
+            time,
+            description: self.description.read(cx).value().to_string(),
+            postings,
+        })
+    }
+
+    fn add(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match self.parse_transaction(cx) {
+            Ok(transaction) => {
+                self.error = None;
+                match self.editing.take() {
+                    Some(original) => {
+                        let replacement = keep_statuses(&original, transaction);
+                        self.state.update(cx, |state, cx| {
+                            state.replace_transaction(&original, replacement, cx);
+                        });
+                    }
+                    None => self.state.update(cx, |state, cx| {
+                        state.add_hypothetical(transaction, cx);
+                    }),
+                }
+                self.clear(window, cx);
+            }
+            // Problems with the postings are already shown next to them
+            Err(_) if !self.validate(cx).is_valid() => self.error = None,
+            Err(e) => self.error = Some(e),
+        }
+        cx.notify();
+    }
+
+    fn render_posting_rows(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let removable = self.postings.len() > 2;
+        let validation = self.validate(cx);
+        let formatter = self.state.read(cx).amount_formatter(cx);
+        let theme = cx.theme();
+        v_flex()
+            .gap_2()
+            .children(
+                self.postings
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, row)| {
+                        let invalid = validation
+                            .invalid
+                            .iter()
+                            .find(|(invalid_ix, _)| *invalid_ix == ix)
+                            .map(|(_, error)| {
+                                div()
+                                    .text_xs()
+                                    .text_color(theme.danger)
+                                    .child(error.clone())
+                            });
+                        let implied = validation
+                            .implied
+                            .as_ref()
+                            .filter(|(implied_ix, _)| *implied_ix == ix)
+                            .map(|(_, implied)| {
+                                div()
+                                    .text_xs()
+                                    .text_color(theme.muted_foreground)
+                                    .child(format!("Balances with {}", formatter.format(implied)))
+                            });
+                        v_flex()
+                            .child(
+                                h_flex()
+                                    .gap_1()
+                                    .child(div().flex_1().child(row.account.clone()))
+                                    .when(self.splitting && ix > 0, |this| {
+                                        this.child(div().w(px(60.)).child(Input::new(&row.share)))
+                                    })
+                                    .child(div().w(px(90.)).child(Input::new(&row.amount)))
+                                    .child(
+                                        Button::new(("what-if-remove-posting", ix))
+                                            .label("×")
+                                            .xsmall()
+                                            .ghost()
+                                            .disabled(!removable)
+                                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                                this.remove_posting_row(ix, cx);
+                                            })),
+                                    ),
+                            )
+                            .children(invalid)
+                            .children(implied)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .children(
+                validation
+                    .error
+                    .map(|error| div().text_xs().text_color(theme.danger).child(error)),
+            )
+    }
+}
+
+/// Live result of checking the posting rows.
+#[derive(Default)]
+struct Validation {
+    /// Rows whose amount does not parse, with the error.
+    invalid: Vec<(usize, String)>,
+    /// Row without an amount and the amount that balances the transaction.
+    implied: Option<(usize, CurrencyAmount)>,
+    error: Option<String>,
+}
+
+impl Validation {
+    fn is_valid(&self) -> bool {
+        self.invalid.is_empty() && self.error.is_none()
+    }
+}
+
+/// Takes over the cleared marks and notes of the original's postings that the form
+/// doesn't show.
+fn keep_statuses(original: &Transaction, mut transaction: Transaction) -> Transaction {
+    for (posting, before) in transaction.postings.iter_mut().zip(&original.postings) {
+        if posting.account == before.account {
+            posting.status = before.status;
+            posting.note = before.note.clone();
+        }
+    }
+    Transaction {
+        file: original.file.clone(),
+        line: original.line,
+        ..transaction
+    }
+}
+
+fn today() -> String {
+    chrono::Local::now()
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+impl Render for WhatIfView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let hypothetical = self
+            .state
+            .read(cx)
+            .hypothetical
+            .iter()
+            .map(|transaction| {
+                format!(
+                    "{} {}",
+                    transaction.time.format("%Y-%m-%d"),
+                    transaction.description
+                )
+            })
+            .collect::<Vec<_>>();
+        let has_hypothetical = !hypothetical.is_empty();
+        let editing = self.editing.is_some();
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .p_2()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(div().flex_1().text_sm().child(if editing {
+                        "Edit transaction"
+                    } else {
+                        "What-if"
+                    }))
+                    .child(
+                        Button::new("what-if-split")
+                            .label("Split")
+                            .xsmall()
+                            .ghost()
+                            .selected(self.splitting)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.splitting = !this.splitting;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("what-if-repeat-last")
+                            .label("Repeat last")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.repeat_last(window, cx);
+                            })),
+                    ),
+            )
+            .child(Input::new(&self.date))
+            .child(Input::new(&self.description))
+            .when(self.splitting, |this| {
+                this.child(
+                    h_flex()
+                        .gap_1()
+                        .child(div().flex_1().child(Input::new(&self.total)))
+                        .child(
+                            Button::new("what-if-apply-split")
+                                .label("Apply split")
+                                .xsmall()
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.apply_split(window, cx);
+                                })),
+                        ),
+                )
+            })
+            .child(self.render_posting_rows(cx))
+            .child(
+                Button::new("what-if-add-posting")
+                    .label("Add posting")
+                    .xsmall()
+                    .ghost()
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.add_posting_row(window, cx);
+                    })),
+            )
+            .children(
+                self.error
+                    .clone()
+                    .map(|error| div().text_xs().text_color(cx.theme().danger).child(error)),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("what-if-add")
+                            .label(if editing { "Save" } else { "Add" })
+                            .on_click(cx.listener(|this, _, window, cx| this.add(window, cx))),
+                    )
+                    .when(editing, |this| {
+                        this.child(
+                            Button::new("what-if-cancel-edit")
+                                .label("Cancel")
+                                .ghost()
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.editing = None;
+                                    this.clear(window, cx);
+                                    cx.notify();
+                                })),
+                        )
+                    }),
+            )
+            .children(
+                hypothetical
+                    .into_iter()
+                    .map(|transaction| div().text_xs().child(transaction)),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("what-if-commit")
+                            .label("Commit")
+                            .primary()
+                            .disabled(!has_hypothetical)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.state
+                                    .update(cx, |state, cx| state.commit_hypothetical(cx));
+                            })),
+                    )
+                    .child(
+                        Button::new("what-if-discard")
+                            .label("Discard")
+                            .disabled(!has_hypothetical)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.state
+                                    .update(cx, |state, cx| state.discard_hypothetical(cx));
+                            })),
+                    ),
+            )
+    }
+}
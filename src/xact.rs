@@ -0,0 +1,166 @@
+//! Drafting a transaction from the payee's history, like `ledger xact`.
+//!
+//! The payee's most recent transaction is the template: its accounts are kept, and its
+//! amounts are either copied or scaled to a new amount, keeping their proportions.
+
+use chrono::NaiveDate;
+use fastnum::D128;
+
+use crate::transactions::{Amount, CurrencyAmount, Posting, PostingStatus, Transaction};
+
+/// Most recent transaction of the payee. A payee matching exactly, ignoring case, is
+/// preferred over one only containing `payee`.
+pub fn last_for_payee<'a>(
+    transactions: &[&'a Transaction],
+    payee: &str,
+) -> Option<&'a Transaction> {
+    let payee = payee.trim().to_lowercase();
+    if payee.is_empty() {
+        return None;
+    }
+    let latest = |matches: &dyn Fn(&str) -> bool| {
+        transactions
+            .iter()
+            .filter(|transaction| matches(&transaction.description.to_lowercase()))
+            .max_by_key(|transaction| transaction.time)
+            .copied()
+    };
+    latest(&|description| description == payee)
+        .or_else(|| latest(&|description| description.contains(&payee)))
+}
+
+/// New transaction dated `date` with the postings of the payee's most recent one.
+pub fn draft(transactions: &[&Transaction], payee: &str, date: NaiveDate) -> Option<Transaction> {
+    let template = last_for_payee(transactions, payee)?;
+    Some(Transaction {
+        file: std::path::PathBuf::new(),
+        line: 0,
+        time: date,
+        description: template.description.clone(),
+        postings: template
+            .postings
+            .iter()
+            .map(|posting| Posting {
+                account: posting.account.clone(),
+                amount: posting.amount.clone(),
+                status: PostingStatus::Uncleared,
+                note: None,
+            })
+            .collect(),
+    })
+}
+
+/// The draft with its first posting set to `amount`. The other postings in the first
+/// posting's commodity keep their proportion to it, and the last of them balances the
+/// rest. A draft whose first posting is zero has nothing to scale and is kept as is.
+pub fn with_amount(draft: &Transaction, amount: &CurrencyAmount) -> Transaction {
+    let mut draft = draft.clone();
+    let Some(first) = draft
+        .postings
+        .first()
+        .map(|posting| posting.amount.value.clone())
+        .filter(|first| !first.value.is_zero())
+    else {
+        return draft;
+    };
+    let places = amount.value.fractional_digits_count().max(0);
+    let scaled = draft
+        .postings
+        .iter_mut()
+        .filter(|posting| posting.amount.value.commodity == first.commodity)
+        .collect::<Vec<_>>();
+    let count = scaled.len();
+    let mut total = D128::ZERO;
+    for (ix, posting) in scaled.into_iter().enumerate() {
+        let value = if ix == 0 {
+            amount.value
+        } else if ix + 1 == count {
+            -total
+        } else {
+            (posting.amount.value.value * amount.value / first.value).round(places)
+        };
+        total += value;
+        posting.amount = Amount {
+            value: CurrencyAmount {
+                value,
+                commodity: amount.commodity.clone(),
+            },
+            price: None,
+            date: None,
+            cost: None,
+        };
+    }
+    draft
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(date: (i32, u32, u32), payee: &str, postings: &[(&str, &str)]) -> Transaction {
+        let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+        Transaction::fixture(date, payee, postings)
+    }
+
+    fn postings(transaction: &Transaction) -> Vec<String> {
+        transaction
+            .postings
+            .iter()
+            .map(|posting| format!("{} {}", posting.account, posting.amount))
+            .collect()
+    }
+
+    #[test]
+    fn test_draft() {
+        let transactions = [
+            transaction(
+                (2025, 1, 5),
+                "Grocer",
+                &[("Expenses:Food", "40 USD"), ("Assets:Cash", "-40 USD")],
+            ),
+            transaction(
+                (2025, 2, 5),
+                "Grocer",
+                &[
+                    ("Expenses:Food", "60 USD"),
+                    ("Expenses:Household", "30 USD"),
+                    ("Assets:Checking", "-90 USD"),
+                ],
+            ),
+            transaction(
+                (2025, 3, 5),
+                "Grocery Outlet",
+                &[("Expenses:Food", "10 USD"), ("Assets:Cash", "-10 USD")],
+            ),
+        ];
+        let transactions = transactions.iter().collect::<Vec<_>>();
+        let date = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+
+        let copied = draft(&transactions, "grocer", date).unwrap();
+        assert_eq!(copied.time, date);
+        assert_eq!(copied.description, "Grocer");
+        assert_eq!(
+            postings(&copied),
+            [
+                "Expenses:Food 60 USD",
+                "Expenses:Household 30 USD",
+                "Assets:Checking -90 USD",
+            ]
+        );
+
+        let amount = CurrencyAmount::parse("50.00 USD").unwrap();
+        let scaled = with_amount(&copied, &amount);
+        assert_eq!(
+            postings(&scaled),
+            [
+                "Expenses:Food 50.00 USD",
+                "Expenses:Household 25.00 USD",
+                "Assets:Checking -75.00 USD",
+            ]
+        );
+
+        let outlet = draft(&transactions, "outlet", date).unwrap();
+        assert_eq!(outlet.description, "Grocery Outlet");
+        assert!(draft(&transactions, "Bakery", date).is_none());
+    }
+}